@@ -0,0 +1,62 @@
+/*
+ * Copyright 2021 apepkuss
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/*
+ * C API of graphx, built with `cargo build --release --features capi`.
+ *
+ * Graphs are opaque handles owned by the caller and released with
+ * graphx_digraph_free. Strings are NUL-terminated UTF-8. Functions returning
+ * int report 0 on success and -1 on invalid arguments.
+ */
+
+#ifndef GRAPHX_H
+#define GRAPHX_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct GraphxDiGraph GraphxDiGraph;
+
+#define GRAPHX_MATCH_SUBGRAPH 0
+#define GRAPHX_MATCH_MONO 1
+
+GraphxDiGraph *graphx_digraph_new(void);
+/* Returns NULL if json is not a valid graph. */
+GraphxDiGraph *graphx_digraph_from_json(const char *json);
+void graphx_digraph_free(GraphxDiGraph *graph);
+
+/* weight may be NULL. */
+int graphx_digraph_add_node(GraphxDiGraph *graph, const char *name, const char *weight);
+int graphx_digraph_add_edge(GraphxDiGraph *graph, const char *from, const char *to);
+int graphx_digraph_add_weighted_edge(GraphxDiGraph *graph, const char *from, const char *to,
+                                     int64_t weight);
+
+size_t graphx_digraph_node_count(const GraphxDiGraph *graph);
+size_t graphx_digraph_edge_count(const GraphxDiGraph *graph);
+
+/* Count matches of pattern in host; limit 0 means no limit. Returns -1 on error. */
+int64_t graphx_subgraph_match_count(const GraphxDiGraph *host, const GraphxDiGraph *pattern,
+                                    int mode, size_t limit);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GRAPHX_H */
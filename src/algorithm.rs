@@ -12,6 +12,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod approximation;
+#[cfg(feature = "parallel")]
+pub mod apsp;
+pub mod arborescence;
+pub mod chordal;
+pub mod complement;
+pub mod components;
+pub mod cycles;
+pub mod dag;
+pub mod dedup;
+pub mod dominators;
+pub mod flow;
+pub mod generators;
+pub mod incremental;
+pub mod invariants;
 pub mod isomorphism;
+pub mod link_prediction;
+pub mod matching;
+pub mod metrics;
+pub mod motifs;
+pub mod oracle;
+pub mod partition;
+pub mod path;
+pub mod planarity;
+pub mod sample;
+pub mod schedule;
+#[cfg(feature = "linalg")]
+pub mod spectral;
 pub mod sssp;
+pub mod summary;
 pub mod topsort;
+pub mod traveling_salesman;
+pub mod traversal;
+pub mod tree;
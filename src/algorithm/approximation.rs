@@ -0,0 +1,238 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fast approximations for covering and density problems. Edge direction is ignored, so
+//! these work on an [`UnGraph`](crate::graph::UnGraph) as well as on directed
+//! graphs. Results list nodes in graph order and ties are broken by graph
+//! order, so they are deterministic.
+
+use crate::graph::{GraphBase, Node};
+use std::collections::HashMap;
+
+/// A vertex cover at most twice the minimum size: both endpoints of every edge
+/// of a greedily built maximal matching. A node with a self-loop is always in
+/// the cover.
+pub fn vertex_cover<G: GraphBase>(graph: &G) -> Vec<String> {
+    let (names, nbrs, loops) = adjacency(graph);
+    let mut covered = loops;
+    for v in 0..names.len() {
+        if covered[v] {
+            continue;
+        }
+        if let Some(&w) = nbrs[v].iter().find(|&&w| !covered[w]) {
+            covered[v] = true;
+            covered[w] = true;
+        }
+    }
+    select(&names, &covered)
+}
+
+/// A dominating set, so every node is in it or next to a node in it, built by
+/// repeatedly taking the node that dominates the most nodes not yet dominated.
+/// Its size is within a factor `ln(max degree + 1) + 1` of the minimum.
+pub fn dominating_set<G: GraphBase>(graph: &G) -> Vec<String> {
+    let (names, nbrs, _) = adjacency(graph);
+    let n = names.len();
+    let mut dominated = vec![false; n];
+    let mut chosen = vec![false; n];
+    let mut remaining = n;
+    while remaining > 0 {
+        let gain = |v: usize| {
+            usize::from(!dominated[v]) + nbrs[v].iter().filter(|&&w| !dominated[w]).count()
+        };
+        let v = (0..n)
+            .filter(|&v| !chosen[v])
+            .max_by_key(|&v| (gain(v), std::cmp::Reverse(v)))
+            .unwrap();
+        chosen[v] = true;
+        for w in std::iter::once(v).chain(nbrs[v].iter().copied()) {
+            if !dominated[w] {
+                dominated[w] = true;
+                remaining -= 1;
+            }
+        }
+    }
+    select(&names, &chosen)
+}
+
+/// A maximal independent set, so no two of its nodes are adjacent and no other
+/// node can be added, built by repeatedly taking a node of minimum degree among
+/// the remaining nodes and removing it with its neighbors. Nodes with a
+/// self-loop are never taken.
+pub fn independent_set<G: GraphBase>(graph: &G) -> Vec<String> {
+    let (names, nbrs, loops) = adjacency(graph);
+    let n = names.len();
+    let mut removed = loops;
+    let mut degree: Vec<usize> = (0..n)
+        .map(|v| nbrs[v].iter().filter(|&&w| !removed[w]).count())
+        .collect();
+    let mut chosen = vec![false; n];
+    while let Some(v) = (0..n).filter(|&v| !removed[v]).min_by_key(|&v| degree[v]) {
+        chosen[v] = true;
+        removed[v] = true;
+        for &w in nbrs[v].iter() {
+            if !removed[w] {
+                removed[w] = true;
+                for &x in nbrs[w].iter() {
+                    degree[x] -= 1;
+                }
+            }
+        }
+    }
+    select(&names, &chosen)
+}
+
+/// A subgraph whose density, its edges per node, is at least half the maximum,
+/// found by Charikar's peeling: repeatedly removing a node of minimum degree
+/// and keeping the densest of the graphs left along the way. Returns the nodes
+/// of that subgraph and its density, with self-loops not counted as edges; a
+/// graph without edges gives all its nodes and density 0.
+///
+/// Dense subgraphs of interaction graphs point at hotspots, e.g. accounts
+/// trading heavily among themselves.
+pub fn densest_subgraph<G: GraphBase>(graph: &G) -> (Vec<String>, f64) {
+    let (names, nbrs, _) = adjacency(graph);
+    let n = names.len();
+    let mut degree: Vec<usize> = nbrs.iter().map(Vec::len).collect();
+    let mut edges = degree.iter().sum::<usize>() / 2;
+    let mut best_density = if n == 0 { 0.0 } else { edges as f64 / n as f64 };
+    let mut best_peeled = 0;
+
+    let mut removed = vec![false; n];
+    let mut peeled = Vec::with_capacity(n);
+    while let Some(v) = (0..n).filter(|&v| !removed[v]).min_by_key(|&v| degree[v]) {
+        removed[v] = true;
+        peeled.push(v);
+        edges -= degree[v];
+        for &w in nbrs[v].iter() {
+            if !removed[w] {
+                degree[w] -= 1;
+            }
+        }
+        let left = n - peeled.len();
+        if left > 0 && edges as f64 / left as f64 > best_density {
+            best_density = edges as f64 / left as f64;
+            best_peeled = peeled.len();
+        }
+    }
+
+    let mut kept = vec![true; n];
+    for &v in peeled[..best_peeled].iter() {
+        kept[v] = false;
+    }
+    (select(&names, &kept), best_density)
+}
+
+/// Node names, undirected neighbor lists without self-loops, and which nodes
+/// have a self-loop.
+fn adjacency<G: GraphBase>(graph: &G) -> (Vec<&str>, Vec<Vec<usize>>, Vec<bool>) {
+    let names: Vec<&str> = graph.node_names().collect();
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+    let mut nbrs = vec![Vec::new(); names.len()];
+    let mut loops = vec![false; names.len()];
+    for (v, &name) in names.iter().enumerate() {
+        let node = graph.node(name).unwrap();
+        for other in node.successor_names().chain(node.predecessor_names()) {
+            let w = index[other];
+            if w == v {
+                loops[v] = true;
+            } else if !nbrs[v].contains(&w) {
+                nbrs[v].push(w);
+            }
+        }
+    }
+    (names, nbrs, loops)
+}
+
+fn select(names: &[&str], chosen: &[bool]) -> Vec<String> {
+    names
+        .iter()
+        .zip(chosen)
+        .filter(|&(_, &chosen)| chosen)
+        .map(|(&name, _)| name.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{UnGraph, UnNode};
+
+    #[test]
+    fn test_vertex_cover() {
+        let mut g: UnGraph = vec![("a", "b"), ("b", "c"), ("c", "d"), ("e", "e")]
+            .into_iter()
+            .collect();
+        g.add_node(UnNode::new("f", None));
+
+        let cover = vertex_cover(&g);
+        assert_eq!(cover, vec!["a", "b", "c", "d", "e"]);
+        for (a, b) in g.edges() {
+            assert!(cover.iter().any(|name| name == a || name == b));
+        }
+    }
+
+    #[test]
+    fn test_dominating_set() {
+        // a star with a tail: the hub and the end of the tail
+        let g: UnGraph = vec![("h", "a"), ("h", "b"), ("h", "c"), ("c", "d"), ("d", "e")]
+            .into_iter()
+            .collect();
+        assert_eq!(dominating_set(&g), vec!["h", "d"]);
+    }
+
+    #[test]
+    fn test_independent_set() {
+        let g: UnGraph = vec![("h", "a"), ("h", "b"), ("h", "c"), ("c", "d"), ("d", "d")]
+            .into_iter()
+            .collect();
+        let set = independent_set(&g);
+        assert_eq!(set, vec!["a", "b", "c"]);
+        for a in set.iter() {
+            for b in set.iter() {
+                assert!(!g.contains_edge(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_densest_subgraph() {
+        // a four-clique with a two-edge tail and a self-loop
+        let g: UnGraph = vec![
+            ("a", "b"),
+            ("a", "c"),
+            ("a", "d"),
+            ("b", "c"),
+            ("b", "d"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "f"),
+            ("f", "f"),
+        ]
+        .into_iter()
+        .collect();
+        let (nodes, density) = densest_subgraph(&g);
+        assert_eq!(nodes, vec!["a", "b", "c", "d"]);
+        assert_eq!(density, 1.5);
+
+        let mut g = UnGraph::new(None);
+        assert_eq!(densest_subgraph(&g), (vec![], 0.0));
+        g.add_node(UnNode::new("a", None));
+        assert_eq!(densest_subgraph(&g), (vec!["a".to_string()], 0.0));
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! All-pairs shortest distances, computed in parallel with `rayon`.
+
+use super::sssp::{check_nonnegative, nearest_sources};
+use crate::{error::GraphError, graph::Weighted};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Default cap on the number of entries of a [`DistanceMatrix`]: 2^28 entries,
+/// or 2 GiB.
+pub const DEFAULT_MAX_ENTRIES: usize = 1 << 28;
+
+/// Shortest distances between all pairs of nodes, stored row-major: the
+/// distance from `names()[i]` to `names()[j]` is `distances()[i * n + j]`, or
+/// `i64::MAX` if there is no path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceMatrix {
+    names: Vec<String>,
+    distances: Vec<i64>,
+    // position of every name in `names`
+    index: HashMap<String, usize>,
+}
+impl DistanceMatrix {
+    /// Distance from `from` to `to`, or `None` if there is no path or either
+    /// node is unknown.
+    pub fn get(&self, from: &str, to: &str) -> Option<i64> {
+        let i = *self.index.get(from)?;
+        let j = *self.index.get(to)?;
+        self.row(i).get(j).copied().filter(|&d| d != i64::MAX)
+    }
+
+    /// Node names in graph order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The distances, row-major.
+    pub fn distances(&self) -> &[i64] {
+        &self.distances
+    }
+
+    /// Position of a node in [`DistanceMatrix::names`].
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    /// Distances from the `i`th node to every node.
+    pub fn row(&self, i: usize) -> &[i64] {
+        let n = self.names.len();
+        &self.distances[i * n..(i + 1) * n]
+    }
+}
+
+/// [`all_pairs_dijkstra_parallel_with_limit`] with at most
+/// [`DEFAULT_MAX_ENTRIES`] entries.
+pub fn all_pairs_dijkstra_parallel<G: Weighted + Sync>(
+    graph: &G,
+) -> Result<DistanceMatrix, GraphError> {
+    all_pairs_dijkstra_parallel_with_limit(graph, DEFAULT_MAX_ENTRIES)
+}
+
+/// Shortest distances between all pairs of nodes, running Dijkstra from every
+/// source on the `rayon` thread pool. Edges without a weight count as 1.
+///
+/// Fails before doing any work with `GraphError::TooLarge` if the matrix would
+/// have more than `max_entries` entries, and with `GraphError::NegativeWeight`
+/// if an edge has negative weight.
+pub fn all_pairs_dijkstra_parallel_with_limit<G: Weighted + Sync>(
+    graph: &G,
+    max_entries: usize,
+) -> Result<DistanceMatrix, GraphError> {
+    let names: Vec<&str> = graph.node_names().collect();
+    let n = names.len();
+    let entries = n
+        .checked_mul(n)
+        .filter(|&entries| entries <= max_entries)
+        .ok_or(GraphError::TooLarge(n, max_entries))?;
+    check_nonnegative(graph)?;
+
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+    let mut distances = vec![i64::MAX; entries];
+    if n > 0 {
+        distances
+            .par_chunks_mut(n)
+            .zip(names.par_iter())
+            .try_for_each(|(row, &source)| {
+                for (name, (distance, _, _)) in nearest_sources(graph, &[source])? {
+                    row[index[name]] = distance;
+                }
+                Ok::<_, GraphError>(())
+            })?;
+    }
+    Ok(DistanceMatrix {
+        names: names.into_iter().map(String::from).collect(),
+        distances,
+        index: index
+            .into_iter()
+            .map(|(name, i)| (name.to_string(), i))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_all_pairs_dijkstra_parallel() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 3);
+        g.add_weighted_edge("B", "C", 1);
+        g.add_weighted_edge("A", "C", 5);
+        g.add_edge(Some("C"), Some("A"));
+
+        let matrix = all_pairs_dijkstra_parallel(&g).unwrap();
+        assert_eq!(matrix.names(), ["A", "B", "C"]);
+        assert_eq!(matrix.distances(), [0, 3, 4, 2, 0, 1, 1, 4, 0]);
+        assert_eq!(matrix.index_of("C"), Some(2));
+        assert_eq!(matrix.get("B", "A"), Some(2));
+        assert_eq!(matrix.row(2), [1, 4, 0]);
+
+        g.add_node(crate::graph::DiNode::new("D", None));
+        let matrix = all_pairs_dijkstra_parallel(&g).unwrap();
+        assert_eq!(matrix.get("A", "D"), None);
+        assert_eq!(matrix.get("D", "D"), Some(0));
+        assert_eq!(matrix.get("A", "Z"), None);
+
+        assert!(matches!(
+            all_pairs_dijkstra_parallel_with_limit(&g, 15),
+            Err(GraphError::TooLarge(4, 15))
+        ));
+        let empty = all_pairs_dijkstra_parallel(&DiGraph::new(None)).unwrap();
+        assert!(empty.distances().is_empty());
+
+        g.add_weighted_edge("D", "A", -1);
+        assert!(matches!(
+            all_pairs_dijkstra_parallel(&g),
+            Err(GraphError::NegativeWeight(from, to, -1)) if from == "D" && to == "A"
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    error::GraphError,
+    graph::{Node, Weighted},
+};
+use std::collections::{HashMap, VecDeque};
+
+/// A directed spanning tree with all edges pointing away from its root.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Arborescence {
+    /// `(parent, child)` edges, in graph order of the child.
+    pub edges: Vec<(String, String)>,
+    /// Total weight of the edges.
+    pub cost: i64,
+}
+
+/// Minimum spanning arborescence rooted at `root`, found with the algorithm of
+/// Chu-Liu and Edmonds in `O(V * E)` time.
+///
+/// The result spans every node reachable from `root`; others cannot be part of
+/// a tree grown from it and are left out. Edges without a weight count as 1,
+/// and self-loops and edges into `root` are ignored. Fails with
+/// `GraphError::NotFoundNode` if `root` is not in the graph.
+pub fn minimum_spanning_arborescence<G: Weighted>(
+    graph: &G,
+    root: &str,
+) -> Result<Arborescence, GraphError> {
+    if graph.node(root).is_none() {
+        return Err(GraphError::NotFoundNode(root.to_string()));
+    }
+
+    // index the nodes reachable from the root, root first
+    let mut names = vec![root];
+    let mut index: HashMap<&str, usize> = HashMap::from([(root, 0)]);
+    let mut queue = VecDeque::from([root]);
+    while let Some(name) = queue.pop_front() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            if !index.contains_key(succ) {
+                index.insert(succ, names.len());
+                names.push(succ);
+                queue.push_back(succ);
+            }
+        }
+    }
+
+    let mut edges = Vec::new();
+    for (u, &name) in names.iter().enumerate() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            let v = index[succ];
+            if v != u && v != 0 {
+                edges.push((u, v, graph.edge_weight(name, succ).unwrap_or(1)));
+            }
+        }
+    }
+
+    let mut chosen: Vec<(usize, usize, i64)> = contract(names.len(), 0, &edges)
+        .into_iter()
+        .map(|k| edges[k])
+        .collect();
+    let order: HashMap<&str, usize> = graph
+        .node_names()
+        .enumerate()
+        .map(|(i, name)| (name, i))
+        .collect();
+    chosen.sort_by_key(|&(_, v, _)| order[names[v]]);
+    Ok(Arborescence {
+        cost: chosen.iter().map(|e| e.2).sum(),
+        edges: chosen
+            .into_iter()
+            .map(|(u, v, _)| (names[u].to_string(), names[v].to_string()))
+            .collect(),
+    })
+}
+
+/// Indices of the edges of a minimum arborescence over nodes `0..n`, where
+/// every node other than `root` has an incoming edge.
+fn contract(n: usize, root: usize, edges: &[(usize, usize, i64)]) -> Vec<usize> {
+    // cheapest incoming edge of every node
+    let mut best = vec![usize::MAX; n];
+    for (k, &(_, v, w)) in edges.iter().enumerate() {
+        if best[v] == usize::MAX || w < edges[best[v]].2 {
+            best[v] = k;
+        }
+    }
+
+    // follow the cheapest edges backwards to find cycles among them
+    let mut comp = vec![usize::MAX; n];
+    let mut walk = vec![usize::MAX; n];
+    let mut in_cycle = vec![false; n];
+    let mut count = 0;
+    for start in 0..n {
+        let mut v = start;
+        while v != root && walk[v] == usize::MAX && comp[v] == usize::MAX {
+            walk[v] = start;
+            v = edges[best[v]].0;
+        }
+        if v != root && walk[v] == start && comp[v] == usize::MAX {
+            let mut u = v;
+            loop {
+                comp[u] = count;
+                in_cycle[u] = true;
+                u = edges[best[u]].0;
+                if u == v {
+                    break;
+                }
+            }
+            count += 1;
+        }
+    }
+    if count == 0 {
+        return (0..n).filter(|&v| v != root).map(|v| best[v]).collect();
+    }
+    for c in comp.iter_mut() {
+        if *c == usize::MAX {
+            *c = count;
+            count += 1;
+        }
+    }
+
+    // contract every cycle into one node; an edge entering a cycle costs what
+    // it saves over the cycle edge it would replace
+    let mut contracted = Vec::new();
+    let mut origin = Vec::new();
+    for (k, &(u, v, w)) in edges.iter().enumerate() {
+        if comp[u] != comp[v] {
+            let w = if in_cycle[v] { w - edges[best[v]].2 } else { w };
+            contracted.push((comp[u], comp[v], w));
+            origin.push(k);
+        }
+    }
+
+    let mut chosen = Vec::with_capacity(n - 1);
+    let mut entered = vec![false; n];
+    for k in contract(count, comp[root], &contracted) {
+        let k = origin[k];
+        entered[edges[k].1] = true;
+        chosen.push(k);
+    }
+    // keep the cycle edges except the one into the node where the cycle is entered
+    for v in 0..n {
+        if in_cycle[v] && !entered[v] {
+            chosen.push(best[v]);
+        }
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_minimum_spanning_arborescence() {
+        // the cheap edges B <-> C form a cycle that must be broken
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("R", "B", 10);
+        g.add_weighted_edge("R", "C", 12);
+        g.add_weighted_edge("B", "C", 1);
+        g.add_weighted_edge("C", "B", 1);
+        g.add_weighted_edge("C", "D", 4);
+        g.add_weighted_edge("D", "R", 1);
+        g.add_weighted_edge("X", "B", 1);
+
+        let arborescence = minimum_spanning_arborescence(&g, "R").unwrap();
+        assert_eq!(arborescence.cost, 15);
+        assert_eq!(
+            arborescence.edges,
+            vec![
+                ("R".to_string(), "B".to_string()),
+                ("B".to_string(), "C".to_string()),
+                ("C".to_string(), "D".to_string()),
+            ]
+        );
+
+        assert!(minimum_spanning_arborescence(&g, "Z").is_err());
+    }
+
+    #[test]
+    fn test_minimum_spanning_arborescence_nested_cycles() {
+        // cheapest in-edges: B <- C, C <- D, D <- B; the cycle is entered at
+        // the node where leaving out its cycle edge costs least
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("R", "B", 20);
+        g.add_weighted_edge("R", "C", 5);
+        g.add_weighted_edge("R", "D", 30);
+        g.add_weighted_edge("C", "B", 1);
+        g.add_weighted_edge("D", "C", 2);
+        g.add_weighted_edge("B", "D", 3);
+
+        let arborescence = minimum_spanning_arborescence(&g, "R").unwrap();
+        // enter at C (5 instead of 2): R->C, C->B, B->D
+        assert_eq!(arborescence.cost, 9);
+        assert_eq!(arborescence.edges.len(), 3);
+    }
+}
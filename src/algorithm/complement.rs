@@ -0,0 +1,84 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::graph::{DiGraph, DiNode, Directed, Node};
+
+/// The graph on the same nodes with an edge `A -> B` exactly where `graph` has
+/// none, for distinct `A` and `B`; self-loops are neither kept nor added.
+///
+/// Nodes keep their weights but not their attributes, and appear in graph
+/// order. An [`UnGraph`](crate::graph::UnGraph) acts as a graph with both arcs
+/// for every edge, so its complement has both arcs for every non-adjacent
+/// pair, and a clique of the graph is an independent set of the complement.
+pub fn complement<G: Directed>(graph: &G) -> DiGraph {
+    complement_impl(graph, false)
+}
+
+/// Like [`complement`], but a node gets a self-loop exactly when it has none
+/// in `graph`.
+pub fn complement_with_self_loops<G: Directed>(graph: &G) -> DiGraph {
+    complement_impl(graph, true)
+}
+
+fn complement_impl<G: Directed>(graph: &G, self_loops: bool) -> DiGraph {
+    let mut result = DiGraph::new(None);
+    for node in graph.nodes() {
+        result.add_node(DiNode::new(node.name(), node.weight().map(String::from)));
+    }
+    for from in graph.node_names() {
+        for to in graph.node_names() {
+            if (from != to || self_loops) && !graph.contains_edge(from, to) {
+                result.add_edge(Some(from), Some(to));
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::UnGraph;
+
+    #[test]
+    fn test_complement() {
+        let mut g: DiGraph = vec![("A", "B"), ("B", "C"), ("C", "C")]
+            .into_iter()
+            .collect();
+        g.add_node(DiNode::new("D", Some("d".to_string())));
+
+        let c = complement(&g);
+        assert_eq!(c.get_nodes(), vec!["A", "B", "C", "D"]);
+        assert_eq!(c.edge_count(), 12 - 2);
+        assert!(!c.contains_edge("A", "B"));
+        assert!(c.contains_edge("B", "A"));
+        assert!(!c.contains_edge("A", "A"));
+        assert_eq!(c.get_node("D").unwrap().get_weight(), Some("d".to_string()));
+
+        let c = complement_with_self_loops(&g);
+        assert_eq!(c.edge_count(), 16 - 3);
+        assert!(c.contains_edge("A", "A"));
+        assert!(!c.contains_edge("C", "C"));
+
+        // no edges are left inside the triangle A, B, C
+        let g: UnGraph = vec![("A", "B"), ("B", "C"), ("C", "A"), ("C", "D")]
+            .into_iter()
+            .collect();
+        let c = complement(&g);
+        assert_eq!(
+            c.edges().collect::<Vec<_>>(),
+            vec![("A", "D"), ("B", "D"), ("D", "A"), ("D", "B")]
+        );
+    }
+}
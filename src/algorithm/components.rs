@@ -0,0 +1,141 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    error::GraphError,
+    graph::{DiGraph, DiNode, GraphBase, Node, Weighted},
+};
+use std::collections::{HashSet, VecDeque};
+
+/// Split the nodes into weakly connected components, i.e. the components of
+/// the graph with edge directions ignored.
+///
+/// Components are listed in the graph order of their first node, and the
+/// nodes of a component in breadth-first order from that node.
+pub fn weakly_connected_components<G: GraphBase>(graph: &G) -> Vec<Vec<String>> {
+    let mut seen = HashSet::new();
+    let mut components = Vec::new();
+    for start in graph.node_names() {
+        if seen.contains(start) {
+            continue;
+        }
+        components.push(component(graph, start, &mut seen));
+    }
+    components
+}
+
+/// The weakly connected component containing `name`, in breadth-first order
+/// from it. Fails with `GraphError::NotFoundNode` if the node is missing.
+pub fn component_of<G: GraphBase>(graph: &G, name: &str) -> Result<Vec<String>, GraphError> {
+    let name = graph
+        .node(name)
+        .ok_or_else(|| GraphError::NotFoundNode(name.to_string()))?
+        .name();
+    Ok(component(graph, name, &mut HashSet::new()))
+}
+
+/// The subgraph induced by the largest weakly connected component, the first
+/// one in graph order on ties, to drop disconnected debris before analysis.
+///
+/// Nodes keep their weights but not their attributes, edges keep their
+/// weights, and nodes appear in graph order. An empty graph gives an empty
+/// graph.
+pub fn largest_component<G: Weighted>(graph: &G) -> DiGraph {
+    let mut largest: Vec<String> = Vec::new();
+    for nodes in weakly_connected_components(graph) {
+        if nodes.len() > largest.len() {
+            largest = nodes;
+        }
+    }
+    let keep: HashSet<&str> = largest.iter().map(String::as_str).collect();
+
+    let mut result = DiGraph::new(None);
+    for node in graph.nodes().filter(|node| keep.contains(node.name())) {
+        result.add_node(DiNode::new(node.name(), node.weight().map(String::from)));
+    }
+    for node in graph.nodes().filter(|node| keep.contains(node.name())) {
+        for succ in node.successor_names() {
+            match graph.edge_weight(node.name(), succ) {
+                Some(weight) => result.add_weighted_edge(node.name(), succ, weight),
+                None => result.add_edge(Some(node.name()), Some(succ)),
+            }
+        }
+    }
+    result
+}
+
+/// Breadth-first search over edges in both directions from `start`, which
+/// must not have been seen yet.
+fn component<'a, G: GraphBase>(
+    graph: &'a G,
+    start: &'a str,
+    seen: &mut HashSet<&'a str>,
+) -> Vec<String> {
+    seen.insert(start);
+    let mut component = Vec::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(name) = queue.pop_front() {
+        component.push(name.to_string());
+        let node = graph.node(name).unwrap();
+        for nbr in node.successor_names().chain(node.predecessor_names()) {
+            if seen.insert(nbr) {
+                queue.push_back(nbr);
+            }
+        }
+    }
+    component
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_weakly_connected_components() {
+        let mut g: DiGraph = vec![("A", "B"), ("C", "B"), ("D", "E")]
+            .into_iter()
+            .collect();
+        g.add_edge(Some("F"), None);
+
+        let components = weakly_connected_components(&g);
+        assert_eq!(
+            components,
+            vec![vec!["A", "B", "C"], vec!["D", "E"], vec!["F"]]
+        );
+        assert!(weakly_connected_components(&DiGraph::new(None)).is_empty());
+    }
+
+    #[test]
+    fn test_largest_component() {
+        let mut g: DiGraph = vec![("X", "Y"), ("A", "B"), ("C", "B"), ("D", "E")]
+            .into_iter()
+            .collect();
+        g.add_weighted_edge("B", "D", 7);
+        g.add_edge(Some("F"), None);
+
+        assert_eq!(
+            component_of(&g, "E").unwrap(),
+            vec!["E", "D", "B", "A", "C"]
+        );
+        assert_eq!(component_of(&g, "F").unwrap(), vec!["F"]);
+        assert!(component_of(&g, "Z").is_err());
+
+        let largest = largest_component(&g);
+        assert_eq!(largest.get_nodes(), vec!["A", "B", "C", "D", "E"]);
+        assert_eq!(largest.edge_count(), 4);
+        assert_eq!(largest.get_edge_weight("B", "D"), Some(7));
+        assert_eq!(largest_component(&DiGraph::new(None)).node_count(), 0);
+    }
+}
@@ -0,0 +1,592 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::graph::{GraphBase, Node};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Enumerate the elementary cycles of a graph with Johnson's algorithm.
+///
+/// Cycles are produced lazily as node sequences; the edge from the last node
+/// back to the first closes each cycle. Self-loops come first, as one-node
+/// cycles. The iterator owns a compact copy of the graph's structure, so the
+/// graph can be changed while iterating.
+pub fn simple_cycles<G: GraphBase>(graph: &G) -> SimpleCycles {
+    let names: Vec<String> = graph.node_names().map(String::from).collect();
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), i))
+        .collect();
+
+    let mut adj = vec![Vec::new(); names.len()];
+    let mut self_loops = Vec::new();
+    for (v, name) in names.iter().enumerate() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            if succ == name {
+                self_loops.push(v);
+            } else {
+                adj[v].push(index[succ]);
+            }
+        }
+    }
+    self_loops.reverse();
+
+    let all: Vec<usize> = (0..names.len()).collect();
+    let sccs = strongly_connected(&adj, &all);
+    let n = names.len();
+    SimpleCycles {
+        names,
+        adj,
+        self_loops,
+        sccs,
+        current: None,
+        in_scc: vec![false; n],
+        start: 0,
+        path: Vec::new(),
+        blocked: vec![false; n],
+        closed: vec![false; n],
+        b: vec![HashSet::new(); n],
+        stack: Vec::new(),
+    }
+}
+
+/// Iterator returned by [`simple_cycles`].
+#[derive(Debug, Clone)]
+pub struct SimpleCycles {
+    names: Vec<String>,
+    adj: Vec<Vec<usize>>,
+    self_loops: Vec<usize>,
+    // strongly connected components with more than one node, still to search
+    sccs: Vec<Vec<usize>>,
+    // the component being searched, without its start node
+    current: Option<Vec<usize>>,
+    in_scc: Vec<bool>,
+    start: usize,
+    path: Vec<usize>,
+    blocked: Vec<bool>,
+    closed: Vec<bool>,
+    b: Vec<HashSet<usize>>,
+    stack: Vec<(usize, Vec<usize>)>,
+}
+impl SimpleCycles {
+    fn neighbors(&self, v: usize) -> Vec<usize> {
+        self.adj[v]
+            .iter()
+            .copied()
+            .filter(|&w| self.in_scc[w])
+            .collect()
+    }
+
+    fn unblock(&mut self, v: usize) {
+        let mut stack = vec![v];
+        while let Some(node) = stack.pop() {
+            if self.blocked[node] {
+                self.blocked[node] = false;
+                stack.extend(self.b[node].drain());
+            }
+        }
+    }
+
+    /// Start searching the next component; false once all are done.
+    fn next_component(&mut self) -> bool {
+        if let Some(rest) = self.current.take() {
+            for &v in rest.iter() {
+                self.in_scc[v] = false;
+            }
+            self.in_scc[self.start] = false;
+            // cycles through the start node are done, so search what is left
+            self.sccs.extend(strongly_connected(&self.adj, &rest));
+        }
+
+        let mut scc = match self.sccs.pop() {
+            Some(scc) => scc,
+            None => return false,
+        };
+        for &v in scc.iter() {
+            self.in_scc[v] = true;
+            self.blocked[v] = false;
+            self.closed[v] = false;
+            self.b[v].clear();
+        }
+        self.start = scc.pop().unwrap();
+        self.blocked[self.start] = true;
+        self.path = vec![self.start];
+        self.stack = vec![(self.start, self.neighbors(self.start))];
+        self.current = Some(scc);
+        true
+    }
+}
+impl Iterator for SimpleCycles {
+    type Item = Vec<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(v) = self.self_loops.pop() {
+            return Some(vec![self.names[v].clone()]);
+        }
+
+        loop {
+            while let Some((node, nbrs)) = self.stack.last_mut() {
+                let node = *node;
+                if let Some(next) = nbrs.pop() {
+                    if next == self.start {
+                        for &v in self.path.iter() {
+                            self.closed[v] = true;
+                        }
+                        return Some(self.path.iter().map(|&v| self.names[v].clone()).collect());
+                    } else if !self.blocked[next] {
+                        self.path.push(next);
+                        let nbrs = self.neighbors(next);
+                        self.stack.push((next, nbrs));
+                        self.closed[next] = false;
+                        self.blocked[next] = true;
+                        continue;
+                    }
+                }
+
+                if self.stack.last().unwrap().1.is_empty() {
+                    if self.closed[node] {
+                        self.unblock(node);
+                    } else {
+                        for w in self.neighbors(node) {
+                            self.b[w].insert(node);
+                        }
+                    }
+                    self.stack.pop();
+                    self.path.pop();
+                }
+            }
+
+            if !self.next_component() {
+                return None;
+            }
+        }
+    }
+}
+
+/// A small set of edges whose removal leaves the graph acyclic, found with the
+/// greedy heuristic of Eades, Lin and Smyth in `O(V^2 + E)` time.
+///
+/// Nodes are laid out in a sequence by repeatedly taking sinks to the back,
+/// sources to the front, and otherwise the node with the largest out-degree
+/// minus in-degree to the front; every edge pointing backwards in the sequence,
+/// including every self-loop, is returned. The result is not always minimum.
+pub fn minimum_feedback_arc_set<G: GraphBase>(graph: &G) -> Vec<(String, String)> {
+    let names: Vec<&str> = graph.node_names().collect();
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+    let n = names.len();
+
+    let mut outputs = vec![Vec::new(); n];
+    let mut inputs = vec![Vec::new(); n];
+    for (v, &name) in names.iter().enumerate() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            if succ != name {
+                outputs[v].push(index[succ]);
+                inputs[index[succ]].push(v);
+            }
+        }
+    }
+    let mut out_degree: Vec<usize> = outputs.iter().map(|succs| succs.len()).collect();
+    let mut in_degree: Vec<usize> = inputs.iter().map(|preds| preds.len()).collect();
+
+    let mut sinks: Vec<usize> = (0..n).rev().filter(|&v| out_degree[v] == 0).collect();
+    let mut sources: Vec<usize> = (0..n).rev().filter(|&v| in_degree[v] == 0).collect();
+    let mut removed = vec![false; n];
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    while front.len() + back.len() < n {
+        let (v, to_front) = if let Some(v) = sinks.pop() {
+            (v, false)
+        } else if let Some(v) = sources.pop() {
+            (v, true)
+        } else {
+            let v = (0..n)
+                .filter(|&v| !removed[v])
+                .max_by_key(|&v| (out_degree[v] as i64 - in_degree[v] as i64, Reverse(v)))
+                .unwrap();
+            (v, true)
+        };
+        if removed[v] {
+            continue;
+        }
+
+        removed[v] = true;
+        if to_front {
+            front.push(v);
+        } else {
+            back.push(v);
+        }
+        for &w in outputs[v].iter() {
+            in_degree[w] -= 1;
+            if in_degree[w] == 0 && !removed[w] {
+                sources.push(w);
+            }
+        }
+        for &u in inputs[v].iter() {
+            out_degree[u] -= 1;
+            if out_degree[u] == 0 && !removed[u] {
+                sinks.push(u);
+            }
+        }
+    }
+    front.extend(back.into_iter().rev());
+
+    let mut position = vec![0; n];
+    for (i, &v) in front.iter().enumerate() {
+        position[v] = i;
+    }
+    let mut arcs = Vec::new();
+    for (v, &name) in names.iter().enumerate() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            if position[index[succ]] <= position[v] {
+                arcs.push((name.to_string(), succ.to_string()));
+            }
+        }
+    }
+    arcs
+}
+
+/// A small set of nodes whose removal leaves the graph acyclic, for when
+/// dropping nodes rather than edges is the natural way to break cycles.
+///
+/// Every node with a self-loop is taken first. After that, nodes that cannot
+/// lie on a cycle, those without predecessors or without successors left, are
+/// pruned, and the node with the largest product of in- and out-degree is
+/// taken, until no node is left. Finally, taken nodes whose return would not
+/// close a cycle are dropped again, last taken first. The nodes come in graph
+/// order; the result is not always minimum.
+pub fn minimum_feedback_vertex_set<G: GraphBase>(graph: &G) -> Vec<String> {
+    let names: Vec<&str> = graph.node_names().collect();
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+    let n = names.len();
+
+    let mut outputs = vec![Vec::new(); n];
+    let mut inputs = vec![Vec::new(); n];
+    let mut taken = Vec::new();
+    for (v, &name) in names.iter().enumerate() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            if succ == name {
+                taken.push(v);
+            } else {
+                outputs[v].push(index[succ]);
+                inputs[index[succ]].push(v);
+            }
+        }
+    }
+    let self_loops = taken.len();
+
+    let mut out_degree: Vec<usize> = outputs.iter().map(|succs| succs.len()).collect();
+    let mut in_degree: Vec<usize> = inputs.iter().map(|preds| preds.len()).collect();
+    let mut removed = vec![false; n];
+    let mut stack: Vec<usize> = taken.clone();
+    stack.extend((0..n).filter(|&v| out_degree[v] == 0 || in_degree[v] == 0));
+    loop {
+        // remove the nodes on the stack, and with them every node left
+        // without predecessors or successors
+        while let Some(v) = stack.pop() {
+            if removed[v] {
+                continue;
+            }
+            removed[v] = true;
+            for &w in outputs[v].iter() {
+                in_degree[w] -= 1;
+                if in_degree[w] == 0 && !removed[w] {
+                    stack.push(w);
+                }
+            }
+            for &u in inputs[v].iter() {
+                out_degree[u] -= 1;
+                if out_degree[u] == 0 && !removed[u] {
+                    stack.push(u);
+                }
+            }
+        }
+        match (0..n)
+            .filter(|&v| !removed[v])
+            .max_by_key(|&v| (in_degree[v] * out_degree[v], Reverse(v)))
+        {
+            Some(v) => {
+                taken.push(v);
+                stack.push(v);
+            }
+            None => break,
+        }
+    }
+
+    let mut excluded = vec![false; n];
+    for &v in taken.iter() {
+        excluded[v] = true;
+    }
+    for &v in taken[self_loops..].iter().rev() {
+        excluded[v] = false;
+        if !is_acyclic(&outputs, &excluded) {
+            excluded[v] = true;
+        }
+    }
+    (0..n)
+        .filter(|&v| excluded[v])
+        .map(|v| names[v].to_string())
+        .collect()
+}
+
+/// Kahn's algorithm on the graph without the `excluded` nodes.
+fn is_acyclic(adj: &[Vec<usize>], excluded: &[bool]) -> bool {
+    let mut in_degree = vec![0; adj.len()];
+    for v in (0..adj.len()).filter(|&v| !excluded[v]) {
+        for &w in adj[v].iter() {
+            in_degree[w] += 1;
+        }
+    }
+    let mut stack: Vec<usize> = (0..adj.len())
+        .filter(|&v| !excluded[v] && in_degree[v] == 0)
+        .collect();
+    let mut visited = 0;
+    while let Some(v) = stack.pop() {
+        visited += 1;
+        for &w in adj[v].iter() {
+            in_degree[w] -= 1;
+            if in_degree[w] == 0 && !excluded[w] {
+                stack.push(w);
+            }
+        }
+    }
+    visited == excluded.iter().filter(|&&x| !x).count()
+}
+
+/// Tarjan's algorithm on the subgraph induced by `nodes`, keeping only the
+/// components with more than one node.
+pub(super) fn strongly_connected(adj: &[Vec<usize>], nodes: &[usize]) -> Vec<Vec<usize>> {
+    let mut member = vec![false; adj.len()];
+    for &v in nodes {
+        member[v] = true;
+    }
+
+    let mut index: Vec<Option<usize>> = vec![None; adj.len()];
+    let mut low = vec![0; adj.len()];
+    let mut on_stack = vec![false; adj.len()];
+    let mut stack = Vec::new();
+    let mut counter = 0;
+    let mut components = Vec::new();
+
+    for &root in nodes {
+        if index[root].is_some() {
+            continue;
+        }
+        index[root] = Some(counter);
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        // (node, position of the next successor to visit)
+        let mut calls = vec![(root, 0)];
+        while let Some(&(v, i)) = calls.last() {
+            if i < adj[v].len() {
+                calls.last_mut().unwrap().1 += 1;
+                let w = adj[v][i];
+                if !member[w] {
+                    continue;
+                }
+                match index[w] {
+                    None => {
+                        index[w] = Some(counter);
+                        low[w] = counter;
+                        counter += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        calls.push((w, 0));
+                    }
+                    Some(w_index) if on_stack[w] => low[v] = low[v].min(w_index),
+                    _ => {}
+                }
+            } else {
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    low[parent] = low[parent].min(low[v]);
+                }
+                if Some(low[v]) == index[v] {
+                    let mut component = Vec::new();
+                    loop {
+                        let w = stack.pop().unwrap();
+                        on_stack[w] = false;
+                        component.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    if component.len() > 1 {
+                        components.push(component);
+                    }
+                }
+            }
+        }
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    // rotate each cycle to start at its smallest name, then sort the cycles
+    fn normalize(cycles: impl Iterator<Item = Vec<String>>) -> Vec<Vec<String>> {
+        let mut cycles: Vec<Vec<String>> = cycles
+            .map(|mut cycle| {
+                let min = (0..cycle.len()).min_by_key(|&i| &cycle[i]).unwrap();
+                cycle.rotate_left(min);
+                cycle
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    #[test]
+    fn test_simple_cycles() {
+        let g: DiGraph = vec![
+            ("0", "0"),
+            ("0", "1"),
+            ("0", "2"),
+            ("1", "2"),
+            ("2", "0"),
+            ("2", "1"),
+            ("2", "2"),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut cycles = simple_cycles(&g);
+        assert_eq!(cycles.next().unwrap(), vec!["0"]);
+        assert_eq!(cycles.next().unwrap(), vec!["2"]);
+
+        let expected: Vec<Vec<String>> = vec![
+            vec!["0"],
+            vec!["0", "1", "2"],
+            vec!["0", "2"],
+            vec!["1", "2"],
+            vec!["2"],
+        ]
+        .into_iter()
+        .map(|cycle| cycle.into_iter().map(String::from).collect())
+        .collect();
+        assert_eq!(normalize(simple_cycles(&g)), expected);
+    }
+
+    #[test]
+    fn test_simple_cycles_components() {
+        // two cycles joined by a one-way bridge, with a tail
+        let g: DiGraph = vec![
+            ("A", "B"),
+            ("B", "C"),
+            ("C", "A"),
+            ("C", "D"),
+            ("D", "E"),
+            ("E", "F"),
+            ("F", "D"),
+            ("F", "G"),
+        ]
+        .into_iter()
+        .collect();
+        let cycles = normalize(simple_cycles(&g));
+        assert_eq!(cycles, vec![vec!["A", "B", "C"], vec!["D", "E", "F"]]);
+
+        let dag: DiGraph = vec![("A", "B"), ("B", "C"), ("A", "C")]
+            .into_iter()
+            .collect();
+        assert_eq!(simple_cycles(&dag).count(), 0);
+
+        // the complete digraph on four nodes has 20 elementary cycles
+        let mut complete = DiGraph::new(None);
+        for from in ["a", "b", "c", "d"] {
+            for to in ["a", "b", "c", "d"] {
+                if from != to {
+                    complete.add_edge(Some(from), Some(to));
+                }
+            }
+        }
+        assert_eq!(simple_cycles(&complete).count(), 20);
+    }
+
+    #[test]
+    fn test_minimum_feedback_arc_set() {
+        let mut g: DiGraph = vec![
+            ("A", "B"),
+            ("B", "C"),
+            ("C", "A"),
+            ("C", "D"),
+            ("D", "E"),
+            ("E", "C"),
+            ("E", "E"),
+        ]
+        .into_iter()
+        .collect();
+
+        let arcs = minimum_feedback_arc_set(&g);
+        assert_eq!(arcs.len(), 3);
+        assert!(arcs.contains(&("E".to_string(), "E".to_string())));
+
+        let g2: DiGraph = g
+            .edges()
+            .filter(|&(from, to)| !arcs.contains(&(from.to_string(), to.to_string())))
+            .collect();
+        assert_eq!(simple_cycles(&g2).count(), 0);
+
+        g = vec![("A", "B"), ("B", "C")].into_iter().collect();
+        assert!(minimum_feedback_arc_set(&g).is_empty());
+    }
+
+    #[test]
+    fn test_minimum_feedback_vertex_set() {
+        // two triangles sharing C, a self-loop on F hanging off them
+        let g: DiGraph = vec![
+            ("A", "B"),
+            ("B", "C"),
+            ("C", "A"),
+            ("C", "D"),
+            ("D", "E"),
+            ("E", "C"),
+            ("E", "F"),
+            ("F", "F"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(minimum_feedback_vertex_set(&g), vec!["C", "F"]);
+
+        // two disjoint cycles need one node each
+        let g: DiGraph = vec![("A", "B"), ("B", "A"), ("C", "D"), ("D", "E"), ("E", "C")]
+            .into_iter()
+            .collect();
+        let nodes = minimum_feedback_vertex_set(&g);
+        assert_eq!(nodes.len(), 2);
+        let g2: DiGraph = g
+            .edges()
+            .filter(|&(from, to)| !nodes.iter().any(|n| n == from || n == to))
+            .collect();
+        assert_eq!(simple_cycles(&g2).count(), 0);
+
+        let dag: DiGraph = vec![("A", "B"), ("B", "C"), ("A", "C")]
+            .into_iter()
+            .collect();
+        assert!(minimum_feedback_vertex_set(&dag).is_empty());
+    }
+}
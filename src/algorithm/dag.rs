@@ -0,0 +1,210 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Path algorithms for directed acyclic graphs that run in linear time by
+//! visiting nodes in topological order. Edges without a weight count as 1 and
+//! negative weights are allowed.
+
+use super::{cycles::strongly_connected, path::Path, topsort::topsort};
+use crate::{
+    error::GraphError,
+    graph::{GraphBase, Node, Weighted},
+};
+use std::collections::{HashMap, HashSet};
+
+/// Topological order of all nodes, or `GraphError::CycleDetected` with the
+/// sorted names of the nodes on a cycle. Nodes that cannot be ordered only
+/// because a cycle leads to them are left out.
+pub fn topological_order<G: GraphBase>(graph: &G) -> Result<Vec<String>, GraphError> {
+    let order = topsort(graph);
+    if order.len() < graph.node_count() {
+        let sorted: HashSet<&str> = order.iter().map(|name| name.as_str()).collect();
+        let unsorted: Vec<&str> = graph
+            .node_names()
+            .filter(|name| !sorted.contains(name))
+            .collect();
+        let index: HashMap<&str, usize> = unsorted
+            .iter()
+            .enumerate()
+            .map(|(i, &name)| (name, i))
+            .collect();
+        let adj: Vec<Vec<usize>> = unsorted
+            .iter()
+            .map(|&name| {
+                let node = graph.node(name).unwrap();
+                node.successor_names()
+                    .filter_map(|succ| index.get(succ).copied())
+                    .collect()
+            })
+            .collect();
+
+        // components of several nodes, and nodes with a self-loop
+        let all: Vec<usize> = (0..unsorted.len()).collect();
+        let mut cyclic: Vec<String> = strongly_connected(&adj, &all)
+            .into_iter()
+            .flatten()
+            .chain(all.iter().copied().filter(|&v| adj[v].contains(&v)))
+            .map(|v| unsorted[v].to_string())
+            .collect();
+        cyclic.sort();
+        cyclic.dedup();
+        return Err(GraphError::CycleDetected(cyclic));
+    }
+    Ok(order)
+}
+
+fn weight<G: Weighted>(graph: &G, from: &str, to: &str) -> i64 {
+    graph.edge_weight(from, to).unwrap_or(1)
+}
+
+/// Shortest distances from `source` to every node reachable from it.
+pub fn dag_shortest_path<G: Weighted>(
+    graph: &G,
+    source: &str,
+) -> Result<HashMap<String, i64>, GraphError> {
+    if graph.node(source).is_none() {
+        return Err(GraphError::NotFoundNode(source.to_string()));
+    }
+    let order = topological_order(graph)?;
+
+    let mut dist: HashMap<String, i64> = HashMap::new();
+    dist.insert(source.to_string(), 0);
+    for name in order.iter() {
+        let base = match dist.get(name) {
+            Some(&base) => base,
+            None => continue,
+        };
+        for succ in graph.node(name).unwrap().successor_names() {
+            let new_dist = base + weight(graph, name, succ);
+            if dist.get(succ).is_none_or(|&cur| new_dist < cur) {
+                dist.insert(succ.to_string(), new_dist);
+            }
+        }
+    }
+    Ok(dist)
+}
+
+/// The path of greatest total weight in the graph, which may start at any node.
+/// Ties are broken by graph order. An empty graph has an empty path.
+pub fn dag_longest_path<G: Weighted>(graph: &G) -> Result<Path, GraphError> {
+    Ok(Path::from_nodes(graph, longest_path(graph)?.0))
+}
+
+/// Total weight of the path returned by [`dag_longest_path`].
+pub fn dag_longest_path_length<G: Weighted>(graph: &G) -> Result<i64, GraphError> {
+    Ok(longest_path(graph)?.1)
+}
+
+fn longest_path<G: Weighted>(graph: &G) -> Result<(Vec<String>, i64), GraphError> {
+    let order = topological_order(graph)?;
+
+    // dist[v]: weight of the longest path ending at v; pred[v]: previous node on it
+    let mut dist: HashMap<&str, i64> = HashMap::new();
+    let mut pred: HashMap<&str, &str> = HashMap::new();
+    for name in order.iter() {
+        let mut best: Option<(i64, &str)> = None;
+        for from in graph.node(name).unwrap().predecessor_names() {
+            let length = dist[from] + weight(graph, from, name);
+            if best.is_none_or(|(cur, _)| length > cur) {
+                best = Some((length, from));
+            }
+        }
+        match best {
+            Some((length, from)) if length >= 0 => {
+                dist.insert(name.as_str(), length);
+                pred.insert(name.as_str(), from);
+            }
+            // a path starting here beats one with a negative weight
+            _ => {
+                dist.insert(name.as_str(), 0);
+            }
+        }
+    }
+
+    let mut end = match order.first() {
+        Some(name) => name.as_str(),
+        None => return Ok((Vec::new(), 0)),
+    };
+    for name in order.iter() {
+        if dist[name.as_str()] > dist[end] {
+            end = name.as_str();
+        }
+    }
+
+    let length = dist[end];
+    let mut path = vec![end.to_string()];
+    while let Some(&from) = pred.get(end) {
+        path.push(from.to_string());
+        end = from;
+    }
+    path.reverse();
+    Ok((path, length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    fn sample() -> DiGraph {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 3);
+        g.add_weighted_edge("A", "C", 2);
+        g.add_weighted_edge("B", "D", 4);
+        g.add_weighted_edge("C", "D", 1);
+        g.add_weighted_edge("C", "E", 10);
+        g.add_edge(Some("D"), Some("E"));
+        g
+    }
+
+    #[test]
+    fn test_dag_shortest_path() {
+        let g = sample();
+        let actual = dag_shortest_path(&g, "A").unwrap();
+        let expected: HashMap<String, i64> = vec![("A", 0), ("B", 3), ("C", 2), ("D", 3), ("E", 4)]
+            .into_iter()
+            .map(|(x, y)| (x.to_string(), y))
+            .collect();
+        assert_eq!(actual, expected);
+
+        assert_eq!(dag_shortest_path(&g, "D").unwrap().len(), 2);
+        assert!(matches!(
+            dag_shortest_path(&g, "Z"),
+            Err(GraphError::NotFoundNode(_))
+        ));
+    }
+
+    #[test]
+    fn test_dag_longest_path() {
+        let mut g = sample();
+        let path = dag_longest_path(&g).unwrap();
+        assert_eq!(path.nodes(), ["A", "C", "E"]);
+        assert_eq!(path.total_weight(), dag_longest_path_length(&g).unwrap());
+        assert_eq!(path.total_weight(), 12);
+
+        // a negative edge is not worth taking
+        g.add_weighted_edge("X", "A", -5);
+        assert_eq!(dag_longest_path(&g).unwrap().nodes(), ["A", "C", "E"]);
+
+        assert!(dag_longest_path(&DiGraph::new(None)).unwrap().is_empty());
+
+        g.add_edge(Some("E"), Some("A"));
+        match dag_longest_path(&g) {
+            Err(GraphError::CycleDetected(names)) => {
+                assert_eq!(names, vec!["A", "B", "C", "D", "E"])
+            }
+            _ => panic!("expected a cycle"),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Collections of graphs that keep one graph per isomorphism class.
+
+use super::isomorphism::DiGraphMatcher;
+use crate::graph::{DiGraph, GraphBase, Node, Ported};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Refinement rounds of [`weisfeiler_lehman_hash`] used by [`GraphDedup`].
+pub const DEFAULT_WL_ITERATIONS: usize = 3;
+
+/// A hash that isomorphic graphs share, after `iterations` rounds of
+/// Weisfeiler-Lehman refinement: every node starts with a label made of its
+/// weight and degrees, and each round replaces it with a hash of the label and
+/// the sorted labels of its predecessors and successors.
+///
+/// Node weights are compared as the matcher compares them by default; edge
+/// weights are ignored. Different hashes rule out isomorphism, equal ones do
+/// not prove it. The hash is stable within a process, not across Rust
+/// releases, so it should not be stored.
+pub fn weisfeiler_lehman_hash<G: GraphBase>(graph: &G, iterations: usize) -> u64 {
+    let index: HashMap<&str, usize> = graph
+        .node_names()
+        .enumerate()
+        .map(|(i, name)| (name, i))
+        .collect();
+    let preds: Vec<Vec<usize>> = graph
+        .nodes()
+        .map(|node| node.predecessor_names().map(|name| index[name]).collect())
+        .collect();
+    let succs: Vec<Vec<usize>> = graph
+        .nodes()
+        .map(|node| node.successor_names().map(|name| index[name]).collect())
+        .collect();
+
+    let mut labels: Vec<u64> = graph
+        .nodes()
+        .map(|node| {
+            let self_loop = node.successor_names().any(|name| name == node.name());
+            hash(&(
+                node.weight(),
+                node.in_degree(),
+                node.out_degree(),
+                self_loop,
+            ))
+        })
+        .collect();
+    for _ in 0..iterations {
+        labels = (0..labels.len())
+            .map(|v| {
+                let sorted = |nbrs: &[usize]| {
+                    let mut nbrs: Vec<u64> = nbrs.iter().map(|&w| labels[w]).collect();
+                    nbrs.sort_unstable();
+                    nbrs
+                };
+                hash(&(labels[v], sorted(&preds[v]), sorted(&succs[v])))
+            })
+            .collect();
+    }
+    labels.sort_unstable();
+    hash(&labels)
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Outcome of [`GraphDedup::insert`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupResult {
+    /// The graph was kept under this id.
+    New(usize),
+    /// The graph is isomorphic to the one kept under this id, and was dropped.
+    DuplicateOf(usize),
+}
+
+/// Graphs kept up to isomorphism: inserting a graph isomorphic to one already
+/// kept returns the id of that one instead.
+///
+/// Graphs are bucketed by [`weisfeiler_lehman_hash`], so a new graph is only
+/// compared with the isomorphism matcher against those in its bucket, which
+/// for small graphs are nearly always isomorphic to it. Ids are consecutive
+/// from 0 in insertion order.
+pub struct GraphDedup<G: GraphBase = DiGraph> {
+    graphs: Vec<G>,
+    buckets: HashMap<u64, Vec<usize>>,
+    iterations: usize,
+}
+impl<G: Ported> GraphDedup<G> {
+    pub fn new() -> Self {
+        GraphDedup::with_iterations(DEFAULT_WL_ITERATIONS)
+    }
+
+    /// Hash with `iterations` rounds of refinement. More rounds tell apart more
+    /// graphs before the matcher runs, at a cost to every insertion.
+    pub fn with_iterations(iterations: usize) -> Self {
+        GraphDedup {
+            graphs: Vec::new(),
+            buckets: HashMap::new(),
+            iterations,
+        }
+    }
+
+    /// Keep the graph unless it is isomorphic to a graph already kept.
+    pub fn insert(&mut self, graph: G) -> DedupResult {
+        let key = weisfeiler_lehman_hash(&graph, self.iterations);
+        if let Some(id) = self.find_in(key, &graph) {
+            return DedupResult::DuplicateOf(id);
+        }
+        let id = self.graphs.len();
+        self.graphs.push(graph);
+        self.buckets.entry(key).or_default().push(id);
+        DedupResult::New(id)
+    }
+
+    /// The id of a kept graph isomorphic to `graph`, if any.
+    pub fn find(&self, graph: &G) -> Option<usize> {
+        self.find_in(weisfeiler_lehman_hash(graph, self.iterations), graph)
+    }
+
+    fn find_in(&self, key: u64, graph: &G) -> Option<usize> {
+        self.buckets.get(&key)?.iter().copied().find(|&id| {
+            let mut matcher = DiGraphMatcher::new(&self.graphs[id], graph);
+            matcher.is_isomorphic()
+        })
+    }
+
+    pub fn get(&self, id: usize) -> Option<&G> {
+        self.graphs.get(id)
+    }
+
+    /// The kept graphs, in order of their ids.
+    pub fn graphs(&self) -> impl Iterator<Item = &G> {
+        self.graphs.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.graphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.graphs.is_empty()
+    }
+
+    /// Take the kept graphs, in order of their ids.
+    pub fn into_graphs(self) -> Vec<G> {
+        self.graphs
+    }
+}
+impl<G: Ported> Default for GraphDedup<G> {
+    fn default() -> Self {
+        GraphDedup::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiNode;
+
+    fn path(names: &[&str]) -> DiGraph {
+        names.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    }
+
+    #[test]
+    fn test_weisfeiler_lehman_hash() {
+        let g = path(&["a", "b", "c"]);
+        let h = path(&["z", "y", "x"]);
+        assert_eq!(weisfeiler_lehman_hash(&g, 3), weisfeiler_lehman_hash(&h, 3));
+        let reversed: DiGraph = vec![("a", "b"), ("c", "b")].into_iter().collect();
+        assert_ne!(
+            weisfeiler_lehman_hash(&g, 3),
+            weisfeiler_lehman_hash(&reversed, 3)
+        );
+
+        let mut weighted = g.clone();
+        weighted.add_node(DiNode::new("d", Some("add".to_string())));
+        let mut other = g.clone();
+        other.add_node(DiNode::new("d", Some("mul".to_string())));
+        assert_ne!(
+            weisfeiler_lehman_hash(&weighted, 3),
+            weisfeiler_lehman_hash(&other, 3)
+        );
+    }
+
+    #[test]
+    fn test_graph_dedup() {
+        let mut dedup = GraphDedup::new();
+        assert_eq!(dedup.insert(path(&["a", "b", "c"])), DedupResult::New(0));
+        assert_eq!(
+            dedup.insert(path(&["x", "y", "z"])),
+            DedupResult::DuplicateOf(0)
+        );
+        let cycle: DiGraph = vec![("a", "b"), ("b", "c"), ("c", "a")]
+            .into_iter()
+            .collect();
+        assert_eq!(dedup.insert(cycle), DedupResult::New(1));
+
+        // two disjoint triangles and a hexagon are not told apart by their
+        // hashes, so the matcher must be
+        let triangles: DiGraph = vec![
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("d", "e"),
+            ("e", "f"),
+            ("f", "d"),
+        ]
+        .into_iter()
+        .collect();
+        let hexagon = path(&["a", "b", "c", "d", "e", "f", "a"]);
+        assert_eq!(
+            weisfeiler_lehman_hash(&triangles, 3),
+            weisfeiler_lehman_hash(&hexagon, 3)
+        );
+        assert_eq!(dedup.insert(triangles), DedupResult::New(2));
+        assert_eq!(dedup.insert(hexagon.clone()), DedupResult::New(3));
+        assert_eq!(dedup.find(&hexagon), Some(3));
+        assert_eq!(dedup.find(&path(&["a"])), None);
+
+        assert_eq!(dedup.len(), 4);
+        assert_eq!(dedup.get(1).unwrap().edge_count(), 3);
+        assert_eq!(dedup.graphs().count(), 4);
+        assert_eq!(dedup.into_graphs().len(), 4);
+    }
+}
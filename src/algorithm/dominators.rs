@@ -0,0 +1,234 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dominators of control-flow graphs: a node `d` dominates `n` if every path
+//! from the root to `n` goes through `d`.
+
+use crate::{
+    error::GraphError,
+    graph::{GraphBase, Node},
+};
+use std::collections::{HashMap, HashSet};
+
+/// The immediate dominator of every node reachable from `root`, that is its
+/// closest strict dominator, which makes up the dominator tree. The root is
+/// its own immediate dominator.
+///
+/// Uses the iterative algorithm of Cooper, Harvey and Kennedy. Fails with
+/// `GraphError::NotFoundNode` if `root` is not in the graph.
+pub fn immediate_dominators<G: GraphBase>(
+    graph: &G,
+    root: &str,
+) -> Result<HashMap<String, String>, GraphError> {
+    let (order, idom) = dominator_tree(graph, root)?;
+    Ok(idom
+        .iter()
+        .enumerate()
+        .map(|(v, &d)| (order[v].to_string(), order[d].to_string()))
+        .collect())
+}
+
+/// The dominance frontier of every node reachable from `root`: the nodes where
+/// its dominance ends, each having a predecessor it dominates without strictly
+/// dominating the node itself. These are where SSA construction places phi
+/// functions for the variables assigned in a block.
+///
+/// Fails with `GraphError::NotFoundNode` if `root` is not in the graph.
+pub fn dominance_frontiers<G: GraphBase>(
+    graph: &G,
+    root: &str,
+) -> Result<HashMap<String, HashSet<String>>, GraphError> {
+    let (order, idom) = dominator_tree(graph, root)?;
+    let index: HashMap<&str, usize> = order.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+
+    let mut frontiers = vec![HashSet::new(); order.len()];
+    for (b, &name) in order.iter().enumerate() {
+        let preds: Vec<usize> = graph
+            .node(name)
+            .unwrap()
+            .predecessor_names()
+            .filter_map(|pred| index.get(pred).copied())
+            .collect();
+        if preds.len() < 2 {
+            continue;
+        }
+        for mut runner in preds {
+            while runner != idom[b] {
+                frontiers[runner].insert(name.to_string());
+                runner = idom[runner];
+            }
+        }
+    }
+    Ok(order
+        .iter()
+        .zip(frontiers)
+        .map(|(name, frontier)| (name.to_string(), frontier))
+        .collect())
+}
+
+/// The nodes reachable from `root` in postorder, so the root comes last, and
+/// the immediate dominator of each as an index into them.
+fn dominator_tree<'a, G: GraphBase>(
+    graph: &'a G,
+    root: &'a str,
+) -> Result<(Vec<&'a str>, Vec<usize>), GraphError> {
+    let root_node = graph
+        .node(root)
+        .ok_or_else(|| GraphError::NotFoundNode(root.to_string()))?;
+
+    let mut order = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut visited = HashSet::from([root]);
+    let mut stack = vec![(root, root_node.successor_names())];
+    while let Some((name, succs)) = stack.last_mut() {
+        match succs.find(|succ| !visited.contains(succ)) {
+            Some(succ) => {
+                visited.insert(succ);
+                stack.push((succ, graph.node(succ).unwrap().successor_names()));
+            }
+            None => {
+                index.insert(name, order.len());
+                order.push(*name);
+                stack.pop();
+            }
+        }
+    }
+
+    let preds: Vec<Vec<usize>> = order
+        .iter()
+        .map(|&name| {
+            let node = graph.node(name).unwrap();
+            node.predecessor_names()
+                .filter_map(|pred| index.get(pred).copied())
+                .collect()
+        })
+        .collect();
+
+    // postorder numbers grow towards the root, so walk up to the larger one
+    let intersect = |idom: &[Option<usize>], mut a: usize, mut b: usize| {
+        while a != b {
+            while a < b {
+                a = idom[a].unwrap();
+            }
+            while b < a {
+                b = idom[b].unwrap();
+            }
+        }
+        a
+    };
+
+    let start = order.len() - 1;
+    let mut idom = vec![None; order.len()];
+    idom[start] = Some(start);
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for b in (0..start).rev() {
+            let mut new_idom = None;
+            for &p in &preds[b] {
+                if idom[p].is_some() {
+                    new_idom = Some(match new_idom {
+                        Some(d) => intersect(&idom, p, d),
+                        None => p,
+                    });
+                }
+            }
+            if new_idom != idom[b] {
+                idom[b] = new_idom;
+                changed = true;
+            }
+        }
+    }
+    Ok((order, idom.into_iter().map(Option::unwrap).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    // the control-flow graph from Cooper, Harvey and Kennedy, figure 4
+    fn cfg() -> DiGraph {
+        vec![
+            ("6", "5"),
+            ("6", "4"),
+            ("5", "1"),
+            ("4", "2"),
+            ("4", "3"),
+            ("1", "2"),
+            ("2", "1"),
+            ("2", "3"),
+            ("3", "2"),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_immediate_dominators() {
+        let mut g = cfg();
+        g.add_edge(Some("unreachable"), Some("1"));
+        let idom = immediate_dominators(&g, "6").unwrap();
+        assert_eq!(idom.len(), 6);
+        assert!(idom.values().all(|d| d == "6"));
+
+        // a diamond with a loop back to the top
+        let g: DiGraph = vec![
+            ("entry", "head"),
+            ("head", "then"),
+            ("head", "else"),
+            ("then", "join"),
+            ("else", "join"),
+            ("join", "head"),
+            ("join", "exit"),
+        ]
+        .into_iter()
+        .collect();
+        let idom = immediate_dominators(&g, "entry").unwrap();
+        assert_eq!(idom["entry"], "entry");
+        assert_eq!(idom["then"], "head");
+        assert_eq!(idom["join"], "head");
+        assert_eq!(idom["exit"], "join");
+        assert!(immediate_dominators(&g, "Z").is_err());
+    }
+
+    #[test]
+    fn test_dominance_frontiers() {
+        let g = cfg();
+        let df = dominance_frontiers(&g, "6").unwrap();
+        let set = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<HashSet<_>>();
+        assert_eq!(df["6"], set(&[]));
+        assert_eq!(df["5"], set(&["1"]));
+        assert_eq!(df["4"], set(&["2", "3"]));
+        assert_eq!(df["1"], set(&["2"]));
+        assert_eq!(df["2"], set(&["1", "3"]));
+        assert_eq!(df["3"], set(&["2"]));
+
+        let g: DiGraph = vec![
+            ("entry", "head"),
+            ("head", "then"),
+            ("head", "else"),
+            ("then", "join"),
+            ("else", "join"),
+            ("join", "head"),
+        ]
+        .into_iter()
+        .collect();
+        let df = dominance_frontiers(&g, "entry").unwrap();
+        assert_eq!(df["then"], set(&["join"]));
+        assert_eq!(df["join"], set(&["head"]));
+        assert_eq!(df["head"], set(&["head"]));
+        assert_eq!(df["entry"], set(&[]));
+    }
+}
@@ -0,0 +1,388 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Maximum flows and minimum cuts, with edge weights as capacities.
+
+use crate::{
+    error::GraphError,
+    graph::{Node, Weighted},
+};
+use std::collections::{HashMap, VecDeque};
+
+/// How [`maximum_flow`] finds a flow. Both give a flow of the same value,
+/// though not always the same flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlowAlgorithm {
+    /// Edmonds-Karp: augment along shortest residual paths, in `O(V E^2)`
+    /// time. Fast on sparse graphs and small flows.
+    #[default]
+    AugmentingPath,
+    /// FIFO push-relabel with the gap heuristic, in `O(V^3)` time. Usually
+    /// faster on dense graphs.
+    PushRelabel,
+}
+
+/// A maximum flow from a source to a sink.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxFlow {
+    pub value: i64,
+    /// `(from, to, flow)` of every edge carrying flow, in graph order.
+    pub flows: Vec<(String, String, i64)>,
+    /// Nodes the source still reaches through edges with spare capacity, in
+    /// graph order: the source side of a minimum cut, whose capacity is
+    /// `value`.
+    pub source_side: Vec<String>,
+}
+impl MaxFlow {
+    /// Flow on the edge from `from` to `to`, 0 if it carries none.
+    pub fn flow(&self, from: &str, to: &str) -> i64 {
+        self.flows
+            .iter()
+            .find(|(a, b, _)| a == from && b == to)
+            .map_or(0, |&(_, _, flow)| flow)
+    }
+}
+
+/// A flow of largest value from `source` to `sink`, where the weight of every
+/// edge is its capacity. Edges without a weight have capacity 1, negative
+/// capacities count as 0 and self-loops are ignored. The flow is 0 when
+/// `source` is `sink`.
+///
+/// Fails with `GraphError::NotFoundNode` if either node is missing.
+pub fn maximum_flow<G: Weighted>(
+    graph: &G,
+    source: &str,
+    sink: &str,
+    algorithm: FlowAlgorithm,
+) -> Result<MaxFlow, GraphError> {
+    for name in [source, sink] {
+        if graph.node(name).is_none() {
+            return Err(GraphError::NotFoundNode(name.to_string()));
+        }
+    }
+    let names: Vec<&str> = graph.node_names().collect();
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+
+    let mut network = Network::new(names.len());
+    let mut edges = Vec::new();
+    for node in graph.nodes() {
+        for succ in node.successor_names() {
+            if succ != node.name() {
+                let capacity = graph.edge_weight(node.name(), succ).unwrap_or(1).max(0);
+                let arc = network.add_arc(index[node.name()], index[succ], capacity);
+                edges.push((node.name(), succ, arc, capacity));
+            }
+        }
+    }
+
+    let (s, t) = (index[source], index[sink]);
+    let value = if s == t {
+        0
+    } else {
+        match algorithm {
+            FlowAlgorithm::AugmentingPath => network.augmenting_path(s, t),
+            FlowAlgorithm::PushRelabel => network.push_relabel(s, t),
+        }
+    };
+
+    let flows = edges
+        .into_iter()
+        .map(|(from, to, arc, capacity)| (from, to, capacity - network.cap[arc]))
+        .filter(|&(_, _, flow)| flow > 0)
+        .map(|(from, to, flow)| (from.to_string(), to.to_string(), flow))
+        .collect();
+    let reached = network.residual_reach(s);
+    let source_side = names
+        .iter()
+        .zip(reached)
+        .filter(|&(_, reached)| reached)
+        .map(|(&name, _)| name.to_string())
+        .collect();
+    Ok(MaxFlow {
+        value,
+        flows,
+        source_side,
+    })
+}
+
+/// Residual network: arc `e ^ 1` is the reverse of arc `e`.
+struct Network {
+    arcs: Vec<Vec<usize>>,
+    to: Vec<usize>,
+    cap: Vec<i64>,
+}
+impl Network {
+    fn new(n: usize) -> Self {
+        Network {
+            arcs: vec![Vec::new(); n],
+            to: Vec::new(),
+            cap: Vec::new(),
+        }
+    }
+
+    fn add_arc(&mut self, u: usize, v: usize, capacity: i64) -> usize {
+        let e = self.to.len();
+        self.to.extend([v, u]);
+        self.cap.extend([capacity, 0]);
+        self.arcs[u].push(e);
+        self.arcs[v].push(e + 1);
+        e
+    }
+
+    fn push(&mut self, e: usize, amount: i64) {
+        self.cap[e] -= amount;
+        self.cap[e ^ 1] += amount;
+    }
+
+    /// Edmonds-Karp, returning the value of the flow.
+    fn augmenting_path(&mut self, s: usize, t: usize) -> i64 {
+        let mut value = 0;
+        loop {
+            // arc into every node on the shortest residual paths from `s`
+            let mut via = vec![None; self.arcs.len()];
+            let mut queue = VecDeque::from([s]);
+            while let Some(u) = queue.pop_front() {
+                for &e in self.arcs[u].iter() {
+                    let v = self.to[e];
+                    if self.cap[e] > 0 && v != s && via[v].is_none() {
+                        via[v] = Some(e);
+                        queue.push_back(v);
+                    }
+                }
+            }
+            if via[t].is_none() {
+                return value;
+            }
+
+            let mut path = Vec::new();
+            let mut v = t;
+            while let Some(e) = via[v] {
+                path.push(e);
+                v = self.to[e ^ 1];
+            }
+            let amount = path.iter().map(|&e| self.cap[e]).min().unwrap();
+            for e in path {
+                self.push(e, amount);
+            }
+            value += amount;
+        }
+    }
+
+    /// FIFO push-relabel with the gap heuristic, returning the value of the
+    /// flow. Excess that cannot reach `t` is pushed back to `s`, so the
+    /// preflow ends as a flow.
+    fn push_relabel(&mut self, s: usize, t: usize) -> i64 {
+        let n = self.arcs.len();
+        let mut height = vec![0; n];
+        let mut excess = vec![0i64; n];
+        // number of nodes at every height, for the gap heuristic
+        let mut count = vec![0usize; 2 * n + 1];
+        let mut current = vec![0; n];
+        let mut active = vec![false; n];
+        let mut queue = VecDeque::new();
+        height[s] = n;
+        count[0] = n - 1;
+        count[n] = 1;
+
+        for i in 0..self.arcs[s].len() {
+            let e = self.arcs[s][i];
+            let (v, amount) = (self.to[e], self.cap[e]);
+            if amount > 0 {
+                self.push(e, amount);
+                excess[s] -= amount;
+                excess[v] += amount;
+                if v != t && !active[v] {
+                    active[v] = true;
+                    queue.push_back(v);
+                }
+            }
+        }
+
+        while let Some(u) = queue.pop_front() {
+            active[u] = false;
+            while excess[u] > 0 {
+                if current[u] == self.arcs[u].len() {
+                    // relabel, just above the lowest neighbor with spare
+                    // capacity; one exists while `u` has excess
+                    let old = height[u];
+                    let lowest = self.arcs[u]
+                        .iter()
+                        .filter(|&&e| self.cap[e] > 0)
+                        .map(|&e| height[self.to[e]])
+                        .min()
+                        .unwrap();
+                    count[old] -= 1;
+                    height[u] = lowest + 1;
+                    if count[old] == 0 && old < n {
+                        // no node is left at height `old`, so nodes above it
+                        // cannot reach `t` any more
+                        for w in 0..n {
+                            if w != s && height[w] > old && height[w] < n {
+                                if w != u {
+                                    count[height[w]] -= 1;
+                                    count[n + 1] += 1;
+                                }
+                                height[w] = n + 1;
+                                current[w] = 0;
+                            }
+                        }
+                    }
+                    count[height[u]] += 1;
+                    current[u] = 0;
+                    continue;
+                }
+
+                let e = self.arcs[u][current[u]];
+                let v = self.to[e];
+                if self.cap[e] > 0 && height[u] == height[v] + 1 {
+                    let amount = excess[u].min(self.cap[e]);
+                    self.push(e, amount);
+                    excess[u] -= amount;
+                    excess[v] += amount;
+                    if v != s && v != t && !active[v] {
+                        active[v] = true;
+                        queue.push_back(v);
+                    }
+                } else {
+                    current[u] += 1;
+                }
+            }
+        }
+        excess[t]
+    }
+
+    /// Whether each node is reached from `s` through arcs with spare capacity.
+    fn residual_reach(&self, s: usize) -> Vec<bool> {
+        let mut reached = vec![false; self.arcs.len()];
+        reached[s] = true;
+        let mut stack = vec![s];
+        while let Some(u) = stack.pop() {
+            for &e in self.arcs[u].iter() {
+                let v = self.to[e];
+                if self.cap[e] > 0 && !reached[v] {
+                    reached[v] = true;
+                    stack.push(v);
+                }
+            }
+        }
+        reached
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, DiNode};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const ALGORITHMS: [FlowAlgorithm; 2] =
+        [FlowAlgorithm::AugmentingPath, FlowAlgorithm::PushRelabel];
+
+    #[test]
+    fn test_maximum_flow() {
+        // the network of Cormen et al., figure 26.1
+        let mut g = DiGraph::new(None);
+        for (from, to, capacity) in [
+            ("s", "v1", 16),
+            ("s", "v2", 13),
+            ("v2", "v1", 4),
+            ("v1", "v3", 12),
+            ("v3", "v2", 9),
+            ("v2", "v4", 14),
+            ("v4", "v3", 7),
+            ("v3", "t", 20),
+            ("v4", "t", 4),
+        ] {
+            g.add_weighted_edge(from, to, capacity);
+        }
+        for algorithm in ALGORITHMS {
+            let flow = maximum_flow(&g, "s", "t", algorithm).unwrap();
+            assert_eq!(flow.value, 23);
+            assert_eq!(flow.flow("v3", "t"), 19);
+            assert_eq!(flow.flow("t", "v3"), 0);
+            assert_eq!(flow.source_side, vec!["s", "v1", "v2", "v4"]);
+        }
+
+        // unweighted edges have capacity 1
+        let g: DiGraph = vec![("a", "b"), ("a", "c"), ("b", "d"), ("c", "d"), ("d", "d")]
+            .into_iter()
+            .collect();
+        for algorithm in ALGORITHMS {
+            let flow = maximum_flow(&g, "a", "d", algorithm).unwrap();
+            assert_eq!(flow.value, 2);
+            assert_eq!(flow.flows.len(), 4);
+            assert_eq!(maximum_flow(&g, "d", "a", algorithm).unwrap().value, 0);
+            assert_eq!(maximum_flow(&g, "a", "a", algorithm).unwrap().value, 0);
+        }
+        assert!(matches!(
+            maximum_flow(&g, "a", "z", FlowAlgorithm::default()),
+            Err(GraphError::NotFoundNode(name)) if name == "z"
+        ));
+    }
+
+    #[test]
+    fn test_maximum_flow_random() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..200 {
+            let n = rng.random_range(2..10);
+            let mut g = DiGraph::new(None);
+            for i in 0..n {
+                g.add_node(DiNode::new(&i.to_string(), None));
+            }
+            for i in 0..n {
+                for j in 0..n {
+                    if i != j && rng.random_bool(0.4) {
+                        let capacity = rng.random_range(0..10);
+                        g.add_weighted_edge(&i.to_string(), &j.to_string(), capacity);
+                    }
+                }
+            }
+            let sink = (n - 1).to_string();
+            let flows: Vec<MaxFlow> = ALGORITHMS
+                .iter()
+                .map(|&algorithm| maximum_flow(&g, "0", &sink, algorithm).unwrap())
+                .collect();
+            assert_eq!(flows[0].value, flows[1].value);
+
+            for flow in flows.iter() {
+                // capacities and conservation hold
+                let mut balance = vec![0; n];
+                for (from, to, amount) in flow.flows.iter() {
+                    assert!(*amount <= g.get_edge_weight(from, to).unwrap());
+                    balance[from.parse::<usize>().unwrap()] -= amount;
+                    balance[to.parse::<usize>().unwrap()] += amount;
+                }
+                assert_eq!(balance[0], -flow.value);
+                assert_eq!(balance[n - 1], flow.value);
+                assert!(balance[1..n - 1].iter().all(|&b| b == 0));
+
+                // the cut is saturated and as large as the flow
+                let cut: i64 = g
+                    .weighted_edges()
+                    .filter(|(from, to, _)| {
+                        flow.source_side.iter().any(|name| name == from)
+                            && !flow.source_side.iter().any(|name| name == to)
+                    })
+                    .map(|(_, _, capacity)| capacity.unwrap())
+                    .sum();
+                assert_eq!(cut, flow.value);
+                assert!(!flow.source_side.contains(&sink));
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Random graphs with known structure, e.g. to benchmark the matcher against
+//! a ground truth.
+//!
+//! Every generator draws from a seed, so the same settings always give the
+//! same graph.
+
+use crate::graph::{DiGraph, DiNode, Node, Weighted};
+use rand::{rngs::StdRng, seq::index, Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// Settings of [`planted_graph`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlantedConfig {
+    /// Nodes of the host graph, named `n0`, `n1`, ...
+    pub nodes: usize,
+    /// Random edges between distinct host nodes, drawn with replacement
+    /// before the copies are planted, so repeats make fewer edges.
+    pub edges: usize,
+    /// Inclusive range the weights of random edges are drawn from, or `None`
+    /// to leave them unweighted.
+    pub weights: Option<(i64, i64)>,
+    /// Node weights drawn uniformly for host nodes outside the copies; with
+    /// none, those nodes have no weight.
+    pub labels: Vec<String>,
+    /// Copies of the pattern to plant, on disjoint sets of host nodes.
+    pub copies: usize,
+    /// Chance of a copy being perturbed: one of its pattern edges, picked
+    /// uniformly, is left out of the host graph.
+    pub perturbation: f64,
+    pub seed: u64,
+}
+impl Default for PlantedConfig {
+    fn default() -> Self {
+        PlantedConfig {
+            nodes: 1000,
+            edges: 4000,
+            weights: Some((1, 10)),
+            labels: Vec::new(),
+            copies: 10,
+            perturbation: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+/// One copy of the pattern planted by [`planted_graph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlantedCopy {
+    /// Host node of every pattern node.
+    pub mapping: HashMap<String, String>,
+    /// The pattern edge left out of a perturbed copy, as pattern node names.
+    pub missing_edge: Option<(String, String)>,
+}
+
+/// A host graph with the copies planted in it.
+#[derive(Debug, Clone)]
+pub struct PlantedGraph {
+    pub graph: DiGraph,
+    pub copies: Vec<PlantedCopy>,
+}
+
+/// A random weighted host graph with `config.copies` copies of `pattern`
+/// planted in it, and where they are.
+///
+/// Each copy takes over host nodes picked at random: they get the weights of
+/// the pattern nodes, and the pattern edges are added between them with the
+/// pattern edge weights. Every copy that is not perturbed is then a match of
+/// `pattern` in the host under its mapping, for subgraph monomorphism; random
+/// edges among its nodes may keep it from being an induced match, and the host
+/// may hold matches besides the planted ones.
+///
+/// Panics if the copies need more nodes than the host has.
+pub fn planted_graph<G: Weighted>(pattern: &G, config: &PlantedConfig) -> PlantedGraph {
+    let names: Vec<&str> = pattern.node_names().collect();
+    let needed = names.len() * config.copies;
+    assert!(
+        needed <= config.nodes,
+        "{} copies of {} nodes do not fit in {} nodes",
+        config.copies,
+        names.len(),
+        config.nodes
+    );
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    let mut graph = DiGraph::new(None);
+    for i in 0..config.nodes {
+        let weight = if config.labels.is_empty() {
+            None
+        } else {
+            Some(config.labels[rng.random_range(0..config.labels.len())].clone())
+        };
+        graph.add_node(DiNode::new(&format!("n{}", i), weight));
+    }
+    if config.nodes > 1 {
+        for _ in 0..config.edges {
+            let from = rng.random_range(0..config.nodes);
+            let to = (from + rng.random_range(1..config.nodes)) % config.nodes;
+            let (from, to) = (format!("n{}", from), format!("n{}", to));
+            match config.weights {
+                Some((low, high)) => {
+                    graph.add_weighted_edge(&from, &to, rng.random_range(low..=high))
+                }
+                None => graph.add_edge(Some(from.as_str()), Some(to.as_str())),
+            }
+        }
+    }
+
+    let pattern_edges: Vec<(&str, &str)> = pattern
+        .nodes()
+        .flat_map(|node| node.successor_names().map(move |succ| (node.name(), succ)))
+        .collect();
+    let hosts = index::sample(&mut rng, config.nodes, needed).into_vec();
+    let mut copies = Vec::with_capacity(config.copies);
+    for chunk in hosts.chunks(names.len().max(1)).take(config.copies) {
+        let mapping: HashMap<String, String> = names
+            .iter()
+            .zip(chunk)
+            .map(|(&name, &host)| (name.to_string(), format!("n{}", host)))
+            .collect();
+        for &name in names.iter() {
+            let weight = pattern.node(name).unwrap().weight().map(String::from);
+            graph
+                .get_node_mut(&mapping[name])
+                .unwrap()
+                .set_weight(weight);
+        }
+
+        let perturbed = !pattern_edges.is_empty() && rng.random_bool(config.perturbation);
+        let missing = if perturbed {
+            Some(pattern_edges[rng.random_range(0..pattern_edges.len())])
+        } else {
+            None
+        };
+        for &(from, to) in pattern_edges.iter() {
+            let (host_from, host_to) = (mapping[from].as_str(), mapping[to].as_str());
+            if missing == Some((from, to)) {
+                graph.remove_edge(host_from, host_to);
+                continue;
+            }
+            match pattern.edge_weight(from, to) {
+                Some(weight) => graph.add_weighted_edge(host_from, host_to, weight),
+                None => graph.add_edge(Some(host_from), Some(host_to)),
+            }
+        }
+        copies.push(PlantedCopy {
+            mapping,
+            missing_edge: missing.map(|(from, to)| (from.to_string(), to.to_string())),
+        });
+    }
+    PlantedGraph { graph, copies }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::isomorphism::DiGraphMatcher;
+
+    fn pattern() -> DiGraph {
+        let mut pattern = DiGraph::new(None);
+        pattern.add_node(DiNode::new("x", Some("add".to_string())));
+        pattern.add_weighted_edge("x", "y", 7);
+        pattern.add_edge(Some("y"), Some("z"));
+        pattern.add_edge(Some("z"), Some("x"));
+        pattern
+    }
+
+    #[test]
+    fn test_planted_graph() {
+        let pattern = pattern();
+        let config = PlantedConfig {
+            nodes: 200,
+            edges: 400,
+            labels: vec!["mul".to_string(), "sub".to_string()],
+            copies: 12,
+            perturbation: 0.5,
+            seed: 7,
+            ..PlantedConfig::default()
+        };
+        let planted = planted_graph(&pattern, &config);
+        let g = &planted.graph;
+        assert_eq!(g.node_count(), 200);
+        assert_eq!(planted.copies.len(), 12);
+        let perturbed = planted
+            .copies
+            .iter()
+            .filter(|copy| copy.missing_edge.is_some())
+            .count();
+        assert!(perturbed > 0 && perturbed < 12);
+
+        let mut found = Vec::new();
+        DiGraphMatcher::new(g, &pattern).subgraph_monomorphism_iter(&mut found);
+        for copy in planted.copies.iter() {
+            let host = |name: &str| copy.mapping[name].as_str();
+            assert_eq!(g.get_node(host("x")).unwrap().weight(), Some("add"));
+            match &copy.missing_edge {
+                None => {
+                    assert_eq!(g.get_edge_weight(host("x"), host("y")), Some(7));
+                    assert!(g.contains_edge(host("y"), host("z")));
+                    assert!(found.contains(&copy.mapping));
+                }
+                Some((from, to)) => assert!(!g.contains_edge(host(from), host(to))),
+            }
+        }
+
+        // the same settings give the same graph
+        let again = planted_graph(&pattern, &config);
+        assert_eq!(again.graph.to_string(), g.to_string());
+        assert_eq!(again.copies, planted.copies);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_planted_graph_too_many_copies() {
+        let config = PlantedConfig {
+            nodes: 5,
+            copies: 2,
+            ..PlantedConfig::default()
+        };
+        planted_graph(&pattern(), &config);
+    }
+}
@@ -0,0 +1,311 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Connectivity and reachability kept up to date as nodes and edges are
+//! inserted, for graphs that grow over time, e.g. while ingesting a stream.
+//!
+//! Both structures only grow; they do not support removals. To follow a
+//! [`DiGraph`](crate::graph::DiGraph), feed them its `NodeAdded` and
+//! `EdgeAdded` events from [`DiGraph::subscribe`](crate::graph::DiGraph::subscribe).
+
+use crate::{
+    error::{GraphError, ResultExt},
+    graph::{GraphBase, Node},
+};
+use std::collections::HashMap;
+
+/// Weakly connected components under insertions, as a union-find with path
+/// halving and union by size, so each operation takes nearly constant time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IncrementalConnectivity {
+    index: HashMap<String, usize>,
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+impl IncrementalConnectivity {
+    pub fn new() -> Self {
+        IncrementalConnectivity::default()
+    }
+
+    /// The components of a graph, to keep up to date from here on.
+    pub fn from_graph<G: GraphBase>(graph: &G) -> Self {
+        let mut connectivity = IncrementalConnectivity::new();
+        for node in graph.nodes() {
+            for succ in node.successor_names() {
+                connectivity.add_edge(node.name(), succ);
+            }
+            connectivity.add_node(node.name());
+        }
+        connectivity
+    }
+
+    /// Add a node in a component of its own, returning whether it is new.
+    pub fn add_node(&mut self, name: &str) -> bool {
+        if self.index.contains_key(name) {
+            return false;
+        }
+        let id = self.parent.len();
+        self.index.insert(name.to_string(), id);
+        self.parent.push(id);
+        self.size.push(1);
+        self.components += 1;
+        true
+    }
+
+    /// Add an edge, creating missing endpoints, and return whether it joined
+    /// two components.
+    pub fn add_edge(&mut self, from: &str, to: &str) -> bool {
+        self.add_node(from);
+        self.add_node(to);
+        let (a, b) = (self.find(self.index[from]), self.find(self.index[to]));
+        if a == b {
+            return false;
+        }
+        let (big, small) = if self.size[a] < self.size[b] {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+        self.components -= 1;
+        true
+    }
+
+    /// Whether a path joins two nodes, with edge direction ignored. Unknown
+    /// nodes are connected to nothing.
+    pub fn connected(&mut self, a: &str, b: &str) -> bool {
+        match (self.index.get(a), self.index.get(b)) {
+            (Some(&a), Some(&b)) => self.find(a) == self.find(b),
+            _ => false,
+        }
+    }
+
+    /// Number of nodes in the component of a node.
+    pub fn component_size(&mut self, name: &str) -> Option<usize> {
+        let id = *self.index.get(name)?;
+        let root = self.find(id);
+        Some(self.size[root])
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.parent.len()
+    }
+
+    fn find(&mut self, mut v: usize) -> usize {
+        while self.parent[v] != v {
+            self.parent[v] = self.parent[self.parent[v]];
+            v = self.parent[v];
+        }
+        v
+    }
+}
+
+/// Reachability in a DAG under insertions, as the transitive closure kept in
+/// a bitset per node.
+///
+/// Queries take constant time. Inserting an edge takes `O(V^2 / 64)` time at
+/// worst, and an edge that would close a cycle is refused.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IncrementalReachability {
+    index: HashMap<String, usize>,
+    names: Vec<String>,
+    // bit `w` of row `v` is set if `v` reaches `w` by a nonempty path
+    reach: Vec<Vec<u64>>,
+}
+impl IncrementalReachability {
+    pub fn new() -> Self {
+        IncrementalReachability::default()
+    }
+
+    /// The reachability of a DAG, to keep up to date from here on. Fails with
+    /// `GraphError::CycleDetected` if the graph has a cycle.
+    pub fn from_graph<G: GraphBase>(graph: &G) -> Result<Self, GraphError> {
+        let mut reachability = IncrementalReachability::new();
+        for name in graph.node_names() {
+            reachability.add_node(name);
+        }
+        for node in graph.nodes() {
+            for succ in node.successor_names() {
+                reachability
+                    .add_edge(node.name(), succ)
+                    .with_context(|| format!("adding edge {} -> {}", node.name(), succ))?;
+            }
+        }
+        Ok(reachability)
+    }
+
+    /// Add a node without edges, returning whether it is new.
+    pub fn add_node(&mut self, name: &str) -> bool {
+        if self.index.contains_key(name) {
+            return false;
+        }
+        let id = self.names.len();
+        self.index.insert(name.to_string(), id);
+        self.names.push(name.to_string());
+        let words = (id + 1).div_ceil(64);
+        if words > self.reach.first().map_or(0, Vec::len) {
+            for row in self.reach.iter_mut() {
+                row.resize(words, 0);
+            }
+        }
+        self.reach.push(vec![0; words]);
+        true
+    }
+
+    /// Add an edge, creating missing endpoints. If `to` already reaches
+    /// `from`, the edge would close a cycle, so it is not added and this fails
+    /// with `GraphError::CycleDetected` listing the nodes of the cycle in
+    /// insertion order.
+    pub fn add_edge(&mut self, from: &str, to: &str) -> Result<(), GraphError> {
+        self.add_node(from);
+        self.add_node(to);
+        let (u, v) = (self.index[from], self.index[to]);
+        if u == v || self.bit(v, u) {
+            let cycle = (0..self.names.len())
+                .filter(|&x| x == u || x == v || (self.bit(v, x) && self.bit(x, u)))
+                .map(|x| self.names[x].clone())
+                .collect();
+            return Err(GraphError::CycleDetected(cycle));
+        }
+        if self.bit(u, v) {
+            return Ok(());
+        }
+
+        // everything reaching `u`, and `u` itself, now reaches `v` and all
+        // that `v` reaches
+        let mut gained = self.reach[v].clone();
+        gained[v / 64] |= 1 << (v % 64);
+        for x in 0..self.names.len() {
+            if x == u || self.bit(x, u) {
+                for (word, add) in self.reach[x].iter_mut().zip(gained.iter()) {
+                    *word |= add;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a path leads from `from` to `to`. A node reaches itself; unknown
+    /// nodes reach nothing.
+    pub fn reaches(&self, from: &str, to: &str) -> bool {
+        match (self.index.get(from), self.index.get(to)) {
+            (Some(&u), Some(&v)) => u == v || self.bit(u, v),
+            _ => false,
+        }
+    }
+
+    /// The nodes a node reaches, itself excluded, in insertion order.
+    pub fn descendants(&self, name: &str) -> Option<Vec<String>> {
+        let u = *self.index.get(name)?;
+        Some(
+            (0..self.names.len())
+                .filter(|&w| self.bit(u, w))
+                .map(|w| self.names[w].clone())
+                .collect(),
+        )
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.names.len()
+    }
+
+    fn bit(&self, v: usize, w: usize) -> bool {
+        self.reach[v][w / 64] >> (w % 64) & 1 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, GraphEvent};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_incremental_connectivity() {
+        let g: DiGraph = vec![("a", "b"), ("c", "d")].into_iter().collect();
+        let mut cc = IncrementalConnectivity::from_graph(&g);
+        assert_eq!(cc.component_count(), 2);
+        assert!(cc.connected("b", "a"));
+        assert!(!cc.connected("a", "c"));
+        assert!(cc.add_edge("d", "a"));
+        assert!(!cc.add_edge("b", "c"));
+        assert!(cc.connected("a", "c"));
+        assert_eq!(cc.component_size("c"), Some(4));
+        assert!(cc.add_node("e"));
+        assert!(!cc.add_node("e"));
+        assert_eq!((cc.component_count(), cc.node_count()), (2, 5));
+        assert!(!cc.connected("e", "zz"));
+        assert_eq!(cc.component_size("zz"), None);
+
+        // kept in sync with a graph through its listener
+        let shared = Arc::new(Mutex::new(IncrementalConnectivity::new()));
+        let mut g = DiGraph::new(None);
+        let cc = shared.clone();
+        g.subscribe(move |event| {
+            let mut cc = cc.lock().unwrap();
+            match *event {
+                GraphEvent::NodeAdded(name) => {
+                    cc.add_node(name);
+                }
+                GraphEvent::EdgeAdded(from, to) => {
+                    cc.add_edge(from, to);
+                }
+                _ => {}
+            }
+        });
+        g.add_edge(Some("x"), Some("y"));
+        g.add_edge(Some("z"), Some("y"));
+        assert!(shared.lock().unwrap().connected("x", "z"));
+    }
+
+    #[test]
+    fn test_incremental_reachability() {
+        let g: DiGraph = vec![("a", "b"), ("c", "d")].into_iter().collect();
+        let mut dag = IncrementalReachability::from_graph(&g).unwrap();
+        assert!(dag.reaches("a", "b"));
+        assert!(!dag.reaches("b", "a"));
+        assert!(dag.reaches("c", "c"));
+        dag.add_edge("b", "c").unwrap();
+        assert!(dag.reaches("a", "d"));
+        assert_eq!(dag.descendants("a").unwrap(), vec!["b", "c", "d"]);
+
+        match dag.add_edge("d", "b") {
+            Err(GraphError::CycleDetected(cycle)) => assert_eq!(cycle, vec!["b", "c", "d"]),
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+        assert!(!dag.reaches("d", "b"));
+        assert!(dag.add_edge("e", "e").is_err());
+
+        // rows grow past one word
+        for i in 0..100 {
+            dag.add_edge(&format!("n{}", i), &format!("n{}", i + 1))
+                .unwrap();
+        }
+        dag.add_edge("d", "n0").unwrap();
+        assert!(dag.reaches("a", "n100"));
+        assert_eq!(dag.descendants("n0").unwrap().len(), 100);
+        assert_eq!(dag.node_count(), 106);
+
+        let cyclic: DiGraph = vec![("a", "b"), ("b", "a")].into_iter().collect();
+        let err = IncrementalReachability::from_graph(&cyclic).unwrap_err();
+        assert_eq!(err.to_string(), "adding edge b -> a");
+        assert!(matches!(err.root_cause(), GraphError::CycleDetected(_)));
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::metrics::edge_count;
+use crate::graph::{Directed, GraphBase, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Properties of a graph that isomorphic graphs share, cheap enough to
+/// compute up front to tell graphs apart without a matching search.
+///
+/// Equal invariants do not make graphs isomorphic, but different ones rule
+/// it out. Node weights are compared as the matcher compares them by default.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GraphInvariants {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// `(in_degree, out_degree)` of every node, sorted.
+    pub degrees: Vec<(usize, usize)>,
+    /// Weights of all nodes, sorted.
+    pub node_weights: Vec<Option<String>>,
+    pub self_loops: usize,
+    /// Pairs of nodes with edges in both directions.
+    pub mutual_pairs: usize,
+    /// Triangles of the graph with edge directions ignored.
+    pub triangles: usize,
+}
+impl GraphInvariants {
+    pub fn of<G: Directed>(graph: &G) -> Self {
+        let mut degrees: Vec<(usize, usize)> = graph
+            .nodes()
+            .map(|node| (node.in_degree(), node.out_degree()))
+            .collect();
+        degrees.sort_unstable();
+        let mut node_weights: Vec<Option<String>> = graph
+            .nodes()
+            .map(|node| node.weight().map(String::from))
+            .collect();
+        node_weights.sort_unstable();
+
+        let mut self_loops = 0;
+        let mut mutual_pairs = 0;
+        for node in graph.nodes() {
+            for succ in node.successor_names() {
+                if succ == node.name() {
+                    self_loops += 1;
+                } else if succ < node.name() && graph.contains_edge(succ, node.name()) {
+                    mutual_pairs += 1;
+                }
+            }
+        }
+
+        GraphInvariants {
+            node_count: graph.node_count(),
+            edge_count: edge_count(graph),
+            degrees,
+            node_weights,
+            self_loops,
+            mutual_pairs,
+            triangles: triangles(graph),
+        }
+    }
+}
+
+/// Whether `g1` and `g2` may be isomorphic, i.e. their invariants agree.
+pub fn could_be_isomorphic<G: Directed>(g1: &G, g2: &G) -> bool {
+    g1.node_count() == g2.node_count() && GraphInvariants::of(g1) == GraphInvariants::of(g2)
+}
+
+/// Number of triangles, counted once each from their first node in graph order.
+fn triangles<G: GraphBase>(graph: &G) -> usize {
+    let order: HashMap<&str, usize> = graph
+        .node_names()
+        .enumerate()
+        .map(|(i, name)| (name, i))
+        .collect();
+    // neighbors later in graph order
+    let later: HashMap<&str, HashSet<&str>> = graph
+        .nodes()
+        .map(|node| {
+            let nbrs = node
+                .neighbors()
+                .into_iter()
+                .filter(|nbr| order[nbr] > order[node.name()])
+                .collect();
+            (node.name(), nbrs)
+        })
+        .collect();
+
+    let mut count = 0;
+    for nbrs in later.values() {
+        for u in nbrs {
+            count += later[u].intersection(nbrs).count();
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_graph_invariants() {
+        let mut g: DiGraph = vec![("A", "B"), ("B", "C"), ("C", "A"), ("C", "D"), ("D", "C")]
+            .into_iter()
+            .collect();
+        g.add_edge(Some("D"), Some("D"));
+        let inv = GraphInvariants::of(&g);
+        assert_eq!((inv.node_count, inv.edge_count), (4, 6));
+        assert_eq!(inv.degrees, vec![(1, 1), (1, 1), (2, 2), (2, 2)]);
+        assert_eq!((inv.self_loops, inv.mutual_pairs, inv.triangles), (1, 1, 1));
+
+        // the same graph under other names and in another order
+        let mut h: DiGraph = vec![("y", "x"), ("x", "y"), ("x", "w"), ("w", "z"), ("z", "x")]
+            .into_iter()
+            .collect();
+        h.add_edge(Some("y"), Some("y"));
+        assert_eq!(GraphInvariants::of(&h), inv);
+        assert!(could_be_isomorphic(&g, &h));
+
+        // reversing one edge of the triangle keeps the degree multiset
+        // of the undirected graph but not of the directed one
+        h.remove_edge("w", "z");
+        h.add_edge(Some("z"), Some("w"));
+        assert!(!could_be_isomorphic(&g, &h));
+
+        h.remove_edge("z", "w");
+        h.add_edge(Some("w"), Some("z"));
+        h.get_node_mut("w")
+            .unwrap()
+            .set_weight(Some("op".to_string()));
+        assert!(!could_be_isomorphic(&g, &h));
+    }
+}
@@ -12,20 +12,142 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::GraphError;
-use std::collections::{HashMap, HashSet};
-use std::hash::Hash;
+use super::invariants::could_be_isomorphic;
+use crate::{
+    error::GraphError,
+    graph::{Directed, Node, Ported},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The order in which the matcher extends a partial mapping with nodes of G2,
+/// set with [`DiGraphMatcher::set_ordering`].
+///
+/// Whenever the matcher picks the next G2 node among its candidates, it takes
+/// the first in this order. Every order finds the same mappings, though not
+/// in the same sequence, but a good order prunes the search much earlier.
+#[derive(Clone, Default)]
+pub enum OrderingStrategy {
+    /// G2 nodes in graph order.
+    #[default]
+    G2InsertionOrder,
+    /// G2 nodes with the most edges first, ties in graph order.
+    DegreeDescending,
+    /// The order of VF2++ (Juttner and Madarasi): breadth-first from a node
+    /// whose weight is rarest in G1, preferring at each level the nodes with
+    /// the most neighbors already ordered, then the most edges, then the
+    /// rarest weight.
+    VF2pp,
+    /// G2 nodes in increasing order of a key computed from their names, ties
+    /// in graph order.
+    Custom(Arc<dyn Fn(&str) -> i64 + Send + Sync>),
+}
+impl fmt::Debug for OrderingStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrderingStrategy::G2InsertionOrder => write!(f, "G2InsertionOrder"),
+            OrderingStrategy::DegreeDescending => write!(f, "DegreeDescending"),
+            OrderingStrategy::VF2pp => write!(f, "VF2pp"),
+            OrderingStrategy::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// A check the matcher runs on every candidate pair, in this order; the first
+/// that fails prunes the pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FeasibilityRule {
+    /// The nodes are not semantically equal.
+    Semantic,
+    /// The nodes have different numbers of self-loops.
+    SelfLoops,
+    /// Matched edges would feed different ports, with `match_ports` set.
+    Ports,
+    /// The predecessors already mapped do not correspond.
+    Pred,
+    /// The successors already mapped do not correspond.
+    Succ,
+    /// Look-ahead on the Tin sets.
+    In,
+    /// Look-ahead on the Tout sets.
+    Out,
+    /// Look-ahead on the nodes in neither the mapping nor the terminal sets.
+    New,
+}
+
+/// Statistics of the last search of a [`DiGraphMatcher`], collected when its
+/// `report` is set.
+#[derive(Debug, Clone, Default)]
+pub struct MatchReport {
+    /// Partial mappings visited, the empty one included.
+    pub states: usize,
+    /// Candidate pairs pruned, by the rule that failed first.
+    pub prunes: BTreeMap<FeasibilityRule, usize>,
+    /// Most pairs in any partial mapping visited.
+    pub max_depth: usize,
+    /// Time from the start of the search to each mapping found, in order.
+    pub mapping_times: Vec<Duration>,
+    /// Time the whole search took.
+    pub elapsed: Duration,
+    started: Option<Instant>,
+}
+impl MatchReport {
+    /// Candidate pairs pruned by a rule.
+    pub fn pruned_by(&self, rule: FeasibilityRule) -> usize {
+        self.prunes.get(&rule).copied().unwrap_or(0)
+    }
+
+    /// Candidate pairs pruned by any rule.
+    pub fn total_prunes(&self) -> usize {
+        self.prunes.values().sum()
+    }
+}
+
+/// Edges of G2 mapped to edges of G1, as `(from, to)` pairs; see
+/// [`DiGraphMatcher::edge_subgraph_iter`].
+pub type EdgeMapping = HashMap<(String, String), (String, String)>;
+
+type EdgeSets = (Vec<(String, String)>, HashSet<Vec<(String, String)>>);
+
+/// Where a search of a [`DiGraphMatcher`] stopped, to resume it later with
+/// [`DiGraphMatcher::resume`], or [`DiGraphMatcher::resume_edge_subgraph`]
+/// for an edge-induced search, possibly in another process.
+///
+/// The checkpoint holds the pairs leading from the empty mapping to the first
+/// state the search did not explore; everything before it in search order has
+/// been searched. The candidates at each state are recomputed when resuming,
+/// so they must come from the same graphs, with the same node order, ordering
+/// strategy and `match_ports`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchCheckpoint {
+    /// The kind of search: `graph`, `subgraph` or `mono`.
+    pub test: String,
+    /// The `(G1 node, G2 node)` pairs added at each depth.
+    pub path: Vec<(String, String)>,
+    /// The first `fixed` pairs of `path` stay in place: the search ends once
+    /// it has explored under them, which splits a search into
+    /// [`DiGraphMatcher::shards`].
+    pub fixed: usize,
+    /// In an edge-induced search, the sets of G1 edges found before the
+    /// checkpoint, so they are not reported again; `None` in the other
+    /// searches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge_sets: Option<Vec<Vec<(String, String)>>>,
+}
 
 pub struct DiGraphMatcher<'a, T>
 where
-    T: GMGraph,
+    T: Ported,
 {
     pub g1: &'a T,
     pub g2: &'a T,
 
     pub g1_nodes: HashSet<String>,
     pub g2_nodes: HashSet<String>,
-    pub g2_node_order: HashMap<String, usize>,
+    pub g1_node_order: HashMap<String, usize>,
 
     // Declare that we will be searching for a graph-graph isomorphism.
 
@@ -69,25 +191,51 @@ where
 
     // Provide a convenient way to access the isomorphism mapping.
     pub mapping: HashMap<String, String>,
+
+    // Stop searching once this many mappings have been found.
+    pub limit: Option<usize>,
+
+    // Require matched edges to feed the same input port, so operands of
+    // non-commutative operations are not swapped.
+    pub match_ports: bool,
+
+    // Statistics of the last search, collected if this is `Some`; set it to
+    // `Some(MatchReport::default())` before searching to opt in.
+    pub report: Option<MatchReport>,
+
+    // Bitset copies of the adjacency and of the state sets of both graphs, for
+    // the look-ahead counts, if both graphs are small enough.
+    bits: Option<(BitSide, BitSide)>,
+
+    // Pairs added at each depth of the current state, the pinned prefix of
+    // them, and where the last search stopped at its limit.
+    path: Vec<(String, String)>,
+    fixed: usize,
+    checkpoint: Option<SearchCheckpoint>,
+
+    // During an edge-induced search, the edges of G2 and the sets of G1 edges
+    // matched so far.
+    edge_sets: Option<EdgeSets>,
+
+    // Position of every G2 node in the order of `ordering`.
+    ordering: OrderingStrategy,
+    g2_rank: HashMap<String, usize>,
 }
 impl<'a, T> DiGraphMatcher<'a, T>
 where
-    T: GMGraph,
+    T: Ported,
 {
     pub fn new(g1: &'a T, g2: &'a T) -> Self {
         DiGraphMatcher {
             g1,
             g2,
-            g1_nodes: g1.get_nodes().iter().map(|x| x.clone()).collect(),
-            g2_nodes: g2.get_nodes().iter().map(|x| x.clone()).collect(),
-            g2_node_order: g2
-                .get_nodes()
-                .iter()
+            g1_nodes: g1.node_names().map(String::from).collect(),
+            g2_nodes: g2.node_names().map(String::from).collect(),
+            g1_node_order: g1
+                .node_names()
                 .enumerate()
-                .map(|(order, key)| (key.clone(), order))
-                .collect::<Vec<(String, usize)>>()
-                .into_iter()
-                .collect::<HashMap<String, usize>>(),
+                .map(|(order, key)| (key.to_string(), order))
+                .collect(),
             test: String::from("graph"),
             core_1: HashMap::new(),
             core_2: HashMap::new(),
@@ -97,87 +245,544 @@ where
             out_2: HashMap::new(),
             // state: DiGMState::new(),
             mapping: HashMap::new(),
+            limit: None,
+            match_ports: false,
+            report: None,
+            bits: BitSide::new(g1).zip(BitSide::new(g2)),
+            path: Vec::new(),
+            fixed: 0,
+            checkpoint: None,
+            edge_sets: None,
+            ordering: OrderingStrategy::default(),
+            g2_rank: g2
+                .node_names()
+                .enumerate()
+                .map(|(order, key)| (key.to_string(), order))
+                .collect(),
+        }
+    }
+
+    pub fn ordering(&self) -> &OrderingStrategy {
+        &self.ordering
+    }
+
+    /// Choose the order in which G2 nodes are matched, for the searches
+    /// started from now on.
+    pub fn set_ordering(&mut self, ordering: OrderingStrategy) {
+        let order: Vec<&str> = match &ordering {
+            OrderingStrategy::G2InsertionOrder => self.g2.node_names().collect(),
+            OrderingStrategy::DegreeDescending => {
+                let mut names: Vec<&T::Node> = self.g2.nodes().collect();
+                // a stable sort keeps ties in graph order
+                names.sort_by_key(|node| std::cmp::Reverse(node.in_degree() + node.out_degree()));
+                names.into_iter().map(|node| node.name()).collect()
+            }
+            OrderingStrategy::VF2pp => self.vf2pp_order(),
+            OrderingStrategy::Custom(key) => {
+                let mut names: Vec<&str> = self.g2.node_names().collect();
+                names.sort_by_key(|&name| key(name));
+                names
+            }
+        };
+        self.g2_rank = order
+            .into_iter()
+            .enumerate()
+            .map(|(rank, name)| (name.to_string(), rank))
+            .collect();
+        self.ordering = ordering;
+    }
+
+    /// G2 nodes in the order of [`OrderingStrategy::VF2pp`].
+    fn vf2pp_order(&self) -> Vec<&'a str> {
+        let g2 = self.g2;
+        // how many G1 nodes have each weight and are not yet claimed by an
+        // ordered G2 node
+        let mut rarity: HashMap<Option<&str>, usize> = HashMap::new();
+        for node in self.g1.nodes() {
+            *rarity.entry(node.weight()).or_default() += 1;
         }
+        let names: Vec<&str> = g2.node_names().collect();
+        let position: HashMap<&str, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, &name)| (name, i))
+            .collect();
+        let node = |i: usize| g2.node(names[i]).unwrap();
+        let nbrs: Vec<Vec<usize>> = (0..names.len())
+            .map(|i| {
+                node(i)
+                    .neighbors()
+                    .into_iter()
+                    .map(|name| position[name])
+                    .filter(|&j| j != i)
+                    .collect()
+            })
+            .collect();
+        let degree = |i: usize| node(i).in_degree() + node(i).out_degree();
+
+        let mut ordered = vec![false; names.len()];
+        // ordered neighbors of every node
+        let mut conn = vec![0usize; names.len()];
+        let mut order = Vec::with_capacity(names.len());
+        while order.len() < names.len() {
+            let root = (0..names.len())
+                .filter(|&i| !ordered[i])
+                .min_by_key(|&i| {
+                    (
+                        rarity.get(&node(i).weight()).copied().unwrap_or(0),
+                        std::cmp::Reverse(degree(i)),
+                    )
+                })
+                .unwrap();
+            let mut seen = vec![false; names.len()];
+            seen[root] = true;
+            let mut level = vec![root];
+            while !level.is_empty() {
+                let mut next = Vec::new();
+                for &v in level.iter() {
+                    for &w in nbrs[v].iter() {
+                        if !seen[w] && !ordered[w] {
+                            seen[w] = true;
+                            next.push(w);
+                        }
+                    }
+                }
+                while !level.is_empty() {
+                    let (at, &v) = level
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|&(_, &v)| {
+                            (
+                                std::cmp::Reverse(conn[v]),
+                                std::cmp::Reverse(degree(v)),
+                                rarity.get(&node(v).weight()).copied().unwrap_or(0),
+                                v,
+                            )
+                        })
+                        .unwrap();
+                    level.swap_remove(at);
+                    ordered[v] = true;
+                    order.push(names[v]);
+                    for &w in nbrs[v].iter() {
+                        conn[w] += 1;
+                    }
+                    if let Some(count) = rarity.get_mut(&node(v).weight()) {
+                        *count = count.saturating_sub(1);
+                    }
+                }
+                level = next;
+            }
+        }
+        order
     }
 
     pub fn subgraph_isomorphism_iter(&mut self, mapping: &mut Vec<HashMap<String, String>>) {
-        self.test = String::from("subgraph");
+        self.search("subgraph", mapping);
+    }
+
+    /// Find subgraphs of G1 that are monomorphic to G2, i.e. every edge of G2 has
+    /// a matching edge in G1, but G1 may have extra edges between matched nodes.
+    pub fn subgraph_monomorphism_iter(&mut self, mapping: &mut Vec<HashMap<String, String>>) {
+        self.search("mono", mapping);
+    }
+
+    /// Find edge-induced subgraphs of G1 isomorphic to G2: every edge of G2
+    /// maps to a distinct edge of G1, as for monomorphisms, but a set of G1
+    /// edges is reported once, however many node mappings lead to it.
+    ///
+    /// Each match maps the edges of G2 to those of G1, as `(from, to)` pairs,
+    /// and matches come in the order monomorphisms are found. Nodes of G2
+    /// without edges still need G1 nodes of their own, though they do not
+    /// show in the matches. `limit` counts edge sets rather than mappings:
+    /// node mappings onto an edge set already found are dropped during the
+    /// search, which ends once `limit` edge sets are collected.
+    pub fn edge_subgraph_iter(&mut self, matches: &mut Vec<EdgeMapping>) {
+        self.edge_search(matches, HashSet::new(), |matcher, mapping| {
+            matcher.search("mono", mapping)
+        });
+    }
+
+    /// Go on with an edge-induced search from a checkpoint taken by
+    /// [`DiGraphMatcher::edge_subgraph_iter`], adding the edge sets it finds
+    /// after it.
+    ///
+    /// Fails like [`DiGraphMatcher::resume`], and with
+    /// `GraphError::Serialization` if the checkpoint is of another search.
+    pub fn resume_edge_subgraph(
+        &mut self,
+        checkpoint: &SearchCheckpoint,
+        matches: &mut Vec<EdgeMapping>,
+    ) -> Result<(), GraphError> {
+        let seen = match checkpoint.edge_sets.as_ref() {
+            Some(sets) => sets.iter().cloned().collect(),
+            None => {
+                return Err(GraphError::Serialization(String::from(
+                    "checkpoint is not of an edge-induced search",
+                )))
+            }
+        };
+        self.edge_search(matches, seen, |matcher, mapping| {
+            matcher.resume_search(checkpoint, mapping)
+        })
+    }
+
+    /// Run `run` as an edge-induced search, skipping the edge sets in `seen`,
+    /// and add the edge mappings it finds to `matches`.
+    fn edge_search<R>(
+        &mut self,
+        matches: &mut Vec<EdgeMapping>,
+        seen: HashSet<Vec<(String, String)>>,
+        run: impl FnOnce(&mut Self, &mut Vec<HashMap<String, String>>) -> R,
+    ) -> R {
+        let edges: Vec<(String, String)> = self
+            .g2
+            .nodes()
+            .flat_map(|node| {
+                node.successor_names()
+                    .map(move |succ| (node.name().to_string(), succ.to_string()))
+            })
+            .collect();
+        // the search stops once enough new edge sets are found
+        let limit = self.limit;
+        self.limit = limit.map(|limit| limit.saturating_sub(matches.len()));
+        self.edge_sets = Some((edges, seen));
+        let mut mapping = Vec::new();
+        let result = run(self, &mut mapping);
+        let (edges, _) = self.edge_sets.take().unwrap();
+        self.limit = limit;
+
+        for nodes in mapping {
+            matches.push(
+                edges
+                    .iter()
+                    .map(|(from, to)| {
+                        let host = (nodes[from].clone(), nodes[to].clone());
+                        ((from.clone(), to.clone()), host)
+                    })
+                    .collect(),
+            );
+        }
+        result
+    }
+
+    /// Whether the current mapping covers a set of G1 edges not seen yet in
+    /// an edge-induced search, recording it if so. Always true in the other
+    /// searches.
+    fn new_edge_set(&mut self) -> bool {
+        let core_2 = &self.core_2;
+        match self.edge_sets.as_mut() {
+            Some((edges, seen)) => {
+                let mut host: Vec<(String, String)> = edges
+                    .iter()
+                    .map(|(from, to)| (core_2[from].clone(), core_2[to].clone()))
+                    .collect();
+                host.sort_unstable();
+                seen.insert(host)
+            }
+            None => true,
+        }
+    }
+
+    fn search(&mut self, test: &str, mapping: &mut Vec<HashMap<String, String>>) {
+        self.start_search(test, 0);
         let _state = DiGMState::create(self, None, None);
         self.try_match(mapping);
+        self.finish_report();
+    }
+
+    fn start_search(&mut self, test: &str, fixed: usize) {
+        self.test = String::from(test);
+        self.path.clear();
+        self.fixed = fixed;
+        self.checkpoint = None;
+        self.start_report();
+    }
+
+    /// Where the last search stopped on reaching `limit`, or `None` if it
+    /// ran to the end.
+    pub fn checkpoint(&self) -> Option<&SearchCheckpoint> {
+        self.checkpoint.as_ref()
+    }
+
+    /// Go on with a search from a checkpoint, adding the mappings it finds
+    /// after it. The search may stop at `limit` again, with a new checkpoint.
+    ///
+    /// Fails with `GraphError::NotFoundNode` if the checkpoint names a node
+    /// missing from its graph, and with `GraphError::Serialization` if it does
+    /// not fit the search otherwise, e.g. because the graphs changed or it is
+    /// of an edge-induced search.
+    pub fn resume(
+        &mut self,
+        checkpoint: &SearchCheckpoint,
+        mapping: &mut Vec<HashMap<String, String>>,
+    ) -> Result<(), GraphError> {
+        if checkpoint.edge_sets.is_some() {
+            return Err(GraphError::Serialization(String::from(
+                "checkpoint is of an edge-induced search",
+            )));
+        }
+        self.resume_search(checkpoint, mapping)
+    }
+
+    fn resume_search(
+        &mut self,
+        checkpoint: &SearchCheckpoint,
+        mapping: &mut Vec<HashMap<String, String>>,
+    ) -> Result<(), GraphError> {
+        if !["graph", "subgraph", "mono"].contains(&checkpoint.test.as_str()) {
+            return Err(GraphError::Serialization(format!(
+                "unknown search in checkpoint: {}",
+                checkpoint.test
+            )));
+        }
+        for (name1, name2) in checkpoint.path.iter() {
+            if self.g1.node(name1).is_none() {
+                return Err(GraphError::NotFoundNode(name1.clone()));
+            }
+            if self.g2.node(name2).is_none() {
+                return Err(GraphError::NotFoundNode(name2.clone()));
+            }
+        }
+        self.start_search(
+            &checkpoint.test,
+            checkpoint.fixed.min(checkpoint.path.len()),
+        );
+        let _state = DiGMState::create(self, None, None);
+        let result = self.resume_match(&checkpoint.path, mapping);
+        self.finish_report();
+        result
+    }
+
+    /// Follow `path` down from the current state, then search everything
+    /// after it.
+    fn resume_match(
+        &mut self,
+        path: &[(String, String)],
+        mapping: &mut Vec<HashMap<String, String>>,
+    ) -> Result<(), GraphError> {
+        let (next, rest) = match path.split_first() {
+            Some(split) => split,
+            None => {
+                self.try_match(mapping);
+                return Ok(());
+            }
+        };
+        let candidates = self.candidate_paris_iter();
+        let at = candidates
+            .iter()
+            .position(|pair| pair == next)
+            .filter(|_| self.failed_rule(&next.0, &next.1).is_none())
+            .ok_or_else(|| {
+                GraphError::Serialization(format!(
+                    "checkpoint pairs {} with {}, which the search does not reach",
+                    next.0, next.1
+                ))
+            })?;
+
+        self.path.push(next.clone());
+        let state = DiGMState::create(self, Some(next.0.clone()), Some(next.1.clone()));
+        let result = self.resume_match(rest, mapping);
+        state.restore(self);
+        self.path.pop();
+        result?;
+
+        if self.path.len() >= self.fixed {
+            for (g1_node, g2_node) in candidates.into_iter().skip(at + 1) {
+                self.try_pair(g1_node, g2_node, mapping);
+            }
+        }
+        Ok(())
+    }
+
+    /// Split a search into independent parts, one per feasible pair for the
+    /// first G2 node, to run apart, e.g. on several machines, with
+    /// [`DiGraphMatcher::resume`]. Together they find the mappings the
+    /// search finds. `test` is `graph`, `subgraph` or `mono`, as for
+    /// [`SearchCheckpoint::test`].
+    pub fn shards(&mut self, test: &str) -> Vec<SearchCheckpoint> {
+        if test == "graph" && !could_be_isomorphic(self.g1, self.g2) {
+            return Vec::new();
+        }
+        self.test = String::from(test);
+        let _state = DiGMState::create(self, None, None);
+        if self.g2.node_count() == 0 {
+            // the empty mapping is the only state
+            return vec![SearchCheckpoint {
+                test: String::from(test),
+                path: Vec::new(),
+                fixed: 0,
+                edge_sets: None,
+            }];
+        }
+        self.candidate_paris_iter()
+            .into_iter()
+            .filter(|(g1_node, g2_node)| self.failed_rule(g1_node, g2_node).is_none())
+            .map(|pair| SearchCheckpoint {
+                test: String::from(test),
+                path: vec![pair],
+                fixed: 1,
+                edge_sets: None,
+            })
+            .collect()
+    }
+
+    fn finish_report(&mut self) {
+        if let Some(report) = self.report.as_mut() {
+            report.elapsed = report.started.map_or(Duration::ZERO, |at| at.elapsed());
+        }
+    }
+
+    fn start_report(&mut self) {
+        if let Some(report) = self.report.as_mut() {
+            *report = MatchReport {
+                started: Some(Instant::now()),
+                ..MatchReport::default()
+            };
+        }
     }
 
     pub fn try_match(&mut self, mapping: &mut Vec<HashMap<String, String>>) {
+        if self.limit.is_some_and(|limit| mapping.len() >= limit) {
+            if self.checkpoint.is_none() {
+                let edge_sets = self.edge_sets.as_ref().map(|(_, seen)| {
+                    let mut sets: Vec<_> = seen.iter().cloned().collect();
+                    sets.sort_unstable();
+                    sets
+                });
+                self.checkpoint = Some(SearchCheckpoint {
+                    test: self.test.clone(),
+                    path: self.path.clone(),
+                    fixed: self.fixed,
+                    edge_sets,
+                });
+            }
+            return;
+        }
+        if let Some(report) = self.report.as_mut() {
+            report.states += 1;
+            report.max_depth = report.max_depth.max(self.core_1.len());
+        }
+
         if self.core_1.len() == self.g2.node_count() {
+            if !self.new_edge_set() {
+                return;
+            }
             mapping.push(self.core_2.clone());
+            if let Some(report) = self.report.as_mut() {
+                let at = report.started.map_or(Duration::ZERO, |at| at.elapsed());
+                report.mapping_times.push(at);
+            }
         } else {
             for (g1_node, g2_node) in self.candidate_paris_iter() {
-                if self.semantic_feasibility(g1_node.clone(), g2_node.clone()) {
-                    if self.syntactic_feasibility(g1_node.clone(), g2_node.clone()) {
-                        // state.initilize(self, g1_node.clone(), g2_node.clone());
-                        let newstate =
-                            DiGMState::create(self, Some(g1_node.clone()), Some(g2_node.clone()));
-                        self.try_match(mapping);
-                        // state.restore(self);
-                        newstate.restore(self);
-                    }
+                self.try_pair(g1_node, g2_node, mapping);
+            }
+        }
+    }
+
+    /// Extend the current state with a pair and search under it, if the pair
+    /// is feasible.
+    fn try_pair(
+        &mut self,
+        g1_node: String,
+        g2_node: String,
+        mapping: &mut Vec<HashMap<String, String>>,
+    ) {
+        match self.failed_rule(&g1_node, &g2_node) {
+            Some(rule) => {
+                if let Some(report) = self.report.as_mut() {
+                    *report.prunes.entry(rule).or_default() += 1;
                 }
             }
+            None => {
+                // state.initilize(self, g1_node.clone(), g2_node.clone());
+                let newstate =
+                    DiGMState::create(self, Some(g1_node.clone()), Some(g2_node.clone()));
+                self.path.push((g1_node, g2_node));
+                self.try_match(mapping);
+                self.path.pop();
+                // state.restore(self);
+                newstate.restore(self);
+            }
+        }
+    }
+
+    /// The first rule the pair fails, if any.
+    fn failed_rule(&self, g1_node_name: &str, g2_node_name: &str) -> Option<FeasibilityRule> {
+        if self.semantic_feasibility(g1_node_name.to_string(), g2_node_name.to_string()) {
+            self.failed_syntactic_rule(g1_node_name, g2_node_name)
+        } else {
+            Some(FeasibilityRule::Semantic)
         }
     }
 
     /// Check if two nodes from graph and (sub)graph respectively are equal topologically
     pub fn syntactic_feasibility(&self, g1_node_name: String, g2_node_name: String) -> bool {
-        let g1_node = self.g1.get_node(g1_node_name.as_str()).unwrap();
-        let g2_node = self.g2.get_node(g2_node_name.as_str()).unwrap();
+        self.failed_syntactic_rule(g1_node_name.as_str(), g2_node_name.as_str())
+            .is_none()
+    }
+
+    /// The first topological rule the pair fails, if any.
+    fn failed_syntactic_rule(
+        &self,
+        g1_node_name: &str,
+        g2_node_name: &str,
+    ) -> Option<FeasibilityRule> {
+        let g1_node = self.g1.node(g1_node_name).unwrap();
+        let g2_node = self.g2.node(g2_node_name).unwrap();
 
         // R_self for checking self loops
         // The number of selfloops for G1_node must equal the number of
         // self-loops for G2_node. Without this check, we would fail on R_pred
         // at the next recursion level. This should prune the tree even further.
         if !self.r_self(g1_node, g2_node) {
-            return false;
+            return Some(FeasibilityRule::SelfLoops);
+        }
+
+        if self.match_ports && !self.r_ports(g1_node_name, g2_node_name) {
+            return Some(FeasibilityRule::Ports);
         }
 
         // R_pred and R_succ for checking the consistency of the partial solution
         if !self.r_pred(g1_node, g2_node) {
-            return false;
+            return Some(FeasibilityRule::Pred);
         }
 
         if !self.r_succ(g1_node, g2_node) {
-            return false;
+            return Some(FeasibilityRule::Succ);
+        }
+
+        // The look-ahead rules do not hold for monomorphisms
+        if self.test == "mono" {
+            return None;
         }
 
         // R_in, R_out and R_new for pruning the search tree
         // R_in and R_out is 1-look-ahead, and R_new is 2-look-ahead
         if !self.r_in(g1_node, g2_node) {
-            return false;
+            return Some(FeasibilityRule::In);
         }
 
         if !self.r_out(g1_node, g2_node) {
-            return false;
+            return Some(FeasibilityRule::Out);
         }
 
         if !self.r_new(g1_node, g2_node) {
-            return false;
+            return Some(FeasibilityRule::New);
         }
 
-        true
+        None
     }
 
     /// Check if two nodes from graph and (sub)graph respectively are equal semantically
     pub fn semantic_feasibility(&self, g1_node_name: String, g2_node_name: String) -> bool {
-        let g1_node = self.g1.get_node(g1_node_name.as_str());
-        let g2_node = self.g2.get_node(g2_node_name.as_str());
+        let g1_node = self.g1.node(g1_node_name.as_str());
+        let g2_node = self.g2.node(g2_node_name.as_str());
 
-        if g1_node.is_some() && g2_node.is_some() {
-            let node1 = g1_node.unwrap();
-            let node2 = g2_node.unwrap();
-            return node1.semantic_equal(node2);
-        } else if g1_node.is_some() || g2_node.is_some() {
-            return false;
+        match (g1_node, g2_node) {
+            (Some(node1), Some(node2)) => node1.semantic_equal(node2),
+            (None, None) => true,
+            _ => false,
         }
-
-        true
     }
 
     fn candidate_paris_iter(&self) -> Vec<(String, String)> {
@@ -201,16 +806,8 @@ where
 
         // If T1_out and T2_out are both nonempty.
         // P(s) = Tout_1 x {min Tout_2}
-        if tout_1.len() > 0 && tout_2.len() > 0 {
-            let mut name2 = String::new();
-            let mut min_order = usize::MAX;
-            for key in tout_2.iter() {
-                let order = self.g2_node_order.get(key.as_str()).unwrap().clone();
-                if order < min_order {
-                    min_order = order;
-                    name2 = key.clone();
-                }
-            }
+        if !tout_1.is_empty() && !tout_2.is_empty() {
+            let name2 = self.first_in_order(tout_2.iter());
             for name1 in tout_1.iter() {
                 pairs.push((name1.clone(), name2.clone()));
             }
@@ -233,16 +830,8 @@ where
 
             // If T1_in and T2_in are both nonempty.
             // P(s) = T1_out x {min T2_out}
-            if tin_1.len() > 0 && tin_2.len() > 0 {
-                let mut name2 = String::new();
-                let mut min_order = usize::MAX;
-                for key in tin_2.iter() {
-                    let order = self.g2_node_order.get(key.as_str()).unwrap().clone();
-                    if order < min_order {
-                        min_order = order;
-                        name2 = key.clone();
-                    }
-                }
+            if !tin_1.is_empty() && !tin_2.is_empty() {
+                let name2 = self.first_in_order(tin_2.iter());
                 for name1 in tin_1.iter() {
                     pairs.push((name1.clone(), name2.clone()));
                 }
@@ -253,106 +842,141 @@ where
                 let m2 = self.core_2.keys().cloned().collect();
                 let diff_set = self.g2_nodes.difference(&m2);
 
-                let mut name2 = String::new();
-                let mut min_order = usize::MAX;
-                for key in diff_set {
-                    let order = self.g2_node_order.get(key.as_str()).unwrap().clone();
-                    if order < min_order {
-                        min_order = order;
-                        name2 = key.clone();
+                let name2 = self.first_in_order(diff_set);
+                let candidates = self
+                    .g2
+                    .node(name2.as_str())
+                    .and_then(|node| self.g1.semantic_candidates(node));
+                match candidates {
+                    Some(names) => {
+                        for name1 in names {
+                            if !self.core_1.contains_key(name1.as_str()) {
+                                pairs.push((name1, name2.clone()));
+                            }
+                        }
                     }
-                }
-                for name1 in self.g1_nodes.iter() {
-                    if !self.core_1.contains_key(name1.as_str()) {
-                        pairs.push((name1.clone(), name2.clone()));
+                    None => {
+                        for name1 in self.g1_nodes.iter() {
+                            if !self.core_1.contains_key(name1.as_str()) {
+                                pairs.push((name1.clone(), name2.clone()));
+                            }
+                        }
                     }
                 }
             }
         }
+        // try G1 candidates in graph order so matches are found in a stable order
+        pairs.sort_by_key(|(name1, _)| self.g1_node_order.get(name1.as_str()).copied());
         pairs
     }
 
+    /// The G2 node that comes first in the matching order.
+    fn first_in_order<'b>(&self, names: impl Iterator<Item = &'b String>) -> String {
+        names
+            .min_by_key(|name| self.g2_rank.get(name.as_str()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
     /// R_self for checking self loops
     /// The number of selfloops for G1_node must equal the number of
     /// self-loops for G2_node. Without this check, we would fail on R_pred
     /// at the next recursion level. This should prune the tree even further.
-    fn r_self<N: GMNode>(&self, g1_node: &N, g2_node: &N) -> bool {
-        if self
-            .g1
-            .edge_count(g1_node.get_name().as_str(), g1_node.get_name().as_str())
-            != self
-                .g2
-                .edge_count(g2_node.get_name().as_str(), g2_node.get_name().as_str())
+    fn r_self<N: Node>(&self, g1_node: &N, g2_node: &N) -> bool {
+        let g1_name = g1_node.name();
+        let g2_name = g2_node.name();
+        let g1_loop = self.g1.contains_edge(g1_name, g1_name);
+        let g2_loop = self.g2.contains_edge(g2_name, g2_name);
+        if self.test == "mono" {
+            // G1 may have extra self-loops
+            return g1_loop || !g2_loop;
+        }
+        g1_loop == g2_loop
+    }
+
+    /// Every edge of G2 between `g2_node` and the partial mapping, self-loop
+    /// included, must feed the same port as the edge of G1 it is matched to.
+    fn r_ports(&self, g1_name: &str, g2_name: &str) -> bool {
+        let same_port = |g1_from: &str, g1_to: &str, g2_from: &str, g2_to: &str| {
+            !self.g1.contains_edge(g1_from, g1_to)
+                || self.g1.edge_port(g1_from, g1_to) == self.g2.edge_port(g2_from, g2_to)
+        };
+        if self.g2.contains_edge(g2_name, g2_name) && !same_port(g1_name, g1_name, g2_name, g2_name)
         {
             return false;
         }
-
+        let preds = self.g2.predecessors(g2_name).unwrap_or_default();
+        for pred in preds {
+            if let Some(g1_pred) = self.core_2.get(pred.name()) {
+                if !same_port(g1_pred, g1_name, pred.name(), g2_name) {
+                    return false;
+                }
+            }
+        }
+        let succs = self.g2.successors(g2_name).unwrap_or_default();
+        for succ in succs {
+            if let Some(g1_succ) = self.core_2.get(succ.name()) {
+                if !same_port(g1_name, g1_succ, g2_name, succ.name()) {
+                    return false;
+                }
+            }
+        }
         true
     }
 
     /// R_pred and R_succ for checking the consistency of the partial solution
-    fn r_pred<N: GMNode>(&self, g1_node: &N, g2_node: &N) -> bool {
+    fn r_pred<N: Node>(&self, g1_node: &N, g2_node: &N) -> bool {
         // For each predecessor n' of n in the partial mapping, the
         // corresponding node m' is a predecessor of m, and vice versa. Also,
         // the number of edges must be equal
 
-        let result_pred_1 = self.g1.predecessors(g1_node.get_name().as_str());
-        match result_pred_1 {
-            Ok(predecessors1) => {
-                for predecessor in predecessors1 {
-                    if self.core_1.contains_key(predecessor.get_name().as_str()) {
-                        let result_pred_2 = self.g2.predecessors(g2_node.get_name().as_str());
-                        match result_pred_2 {
-                            Ok(predecessors2) => {
-                                if predecessors2.iter().all(|&x| {
-                                    x.get_name()
-                                        != *self
-                                            .core_1
-                                            .get(predecessor.get_name().as_str())
-                                            .unwrap()
-                                }) {
-                                    return false;
-                                } else if self.g1.edge_count(
-                                    predecessor.get_name().as_str(),
-                                    g1_node.get_name().as_str(),
-                                ) != self.g2.edge_count(
-                                    self.core_1.get(predecessor.get_name().as_str()).unwrap(),
-                                    g2_node.get_name().as_str(),
-                                ) {
-                                    return false;
+        // A monomorphism may leave edges of G1 unmatched, so only the G2
+        // side is checked.
+        if self.test != "mono" {
+            let result_pred_1 = self.g1.predecessors(g1_node.name());
+            match result_pred_1 {
+                Ok(predecessors1) => {
+                    for predecessor in predecessors1 {
+                        if self.core_1.contains_key(predecessor.name()) {
+                            let result_pred_2 = self.g2.predecessors(g2_node.name());
+                            match result_pred_2 {
+                                Ok(predecessors2) => {
+                                    if predecessors2.iter().all(|&x| {
+                                        x.name() != *self.core_1.get(predecessor.name()).unwrap()
+                                    }) || self.g1.edge_count(predecessor.name(), g1_node.name())
+                                        != self.g2.edge_count(
+                                            self.core_1.get(predecessor.name()).unwrap(),
+                                            g2_node.name(),
+                                        )
+                                    {
+                                        return false;
+                                    }
                                 }
+                                Err(err) => panic!("{}", err),
                             }
-                            Err(err) => panic!("{}", err),
                         }
                     }
                 }
+                Err(err) => panic!("{}", err),
             }
-            Err(err) => panic!("{}", err),
         }
 
-        let result_pred_2 = self.g2.predecessors(g2_node.get_name().as_str());
+        let result_pred_2 = self.g2.predecessors(g2_node.name());
         match result_pred_2 {
             Ok(predecessors2) => {
                 for predecessor2 in predecessors2 {
-                    if self.core_2.contains_key(predecessor2.get_name().as_str()) {
-                        let result_pred_1 = self.g1.predecessors(g1_node.get_name().as_str());
+                    if self.core_2.contains_key(predecessor2.name()) {
+                        let result_pred_1 = self.g1.predecessors(g1_node.name());
                         match result_pred_1 {
                             Ok(predecessors1) => {
                                 if predecessors1.iter().all(|&x| {
-                                    x.get_name()
-                                        != *self
-                                            .core_2
-                                            .get(predecessor2.get_name().as_str())
-                                            .unwrap()
-                                }) {
-                                    return false;
-                                } else if self.g2.edge_count(
-                                    predecessor2.get_name().as_str(),
-                                    g2_node.get_name().as_str(),
-                                ) != self.g1.edge_count(
-                                    self.core_2.get(predecessor2.get_name().as_str()).unwrap(),
-                                    g1_node.get_name().as_str(),
-                                ) {
+                                    x.name() != *self.core_2.get(predecessor2.name()).unwrap()
+                                }) || self.g2.edge_count(predecessor2.name(), g2_node.name())
+                                    != self.g1.edge_count(
+                                        self.core_2.get(predecessor2.name()).unwrap(),
+                                        g1_node.name(),
+                                    )
+                                {
                                     return false;
                                 }
                             }
@@ -367,62 +991,56 @@ where
     }
 
     /// R_pred and R_succ for checking the consistency of the partial solution
-    fn r_succ<N: GMNode>(&self, g1_node: &N, g2_node: &N) -> bool {
+    fn r_succ<N: Node>(&self, g1_node: &N, g2_node: &N) -> bool {
         // For each successor n' of n in the partial mapping, the corresponding
         // node m' is a successor of m, and vice versa. Also, the number of
         // edges must be equal.
 
-        let result_succ = self.g1.successors(g1_node.get_name().as_str());
-        match result_succ {
-            Ok(successor_vec_1) => {
-                for successor1 in successor_vec_1 {
-                    if self.core_1.contains_key(successor1.get_name().as_str()) {
-                        let result_succ = self.g2.successors(g2_node.get_name().as_str());
-                        match result_succ {
-                            Ok(successor_vec_2) => {
-                                if successor_vec_2.iter().all(|&x| {
-                                    x.get_name()
-                                        != *self.core_1.get(successor1.get_name().as_str()).unwrap()
-                                }) {
-                                    return false;
-                                } else if self.g1.edge_count(
-                                    g1_node.get_name().as_str(),
-                                    successor1.get_name().as_str(),
-                                ) != self.g2.edge_count(
-                                    g2_node.get_name().as_str(),
-                                    self.core_1.get(successor1.get_name().as_str()).unwrap(),
-                                ) {
-                                    return false;
+        if self.test != "mono" {
+            let result_succ = self.g1.successors(g1_node.name());
+            match result_succ {
+                Ok(successor_vec_1) => {
+                    for successor1 in successor_vec_1 {
+                        if self.core_1.contains_key(successor1.name()) {
+                            let result_succ = self.g2.successors(g2_node.name());
+                            match result_succ {
+                                Ok(successor_vec_2) => {
+                                    if successor_vec_2.iter().all(|&x| {
+                                        x.name() != *self.core_1.get(successor1.name()).unwrap()
+                                    }) || self.g1.edge_count(g1_node.name(), successor1.name())
+                                        != self.g2.edge_count(
+                                            g2_node.name(),
+                                            self.core_1.get(successor1.name()).unwrap(),
+                                        )
+                                    {
+                                        return false;
+                                    }
                                 }
+                                Err(err) => panic!("{}", err),
                             }
-                            Err(err) => panic!("{}", err),
                         }
                     }
                 }
+                Err(err) => panic!("{}", err),
             }
-            Err(err) => panic!("{}", err),
         }
 
-        let result_succ = self.g2.successors(g2_node.get_name().as_str());
+        let result_succ = self.g2.successors(g2_node.name());
         match result_succ {
             Ok(successor_vec_2) => {
                 for successor in successor_vec_2 {
-                    if self.core_2.contains_key(successor.get_name().as_str()) {
-                        let result_succ = self.g1.successors(g1_node.get_name().as_str());
+                    if self.core_2.contains_key(successor.name()) {
+                        let result_succ = self.g1.successors(g1_node.name());
                         match result_succ {
                             Ok(successor_vec_1) => {
                                 if successor_vec_1.iter().all(|&x| {
-                                    x.get_name()
-                                        != *self.core_2.get(successor.get_name().as_str()).unwrap()
-                                }) {
-                                    return false;
-                                } else if self.g2.edge_count(
-                                    g2_node.get_name().as_str(),
-                                    successor.get_name().as_str(),
-                                ) != self.g1.edge_count(
-                                    g1_node.get_name().as_str(),
-                                    self.core_2.get(successor.get_name().as_str()).unwrap(),
-                                ) {
+                                    x.name() != *self.core_2.get(successor.name()).unwrap()
+                                }) || self.g2.edge_count(g2_node.name(), successor.name())
+                                    != self.g1.edge_count(
+                                        g1_node.name(),
+                                        self.core_2.get(successor.name()).unwrap(),
+                                    )
+                                {
                                     return false;
                                 }
                             }
@@ -439,19 +1057,24 @@ where
 
     /// R_in, R_out and R_new for pruning the search tree
     /// R_in and R_out is 1-look-ahead, and R_new is 2-look-ahead
-    fn r_in<N: GMNode>(&self, g1_node: &N, g2_node: &N) -> bool {
+    fn r_in<N: Node>(&self, g1_node: &N, g2_node: &N) -> bool {
         // The number of predecessors of n that are in Tin_1 is equal to the
         // number of predecessors of m that are in Tin_2.
 
         // Tin = in - core
+        if let Some((bits1, bits2)) = &self.bits {
+            let count1 = bits1.count(g1_node.name(), bits1.ins & !bits1.core);
+            let count2 = bits2.count(g2_node.name(), bits2.ins & !bits2.core);
+            return self.counts_fit(count1, count2);
+        }
 
         let mut num1 = 0;
-        let result_pred = self.g1.predecessors(g1_node.get_name().as_str());
+        let result_pred = self.g1.predecessors(g1_node.name());
         match result_pred {
             Ok(predecessor_vec) => {
                 for predecessor in predecessor_vec {
-                    if self.in_1.contains_key(predecessor.get_name().as_str())
-                        && !self.core_1.contains_key(predecessor.get_name().as_str())
+                    if self.in_1.contains_key(predecessor.name())
+                        && !self.core_1.contains_key(predecessor.name())
                     {
                         num1 += 1;
                     }
@@ -461,12 +1084,12 @@ where
         }
 
         let mut num2 = 0;
-        let result_pred = self.g2.predecessors(g2_node.get_name().as_str());
+        let result_pred = self.g2.predecessors(g2_node.name());
         match result_pred {
             Ok(predecessor_vec) => {
                 for predecessor in predecessor_vec {
-                    if self.in_2.contains_key(predecessor.get_name().as_str())
-                        && !self.core_2.contains_key(predecessor.get_name().as_str())
+                    if self.in_2.contains_key(predecessor.name())
+                        && !self.core_2.contains_key(predecessor.name())
                     {
                         num2 += 1;
                     }
@@ -475,24 +1098,22 @@ where
             Err(err) => panic!("{}", err),
         }
         if self.test == "graph" {
-            if !(num1 == num2) {
-                return false;
-            }
-        } else {
-            if !(num1 >= num2) {
+            if num1 != num2 {
                 return false;
             }
+        } else if num1 < num2 {
+            return false;
         }
 
         // The number of successors of n that are in Tin_1 is equal to the
         // number of successors of m that are in Tin_2.
         let mut num1 = 0;
-        let result_succ = self.g1.successors(g1_node.get_name().as_str());
+        let result_succ = self.g1.successors(g1_node.name());
         match result_succ {
             Ok(successor_vec) => {
                 for successor in successor_vec {
-                    if self.in_1.contains_key(successor.get_name().as_str())
-                        && !self.core_1.contains_key(successor.get_name().as_str())
+                    if self.in_1.contains_key(successor.name())
+                        && !self.core_1.contains_key(successor.name())
                     {
                         num1 += 1;
                     }
@@ -502,12 +1123,12 @@ where
         }
 
         let mut num2 = 0;
-        let result_succ = self.g2.successors(g2_node.get_name().as_str());
+        let result_succ = self.g2.successors(g2_node.name());
         match result_succ {
             Ok(successor_vec) => {
                 for successor in successor_vec {
-                    if self.in_2.contains_key(successor.get_name().as_str())
-                        && !self.core_2.contains_key(successor.get_name().as_str())
+                    if self.in_2.contains_key(successor.name())
+                        && !self.core_2.contains_key(successor.name())
                     {
                         num2 += 1;
                     }
@@ -516,13 +1137,11 @@ where
             Err(err) => panic!("{}", err),
         }
         if self.test == "graph" {
-            if !(num1 == num2) {
-                return false;
-            }
-        } else {
-            if !(num1 >= num2) {
+            if num1 != num2 {
                 return false;
             }
+        } else if num1 < num2 {
+            return false;
         }
 
         true
@@ -530,19 +1149,24 @@ where
 
     /// R_in, R_out and R_new for pruning the search tree
     /// R_in and R_out is 1-look-ahead, and R_new is 2-look-ahead
-    fn r_out<N: GMNode>(&self, g1_node: &N, g2_node: &N) -> bool {
+    fn r_out<N: Node>(&self, g1_node: &N, g2_node: &N) -> bool {
         // The number of predecessors of n that are in Tout_1 is equal to the
         // number of predecessors of m that are in Tout_2.
 
         // Tout = out - core
+        if let Some((bits1, bits2)) = &self.bits {
+            let count1 = bits1.count(g1_node.name(), bits1.outs & !bits1.core);
+            let count2 = bits2.count(g2_node.name(), bits2.outs & !bits2.core);
+            return self.counts_fit(count1, count2);
+        }
 
         let mut num1 = 0;
-        let result_pred = self.g1.predecessors(g1_node.get_name().as_str());
+        let result_pred = self.g1.predecessors(g1_node.name());
         match result_pred {
             Ok(predecessor_vec) => {
                 for predecessor in predecessor_vec {
-                    if self.out_1.contains_key(predecessor.get_name().as_str())
-                        && !self.core_1.contains_key(predecessor.get_name().as_str())
+                    if self.out_1.contains_key(predecessor.name())
+                        && !self.core_1.contains_key(predecessor.name())
                     {
                         num1 += 1;
                     }
@@ -551,12 +1175,12 @@ where
             Err(err) => panic!("{}", err),
         }
         let mut num2 = 0;
-        let result_pred = self.g2.predecessors(g2_node.get_name().as_str());
+        let result_pred = self.g2.predecessors(g2_node.name());
         match result_pred {
             Ok(predecessor_vec) => {
                 for predecessor in predecessor_vec {
-                    if self.out_2.contains_key(predecessor.get_name().as_str())
-                        && !self.core_2.contains_key(predecessor.get_name().as_str())
+                    if self.out_2.contains_key(predecessor.name())
+                        && !self.core_2.contains_key(predecessor.name())
                     {
                         num2 += 1;
                     }
@@ -565,25 +1189,23 @@ where
             Err(err) => panic!("{}", err),
         }
         if self.test == "graph" {
-            if !(num1 == num2) {
-                return false;
-            }
-        } else {
-            if !(num1 >= num2) {
+            if num1 != num2 {
                 return false;
             }
+        } else if num1 < num2 {
+            return false;
         }
 
         // The number of successors of n that are in Tout_1 is equal to the
         // number of successors of m that are in Tout_2.
 
         let mut num1 = 0;
-        let result_succ = self.g1.successors(g1_node.get_name().as_str());
+        let result_succ = self.g1.successors(g1_node.name());
         match result_succ {
             Ok(successor_vec) => {
                 for successor in successor_vec {
-                    if self.out_1.contains_key(successor.get_name().as_str())
-                        && !self.core_1.contains_key(successor.get_name().as_str())
+                    if self.out_1.contains_key(successor.name())
+                        && !self.core_1.contains_key(successor.name())
                     {
                         num1 += 1;
                     }
@@ -592,12 +1214,12 @@ where
             Err(err) => panic!("{}", err),
         }
         let mut num2 = 0;
-        let result_succ = self.g2.successors(g2_node.get_name().as_str());
+        let result_succ = self.g2.successors(g2_node.name());
         match result_succ {
             Ok(successor_vec) => {
                 for successor in successor_vec {
-                    if self.out_2.contains_key(successor.get_name().as_str())
-                        && !self.core_2.contains_key(successor.get_name().as_str())
+                    if self.out_2.contains_key(successor.name())
+                        && !self.core_2.contains_key(successor.name())
                     {
                         num2 += 1;
                     }
@@ -606,13 +1228,11 @@ where
             Err(err) => panic!("{}", err),
         }
         if self.test == "graph" {
-            if !(num1 == num2) {
-                return false;
-            }
-        } else {
-            if !(num1 >= num2) {
+            if num1 != num2 {
                 return false;
             }
+        } else if num1 < num2 {
+            return false;
         }
 
         true
@@ -620,18 +1240,23 @@ where
 
     /// R_in, R_out and R_new for pruning the search tree
     /// R_in and R_out is 1-look-ahead, and R_new is 2-look-ahead
-    fn r_new<N: GMNode>(&self, g1_node: &N, g2_node: &N) -> bool {
+    fn r_new<N: Node>(&self, g1_node: &N, g2_node: &N) -> bool {
         // The number of predecessors of n that are neither in the core_1 nor
         // Tin_1 nor Tout_1 is equal to the number of predecessors of m
         // that are neither in core_2 nor Tin_2 nor Tout_2.
+        if let Some((bits1, bits2)) = &self.bits {
+            let count1 = bits1.count(g1_node.name(), !(bits1.ins | bits1.outs));
+            let count2 = bits2.count(g2_node.name(), !(bits2.ins | bits2.outs));
+            return self.counts_fit(count1, count2);
+        }
 
         let mut num1 = 0;
-        let result_pred = self.g1.predecessors(g1_node.get_name().as_str());
+        let result_pred = self.g1.predecessors(g1_node.name());
         match result_pred {
             Ok(predecessor_vec) => {
                 for predecessor in predecessor_vec {
-                    if !self.in_1.contains_key(predecessor.get_name().as_str())
-                        && !self.out_1.contains_key(predecessor.get_name().as_str())
+                    if !self.in_1.contains_key(predecessor.name())
+                        && !self.out_1.contains_key(predecessor.name())
                     {
                         num1 += 1;
                     }
@@ -640,12 +1265,12 @@ where
             Err(err) => panic!("{}", err),
         }
         let mut num2 = 0;
-        let result_pred = self.g2.predecessors(g2_node.get_name().as_str());
+        let result_pred = self.g2.predecessors(g2_node.name());
         match result_pred {
             Ok(predecessor_vec) => {
                 for predecessor in predecessor_vec {
-                    if !self.in_2.contains_key(predecessor.get_name().as_str())
-                        && !self.out_2.contains_key(predecessor.get_name().as_str())
+                    if !self.in_2.contains_key(predecessor.name())
+                        && !self.out_2.contains_key(predecessor.name())
                     {
                         num2 += 1;
                     }
@@ -654,13 +1279,11 @@ where
             Err(err) => panic!("{}", err),
         }
         if self.test == "graph" {
-            if !(num1 == num2) {
-                return false;
-            }
-        } else {
-            if !(num1 >= num2) {
+            if num1 != num2 {
                 return false;
             }
+        } else if num1 < num2 {
+            return false;
         }
 
         // The number of successors of n that are neither in the core_1 nor
@@ -668,12 +1291,12 @@ where
         // that are neither in core_2 nor Tin_2 nor Tout_2.
 
         let mut num1 = 0;
-        let result_succ = self.g1.successors(g1_node.get_name().as_str());
+        let result_succ = self.g1.successors(g1_node.name());
         match result_succ {
             Ok(successor_vec) => {
                 for successor in successor_vec {
-                    if !self.in_1.contains_key(successor.get_name().as_str())
-                        && !self.out_1.contains_key(successor.get_name().as_str())
+                    if !self.in_1.contains_key(successor.name())
+                        && !self.out_1.contains_key(successor.name())
                     {
                         num1 += 1;
                     }
@@ -682,12 +1305,12 @@ where
             Err(err) => panic!("{}", err),
         }
         let mut num2 = 0;
-        let result_succ = self.g2.successors(g2_node.get_name().as_str());
+        let result_succ = self.g2.successors(g2_node.name());
         match result_succ {
             Ok(successor_vec) => {
                 for successor in successor_vec {
-                    if !self.in_2.contains_key(successor.get_name().as_str())
-                        && !self.out_2.contains_key(successor.get_name().as_str())
+                    if !self.in_2.contains_key(successor.name())
+                        && !self.out_2.contains_key(successor.name())
                     {
                         num2 += 1;
                     }
@@ -696,16 +1319,73 @@ where
             Err(err) => panic!("{}", err),
         }
         if self.test == "graph" {
-            if !(num1 == num2) {
+            if num1 != num2 {
                 return false;
             }
+        } else if num1 < num2 {
+            return false;
+        }
+
+        true
+    }
+
+    /// Compare `(predecessors, successors)` counts of a look-ahead rule: equal
+    /// for a graph isomorphism, and no fewer in G1 for a subgraph.
+    fn counts_fit(&self, count1: (u32, u32), count2: (u32, u32)) -> bool {
+        if self.test == "graph" {
+            count1 == count2
         } else {
-            if !(num1 >= num2) {
-                return false;
+            count1.0 >= count2.0 && count1.1 >= count2.1
+        }
+    }
+
+    /// Add a node to a terminal set at `depth`, unless it is already in it.
+    fn enter(&mut self, side: Side, set: BitSet, name: &str, depth: usize) {
+        let terminal = match (side, set) {
+            (Side::G1, BitSet::Ins) => &mut self.in_1,
+            (Side::G1, _) => &mut self.out_1,
+            (Side::G2, BitSet::Ins) => &mut self.in_2,
+            (Side::G2, _) => &mut self.out_2,
+        };
+        if !terminal.contains_key(name) {
+            terminal.insert(name.to_string(), depth);
+            self.mark(side, set, name, true);
+        }
+    }
+
+    /// Keep the bitset copy of a state set in step with a node added to it or
+    /// removed from it.
+    fn mark(&mut self, side: Side, set: BitSet, name: &str, on: bool) {
+        if let Some((bits1, bits2)) = self.bits.as_mut() {
+            match side {
+                Side::G1 => bits1.mark(set, name, on),
+                Side::G2 => bits2.mark(set, name, on),
             }
         }
+    }
+}
+impl<'a, G> DiGraphMatcher<'a, G>
+where
+    G: Ported,
+{
+    /// Find isomorphisms between G1 and G2. Graphs whose
+    /// [`GraphInvariants`](super::invariants::GraphInvariants) differ are
+    /// rejected before the search starts.
+    pub fn isomorphisms_iter(&mut self, mapping: &mut Vec<HashMap<String, String>>) {
+        if !could_be_isomorphic(self.g1, self.g2) {
+            self.start_search("graph", 0);
+            return;
+        }
+        self.search("graph", mapping);
+    }
 
-        true
+    /// Check if G1 and G2 are isomorphic, stopping at the first isomorphism.
+    pub fn is_isomorphic(&mut self) -> bool {
+        let limit = self.limit.replace(1);
+        let mut mapping = Vec::new();
+        self.isomorphisms_iter(&mut mapping);
+        self.limit = limit;
+        !mapping.is_empty()
     }
 }
 
@@ -715,7 +1395,7 @@ pub struct DiGMState {
     pub depth: usize,
 }
 impl DiGMState {
-    pub fn create<T: GMGraph>(
+    pub fn create<T: Ported>(
         matcher: &mut DiGraphMatcher<T>,
         g1_node: Option<String>,
         g2_node: Option<String>,
@@ -728,6 +1408,10 @@ impl DiGMState {
             matcher.in_2.clear();
             matcher.out_1.clear();
             matcher.out_2.clear();
+            if let Some((bits1, bits2)) = matcher.bits.as_mut() {
+                bits1.clear();
+                bits2.clear();
+            }
         }
 
         let depth = matcher.core_1.len();
@@ -739,12 +1423,14 @@ impl DiGMState {
             // update matcher
             matcher.core_1.insert(g1_name.clone(), g2_name.clone());
             matcher.core_2.insert(g2_name.clone(), g1_name.clone());
+            matcher.mark(Side::G1, BitSet::Core, &g1_name, true);
+            matcher.mark(Side::G2, BitSet::Core, &g2_name, true);
 
             // First we add the new nodes to Tin_1, Tin_2, Tout_1 and Tout_2
-            matcher.in_1.entry(g1_name.clone()).or_insert(depth);
-            matcher.out_1.entry(g1_name.clone()).or_insert(depth);
-            matcher.in_2.entry(g2_name.clone()).or_insert(depth);
-            matcher.out_2.entry(g2_name.clone()).or_insert(depth);
+            matcher.enter(Side::G1, BitSet::Ins, &g1_name, depth);
+            matcher.enter(Side::G1, BitSet::Outs, &g1_name, depth);
+            matcher.enter(Side::G2, BitSet::Ins, &g2_name, depth);
+            matcher.enter(Side::G2, BitSet::Outs, &g2_name, depth);
 
             // Now we add every other node...
 
@@ -755,7 +1441,7 @@ impl DiGMState {
                 match result_pred {
                     Ok(predecessor_vec) => {
                         for predecessor in predecessor_vec {
-                            if !matcher.core_1.contains_key(predecessor.get_name().as_str()) {
+                            if !matcher.core_1.contains_key(predecessor.name()) {
                                 new_nodes.insert(predecessor);
                             }
                         }
@@ -764,7 +1450,7 @@ impl DiGMState {
                 }
             }
             for node in new_nodes {
-                matcher.in_1.entry(node.get_name().clone()).or_insert(depth);
+                matcher.enter(Side::G1, BitSet::Ins, node.name(), depth);
             }
 
             // Updates for Tin_2
@@ -774,7 +1460,7 @@ impl DiGMState {
                 match result_pred {
                     Ok(predecessor_vec) => {
                         for predecessor in predecessor_vec {
-                            if !matcher.core_2.contains_key(predecessor.get_name().as_str()) {
+                            if !matcher.core_2.contains_key(predecessor.name()) {
                                 new_nodes.insert(predecessor);
                             }
                         }
@@ -783,7 +1469,7 @@ impl DiGMState {
                 }
             }
             for node in new_nodes {
-                matcher.in_2.entry(node.get_name().clone()).or_insert(depth);
+                matcher.enter(Side::G2, BitSet::Ins, node.name(), depth);
             }
 
             // Updates for Tout_1
@@ -793,7 +1479,7 @@ impl DiGMState {
                 match result_succ {
                     Ok(successor_vec) => {
                         for successor in successor_vec {
-                            if !matcher.core_1.contains_key(successor.get_name().as_str()) {
+                            if !matcher.core_1.contains_key(successor.name()) {
                                 new_nodes.insert(successor);
                             }
                         }
@@ -802,10 +1488,7 @@ impl DiGMState {
                 }
             }
             for node in new_nodes {
-                matcher
-                    .out_1
-                    .entry(node.get_name().clone())
-                    .or_insert(depth);
+                matcher.enter(Side::G1, BitSet::Outs, node.name(), depth);
             }
 
             // Updates for Tout_2
@@ -815,7 +1498,7 @@ impl DiGMState {
                 match result_succ {
                     Ok(successor_vec) => {
                         for successor in successor_vec {
-                            if !matcher.core_2.contains_key(successor.get_name().as_str()) {
+                            if !matcher.core_2.contains_key(successor.name()) {
                                 new_nodes.insert(successor);
                             }
                         }
@@ -824,10 +1507,7 @@ impl DiGMState {
                 }
             }
             for node in new_nodes {
-                matcher
-                    .out_2
-                    .entry(node.get_name().clone())
-                    .or_insert(depth);
+                matcher.enter(Side::G2, BitSet::Outs, node.name(), depth);
             }
         }
 
@@ -835,27 +1515,25 @@ impl DiGMState {
             DiGMState {
                 g1_node: g1_node.clone(),
                 g2_node: g2_node.clone(),
-                depth: depth,
+                depth,
             }
         } else {
             DiGMState {
                 g1_node: None,
                 g2_node: None,
-                depth: depth,
+                depth,
             }
         }
     }
 
-    pub fn restore<T: GMGraph>(&self, matcher: &mut DiGraphMatcher<T>) {
+    pub fn restore<T: Ported>(&self, matcher: &mut DiGraphMatcher<T>) {
         // First we remove the node that was added from the core vectors.
         // Watch out! G1_node == 0 should evaluate to True.
-        if self.g1_node.is_some() && self.g2_node.is_some() {
-            matcher
-                .core_1
-                .remove_entry(self.g1_node.as_ref().unwrap().as_str());
-            matcher
-                .core_2
-                .remove_entry(self.g2_node.as_ref().unwrap().as_str());
+        if let (Some(g1_node), Some(g2_node)) = (&self.g1_node, &self.g2_node) {
+            matcher.core_1.remove_entry(g1_node.as_str());
+            matcher.core_2.remove_entry(g2_node.as_str());
+            matcher.mark(Side::G1, BitSet::Core, g1_node, false);
+            matcher.mark(Side::G2, BitSet::Core, g2_node, false);
         }
 
         // Now we revert the other four vectors.
@@ -869,6 +1547,7 @@ impl DiGMState {
             .collect();
         for key in keys {
             matcher.in_1.remove(key.as_str());
+            matcher.mark(Side::G1, BitSet::Ins, &key, false);
         }
 
         let keys: Vec<String> = matcher
@@ -879,6 +1558,7 @@ impl DiGMState {
             .collect();
         for key in keys {
             matcher.in_2.remove(key.as_str());
+            matcher.mark(Side::G2, BitSet::Ins, &key, false);
         }
 
         let keys: Vec<String> = matcher
@@ -889,6 +1569,7 @@ impl DiGMState {
             .collect();
         for key in keys {
             matcher.out_1.remove(key.as_str());
+            matcher.mark(Side::G1, BitSet::Outs, &key, false);
         }
 
         let keys: Vec<String> = matcher
@@ -899,22 +1580,126 @@ impl DiGMState {
             .collect();
         for key in keys {
             matcher.out_2.remove(key.as_str());
+            matcher.mark(Side::G2, BitSet::Outs, &key, false);
         }
     }
 }
 
-pub trait GMGraph {
-    type Node: GMNode + Eq + Hash;
-    fn get_nodes(&self) -> Vec<String>;
-    fn get_node(&self, name: &str) -> Option<&Self::Node>;
-    fn node_count(&self) -> usize;
-    fn edge_count(&self, from: &str, to: &str) -> usize;
-    fn predecessors(&self, name: &str) -> Result<Vec<&Self::Node>, GraphError>;
-    fn successors(&self, name: &str) -> Result<Vec<&Self::Node>, GraphError>;
+/// The graph a state set belongs to.
+#[derive(Clone, Copy)]
+enum Side {
+    G1,
+    G2,
+}
+
+/// The state sets kept as bitsets: the core, Tin and Tout.
+#[derive(Clone, Copy)]
+enum BitSet {
+    Core,
+    Ins,
+    Outs,
+}
+
+/// One graph of a matcher as bitsets over its node indices: the adjacency,
+/// and the nodes in the core, Tin and Tout sets of the current state.
+struct BitSide {
+    index: HashMap<String, usize>,
+    preds: Vec<u128>,
+    succs: Vec<u128>,
+    core: u128,
+    ins: u128,
+    outs: u128,
+}
+impl BitSide {
+    /// `None` if the graph has more nodes than fit in a bitset.
+    fn new<T: Directed>(graph: &T) -> Option<Self> {
+        if graph.node_count() > u128::BITS as usize {
+            return None;
+        }
+        let index: HashMap<String, usize> = graph
+            .node_names()
+            .enumerate()
+            .map(|(i, name)| (name.to_string(), i))
+            .collect();
+        let mask = |nodes: Vec<&T::Node>| {
+            nodes
+                .iter()
+                .fold(0, |bits, node| bits | 1 << index[node.name()])
+        };
+        let mut preds = vec![0; index.len()];
+        let mut succs = vec![0; index.len()];
+        for (name, &i) in index.iter() {
+            preds[i] = mask(graph.predecessors(name).unwrap());
+            succs[i] = mask(graph.successors(name).unwrap());
+        }
+        Some(BitSide {
+            index,
+            preds,
+            succs,
+            core: 0,
+            ins: 0,
+            outs: 0,
+        })
+    }
+
+    fn mark(&mut self, set: BitSet, name: &str, on: bool) {
+        let bit = 1 << self.index[name];
+        let mask = match set {
+            BitSet::Core => &mut self.core,
+            BitSet::Ins => &mut self.ins,
+            BitSet::Outs => &mut self.outs,
+        };
+        if on {
+            *mask |= bit;
+        } else {
+            *mask &= !bit;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.core = 0;
+        self.ins = 0;
+        self.outs = 0;
+    }
+
+    /// Number of predecessors and of successors of `name` in `set`.
+    fn count(&self, name: &str, set: u128) -> (u32, u32) {
+        let i = self.index[name];
+        (
+            (self.preds[i] & set).count_ones(),
+            (self.succs[i] & set).count_ones(),
+        )
+    }
 }
 
-pub trait GMNode {
-    fn get_name(&self) -> String;
-    fn get_weight(&self) -> Option<String>;
-    fn semantic_equal(&self, other: &Self) -> bool;
+/// Neighbor lookups of the matcher, provided for every graph.
+trait GMGraph: Directed {
+    fn edge_count(&self, from: &str, to: &str) -> usize {
+        if !self.contains_edge(from, to) {
+            return 0;
+        }
+        1
+    }
+
+    fn predecessors(&self, name: &str) -> Result<Vec<&Self::Node>, GraphError> {
+        let node = self
+            .node(name)
+            .ok_or_else(|| GraphError::NotFoundNode(String::from(name)))?;
+        Ok(node
+            .predecessor_names()
+            .map(|name| self.node(name).unwrap())
+            .collect())
+    }
+
+    fn successors(&self, name: &str) -> Result<Vec<&Self::Node>, GraphError> {
+        let node = self
+            .node(name)
+            .ok_or_else(|| GraphError::NotFoundNode(String::from(name)))?;
+        Ok(node
+            .successor_names()
+            .map(|name| self.node(name).unwrap())
+            .collect())
+    }
 }
+
+impl<G: Directed> GMGraph for G {}
@@ -0,0 +1,151 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Link prediction scores for pairs of nodes, computed on the undirected
+//! neighbor sets of the nodes (predecessors and successors, without the node
+//! itself).
+//!
+//! Every function scores the given pairs, or, if `pairs` is `None`, every
+//! pair of distinct nodes not yet joined by an edge in either direction, in
+//! graph order. It fails with `GraphError::NotFoundNode` if a pair names an
+//! unknown node.
+
+use crate::{
+    error::GraphError,
+    graph::{Directed, GraphBase, Node},
+};
+use std::collections::HashSet;
+
+/// `(u, v, score)` for each scored pair.
+pub type Scores = Vec<(String, String, f64)>;
+
+/// Common neighbors divided by all neighbors of the two nodes, or 0 if
+/// neither has a neighbor.
+pub fn jaccard_coefficient<G: Directed>(
+    graph: &G,
+    pairs: Option<&[(&str, &str)]>,
+) -> Result<Scores, GraphError> {
+    score(graph, pairs, |nu, nv, _| {
+        let union = nu.union(nv).count();
+        if union == 0 {
+            0.0
+        } else {
+            nu.intersection(nv).count() as f64 / union as f64
+        }
+    })
+}
+
+/// Sum of `1 / ln(degree)` over the common neighbors, so rare shared
+/// neighbors count more than hubs.
+pub fn adamic_adar_index<G: Directed>(
+    graph: &G,
+    pairs: Option<&[(&str, &str)]>,
+) -> Result<Scores, GraphError> {
+    score(graph, pairs, |nu, nv, graph| {
+        nu.intersection(nv)
+            .map(|&w| 1.0 / (neighbors(graph, w).len() as f64).ln())
+            .sum()
+    })
+}
+
+/// Product of the neighbor counts of the two nodes.
+pub fn preferential_attachment<G: Directed>(
+    graph: &G,
+    pairs: Option<&[(&str, &str)]>,
+) -> Result<Scores, GraphError> {
+    score(graph, pairs, |nu, nv, _| (nu.len() * nv.len()) as f64)
+}
+
+fn neighbors<'a, G: GraphBase>(graph: &'a G, name: &str) -> HashSet<&'a str> {
+    let node = graph.node(name).unwrap();
+    node.successor_names()
+        .chain(node.predecessor_names())
+        .filter(|&nbr| nbr != name)
+        .collect()
+}
+
+fn score<'a, G, F>(graph: &'a G, pairs: Option<&[(&str, &str)]>, f: F) -> Result<Scores, GraphError>
+where
+    G: Directed,
+    F: Fn(&HashSet<&'a str>, &HashSet<&'a str>, &'a G) -> f64,
+{
+    let pairs: Vec<(&str, &str)> = match pairs {
+        Some(pairs) => {
+            for &(u, v) in pairs {
+                for name in [u, v] {
+                    if graph.node(name).is_none() {
+                        return Err(GraphError::NotFoundNode(name.to_string()));
+                    }
+                }
+            }
+            pairs.to_vec()
+        }
+        None => {
+            let names: Vec<&str> = graph.node_names().collect();
+            let mut pairs = Vec::new();
+            for (i, &u) in names.iter().enumerate() {
+                for &v in names[i + 1..].iter() {
+                    if !graph.contains_edge(u, v) && !graph.contains_edge(v, u) {
+                        pairs.push((u, v));
+                    }
+                }
+            }
+            pairs
+        }
+    };
+
+    Ok(pairs
+        .into_iter()
+        .map(|(u, v)| {
+            let value = f(&neighbors(graph, u), &neighbors(graph, v), graph);
+            (u.to_string(), v.to_string(), value)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_link_prediction() {
+        // A and C share B; B also points to D
+        let g: DiGraph = vec![("A", "B"), ("C", "B"), ("B", "D"), ("C", "D")]
+            .into_iter()
+            .collect();
+
+        let jaccard = jaccard_coefficient(&g, Some(&[("A", "C")])).unwrap();
+        assert_eq!(jaccard, vec![("A".to_string(), "C".to_string(), 0.5)]);
+
+        let adamic_adar = adamic_adar_index(&g, Some(&[("A", "C")])).unwrap();
+        assert!((adamic_adar[0].2 - 1.0 / 3f64.ln()).abs() < 1e-12);
+
+        let attachment = preferential_attachment(&g, Some(&[("B", "C")])).unwrap();
+        assert_eq!(attachment[0].2, 6.0);
+
+        // all pairs without an edge: A-C and A-D
+        let all = jaccard_coefficient(&g, None).unwrap();
+        let pairs: Vec<(&str, &str)> = all
+            .iter()
+            .map(|(u, v, _)| (u.as_str(), v.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![("A", "C"), ("A", "D")]);
+
+        assert!(matches!(
+            jaccard_coefficient(&g, Some(&[("A", "X")])),
+            Err(GraphError::NotFoundNode(_))
+        ));
+    }
+}
@@ -0,0 +1,932 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::graph::{BipartiteGraph, Directed, Node, Side, Weighted};
+use std::collections::HashMap;
+
+const NONE: usize = usize::MAX;
+
+/// A matching of largest total weight in a general graph, found with Edmonds'
+/// blossom algorithm in `O(n^3)` time.
+///
+/// Edge direction is ignored, so this works for [`UnGraph`](crate::graph::UnGraph)
+/// as well as directed graphs; if both arcs between two nodes are present the
+/// heavier one counts. Edges without a weight count as 1 and self-loops are
+/// ignored. Edges of negative weight never improve the total, so they are
+/// left out. Pairs are returned as `(a, b)` with `a` before `b` in graph
+/// order, sorted by `a`.
+pub fn max_weight_matching<G: Weighted>(graph: &G) -> Vec<(String, String)> {
+    matching(graph, false)
+}
+
+/// Like [`max_weight_matching`], but only among the matchings with the most
+/// edges, so every node that can be matched is.
+pub fn max_weight_max_cardinality_matching<G: Weighted>(graph: &G) -> Vec<(String, String)> {
+    matching(graph, true)
+}
+
+fn matching<G: Weighted>(graph: &G, max_cardinality: bool) -> Vec<(String, String)> {
+    let names: Vec<&str> = graph.node_names().collect();
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+
+    // one edge per unordered pair, keeping the heavier arc
+    let mut weights: HashMap<(usize, usize), i64> = HashMap::new();
+    let mut pairs = Vec::new();
+    for (i, &name) in names.iter().enumerate() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            let j = index[succ];
+            if i == j {
+                continue;
+            }
+            let weight = graph.edge_weight(name, succ).unwrap_or(1);
+            let pair = (i.min(j), i.max(j));
+            match weights.get_mut(&pair) {
+                Some(cur) => *cur = (*cur).max(weight),
+                None => {
+                    weights.insert(pair, weight);
+                    pairs.push(pair);
+                }
+            }
+        }
+    }
+    let edges = pairs
+        .into_iter()
+        .map(|(i, j)| (i, j, weights[&(i, j)]))
+        .collect();
+
+    let mate = Blossom::new(names.len(), edges).solve(max_cardinality);
+    mate.iter()
+        .enumerate()
+        .filter(|&(v, &w)| w != NONE && v < w)
+        .map(|(v, &w)| (names[v].to_string(), names[w].to_string()))
+        .collect()
+}
+
+/// A matching of least total cost between the two sides of a bipartite
+/// graph, as `(left, right)` pairs in the order of the left nodes, and its
+/// total cost, found with the Hungarian (Kuhn-Munkres) algorithm in
+/// `O(n^2 m)` time for `n <= m` nodes on the two sides.
+///
+/// Edges without a weight cost 1. Only the matchings with the most edges are
+/// considered, so every node of the smaller side is matched if it can be, even
+/// when leaving a node with only negative edges unmatched would cost less.
+pub fn min_cost_assignment(graph: &BipartiteGraph) -> (Vec<(String, String)>, i64) {
+    let left: Vec<&str> = graph.side_names(Side::Left).collect();
+    let right: Vec<&str> = graph.side_names(Side::Right).collect();
+    // rows are the smaller side
+    let transposed = left.len() > right.len();
+    let (rows, cols) = if transposed {
+        (&right, &left)
+    } else {
+        (&left, &right)
+    };
+    let pair = |r: usize, c: usize| {
+        if transposed {
+            (cols[c], rows[r])
+        } else {
+            (rows[r], cols[c])
+        }
+    };
+    let weights: Vec<Vec<Option<i64>>> = (0..rows.len())
+        .map(|r| {
+            (0..cols.len())
+                .map(|c| {
+                    let (a, b) = pair(r, c);
+                    if graph.contains_edge(a, b) {
+                        Some(graph.edge_weight(a, b).unwrap_or(1))
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    // a missing edge costs more than any set of edges, so the cheapest
+    // assignment uses as many edges as possible
+    let missing = 1 + weights
+        .iter()
+        .flatten()
+        .flatten()
+        .map(|&w| i128::from(w).abs())
+        .sum::<i128>();
+    let cost = |r: usize, c: usize| weights[r][c].map_or(missing, i128::from);
+
+    // potentials of rows and columns, and the row assigned to each column, all
+    // indexed from 1 so that column 0 can hold the row being added
+    let (n, m) = (rows.len(), cols.len());
+    let mut u = vec![0i128; n + 1];
+    let mut v = vec![0i128; m + 1];
+    let mut row_of = vec![0usize; m + 1];
+    let mut way = vec![0usize; m + 1];
+    for i in 1..=n {
+        row_of[0] = i;
+        let mut j0 = 0;
+        let mut min_slack = vec![i128::MAX; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = row_of[j0];
+            let mut delta = i128::MAX;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if !used[j] {
+                    let slack = cost(i0 - 1, j - 1) - u[i0] - v[j];
+                    if slack < min_slack[j] {
+                        min_slack[j] = slack;
+                        way[j] = j0;
+                    }
+                    if min_slack[j] < delta {
+                        delta = min_slack[j];
+                        j1 = j;
+                    }
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_slack[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if row_of[j0] == 0 {
+                break;
+            }
+        }
+        // flip the augmenting path back to column 0
+        while j0 != 0 {
+            let j1 = way[j0];
+            row_of[j0] = row_of[j1];
+            j0 = j1;
+        }
+    }
+
+    let mut assigned: Vec<(usize, usize, i64)> = (1..=m)
+        .filter(|&j| row_of[j] != 0)
+        .filter_map(|j| {
+            let (r, c) = (row_of[j] - 1, j - 1);
+            weights[r][c].map(|w| if transposed { (c, r, w) } else { (r, c, w) })
+        })
+        .collect();
+    assigned.sort_unstable();
+    let total = assigned.iter().map(|&(_, _, w)| w).sum();
+    let pairs = assigned
+        .into_iter()
+        .map(|(l, r, _)| (left[l].to_string(), right[r].to_string()))
+        .collect();
+    (pairs, total)
+}
+
+/// State of the primal-dual blossom algorithm, after the formulation of
+/// Galil ("Efficient algorithms for finding maximum matching in graphs", 1986)
+/// as implemented by Van Rantwijk.
+///
+/// Vertices are `0..n` and blossoms `n..2n`. Every edge `k` has two endpoints,
+/// `2k` and `2k + 1`, so the remote end of an endpoint `p` is `p ^ 1`.
+/// Weights are integers, so all dual variables stay integral.
+struct Blossom {
+    n: usize,
+    edges: Vec<(usize, usize, i64)>,
+    // endpoint[p]: vertex of endpoint p
+    endpoint: Vec<usize>,
+    // neighbend[v]: remote endpoints of the edges at v
+    neighbend: Vec<Vec<usize>>,
+    // mate[v]: remote endpoint of the matched edge at v, or NONE
+    mate: Vec<usize>,
+    // label of a top-level blossom or vertex: 0 free, 1 S, 2 T (5 while scanning)
+    label: Vec<u8>,
+    // labelend[b]: endpoint through which b got its label, or NONE
+    labelend: Vec<usize>,
+    // inblossom[v]: top-level blossom containing vertex v
+    inblossom: Vec<usize>,
+    blossomparent: Vec<usize>,
+    // sub-blossoms of b in cyclic order, starting with the base
+    blossomchilds: Vec<Vec<usize>>,
+    blossombase: Vec<usize>,
+    // blossomendps[b][i]: endpoint joining blossomchilds[b][i] to the next child
+    blossomendps: Vec<Vec<usize>>,
+    // bestedge[b]: least-slack edge to a different S-blossom, or NONE
+    bestedge: Vec<usize>,
+    blossombestedges: Vec<Option<Vec<usize>>>,
+    unusedblossoms: Vec<usize>,
+    dualvar: Vec<i64>,
+    // allowedge[k]: edge k has zero slack
+    allowedge: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+impl Blossom {
+    fn new(n: usize, edges: Vec<(usize, usize, i64)>) -> Self {
+        let max_weight = edges.iter().map(|e| e.2).max().unwrap_or(0).max(0);
+        let mut endpoint = Vec::with_capacity(2 * edges.len());
+        let mut neighbend = vec![Vec::new(); n];
+        for (k, &(i, j, _)) in edges.iter().enumerate() {
+            endpoint.push(i);
+            endpoint.push(j);
+            neighbend[i].push(2 * k + 1);
+            neighbend[j].push(2 * k);
+        }
+        let mut dualvar = vec![max_weight; n];
+        dualvar.extend(vec![0; n]);
+        Blossom {
+            n,
+            allowedge: vec![false; edges.len()],
+            edges,
+            endpoint,
+            neighbend,
+            mate: vec![NONE; n],
+            label: vec![0; 2 * n],
+            labelend: vec![NONE; 2 * n],
+            inblossom: (0..n).collect(),
+            blossomparent: vec![NONE; 2 * n],
+            blossomchilds: vec![Vec::new(); 2 * n],
+            blossombase: (0..n).chain(std::iter::repeat_n(NONE, n)).collect(),
+            blossomendps: vec![Vec::new(); 2 * n],
+            bestedge: vec![NONE; 2 * n],
+            blossombestedges: vec![None; 2 * n],
+            unusedblossoms: (n..2 * n).collect(),
+            dualvar,
+            queue: Vec::new(),
+        }
+    }
+
+    fn slack(&self, k: usize) -> i64 {
+        let (i, j, weight) = self.edges[k];
+        self.dualvar[i] + self.dualvar[j] - 2 * weight
+    }
+
+    fn leaves(&self, b: usize) -> Vec<usize> {
+        if b < self.n {
+            return vec![b];
+        }
+        self.blossomchilds[b]
+            .iter()
+            .flat_map(|&t| self.leaves(t))
+            .collect()
+    }
+
+    /// Label the top-level blossom of `w` with `t`, reached through endpoint
+    /// `p`; the mate of a new T-blossom becomes an S-blossom.
+    fn assign_label(&mut self, w: usize, t: u8, p: usize) {
+        let b = self.inblossom[w];
+        self.label[w] = t;
+        self.label[b] = t;
+        self.labelend[w] = p;
+        self.labelend[b] = p;
+        self.bestedge[w] = NONE;
+        self.bestedge[b] = NONE;
+        if t == 1 {
+            let leaves = self.leaves(b);
+            self.queue.extend(leaves);
+        } else {
+            let base = self.blossombase[b];
+            let m = self.mate[base];
+            self.assign_label(self.endpoint[m], 1, m ^ 1);
+        }
+    }
+
+    /// Trace back from `v` and `w` to find a new blossom's base, or NONE if
+    /// they lead to different roots and an augmenting path exists.
+    fn scan_blossom(&mut self, mut v: usize, mut w: usize) -> usize {
+        let mut path = Vec::new();
+        let mut base = NONE;
+        while v != NONE || w != NONE {
+            let mut b = self.inblossom[v];
+            if self.label[b] & 4 != 0 {
+                base = self.blossombase[b];
+                break;
+            }
+            path.push(b);
+            self.label[b] = 5;
+            if self.labelend[b] == NONE {
+                v = NONE;
+            } else {
+                v = self.endpoint[self.labelend[b]];
+                b = self.inblossom[v];
+                v = self.endpoint[self.labelend[b]];
+            }
+            if w != NONE {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for b in path {
+            self.label[b] = 1;
+        }
+        base
+    }
+
+    /// Make a new blossom with the given base out of the S-blossoms joined by
+    /// edge `k`.
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (mut v, mut w, _) = self.edges[k];
+        let bb = self.inblossom[base];
+        let mut bv = self.inblossom[v];
+        let mut bw = self.inblossom[w];
+        let b = self.unusedblossoms.pop().unwrap();
+        self.blossombase[b] = base;
+        self.blossomparent[b] = NONE;
+        self.blossomparent[bb] = b;
+
+        let mut path = Vec::new();
+        let mut endps = Vec::new();
+        while bv != bb {
+            self.blossomparent[bv] = b;
+            path.push(bv);
+            endps.push(self.labelend[bv]);
+            v = self.endpoint[self.labelend[bv]];
+            bv = self.inblossom[v];
+        }
+        path.push(bb);
+        path.reverse();
+        endps.reverse();
+        endps.push(2 * k);
+        while bw != bb {
+            self.blossomparent[bw] = b;
+            path.push(bw);
+            endps.push(self.labelend[bw] ^ 1);
+            w = self.endpoint[self.labelend[bw]];
+            bw = self.inblossom[w];
+        }
+        self.label[b] = 1;
+        self.labelend[b] = self.labelend[bb];
+        self.dualvar[b] = 0;
+        for v in self.leaves_of(&path) {
+            if self.label[self.inblossom[v]] == 2 {
+                self.queue.push(v);
+            }
+            self.inblossom[v] = b;
+        }
+
+        // least-slack edges from the new blossom to each other S-blossom
+        let mut bestedgeto = vec![NONE; 2 * self.n];
+        for &bv in path.iter() {
+            let lists: Vec<Vec<usize>> = match self.blossombestedges[bv].take() {
+                Some(list) => vec![list],
+                None => self
+                    .leaves(bv)
+                    .into_iter()
+                    .map(|v| self.neighbend[v].iter().map(|p| p / 2).collect())
+                    .collect(),
+            };
+            for k in lists.into_iter().flatten() {
+                // j: the endpoint outside the new blossom
+                let (i, j, _) = self.edges[k];
+                let j = if self.inblossom[j] == b { i } else { j };
+                let bj = self.inblossom[j];
+                if bj != b
+                    && self.label[bj] == 1
+                    && (bestedgeto[bj] == NONE || self.slack(k) < self.slack(bestedgeto[bj]))
+                {
+                    bestedgeto[bj] = k;
+                }
+            }
+            self.bestedge[bv] = NONE;
+        }
+        let best: Vec<usize> = bestedgeto.into_iter().filter(|&k| k != NONE).collect();
+        self.bestedge[b] = NONE;
+        for &k in best.iter() {
+            if self.bestedge[b] == NONE || self.slack(k) < self.slack(self.bestedge[b]) {
+                self.bestedge[b] = k;
+            }
+        }
+        self.blossombestedges[b] = Some(best);
+        self.blossomchilds[b] = path;
+        self.blossomendps[b] = endps;
+    }
+
+    fn leaves_of(&self, blossoms: &[usize]) -> Vec<usize> {
+        blossoms.iter().flat_map(|&t| self.leaves(t)).collect()
+    }
+
+    /// Dissolve a top-level blossom, relabeling its children if it was a
+    /// T-blossom in the middle of a stage.
+    fn expand_blossom(&mut self, b: usize, endstage: bool) {
+        for s in self.blossomchilds[b].clone() {
+            self.blossomparent[s] = NONE;
+            if s < self.n {
+                self.inblossom[s] = s;
+            } else if endstage && self.dualvar[s] == 0 {
+                self.expand_blossom(s, endstage);
+            } else {
+                for v in self.leaves(s) {
+                    self.inblossom[v] = s;
+                }
+            }
+        }
+
+        if !endstage && self.label[b] == 2 {
+            let childs = self.blossomchilds[b].clone();
+            let endps = self.blossomendps[b].clone();
+            let len = childs.len() as isize;
+            let at = |j: isize| j.rem_euclid(len) as usize;
+
+            // relabel the children along the even-length path from the
+            // entry child to the base
+            let entrychild = self.inblossom[self.endpoint[self.labelend[b] ^ 1]];
+            let mut j = childs.iter().position(|&c| c == entrychild).unwrap() as isize;
+            let (jstep, endptrick) = if j & 1 == 1 {
+                j -= len;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+            let mut p = self.labelend[b];
+            while j != 0 {
+                self.label[self.endpoint[p ^ 1]] = 0;
+                let q = endps[at(j - endptrick)] ^ endptrick as usize ^ 1;
+                self.label[self.endpoint[q]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p);
+                self.allowedge[endps[at(j - endptrick)] / 2] = true;
+                j += jstep;
+                p = endps[at(j - endptrick)] ^ endptrick as usize;
+                self.allowedge[p / 2] = true;
+                j += jstep;
+            }
+            let bv = childs[at(j)];
+            self.label[self.endpoint[p ^ 1]] = 2;
+            self.label[bv] = 2;
+            self.labelend[self.endpoint[p ^ 1]] = p;
+            self.labelend[bv] = p;
+            self.bestedge[bv] = NONE;
+            j += jstep;
+
+            // the remaining children keep a label only if a leaf was reached
+            while childs[at(j)] != entrychild {
+                let bv = childs[at(j)];
+                if self.label[bv] == 1 {
+                    j += jstep;
+                    continue;
+                }
+                if let Some(v) = self.leaves(bv).into_iter().find(|&v| self.label[v] != 0) {
+                    self.label[v] = 0;
+                    let m = self.mate[self.blossombase[bv]];
+                    self.label[self.endpoint[m]] = 0;
+                    self.assign_label(v, 2, self.labelend[v]);
+                }
+                j += jstep;
+            }
+        }
+
+        self.label[b] = 0;
+        self.labelend[b] = NONE;
+        self.blossomchilds[b] = Vec::new();
+        self.blossomendps[b] = Vec::new();
+        self.blossombase[b] = NONE;
+        self.blossombestedges[b] = None;
+        self.bestedge[b] = NONE;
+        self.unusedblossoms.push(b);
+    }
+
+    /// Swap matched and unmatched edges inside blossom `b` along the path
+    /// from vertex `v` to the base, making `v` the new base.
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossomparent[t] != b {
+            t = self.blossomparent[t];
+        }
+        if t >= self.n {
+            self.augment_blossom(t, v);
+        }
+
+        let childs = self.blossomchilds[b].clone();
+        let endps = self.blossomendps[b].clone();
+        let len = childs.len() as isize;
+        let at = |j: isize| j.rem_euclid(len) as usize;
+        let i = childs.iter().position(|&c| c == t).unwrap();
+        let mut j = i as isize;
+        let (jstep, endptrick) = if j & 1 == 1 {
+            j -= len;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+        while j != 0 {
+            j += jstep;
+            let t = childs[at(j)];
+            let p = endps[at(j - endptrick)] ^ endptrick as usize;
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+            j += jstep;
+            let t = childs[at(j)];
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+            self.mate[self.endpoint[p]] = p ^ 1;
+            self.mate[self.endpoint[p ^ 1]] = p;
+        }
+
+        self.blossomchilds[b].rotate_left(i);
+        self.blossomendps[b].rotate_left(i);
+        self.blossombase[b] = self.blossombase[self.blossomchilds[b][0]];
+    }
+
+    /// Flip the augmenting path through edge `k` between two S-vertices.
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            loop {
+                let bs = self.inblossom[s];
+                if bs >= self.n {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = p;
+                if self.labelend[bs] == NONE {
+                    // reached a single vertex root
+                    break;
+                }
+                let t = self.endpoint[self.labelend[bs]];
+                let bt = self.inblossom[t];
+                s = self.endpoint[self.labelend[bt]];
+                let j = self.endpoint[self.labelend[bt] ^ 1];
+                if bt >= self.n {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = self.labelend[bt];
+                p = self.labelend[bt] ^ 1;
+            }
+        }
+    }
+
+    /// Run the stages and return the mate of every vertex, or NONE.
+    fn solve(mut self, max_cardinality: bool) -> Vec<usize> {
+        let n = self.n;
+        for _ in 0..n {
+            self.label.iter_mut().for_each(|l| *l = 0);
+            self.bestedge.iter_mut().for_each(|e| *e = NONE);
+            self.blossombestedges[n..]
+                .iter_mut()
+                .for_each(|e| *e = None);
+            self.allowedge.iter_mut().for_each(|a| *a = false);
+            self.queue.clear();
+            for v in 0..n {
+                if self.mate[v] == NONE && self.label[self.inblossom[v]] == 0 {
+                    self.assign_label(v, 1, NONE);
+                }
+            }
+
+            let mut augmented = false;
+            loop {
+                while let Some(v) = self.queue.pop() {
+                    for p in self.neighbend[v].clone() {
+                        let k = p / 2;
+                        let w = self.endpoint[p];
+                        if self.inblossom[v] == self.inblossom[w] {
+                            continue;
+                        }
+                        let mut kslack = 0;
+                        if !self.allowedge[k] {
+                            kslack = self.slack(k);
+                            if kslack <= 0 {
+                                self.allowedge[k] = true;
+                            }
+                        }
+                        if self.allowedge[k] {
+                            if self.label[self.inblossom[w]] == 0 {
+                                self.assign_label(w, 2, p ^ 1);
+                            } else if self.label[self.inblossom[w]] == 1 {
+                                let base = self.scan_blossom(v, w);
+                                if base != NONE {
+                                    self.add_blossom(base, k);
+                                } else {
+                                    self.augment_matching(k);
+                                    augmented = true;
+                                    break;
+                                }
+                            } else if self.label[w] == 0 {
+                                self.label[w] = 2;
+                                self.labelend[w] = p ^ 1;
+                            }
+                        } else if self.label[self.inblossom[w]] == 1 {
+                            let b = self.inblossom[v];
+                            if self.bestedge[b] == NONE || kslack < self.slack(self.bestedge[b]) {
+                                self.bestedge[b] = k;
+                            }
+                        } else if self.label[w] == 0
+                            && (self.bestedge[w] == NONE || kslack < self.slack(self.bestedge[w]))
+                        {
+                            self.bestedge[w] = k;
+                        }
+                    }
+                    if augmented {
+                        break;
+                    }
+                }
+                if augmented {
+                    break;
+                }
+
+                // no augmenting path with the current duals; find the largest
+                // dual change that keeps every slack non-negative
+                let mut delta: Option<(u8, i64, usize)> = None;
+                if !max_cardinality {
+                    let min_dual = self.dualvar[..n].iter().copied().min().unwrap_or(0);
+                    delta = Some((1, min_dual, NONE));
+                }
+                for v in 0..n {
+                    if self.label[self.inblossom[v]] == 0 && self.bestedge[v] != NONE {
+                        let d = self.slack(self.bestedge[v]);
+                        if delta.is_none_or(|(_, cur, _)| d < cur) {
+                            delta = Some((2, d, self.bestedge[v]));
+                        }
+                    }
+                }
+                for b in 0..2 * n {
+                    if self.blossomparent[b] == NONE
+                        && self.label[b] == 1
+                        && self.bestedge[b] != NONE
+                    {
+                        let kslack = self.slack(self.bestedge[b]);
+                        debug_assert!(kslack % 2 == 0);
+                        let d = kslack / 2;
+                        if delta.is_none_or(|(_, cur, _)| d < cur) {
+                            delta = Some((3, d, self.bestedge[b]));
+                        }
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossombase[b] != NONE
+                        && self.blossomparent[b] == NONE
+                        && self.label[b] == 2
+                        && delta.is_none_or(|(_, cur, _)| self.dualvar[b] < cur)
+                    {
+                        delta = Some((4, self.dualvar[b], b));
+                    }
+                }
+                let (deltatype, delta, target) = delta.unwrap_or_else(|| {
+                    // only with max_cardinality: no further improvement possible
+                    let min_dual = self.dualvar[..n].iter().copied().min().unwrap_or(0);
+                    (1, min_dual.max(0), NONE)
+                });
+
+                for v in 0..n {
+                    match self.label[self.inblossom[v]] {
+                        1 => self.dualvar[v] -= delta,
+                        2 => self.dualvar[v] += delta,
+                        _ => {}
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossombase[b] != NONE && self.blossomparent[b] == NONE {
+                        match self.label[b] {
+                            1 => self.dualvar[b] += delta,
+                            2 => self.dualvar[b] -= delta,
+                            _ => {}
+                        }
+                    }
+                }
+
+                match deltatype {
+                    1 => break,
+                    2 | 3 => {
+                        self.allowedge[target] = true;
+                        let (mut i, j, _) = self.edges[target];
+                        if self.label[self.inblossom[i]] == 0 {
+                            i = j;
+                        }
+                        self.queue.push(i);
+                    }
+                    _ => self.expand_blossom(target, false),
+                }
+            }
+
+            if !augmented {
+                break;
+            }
+            // expand S-blossoms whose dual dropped to zero
+            for b in n..2 * n {
+                if self.blossomparent[b] == NONE
+                    && self.blossombase[b] != NONE
+                    && self.label[b] == 1
+                    && self.dualvar[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+
+        (0..n)
+            .map(|v| match self.mate[v] {
+                NONE => NONE,
+                p => self.endpoint[p],
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph, UnNode};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn pairs(matching: &[(String, String)]) -> Vec<(&str, &str)> {
+        matching
+            .iter()
+            .map(|(a, b)| (a.as_str(), b.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_max_weight_matching() {
+        let mut g = UnGraph::new(None);
+        g.add_weighted_edge("a", "b", 5);
+        g.add_weighted_edge("b", "c", 11);
+        g.add_weighted_edge("c", "d", 5);
+        assert_eq!(pairs(&max_weight_matching(&g)), vec![("b", "c")]);
+        assert_eq!(
+            pairs(&max_weight_max_cardinality_matching(&g)),
+            vec![("a", "b"), ("c", "d")]
+        );
+
+        // negative edges are never worth taking
+        let mut g = UnGraph::new(None);
+        g.add_weighted_edge("a", "b", -2);
+        assert!(max_weight_matching(&g).is_empty());
+        assert_eq!(max_weight_max_cardinality_matching(&g).len(), 1);
+    }
+
+    #[test]
+    fn test_max_weight_matching_blossoms() {
+        // an odd cycle a-b-c with a pendant d: the best matching goes around
+        // the blossom
+        let mut g = UnGraph::new(None);
+        g.add_weighted_edge("a", "b", 8);
+        g.add_weighted_edge("a", "c", 9);
+        g.add_weighted_edge("b", "c", 10);
+        g.add_weighted_edge("c", "d", 7);
+        assert_eq!(
+            pairs(&max_weight_matching(&g)),
+            vec![("a", "b"), ("c", "d")]
+        );
+
+        // pairs on both sides are treated as one undirected edge
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("a", "b", 1);
+        g.add_weighted_edge("b", "a", 3);
+        g.add_weighted_edge("b", "c", 2);
+        assert_eq!(pairs(&max_weight_matching(&g)), vec![("a", "b")]);
+    }
+
+    // best (cardinality, weight) over all matchings of the remaining edges
+    fn brute_force(
+        edges: &[(usize, usize, i64)],
+        used: &mut Vec<bool>,
+        max_cardinality: bool,
+    ) -> (usize, i64) {
+        let mut best = (0, 0);
+        for (k, &(i, j, w)) in edges.iter().enumerate() {
+            if used[i] || used[j] || (w < 0 && !max_cardinality) {
+                continue;
+            }
+            used[i] = true;
+            used[j] = true;
+            let (c, t) = brute_force(&edges[k + 1..], used, max_cardinality);
+            used[i] = false;
+            used[j] = false;
+            let candidate = (c + 1, t + w);
+            let better = if max_cardinality {
+                candidate > best
+            } else {
+                candidate.1 > best.1
+            };
+            if better {
+                best = candidate;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_min_cost_assignment() {
+        let mut g = BipartiteGraph::new(None);
+        for (a, b, w) in [
+            ("a", "x", 4),
+            ("a", "y", 1),
+            ("a", "z", 3),
+            ("b", "x", 2),
+            ("b", "y", 0),
+            ("b", "z", 5),
+            ("c", "x", 3),
+            ("c", "y", 2),
+            ("c", "z", 2),
+        ] {
+            g.add_weighted_edge(a, b, w).unwrap();
+        }
+        let (assignment, cost) = min_cost_assignment(&g);
+        assert_eq!(pairs(&assignment), vec![("a", "y"), ("b", "x"), ("c", "z")]);
+        assert_eq!(cost, 5);
+
+        // more left nodes than right ones, and an unweighted edge
+        let mut g = BipartiteGraph::new(None);
+        g.add_weighted_edge("a", "x", 7).unwrap();
+        g.add_edge("b", "x").unwrap();
+        g.add_weighted_edge("b", "y", -3).unwrap();
+        g.add_weighted_edge("c", "y", 1).unwrap();
+        let (assignment, cost) = min_cost_assignment(&g);
+        assert_eq!(pairs(&assignment), vec![("b", "x"), ("c", "y")]);
+        assert_eq!(cost, 2);
+
+        // edges are only used where they exist
+        let mut g = BipartiteGraph::new(None);
+        g.add_weighted_edge("a", "x", 1).unwrap();
+        g.add_node(Side::Right, UnNode::new("y", None)).unwrap();
+        g.add_node(Side::Left, UnNode::new("b", None)).unwrap();
+        assert_eq!(pairs(&min_cost_assignment(&g).0), vec![("a", "x")]);
+        assert_eq!(min_cost_assignment(&BipartiteGraph::new(None)), (vec![], 0));
+    }
+
+    #[test]
+    fn test_min_cost_assignment_random() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..300 {
+            let (n, m) = (rng.random_range(0..5), rng.random_range(0..5));
+            let mut g = BipartiteGraph::new(None);
+            for i in 0..n {
+                g.add_node(Side::Left, UnNode::new(&format!("l{}", i), None))
+                    .unwrap();
+            }
+            for j in 0..m {
+                g.add_node(Side::Right, UnNode::new(&format!("r{}", j), None))
+                    .unwrap();
+            }
+            // negated costs, for the maximizing brute force over all nodes
+            let mut edges = Vec::new();
+            for i in 0..n {
+                for j in 0..m {
+                    if rng.random_bool(0.6) {
+                        let w = rng.random_range(-5..20);
+                        g.add_weighted_edge(&format!("l{}", i), &format!("r{}", j), w)
+                            .unwrap();
+                        edges.push((i, n + j, -w));
+                    }
+                }
+            }
+            let (assignment, cost) = min_cost_assignment(&g);
+            let total: i64 = assignment
+                .iter()
+                .map(|(a, b)| g.edge_weight(a, b).unwrap())
+                .sum();
+            assert_eq!(total, cost);
+            let best = brute_force(&edges, &mut vec![false; n + m], true);
+            assert_eq!((assignment.len(), -cost), best);
+        }
+    }
+
+    #[test]
+    fn test_max_weight_matching_random() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..300 {
+            let n = rng.random_range(2..9);
+            let mut g = UnGraph::new(None);
+            let mut edges = Vec::new();
+            for i in 0..n {
+                g.add_node(UnNode::new(i.to_string().as_str(), None));
+                for j in 0..i {
+                    if rng.random_bool(0.5) {
+                        let w = rng.random_range(-3..20);
+                        g.add_weighted_edge(j.to_string().as_str(), i.to_string().as_str(), w);
+                        edges.push((j, i, w));
+                    }
+                }
+            }
+            let weight = |matching: &[(String, String)]| -> i64 {
+                matching
+                    .iter()
+                    .map(|(a, b)| g.get_edge_weight(a, b).unwrap())
+                    .sum()
+            };
+            for max_cardinality in [false, true] {
+                let matching = matching(&g, max_cardinality);
+                let mut nodes: Vec<&String> = matching.iter().flat_map(|(a, b)| [a, b]).collect();
+                nodes.sort();
+                nodes.dedup();
+                assert_eq!(nodes.len(), 2 * matching.len());
+
+                let best = brute_force(&edges, &mut vec![false; n], max_cardinality);
+                if max_cardinality {
+                    assert_eq!((matching.len(), weight(&matching)), best);
+                } else {
+                    assert_eq!(weight(&matching), best.1);
+                }
+            }
+        }
+    }
+}
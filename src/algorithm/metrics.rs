@@ -0,0 +1,194 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    error::GraphError,
+    graph::{Directed, GraphBase, Node},
+};
+
+/// Fraction of the `n * (n - 1)` possible directed edges that are present, or
+/// 0 with fewer than two nodes. Self-loops count as edges, so the density of a
+/// graph with self-loops may exceed 1.
+pub fn density<G: GraphBase>(graph: &G) -> f64 {
+    let n = graph.node_count();
+    if n < 2 {
+        return 0.0;
+    }
+    edge_count(graph) as f64 / (n * (n - 1)) as f64
+}
+
+/// Fraction of edges whose reverse edge is also present, or `None` for a graph
+/// without edges. Self-loops are never mutual.
+pub fn reciprocity<G: Directed>(graph: &G) -> Option<f64> {
+    let mut edges = 0;
+    let mut mutual = 0;
+    for node in graph.nodes() {
+        for succ in node.successor_names() {
+            edges += 1;
+            if succ != node.name() && graph.contains_edge(succ, node.name()) {
+                mutual += 1;
+            }
+        }
+    }
+    if edges == 0 {
+        None
+    } else {
+        Some(mutual as f64 / edges as f64)
+    }
+}
+
+/// Degree assortativity: the Pearson correlation, over all edges, between the
+/// out-degree of the source and the in-degree of the target.
+///
+/// Positive values mean edges tend to join nodes of similar degree, negative
+/// values that hubs link to low-degree nodes. Returns `None` if there are no
+/// edges or either degree is the same for every edge, where the correlation
+/// is undefined.
+pub fn assortativity_coefficient<G: GraphBase>(graph: &G) -> Option<f64> {
+    let pairs: Vec<(f64, f64)> = graph
+        .nodes()
+        .flat_map(|node| {
+            let x = node.out_degree() as f64;
+            node.successor_names()
+                .map(move |succ| (x, graph.node(succ).unwrap().in_degree() as f64))
+        })
+        .collect();
+    if pairs.is_empty() {
+        return None;
+    }
+
+    let m = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / m;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / m;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for &(x, y) in pairs.iter() {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x) * (x - mean_x);
+        var_y += (y - mean_y) * (y - mean_y);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some(cov / (var_x * var_y).sqrt())
+}
+
+/// Nodes without any edge, in graph order. A node with only a self-loop is
+/// not isolated.
+pub fn isolates<G: GraphBase>(graph: &G) -> Vec<String> {
+    graph
+        .nodes()
+        .filter(|node| node.in_degree() == 0 && node.out_degree() == 0)
+        .map(|node| node.name().to_string())
+        .collect()
+}
+
+/// Whether a node has no edge. Fails with `GraphError::NotFoundNode` for an
+/// unknown node.
+pub fn is_isolate<G: GraphBase>(graph: &G, name: &str) -> Result<bool, GraphError> {
+    let node = graph
+        .node(name)
+        .ok_or_else(|| GraphError::NotFoundNode(name.to_string()))?;
+    Ok(node.in_degree() == 0 && node.out_degree() == 0)
+}
+
+/// Number of edges from a node to itself.
+pub fn number_of_selfloops<G: GraphBase>(graph: &G) -> usize {
+    graph
+        .nodes()
+        .map(|node| {
+            node.successor_names()
+                .filter(|&succ| succ == node.name())
+                .count()
+        })
+        .sum()
+}
+
+/// Nodes with an edge to themselves, in graph order.
+pub fn nodes_with_selfloops<G: GraphBase>(graph: &G) -> Vec<String> {
+    graph
+        .nodes()
+        .filter(|node| node.successor_names().any(|succ| succ == node.name()))
+        .map(|node| node.name().to_string())
+        .collect()
+}
+
+pub(crate) fn edge_count<G: GraphBase>(graph: &G) -> usize {
+    graph.nodes().map(|node| node.out_degree()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, DiNode};
+
+    #[test]
+    fn test_density_and_reciprocity() {
+        let mut g: DiGraph = vec![("A", "B"), ("B", "A"), ("B", "C")]
+            .into_iter()
+            .collect();
+        assert_eq!(density(&g), 0.5);
+        assert_eq!(reciprocity(&g), Some(2.0 / 3.0));
+
+        g.add_edge(Some("C"), Some("C"));
+        assert_eq!(reciprocity(&g), Some(0.5));
+
+        assert_eq!(density(&DiGraph::new(None)), 0.0);
+        assert_eq!(reciprocity(&DiGraph::new(None)), None);
+    }
+
+    #[test]
+    fn test_isolates_and_selfloops() {
+        let mut g: DiGraph = vec![("A", "B"), ("C", "C")].into_iter().collect();
+        g.add_node(DiNode::new("D", None));
+        g.add_node(DiNode::new("E", None));
+        assert_eq!(isolates(&g), vec!["D", "E"]);
+        assert!(is_isolate(&g, "D").unwrap());
+        assert!(!is_isolate(&g, "C").unwrap());
+        assert!(matches!(
+            is_isolate(&g, "Z"),
+            Err(GraphError::NotFoundNode(_))
+        ));
+        assert_eq!(number_of_selfloops(&g), 1);
+        assert_eq!(nodes_with_selfloops(&g), vec!["C"]);
+
+        g.add_edge(Some("A"), Some("A"));
+        g.add_edge(Some("D"), Some("B"));
+        assert_eq!(isolates(&g), vec!["E"]);
+        assert_eq!(number_of_selfloops(&g), 2);
+        assert_eq!(nodes_with_selfloops(&g), vec!["A", "C"]);
+    }
+
+    #[test]
+    fn test_assortativity_coefficient() {
+        // a source hub fanning out, and single edges fanning into a sink hub
+        let g: DiGraph = vec![
+            ("H", "a"),
+            ("H", "b"),
+            ("H", "c"),
+            ("p", "S"),
+            ("q", "S"),
+            ("r", "S"),
+        ]
+        .into_iter()
+        .collect();
+        let r = assortativity_coefficient(&g).unwrap();
+        assert!((r + 1.0).abs() < 1e-12);
+
+        // every edge looks the same
+        let cycle: DiGraph = vec![("A", "B"), ("B", "C"), ("C", "A")]
+            .into_iter()
+            .collect();
+        assert_eq!(assortativity_coefficient(&cycle), None);
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Counting small recurring patterns: the triad census and motif counts.
+
+use super::isomorphism::DiGraphMatcher;
+use crate::graph::{Directed, Node, Ported};
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+
+/// The 16 isomorphism classes of directed triads, in the MAN notation of
+/// Holland and Leinhardt: the numbers of mutual, asymmetric and null dyads,
+/// followed by a letter telling apart triads with the same numbers.
+pub const TRIAD_NAMES: [&str; 16] = [
+    "003", "012", "102", "021D", "021U", "021C", "111D", "111U", "030T", "030C", "201", "120D",
+    "120U", "120C", "210", "300",
+];
+
+// triad type, as a 1-based index into TRIAD_NAMES, of every tricode
+const TRICODES: [usize; 64] = [
+    1, 2, 2, 3, 2, 4, 6, 8, 2, 6, 5, 7, 3, 8, 7, 11, 2, 6, 4, 8, 5, 9, 9, 13, 6, 10, 9, 14, 7, 14,
+    12, 15, 2, 5, 6, 7, 6, 9, 10, 14, 4, 9, 9, 12, 8, 13, 14, 15, 3, 7, 8, 11, 7, 12, 14, 15, 8,
+    14, 13, 15, 11, 15, 15, 16,
+];
+
+/// Number of node triples of every triad type, keyed and ordered by
+/// [`TRIAD_NAMES`]. Self-loops are ignored.
+///
+/// Uses the algorithm of Batagelj and Mrvar, which only visits connected
+/// triads and counts the others in bulk, in `O(E * max degree)` time.
+pub fn triadic_census<G: Directed>(graph: &G) -> IndexMap<&'static str, usize> {
+    let order: HashMap<&str, usize> = graph
+        .node_names()
+        .enumerate()
+        .map(|(i, name)| (name, i))
+        .collect();
+    let nbrs: HashMap<&str, HashSet<&str>> = graph
+        .nodes()
+        .map(|node| {
+            let mut set: HashSet<&str> = node.neighbors().into_iter().collect();
+            set.remove(node.name());
+            (node.name(), set)
+        })
+        .collect();
+
+    let n = graph.node_count();
+    let mut census = [0; 16];
+    for v in graph.node_names() {
+        for &u in &nbrs[v] {
+            if order[u] <= order[v] {
+                continue;
+            }
+            let mut others: HashSet<&str> = nbrs[v].union(&nbrs[u]).copied().collect();
+            others.remove(u);
+            others.remove(v);
+            // count every connected triad once, from its smallest connected pair
+            for &w in &others {
+                if order[u] < order[w]
+                    || (order[v] < order[w] && order[w] < order[u] && !nbrs[v].contains(w))
+                {
+                    census[TRICODES[tricode(graph, v, u, w)] - 1] += 1;
+                }
+            }
+            // triads where the third node is connected to neither
+            let dyad = if graph.contains_edge(u, v) && graph.contains_edge(v, u) {
+                2
+            } else {
+                1
+            };
+            census[dyad] += n - others.len() - 2;
+        }
+    }
+    let total = if n < 3 { 0 } else { n * (n - 1) * (n - 2) / 6 };
+    census[0] = total - census.iter().sum::<usize>();
+
+    TRIAD_NAMES.iter().copied().zip(census).collect()
+}
+
+/// A 6-bit code of the edges among three nodes.
+fn tricode<G: Directed>(graph: &G, v: &str, u: &str, w: &str) -> usize {
+    [(v, u), (u, v), (v, w), (w, v), (u, w), (w, u)]
+        .iter()
+        .enumerate()
+        .filter(|(_, (from, to))| graph.contains_edge(from, to))
+        .map(|(bit, _)| 1 << bit)
+        .sum()
+}
+
+/// Number of distinct occurrences of `motif` in `graph`, e.g. of a 3- or
+/// 4-node pattern. With `induced`, an occurrence must have no edges besides
+/// those of the motif; otherwise extra edges are allowed. Node weights must
+/// match as in the isomorphism matcher.
+///
+/// The matcher finds every occurrence once per automorphism of the motif, so
+/// the number of matches is divided by the number of automorphisms.
+pub fn count_motif<G: Ported>(graph: &G, motif: &G, induced: bool) -> usize {
+    if motif.node_count() == 0 {
+        return 0;
+    }
+    match_count(graph, motif, induced) / automorphism_count(motif)
+}
+
+/// Number of distinct occurrences of `pattern` in each of `graphs`, extra
+/// edges allowed, as [`count_motif`] counts them. The graphs containing the
+/// pattern at least once are those with a nonzero count.
+///
+/// The automorphisms of the pattern are counted once for all graphs. With the
+/// `parallel` feature, the graphs are searched on the `rayon` thread pool.
+pub fn count_embeddings<G: Ported + Sync>(pattern: &G, graphs: &[G]) -> Vec<usize> {
+    if pattern.node_count() == 0 {
+        return vec![0; graphs.len()];
+    }
+    let automorphisms = automorphism_count(pattern);
+    let count = |graph: &G| match_count(graph, pattern, false) / automorphisms;
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        graphs.par_iter().map(count).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        graphs.iter().map(count).collect()
+    }
+}
+
+/// Number of matches of `motif` in `graph`, once per automorphism of `motif`.
+fn match_count<G: Ported>(graph: &G, motif: &G, induced: bool) -> usize {
+    let mut matches = Vec::new();
+    let mut matcher = DiGraphMatcher::new(graph, motif);
+    if induced {
+        matcher.subgraph_isomorphism_iter(&mut matches);
+    } else {
+        matcher.subgraph_monomorphism_iter(&mut matches);
+    }
+    matches.len()
+}
+
+fn automorphism_count<G: Ported>(motif: &G) -> usize {
+    let mut automorphisms = Vec::new();
+    DiGraphMatcher::new(motif, motif).subgraph_isomorphism_iter(&mut automorphisms);
+    automorphisms.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_triadic_census() {
+        // every triad type of a single triple, with a self-loop that is ignored
+        let single = |edges: &[(&str, &str)], name: &str| {
+            let mut g: DiGraph = edges.iter().copied().collect();
+            for node in ["a", "b", "c"] {
+                g.add_edge(Some(node), None);
+            }
+            g.add_edge(Some("a"), Some("a"));
+            let census = triadic_census(&g);
+            assert_eq!(census[name], 1, "{}", name);
+            assert_eq!(census.values().sum::<usize>(), 1);
+        };
+        single(&[], "003");
+        single(&[("a", "b")], "012");
+        single(&[("a", "b"), ("b", "a")], "102");
+        single(&[("b", "a"), ("b", "c")], "021D");
+        single(&[("a", "b"), ("c", "b")], "021U");
+        single(&[("a", "b"), ("b", "c")], "021C");
+        single(&[("a", "b"), ("b", "a"), ("c", "a")], "111D");
+        single(&[("a", "b"), ("b", "a"), ("a", "c")], "111U");
+        single(&[("a", "b"), ("b", "c"), ("a", "c")], "030T");
+        single(&[("a", "b"), ("b", "c"), ("c", "a")], "030C");
+        single(&[("a", "b"), ("b", "a"), ("a", "c"), ("c", "a")], "201");
+        single(&[("a", "b"), ("b", "a"), ("c", "a"), ("c", "b")], "120D");
+        single(&[("a", "b"), ("b", "a"), ("a", "c"), ("b", "c")], "120U");
+        single(&[("a", "b"), ("b", "a"), ("a", "c"), ("c", "b")], "120C");
+        single(
+            &[("a", "b"), ("b", "a"), ("a", "c"), ("c", "a"), ("b", "c")],
+            "210",
+        );
+        single(
+            &[
+                ("a", "b"),
+                ("b", "a"),
+                ("a", "c"),
+                ("c", "a"),
+                ("b", "c"),
+                ("c", "b"),
+            ],
+            "300",
+        );
+
+        let g: DiGraph = vec![("A", "B"), ("B", "C"), ("C", "A"), ("C", "D"), ("D", "C")]
+            .into_iter()
+            .collect();
+        let census = triadic_census(&g);
+        assert_eq!(census.keys().copied().collect::<Vec<_>>(), TRIAD_NAMES);
+        // ABC, then ABD, ACD, BCD
+        assert_eq!(census["030C"], 1);
+        assert_eq!(census["012"], 1);
+        assert_eq!((census["111D"], census["111U"]), (1, 1));
+        assert_eq!(census.values().sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn test_count_motif() {
+        // two feed-forward loops sharing the edge A -> C
+        let g: DiGraph = vec![("A", "B"), ("B", "C"), ("A", "C"), ("A", "D"), ("D", "C")]
+            .into_iter()
+            .collect();
+        let ffl: DiGraph = vec![("x", "y"), ("y", "z"), ("x", "z")]
+            .into_iter()
+            .collect();
+        assert_eq!(count_motif(&g, &ffl, true), 2);
+
+        // the fan-in B -> C <- D has two automorphisms but occurs once
+        let fan_in: DiGraph = vec![("x", "z"), ("y", "z")].into_iter().collect();
+        assert_eq!(count_motif(&g, &fan_in, true), 1);
+        assert_eq!(count_motif(&g, &fan_in, false), 3);
+
+        // the 4-cycle has four rotations
+        let square: DiGraph = vec![("1", "2"), ("2", "3"), ("3", "4"), ("4", "1")]
+            .into_iter()
+            .collect();
+        let cycle: DiGraph = vec![("w", "x"), ("x", "y"), ("y", "z"), ("z", "w")]
+            .into_iter()
+            .collect();
+        assert_eq!(count_motif(&square, &cycle, true), 1);
+        assert_eq!(count_motif(&square, &DiGraph::new(None), true), 0);
+    }
+
+    #[test]
+    fn test_count_embeddings() {
+        let fan_in: DiGraph = vec![("x", "z"), ("y", "z")].into_iter().collect();
+        let graphs: Vec<DiGraph> = vec![
+            // three edges into C: three pairs of them
+            vec![("A", "C"), ("B", "C"), ("D", "C")]
+                .into_iter()
+                .collect(),
+            vec![("A", "B"), ("B", "C")].into_iter().collect(),
+            DiGraph::new(None),
+            vec![("A", "C"), ("B", "C"), ("A", "B")]
+                .into_iter()
+                .collect(),
+        ];
+        assert_eq!(count_embeddings(&fan_in, &graphs), vec![3, 0, 0, 1]);
+        for (graph, count) in graphs.iter().zip(count_embeddings(&fan_in, &graphs)) {
+            assert_eq!(count_motif(graph, &fan_in, false), count);
+        }
+        assert_eq!(count_embeddings(&DiGraph::new(None), &graphs), vec![0; 4]);
+        assert!(count_embeddings(&fan_in, &[]).is_empty());
+    }
+}
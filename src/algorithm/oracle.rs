@@ -0,0 +1,172 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Approximate distances for graphs too big for all-pairs shortest paths.
+
+use super::sssp::{multi_source_dijkstra, single_target_shortest_path};
+use crate::{
+    error::GraphError,
+    graph::{Node, Weighted},
+};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Answers approximate distance queries from precomputed distances between
+/// every node and a few landmarks, in `O(landmarks)` time per query.
+///
+/// Edges without a weight count as 1; weights must not be negative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceOracle {
+    landmarks: Vec<String>,
+    // distances from every landmark, and to every landmark
+    from_landmark: Vec<HashMap<String, i64>>,
+    to_landmark: Vec<HashMap<String, i64>>,
+}
+impl DistanceOracle {
+    /// Pick the `num_landmarks` nodes of highest degree as landmarks, ties
+    /// broken by graph order, and run Dijkstra from and to each of them.
+    ///
+    /// Fails with `GraphError::NegativeWeight` if a landmark reaches or is
+    /// reached over an edge of negative weight.
+    pub fn build<G: Weighted>(graph: &G, num_landmarks: usize) -> Result<Self, GraphError> {
+        let mut nodes: Vec<&G::Node> = graph.nodes().collect();
+        nodes.sort_by_key(|node| Reverse(node.in_degree() + node.out_degree()));
+        let landmarks: Vec<String> = nodes
+            .iter()
+            .take(num_landmarks)
+            .map(|node| node.name().to_string())
+            .collect();
+        Ok(DistanceOracle {
+            from_landmark: landmarks
+                .iter()
+                .map(|landmark| {
+                    let dist = multi_source_dijkstra(graph, &[landmark])?;
+                    Ok(dist.into_iter().map(|(name, (d, _))| (name, d)).collect())
+                })
+                .collect::<Result<_, GraphError>>()?,
+            to_landmark: landmarks
+                .iter()
+                .map(|landmark| {
+                    let dist = single_target_shortest_path(graph, landmark)?;
+                    Ok(dist.into_iter().map(|(name, (d, _))| (name, d)).collect())
+                })
+                .collect::<Result<_, GraphError>>()?,
+            landmarks,
+        })
+    }
+
+    pub fn landmarks(&self) -> &[String] {
+        &self.landmarks
+    }
+
+    /// An upper bound on the distance from `from` to `to`: the length of the
+    /// shortest path through a landmark. `None` if no landmark lies on a path
+    /// between them, which is always the case if `to` is unreachable.
+    pub fn estimate(&self, from: &str, to: &str) -> Option<i64> {
+        if from == to {
+            return Some(0);
+        }
+        (0..self.landmarks.len())
+            .filter_map(|i| Some(self.to_landmark[i].get(from)? + self.from_landmark[i].get(to)?))
+            .min()
+    }
+
+    /// A lower bound on the distance from `from` to `to`, by the triangle
+    /// inequality through every landmark. 0 if no landmark says otherwise.
+    pub fn lower_bound(&self, from: &str, to: &str) -> i64 {
+        let mut bound = 0;
+        for i in 0..self.landmarks.len() {
+            let (out, into) = (&self.from_landmark[i], &self.to_landmark[i]);
+            // d(l, to) <= d(l, from) + d(from, to)
+            if let (Some(l_from), Some(l_to)) = (out.get(from), out.get(to)) {
+                bound = bound.max(l_to - l_from);
+            }
+            // d(from, l) <= d(from, to) + d(to, l)
+            if let (Some(from_l), Some(to_l)) = (into.get(from), into.get(to)) {
+                bound = bound.max(from_l - to_l);
+            }
+        }
+        bound
+    }
+
+    /// A lower bound on the diameter: the greatest finite distance from or to
+    /// a landmark. It is at least half the diameter of a connected undirected
+    /// graph.
+    pub fn approximate_diameter(&self) -> i64 {
+        self.from_landmark
+            .iter()
+            .chain(self.to_landmark.iter())
+            .flat_map(|dist| dist.values().copied())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn test_distance_oracle_path() {
+        // a hub with two long arms
+        let mut g = UnGraph::new(None);
+        for arm in ["a", "b"] {
+            let mut prev = "hub".to_string();
+            for i in 1..=3 {
+                let name = format!("{}{}", arm, i);
+                g.add_edge(&prev, &name);
+                prev = name;
+            }
+        }
+        g.add_edge("hub", "c1");
+        let oracle = DistanceOracle::build(&g, 1).unwrap();
+        assert_eq!(oracle.landmarks(), ["hub"]);
+        // exact for paths through the hub
+        assert_eq!(oracle.estimate("a3", "b2"), Some(5));
+        assert_eq!(oracle.lower_bound("a3", "b2"), 1);
+        // overestimated along an arm
+        assert_eq!(oracle.estimate("a3", "a2"), Some(5));
+        assert_eq!(oracle.lower_bound("a3", "a2"), 1);
+        assert_eq!(oracle.estimate("a1", "a1"), Some(0));
+        assert_eq!(oracle.approximate_diameter(), 3);
+    }
+
+    #[test]
+    fn test_distance_oracle_directed() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 4);
+        g.add_weighted_edge("B", "C", 1);
+        g.add_weighted_edge("A", "C", 2);
+        g.add_weighted_edge("C", "D", 3);
+        let oracle = DistanceOracle::build(&g, 10).unwrap();
+        assert_eq!(oracle.landmarks().len(), 4);
+        assert_eq!(oracle.landmarks()[0], "C");
+        // with every node a landmark the estimates are exact
+        assert_eq!(oracle.estimate("A", "D"), Some(5));
+        assert_eq!(oracle.lower_bound("A", "D"), 5);
+        assert_eq!(oracle.estimate("D", "A"), None);
+        assert_eq!(oracle.approximate_diameter(), 5);
+        assert_eq!(
+            DistanceOracle::build(&g, 0).unwrap().estimate("A", "D"),
+            None
+        );
+
+        g.add_weighted_edge("D", "C", -1);
+        assert!(matches!(
+            DistanceOracle::build(&g, 1),
+            Err(GraphError::NegativeWeight(..))
+        ));
+    }
+}
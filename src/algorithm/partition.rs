@@ -0,0 +1,216 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::graph::{GraphBase, Node};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// A split of the nodes of a graph into parts `0` and `1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bisection {
+    /// Part of every node.
+    pub assignment: HashMap<String, usize>,
+    /// Number of edges whose endpoints are in different parts.
+    pub cut_size: usize,
+}
+
+/// Split the nodes into two parts with few edges between them, using
+/// Kernighan-Lin style passes of Fiduccia-Mattheyses single-node moves.
+///
+/// The first half of the nodes, in graph order, starts in part `0`. Each pass
+/// tentatively moves every node once, greedily by gain, and keeps the best
+/// prefix of moves that leaves the part sizes at most `max_imbalance` apart
+/// (or 1 apart if the node count is odd). Passes stop after `max_passes` or
+/// once a pass no longer reduces the cut. Edge direction and weight are
+/// ignored; each edge between the parts counts once.
+pub fn kernighan_lin_bisection<G: GraphBase>(
+    graph: &G,
+    max_imbalance: usize,
+    max_passes: usize,
+) -> Bisection {
+    let names: Vec<&str> = graph.node_names().collect();
+    let n = names.len();
+    let initial: Vec<usize> = (0..n).map(|v| if v < n / 2 { 0 } else { 1 }).collect();
+    refine(graph, &names, initial, max_imbalance, max_passes)
+}
+
+/// Like [`kernighan_lin_bisection`], but start from a given assignment; nodes
+/// missing from it start in part `0`. An unbalanced start is only changed by
+/// a pass that reaches a balanced state with a smaller cut.
+pub fn fiduccia_mattheyses<G: GraphBase>(
+    graph: &G,
+    initial: &HashMap<String, usize>,
+    max_imbalance: usize,
+    max_passes: usize,
+) -> Bisection {
+    let names: Vec<&str> = graph.node_names().collect();
+    let initial: Vec<usize> = names
+        .iter()
+        .map(|&name| initial.get(name).map_or(0, |&part| part.min(1)))
+        .collect();
+    refine(graph, &names, initial, max_imbalance, max_passes)
+}
+
+fn refine<G: GraphBase>(
+    graph: &G,
+    names: &[&str],
+    mut part: Vec<usize>,
+    max_imbalance: usize,
+    max_passes: usize,
+) -> Bisection {
+    let n = names.len();
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+
+    // undirected neighbor lists; an edge in both directions appears twice
+    let mut nbrs = vec![Vec::new(); n];
+    for (v, &name) in names.iter().enumerate() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            let w = index[succ];
+            if w != v {
+                nbrs[v].push(w);
+                nbrs[w].push(v);
+            }
+        }
+    }
+
+    let limit = max_imbalance.max(n % 2);
+    let imbalance = |part: &[usize]| {
+        let ones = part.iter().filter(|&&p| p == 1).count();
+        (n - ones).abs_diff(ones)
+    };
+    // gain of moving v: cut edges it would remove minus cut edges it would add
+    let gain = |part: &[usize], v: usize| -> i64 {
+        nbrs[v]
+            .iter()
+            .map(|&w| if part[w] != part[v] { 1 } else { -1 })
+            .sum()
+    };
+
+    for _ in 0..max_passes {
+        let mut locked = vec![false; n];
+        let mut gains: Vec<i64> = (0..n).map(|v| gain(&part, v)).collect();
+        let mut moves = Vec::new();
+        let mut total = 0;
+        let mut best = (0, 0); // (total gain, number of moves)
+
+        loop {
+            // intermediate states may be one move out of balance
+            let ones = part.iter().filter(|&&p| p == 1).count();
+            let bound = (limit + 2).max(imbalance(&part));
+            let candidate = (0..n)
+                .filter(|&v| !locked[v])
+                .filter(|&v| {
+                    let ones = if part[v] == 0 { ones + 1 } else { ones - 1 };
+                    (n - ones).abs_diff(ones) <= bound
+                })
+                .max_by_key(|&v| (gains[v], Reverse(v)));
+            let v = match candidate {
+                Some(v) => v,
+                None => break,
+            };
+
+            total += gains[v];
+            part[v] = 1 - part[v];
+            locked[v] = true;
+            moves.push(v);
+            for &w in nbrs[v].iter() {
+                gains[w] = gain(&part, w);
+            }
+            if total > best.0 && imbalance(&part) <= limit {
+                best = (total, moves.len());
+            }
+        }
+
+        // undo the moves after the best prefix
+        for &v in moves[best.1..].iter() {
+            part[v] = 1 - part[v];
+        }
+        if best.0 <= 0 {
+            break;
+        }
+    }
+
+    let cut_size = (0..n)
+        .map(|v| nbrs[v].iter().filter(|&&w| part[w] != part[v]).count())
+        .sum::<usize>()
+        / 2;
+    Bisection {
+        assignment: names
+            .iter()
+            .zip(part)
+            .map(|(&name, p)| (name.to_string(), p))
+            .collect(),
+        cut_size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_kernighan_lin_bisection() {
+        // two 4-cliques joined by a single edge, interleaved in insertion order
+        let mut g = DiGraph::new(None);
+        let left = ["a", "b", "c", "d"];
+        let right = ["w", "x", "y", "z"];
+        for i in 0..4 {
+            g.add_edge(Some(left[i]), None);
+            g.add_edge(Some(right[i]), None);
+        }
+        for group in [left, right] {
+            for i in 0..4 {
+                for j in i + 1..4 {
+                    g.add_edge(Some(group[i]), Some(group[j]));
+                }
+            }
+        }
+        g.add_edge(Some("d"), Some("w"));
+
+        let bisection = kernighan_lin_bisection(&g, 0, 10);
+        assert_eq!(bisection.cut_size, 1);
+        let side = bisection.assignment["a"];
+        for name in left {
+            assert_eq!(bisection.assignment[name], side);
+        }
+        for name in right {
+            assert_eq!(bisection.assignment[name], 1 - side);
+        }
+    }
+
+    #[test]
+    fn test_fiduccia_mattheyses_balance() {
+        // a star cannot be cut cleanly; balance must still hold
+        let g: DiGraph = vec![("h", "1"), ("h", "2"), ("h", "3"), ("h", "4"), ("h", "5")]
+            .into_iter()
+            .collect();
+        let initial: HashMap<String, usize> = vec![("3", 1), ("4", 1), ("5", 1)]
+            .into_iter()
+            .map(|(x, y)| (x.to_string(), y))
+            .collect();
+
+        let bisection = fiduccia_mattheyses(&g, &initial, 0, 10);
+        let ones = bisection.assignment.values().filter(|&&p| p == 1).count();
+        assert_eq!(ones, 3);
+        assert_eq!(bisection.cut_size, 3);
+
+        let bisection = fiduccia_mattheyses(&g, &initial, 4, 10);
+        assert_eq!(bisection.cut_size, 1);
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::graph::Weighted;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A walk through a graph, as returned by the path-finding algorithms: its
+/// nodes in order, with the weight of every edge between them.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Path {
+    nodes: Vec<String>,
+    // weights[i]: weight of the edge from nodes[i] to nodes[i + 1]
+    weights: Vec<Option<i64>>,
+}
+impl Path {
+    /// Follow `nodes` through `graph`, looking up the weight of every edge.
+    pub fn from_nodes<G: Weighted>(graph: &G, nodes: Vec<String>) -> Self {
+        let weights = nodes
+            .windows(2)
+            .map(|pair| graph.edge_weight(&pair[0], &pair[1]))
+            .collect();
+        Path { nodes, weights }
+    }
+
+    pub fn nodes(&self) -> &[String] {
+        &self.nodes
+    }
+
+    pub fn into_nodes(self) -> Vec<String> {
+        self.nodes
+    }
+
+    /// The edges in order as `(source, target, weight)` triples.
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str, Option<i64>)> {
+        self.nodes
+            .windows(2)
+            .zip(self.weights.iter())
+            .map(|(pair, &weight)| (pair[0].as_str(), pair[1].as_str(), weight))
+    }
+
+    /// Sum of the edge weights, with edges without a weight counting as 1.
+    pub fn total_weight(&self) -> i64 {
+        self.weights.iter().map(|weight| weight.unwrap_or(1)).sum()
+    }
+
+    /// Number of edges.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Whether the path has no edges; it may still have a single node.
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    pub fn contains(&self, node: &str) -> bool {
+        self.nodes.iter().any(|name| name == node)
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        self.nodes.first().map(String::as_str)
+    }
+
+    pub fn target(&self) -> Option<&str> {
+        self.nodes.last().map(String::as_str)
+    }
+
+    /// Iterate over the node names in order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().map(String::as_str)
+    }
+}
+impl<'a> IntoIterator for &'a Path {
+    type Item = &'a String;
+    type IntoIter = std::slice::Iter<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.iter()
+    }
+}
+impl fmt::Display for Path {
+    /// The nodes joined by arrows, e.g. `A -> B -> C`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.nodes.join(" -> "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_path() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 3);
+        g.add_edge(Some("B"), Some("C"));
+        let path = Path::from_nodes(&g, vec!["A".into(), "B".into(), "C".into()]);
+
+        assert_eq!(path.len(), 2);
+        assert_eq!(path.total_weight(), 4);
+        assert_eq!(
+            path.edges().collect::<Vec<_>>(),
+            vec![("A", "B", Some(3)), ("B", "C", None)]
+        );
+        assert!(path.contains("B") && !path.contains("D"));
+        assert_eq!((path.source(), path.target()), (Some("A"), Some("C")));
+        assert_eq!(path.iter().collect::<Vec<_>>(), vec!["A", "B", "C"]);
+        assert_eq!(path.to_string(), "A -> B -> C");
+
+        let single = Path::from_nodes(&g, vec!["A".into()]);
+        assert!(single.is_empty());
+        assert_eq!(single.total_weight(), 0);
+        assert_eq!(Path::default().source(), None);
+    }
+}
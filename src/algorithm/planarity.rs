@@ -0,0 +1,678 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::graph::{GraphBase, Node};
+use std::collections::{HashMap, HashSet};
+
+/// The outcome of a planarity test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Planarity {
+    /// A combinatorial embedding: the neighbors of every node in clockwise
+    /// order around it. Isolated nodes have no neighbors.
+    Planar(HashMap<String, Vec<String>>),
+    /// The edges of a subdivision of K5 or K3,3 contained in the graph, as
+    /// `(a, b)` pairs with `a` before `b` in graph order, sorted by graph order.
+    NonPlanar(Vec<(String, String)>),
+}
+impl Planarity {
+    pub fn is_planar(&self) -> bool {
+        matches!(self, Planarity::Planar(_))
+    }
+}
+
+/// Test whether a graph can be drawn in the plane without crossing edges, with
+/// the left-right planarity test of de Fraysseix and Rosenstiehl in `O(V + E)`
+/// time.
+///
+/// Edge direction is ignored, and self-loops and parallel edges never affect
+/// planarity, so they are left out of the embedding. A non-planar graph is
+/// proven so by a Kuratowski subgraph, found by dropping every edge that is
+/// not needed to keep the rest non-planar, which takes `O(E * (V + E))` time.
+pub fn is_planar<G: GraphBase>(graph: &G) -> Planarity {
+    let names: Vec<&str> = graph.node_names().collect();
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+    let mut nbrs = vec![Vec::new(); names.len()];
+    for (v, &name) in names.iter().enumerate() {
+        let node = graph.node(name).unwrap();
+        for other in node.successor_names().chain(node.predecessor_names()) {
+            let w = index[other];
+            if w != v && !nbrs[v].contains(&w) {
+                nbrs[v].push(w);
+            }
+        }
+    }
+
+    match lr_planarity(&nbrs) {
+        Some(rotation) => Planarity::Planar(
+            rotation
+                .into_iter()
+                .enumerate()
+                .map(|(v, order)| {
+                    let order = order.into_iter().map(|w| names[w].to_string()).collect();
+                    (names[v].to_string(), order)
+                })
+                .collect(),
+        ),
+        None => Planarity::NonPlanar(
+            kuratowski_edges(nbrs)
+                .into_iter()
+                .map(|(v, w)| (names[v].to_string(), names[w].to_string()))
+                .collect(),
+        ),
+    }
+}
+
+/// Edges `(v, w)` with `v < w` of an edge-minimal non-planar subgraph, which
+/// is a subdivision of K5 or K3,3.
+fn kuratowski_edges(mut nbrs: Vec<Vec<usize>>) -> Vec<(usize, usize)> {
+    for v in 0..nbrs.len() {
+        for w in nbrs[v].clone() {
+            if w < v {
+                continue;
+            }
+            nbrs[v].retain(|&x| x != w);
+            nbrs[w].retain(|&x| x != v);
+            if lr_planarity(&nbrs).is_some() {
+                nbrs[v].push(w);
+                nbrs[w].push(v);
+            }
+        }
+    }
+    let mut edges: Vec<(usize, usize)> = nbrs
+        .iter()
+        .enumerate()
+        .flat_map(|(v, list)| list.iter().filter(move |&&w| v < w).map(move |&w| (v, w)))
+        .collect();
+    edges.sort_unstable();
+    edges
+}
+
+/// Neighbors of every node in clockwise order, or `None` if the simple
+/// undirected graph given by `nbrs` is not planar.
+fn lr_planarity(nbrs: &[Vec<usize>]) -> Option<Vec<Vec<usize>>> {
+    let n = nbrs.len();
+    let m = nbrs.iter().map(|list| list.len()).sum::<usize>() / 2;
+    if n > 2 && m > 3 * n - 6 {
+        return None;
+    }
+
+    let mut state = LrState::new(nbrs);
+    let mut roots = Vec::new();
+    for v in 0..n {
+        if state.height[v].is_none() {
+            state.height[v] = Some(0);
+            roots.push(v);
+            state.orient(v);
+        }
+    }
+
+    let m = state.edges.len();
+    state.lowpt_edge = vec![None; m];
+    state.refs = vec![None; m];
+    state.side = vec![1; m];
+    state.stack_bottom = vec![None; m];
+    state.sort_adjacency(|state, e| state.nesting_depth[e] as i64);
+    for &root in roots.iter() {
+        if !state.test(root) {
+            return None;
+        }
+    }
+
+    let depth: Vec<i64> = (0..m)
+        .map(|e| i64::from(state.sign(e)) * state.nesting_depth[e] as i64)
+        .collect();
+    state.sort_adjacency(|_, e| depth[e]);
+    let mut rotation = Rotation::new(n);
+    for v in 0..n {
+        let mut prev = None;
+        for &e in state.ordered[v].iter() {
+            let w = state.edges[e].1;
+            rotation.add_cw(v, w, prev);
+            prev = Some(w);
+        }
+    }
+    state.left_ref = vec![0; n];
+    state.right_ref = vec![0; n];
+    for &root in roots.iter() {
+        state.embed(root, &mut rotation);
+    }
+    Some(rotation.into_orders())
+}
+
+/// A set of return edges, given by its lowest and highest edge; the edges in
+/// between are chained by `LrState::refs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Interval {
+    low: Option<usize>,
+    high: Option<usize>,
+}
+impl Interval {
+    fn is_empty(&self) -> bool {
+        self.low.is_none() && self.high.is_none()
+    }
+}
+
+/// Return edges that must be embedded on opposite sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct ConflictPair {
+    left: Interval,
+    right: Interval,
+}
+impl ConflictPair {
+    fn swap(&mut self) {
+        std::mem::swap(&mut self.left, &mut self.right);
+    }
+}
+
+struct LrState<'a> {
+    nbrs: &'a [Vec<usize>],
+    height: Vec<Option<usize>>,
+    // DFS orientation: edges as (source, target), each node's outgoing edges
+    edges: Vec<(usize, usize)>,
+    out: Vec<Vec<usize>>,
+    oriented: HashSet<(usize, usize)>,
+    parent_edge: Vec<Option<usize>>,
+    lowpt: Vec<usize>,
+    lowpt2: Vec<usize>,
+    nesting_depth: Vec<usize>,
+    ordered: Vec<Vec<usize>>,
+    // testing phase, indexed by edge
+    lowpt_edge: Vec<Option<usize>>,
+    refs: Vec<Option<usize>>,
+    side: Vec<i8>,
+    stack: Vec<ConflictPair>,
+    stack_bottom: Vec<Option<ConflictPair>>,
+    // embedding phase, indexed by node
+    left_ref: Vec<usize>,
+    right_ref: Vec<usize>,
+}
+impl<'a> LrState<'a> {
+    fn new(nbrs: &'a [Vec<usize>]) -> Self {
+        let n = nbrs.len();
+        LrState {
+            nbrs,
+            height: vec![None; n],
+            edges: Vec::new(),
+            out: vec![Vec::new(); n],
+            oriented: HashSet::new(),
+            parent_edge: vec![None; n],
+            lowpt: Vec::new(),
+            lowpt2: Vec::new(),
+            nesting_depth: Vec::new(),
+            ordered: Vec::new(),
+            lowpt_edge: Vec::new(),
+            refs: Vec::new(),
+            side: Vec::new(),
+            stack: Vec::new(),
+            stack_bottom: Vec::new(),
+            left_ref: Vec::new(),
+            right_ref: Vec::new(),
+        }
+    }
+
+    /// Orient the edges along a DFS from `v` and compute their lowpoints and
+    /// nesting depths.
+    fn orient(&mut self, v: usize) {
+        let parent = self.parent_edge[v];
+        let height = self.height[v].unwrap();
+        let nbrs = self.nbrs;
+        for &w in nbrs[v].iter() {
+            if !self.oriented.insert((v.min(w), v.max(w))) {
+                continue;
+            }
+            let vw = self.edges.len();
+            self.edges.push((v, w));
+            self.out[v].push(vw);
+            self.lowpt.push(height);
+            self.lowpt2.push(height);
+            self.nesting_depth.push(0);
+            match self.height[w] {
+                // tree edge
+                None => {
+                    self.parent_edge[w] = Some(vw);
+                    self.height[w] = Some(height + 1);
+                    self.orient(w);
+                }
+                // back edge
+                Some(target) => self.lowpt[vw] = target,
+            }
+
+            self.nesting_depth[vw] = 2 * self.lowpt[vw] + usize::from(self.lowpt2[vw] < height);
+            if let Some(e) = parent {
+                if self.lowpt[vw] < self.lowpt[e] {
+                    self.lowpt2[e] = self.lowpt[e].min(self.lowpt2[vw]);
+                    self.lowpt[e] = self.lowpt[vw];
+                } else if self.lowpt[vw] > self.lowpt[e] {
+                    self.lowpt2[e] = self.lowpt2[e].min(self.lowpt[vw]);
+                } else {
+                    self.lowpt2[e] = self.lowpt2[e].min(self.lowpt2[vw]);
+                }
+            }
+        }
+    }
+
+    fn sort_adjacency(&mut self, key: impl Fn(&Self, usize) -> i64) {
+        let mut ordered = self.out.clone();
+        for list in ordered.iter_mut() {
+            list.sort_by_key(|&e| key(self, e));
+        }
+        self.ordered = ordered;
+    }
+
+    /// Check the constraints on the return edges below `v`, assigning every
+    /// edge to a side; `false` if they cannot be met.
+    fn test(&mut self, v: usize) -> bool {
+        let parent = self.parent_edge[v];
+        let height = self.height[v].unwrap();
+        for i in 0..self.ordered[v].len() {
+            let ei = self.ordered[v][i];
+            let w = self.edges[ei].1;
+            self.stack_bottom[ei] = self.stack.last().copied();
+            if self.parent_edge[w] == Some(ei) {
+                if !self.test(w) {
+                    return false;
+                }
+            } else {
+                self.lowpt_edge[ei] = Some(ei);
+                self.stack.push(ConflictPair {
+                    left: Interval::default(),
+                    right: Interval {
+                        low: Some(ei),
+                        high: Some(ei),
+                    },
+                });
+            }
+
+            // integrate the return edges of ei
+            if self.lowpt[ei] < height {
+                let e = parent.unwrap();
+                if i == 0 {
+                    self.lowpt_edge[e] = self.lowpt_edge[ei];
+                } else if !self.add_constraints(ei, e) {
+                    return false;
+                }
+            }
+        }
+        if let Some(e) = parent {
+            self.remove_back_edges(e);
+        }
+        true
+    }
+
+    fn conflicting(&self, interval: &Interval, edge: usize) -> bool {
+        interval
+            .high
+            .is_some_and(|high| self.lowpt[high] > self.lowpt[edge])
+    }
+
+    fn lowest(&self, pair: &ConflictPair) -> usize {
+        match (pair.left.low, pair.right.low) {
+            (None, Some(right)) => self.lowpt[right],
+            (Some(left), None) => self.lowpt[left],
+            (Some(left), Some(right)) => self.lowpt[left].min(self.lowpt[right]),
+            (None, None) => unreachable!("empty conflict pair on the stack"),
+        }
+    }
+
+    fn add_constraints(&mut self, ei: usize, e: usize) -> bool {
+        let mut pair = ConflictPair::default();
+
+        // merge the return edges of ei into pair.right
+        loop {
+            let mut q = self.stack.pop().unwrap();
+            if !q.left.is_empty() {
+                q.swap();
+            }
+            if !q.left.is_empty() {
+                return false;
+            }
+            let low = q.right.low.unwrap();
+            if self.lowpt[low] > self.lowpt[e] {
+                if pair.right.is_empty() {
+                    pair.right = q.right;
+                } else {
+                    self.refs[pair.right.low.unwrap()] = q.right.high;
+                }
+                pair.right.low = q.right.low;
+            } else {
+                self.refs[low] = self.lowpt_edge[e];
+            }
+            if self.stack.last().copied() == self.stack_bottom[ei] {
+                break;
+            }
+        }
+
+        // merge the conflicting return edges of earlier siblings into pair.left
+        while let Some(top) = self.stack.last() {
+            if !self.conflicting(&top.left, ei) && !self.conflicting(&top.right, ei) {
+                break;
+            }
+            let mut q = self.stack.pop().unwrap();
+            if self.conflicting(&q.right, ei) {
+                q.swap();
+            }
+            if self.conflicting(&q.right, ei) {
+                return false;
+            }
+            if let Some(low) = pair.right.low {
+                self.refs[low] = q.right.high;
+            }
+            if q.right.low.is_some() {
+                pair.right.low = q.right.low;
+            }
+            if pair.left.is_empty() {
+                pair.left = q.left;
+            } else if let Some(low) = pair.left.low {
+                self.refs[low] = q.left.high;
+            }
+            pair.left.low = q.left.low;
+        }
+
+        if !pair.left.is_empty() || !pair.right.is_empty() {
+            self.stack.push(pair);
+        }
+        true
+    }
+
+    /// Drop the back edges that end at the source of `e` once its subtree is
+    /// done.
+    fn remove_back_edges(&mut self, e: usize) {
+        let u = self.edges[e].0;
+        let height = self.height[u].unwrap();
+        while let Some(top) = self.stack.last() {
+            if self.lowest(top) != height {
+                break;
+            }
+            let pair = self.stack.pop().unwrap();
+            if let Some(low) = pair.left.low {
+                self.side[low] = -1;
+            }
+        }
+
+        if let Some(mut pair) = self.stack.pop() {
+            while let Some(high) = pair.left.high.filter(|&high| self.edges[high].1 == u) {
+                pair.left.high = self.refs[high];
+            }
+            if pair.left.high.is_none() {
+                if let Some(low) = pair.left.low.take() {
+                    self.refs[low] = pair.right.low;
+                    self.side[low] = -1;
+                }
+            }
+            while let Some(high) = pair.right.high.filter(|&high| self.edges[high].1 == u) {
+                pair.right.high = self.refs[high];
+            }
+            if pair.right.high.is_none() {
+                if let Some(low) = pair.right.low.take() {
+                    self.refs[low] = pair.left.low;
+                    self.side[low] = -1;
+                }
+            }
+            self.stack.push(pair);
+        }
+
+        // e goes on the side of its highest return edge
+        if self.lowpt[e] < height {
+            let top = self.stack.last().unwrap();
+            let (left, right) = (top.left.high, top.right.high);
+            self.refs[e] = match (left, right) {
+                (Some(l), Some(r)) if self.lowpt[l] > self.lowpt[r] => left,
+                (Some(_), None) => left,
+                _ => right,
+            };
+        }
+    }
+
+    /// Resolve the side of `e` relative to the edges it refers to.
+    fn sign(&mut self, e: usize) -> i8 {
+        let mut chain = Vec::new();
+        let mut cur = e;
+        while let Some(next) = self.refs[cur] {
+            chain.push(cur);
+            cur = next;
+        }
+        let mut side = self.side[cur];
+        for &edge in chain.iter().rev() {
+            self.side[edge] *= side;
+            self.refs[edge] = None;
+            side = self.side[edge];
+        }
+        self.side[e]
+    }
+
+    /// Add the incoming half-edges of the subtree of `v` to the rotation.
+    fn embed(&mut self, v: usize, rotation: &mut Rotation) {
+        for i in 0..self.ordered[v].len() {
+            let ei = self.ordered[v][i];
+            let w = self.edges[ei].1;
+            if self.parent_edge[w] == Some(ei) {
+                rotation.add_first(w, v);
+                self.left_ref[v] = w;
+                self.right_ref[v] = w;
+                self.embed(w, rotation);
+            } else if self.side[ei] == 1 {
+                rotation.add_cw(w, v, Some(self.right_ref[w]));
+            } else {
+                rotation.add_ccw(w, v, Some(self.left_ref[w]));
+                self.left_ref[w] = v;
+            }
+        }
+    }
+}
+
+/// Cyclic orders of neighbors under construction.
+struct Rotation {
+    cw: Vec<HashMap<usize, usize>>,
+    ccw: Vec<HashMap<usize, usize>>,
+    first: Vec<Option<usize>>,
+}
+impl Rotation {
+    fn new(n: usize) -> Self {
+        Rotation {
+            cw: vec![HashMap::new(); n],
+            ccw: vec![HashMap::new(); n],
+            first: vec![None; n],
+        }
+    }
+
+    /// Put `w` right after `reference` in clockwise order around `v`.
+    fn add_cw(&mut self, v: usize, w: usize, reference: Option<usize>) {
+        let reference = match reference {
+            Some(reference) => reference,
+            None => {
+                self.cw[v].insert(w, w);
+                self.ccw[v].insert(w, w);
+                self.first[v] = Some(w);
+                return;
+            }
+        };
+        let next = self.cw[v][&reference];
+        self.cw[v].insert(reference, w);
+        self.cw[v].insert(w, next);
+        self.ccw[v].insert(next, w);
+        self.ccw[v].insert(w, reference);
+    }
+
+    /// Put `w` right before `reference` in clockwise order around `v`.
+    fn add_ccw(&mut self, v: usize, w: usize, reference: Option<usize>) {
+        match reference {
+            Some(reference) => {
+                let prev = self.ccw[v][&reference];
+                self.add_cw(v, w, Some(prev));
+                if self.first[v] == Some(reference) {
+                    self.first[v] = Some(w);
+                }
+            }
+            None => self.add_cw(v, w, None),
+        }
+    }
+
+    fn add_first(&mut self, v: usize, w: usize) {
+        let first = self.first[v];
+        self.add_ccw(v, w, first);
+    }
+
+    fn into_orders(self) -> Vec<Vec<usize>> {
+        (0..self.first.len())
+            .map(|v| {
+                let mut order = Vec::new();
+                if let Some(first) = self.first[v] {
+                    let mut w = first;
+                    loop {
+                        order.push(w);
+                        w = self.cw[v][&w];
+                        if w == first {
+                            break;
+                        }
+                    }
+                }
+                order
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    // faces of an embedding, walking each face by turning at every node to
+    // the neighbor before the one we came from
+    fn count_faces(rotation: &HashMap<String, Vec<String>>) -> usize {
+        let mut seen = HashSet::new();
+        let mut faces = 0;
+        for (v, order) in rotation.iter() {
+            for w in order.iter() {
+                if seen.contains(&(v, w)) {
+                    continue;
+                }
+                faces += 1;
+                let (mut a, mut b) = (v, w);
+                while seen.insert((a, b)) {
+                    let around = &rotation[b];
+                    let i = around.iter().position(|x| x == a).unwrap();
+                    let next = &around[(i + around.len() - 1) % around.len()];
+                    a = b;
+                    b = next;
+                }
+            }
+        }
+        faces
+    }
+
+    fn complete(n: usize) -> UnGraph {
+        let mut g = UnGraph::new(None);
+        for i in 0..n {
+            for j in i + 1..n {
+                g.add_edge(i.to_string().as_str(), j.to_string().as_str());
+            }
+        }
+        g
+    }
+
+    #[test]
+    fn test_is_planar_embedding() {
+        // a 4x4 grid with one diagonal in every cell
+        let mut g = UnGraph::new(None);
+        let name = |r: usize, c: usize| format!("{}{}", r, c);
+        for r in 0..4 {
+            for c in 0..4 {
+                if c + 1 < 4 {
+                    g.add_edge(name(r, c).as_str(), name(r, c + 1).as_str());
+                }
+                if r + 1 < 4 {
+                    g.add_edge(name(r, c).as_str(), name(r + 1, c).as_str());
+                }
+                if r + 1 < 4 && c + 1 < 4 {
+                    g.add_edge(name(r, c).as_str(), name(r + 1, c + 1).as_str());
+                }
+            }
+        }
+        for g in [complete(4), g] {
+            let result = is_planar(&g);
+            assert!(result.is_planar());
+            let rotation = match result {
+                Planarity::Planar(rotation) => rotation,
+                _ => unreachable!(),
+            };
+            // Euler's formula for a connected plane graph
+            assert_eq!(g.node_count() + count_faces(&rotation), g.edge_count() + 2);
+        }
+
+        // direction, self-loops and opposite arcs are ignored
+        let g: DiGraph = vec![("A", "B"), ("B", "A"), ("B", "C"), ("C", "C")]
+            .into_iter()
+            .collect();
+        match is_planar(&g) {
+            Planarity::Planar(rotation) => {
+                assert_eq!(rotation["B"].len(), 2);
+                assert_eq!(rotation["C"], vec!["B"]);
+            }
+            _ => panic!("a path is planar"),
+        }
+        assert!(is_planar(&DiGraph::new(None)).is_planar());
+    }
+
+    #[test]
+    fn test_is_planar_kuratowski() {
+        let mut k33 = UnGraph::new(None);
+        for a in ["A", "B", "C"] {
+            for b in ["X", "Y", "Z"] {
+                k33.add_edge(a, b);
+            }
+        }
+        // Petersen graph: outer 5-cycle, inner pentagram and spokes
+        let mut petersen = UnGraph::new(None);
+        for i in 0..5 {
+            let (o, p) = (format!("o{}", i), format!("o{}", (i + 1) % 5));
+            petersen.add_edge(o.as_str(), p.as_str());
+            let (o, p) = (format!("i{}", i), format!("i{}", (i + 2) % 5));
+            petersen.add_edge(o.as_str(), p.as_str());
+            petersen.add_edge(format!("o{}", i).as_str(), format!("i{}", i).as_str());
+        }
+
+        for (g, size) in [(complete(5), Some(10)), (k33, Some(9)), (petersen, None)] {
+            let edges = match is_planar(&g) {
+                Planarity::NonPlanar(edges) => edges,
+                _ => panic!("graph should not be planar"),
+            };
+            if let Some(size) = size {
+                assert_eq!(edges.len(), size);
+            }
+            for (a, b) in edges.iter() {
+                assert!(g.contains_edge(a, b));
+            }
+
+            // the witness is non-planar, and removing any edge makes it planar
+            let witness: UnGraph = edges
+                .iter()
+                .map(|(a, b)| (a.as_str(), b.as_str()))
+                .collect();
+            assert!(!is_planar(&witness).is_planar());
+            for (a, b) in edges.iter() {
+                let mut smaller: UnGraph = edges
+                    .iter()
+                    .map(|(a, b)| (a.as_str(), b.as_str()))
+                    .collect();
+                smaller.remove_edge(a, b);
+                assert!(is_planar(&smaller).is_planar());
+            }
+        }
+    }
+}
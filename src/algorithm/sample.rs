@@ -0,0 +1,189 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Random subgraphs, e.g. as mini-batches for training graph neural networks.
+//!
+//! Every sampler draws from `seed`, so the same graph and seed always give the
+//! same sample. Sampled graphs keep node weights, but not attributes, and edge
+//! weights, and list their nodes in graph order.
+
+use crate::{
+    error::GraphError,
+    graph::{DiGraph, DiNode, Node, Weighted},
+};
+use rand::{rngs::StdRng, seq::index, SeedableRng};
+use std::collections::HashSet;
+
+/// The subgraph induced by `k` nodes drawn uniformly without replacement, or
+/// by all nodes if there are no more than `k`.
+pub fn sample_nodes<G: Weighted>(graph: &G, k: usize, seed: u64) -> DiGraph {
+    let names: Vec<&str> = graph.node_names().collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let picked = index::sample(&mut rng, names.len(), k.min(names.len()));
+    let keep: HashSet<&str> = picked.into_iter().map(|i| names[i]).collect();
+    subgraph(
+        graph,
+        |name| keep.contains(name),
+        |from, to| keep.contains(from) && keep.contains(to),
+    )
+}
+
+/// The graph of `k` edges drawn uniformly without replacement, or of all
+/// edges if there are no more than `k`, and their endpoints.
+///
+/// An [`UnGraph`](crate::graph::UnGraph) acts as a graph with both arcs for
+/// every edge, and each arc is drawn on its own.
+pub fn sample_edges<G: Weighted>(graph: &G, k: usize, seed: u64) -> DiGraph {
+    let edges: Vec<(&str, &str)> = graph
+        .nodes()
+        .flat_map(|node| node.successor_names().map(move |succ| (node.name(), succ)))
+        .collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let picked: HashSet<(&str, &str)> = index::sample(&mut rng, edges.len(), k.min(edges.len()))
+        .into_iter()
+        .map(|i| edges[i])
+        .collect();
+    let keep: HashSet<&str> = picked.iter().flat_map(|&(from, to)| [from, to]).collect();
+    subgraph(
+        graph,
+        |name| keep.contains(name),
+        |from, to| picked.contains(&(from, to)),
+    )
+}
+
+/// Fixed-fanout neighbor sampling in the style of GraphSAGE: starting from
+/// `seeds`, hop `i` draws up to `fanouts[i]` predecessors, the neighbors a
+/// node aggregates messages from, of every node first reached in the hop
+/// before, without replacement.
+///
+/// The result holds the seeds, every sampled node, and an edge from every
+/// sampled predecessor to the node it was sampled for. Fails with
+/// `GraphError::NotFoundNode` if a seed is not in the graph.
+pub fn sample_neighbors<G: Weighted>(
+    graph: &G,
+    seeds: &[&str],
+    fanouts: &[usize],
+    seed: u64,
+) -> Result<DiGraph, GraphError> {
+    let mut keep: HashSet<&str> = HashSet::new();
+    let mut frontier = Vec::new();
+    for &name in seeds {
+        let node = graph
+            .node(name)
+            .ok_or_else(|| GraphError::NotFoundNode(name.to_string()))?;
+        if keep.insert(node.name()) {
+            frontier.push(node);
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut edges: HashSet<(&str, &str)> = HashSet::new();
+    for &fanout in fanouts {
+        let mut next = Vec::new();
+        for node in frontier {
+            let preds: Vec<&str> = node.predecessor_names().collect();
+            for i in index::sample(&mut rng, preds.len(), fanout.min(preds.len())) {
+                edges.insert((preds[i], node.name()));
+                if keep.insert(preds[i]) {
+                    next.push(graph.node(preds[i]).unwrap());
+                }
+            }
+        }
+        frontier = next;
+    }
+    Ok(subgraph(
+        graph,
+        |name| keep.contains(name),
+        |from, to| edges.contains(&(from, to)),
+    ))
+}
+
+fn subgraph<G, N, E>(graph: &G, keep_node: N, keep_edge: E) -> DiGraph
+where
+    G: Weighted,
+    N: Fn(&str) -> bool,
+    E: Fn(&str, &str) -> bool,
+{
+    let mut result = DiGraph::new(None);
+    for node in graph.nodes().filter(|node| keep_node(node.name())) {
+        result.add_node(DiNode::new(node.name(), node.weight().map(String::from)));
+    }
+    for node in graph.nodes() {
+        for succ in node.successor_names() {
+            if !keep_edge(node.name(), succ) {
+                continue;
+            }
+            match graph.edge_weight(node.name(), succ) {
+                Some(weight) => result.add_weighted_edge(node.name(), succ, weight),
+                None => result.add_edge(Some(node.name()), Some(succ)),
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn star() -> DiGraph {
+        // ten leaves feeding a hub, each fed by two more nodes
+        let mut g = DiGraph::new(None);
+        for i in 0..10 {
+            let leaf = format!("l{}", i);
+            g.add_weighted_edge(&leaf, "hub", i);
+            g.add_edge(Some(format!("a{}", i).as_str()), Some(leaf.as_str()));
+            g.add_edge(Some(format!("b{}", i).as_str()), Some(leaf.as_str()));
+        }
+        g
+    }
+
+    #[test]
+    fn test_sample_nodes_and_edges() {
+        let g = star();
+        let sample = sample_nodes(&g, 5, 7);
+        assert_eq!(sample.node_count(), 5);
+        assert_eq!(sample, sample_nodes(&g, 5, 7));
+        for (from, to) in sample.edges() {
+            assert!(g.contains_edge(from, to));
+        }
+        assert_eq!(sample_nodes(&g, 100, 7).edge_count(), g.edge_count());
+
+        let sample = sample_edges(&g, 4, 7);
+        assert_eq!(sample.edge_count(), 4);
+        assert!(sample.node_count() <= 8);
+        assert_eq!(sample.nodes().filter(|n| n.all_degree() == 0).count(), 0);
+        let (from, to) = sample.edges().next().unwrap();
+        assert_eq!(
+            sample.get_edge_weight(from, to),
+            g.get_edge_weight(from, to)
+        );
+        assert_eq!(sample_edges(&g, 0, 7).node_count(), 0);
+    }
+
+    #[test]
+    fn test_sample_neighbors() {
+        let g = star();
+        let sample = sample_neighbors(&g, &["hub"], &[3, 2], 11).unwrap();
+        // the hub, three leaves, and both feeders of each leaf
+        assert_eq!(sample.node_count(), 1 + 3 + 6);
+        assert_eq!(sample.in_degree("hub").unwrap(), 3);
+        assert_eq!(sample.edge_count(), 3 + 6);
+        assert!(sample.get_node("hub").is_some());
+
+        let sample = sample_neighbors(&g, &["hub"], &[], 11).unwrap();
+        assert_eq!(sample.get_nodes(), vec!["hub"]);
+        assert!(sample_neighbors(&g, &["nope"], &[1], 11).is_err());
+    }
+}
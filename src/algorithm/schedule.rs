@@ -0,0 +1,153 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::dag::topological_order;
+use crate::{
+    error::GraphError,
+    graph::{GraphBase, Node},
+};
+use std::collections::HashMap;
+
+/// Result of the critical path method on a DAG of tasks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    /// Earliest time each task can start.
+    pub earliest_start: HashMap<String, i64>,
+    /// Latest time each task can start without delaying the whole project.
+    pub latest_start: HashMap<String, i64>,
+    /// `latest_start - earliest_start`; zero for critical tasks.
+    pub slack: HashMap<String, i64>,
+    /// A chain of zero-slack tasks from the start to the end of the project.
+    pub critical_path: Vec<String>,
+    /// Finish time of the whole project.
+    pub makespan: i64,
+}
+
+/// Schedule the tasks of a DAG, where an edge `A -> B` means `B` cannot start
+/// before `A` finishes. Tasks missing from `durations` take no time.
+///
+/// Fails with `GraphError::CycleDetected` if the graph has a cycle.
+pub fn critical_path<G: GraphBase>(
+    dag: &G,
+    durations: &HashMap<String, i64>,
+) -> Result<Schedule, GraphError> {
+    let order = topological_order(dag)?;
+    let duration = |name: &str| durations.get(name).copied().unwrap_or(0);
+
+    // forward pass
+    let mut earliest_start: HashMap<String, i64> = HashMap::new();
+    let mut makespan = 0;
+    for name in order.iter() {
+        let start = dag
+            .node(name)
+            .unwrap()
+            .predecessor_names()
+            .map(|pred| earliest_start[pred] + duration(pred))
+            .max()
+            .unwrap_or(0);
+        makespan = makespan.max(start + duration(name));
+        earliest_start.insert(name.clone(), start);
+    }
+
+    // backward pass
+    let mut latest_start: HashMap<String, i64> = HashMap::new();
+    for name in order.iter().rev() {
+        let finish = dag
+            .node(name)
+            .unwrap()
+            .successor_names()
+            .map(|succ| latest_start[succ])
+            .min()
+            .unwrap_or(makespan);
+        latest_start.insert(name.clone(), finish - duration(name));
+    }
+
+    let slack: HashMap<String, i64> = order
+        .iter()
+        .map(|name| (name.clone(), latest_start[name] - earliest_start[name]))
+        .collect();
+
+    // follow zero-slack tasks that start right when the previous one finishes
+    let mut critical_path = Vec::new();
+    let mut curr = order
+        .iter()
+        .map(|name| name.as_str())
+        .find(|&name| slack[name] == 0 && earliest_start[name] == 0);
+    while let Some(name) = curr {
+        critical_path.push(name.to_string());
+        let finish = earliest_start[name] + duration(name);
+        curr = dag
+            .node(name)
+            .unwrap()
+            .successor_names()
+            .find(|&succ| slack[succ] == 0 && earliest_start[succ] == finish);
+    }
+
+    Ok(Schedule {
+        earliest_start,
+        latest_start,
+        slack,
+        critical_path,
+        makespan,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_schedule_critical_path() {
+        // design -> build -> test -> ship, with docs in parallel to build and test
+        let g: DiGraph = vec![
+            ("design", "build"),
+            ("build", "test"),
+            ("test", "ship"),
+            ("design", "docs"),
+            ("docs", "ship"),
+        ]
+        .into_iter()
+        .collect();
+        let durations: HashMap<String, i64> = vec![
+            ("design", 2),
+            ("build", 5),
+            ("test", 3),
+            ("docs", 4),
+            ("ship", 1),
+        ]
+        .into_iter()
+        .map(|(x, y)| (x.to_string(), y))
+        .collect();
+
+        let schedule = critical_path(&g, &durations).unwrap();
+        assert_eq!(schedule.makespan, 11);
+        assert_eq!(schedule.earliest_start["ship"], 10);
+        assert_eq!(schedule.earliest_start["docs"], 2);
+        assert_eq!(schedule.latest_start["docs"], 6);
+        assert_eq!(schedule.slack["docs"], 4);
+        assert_eq!(schedule.slack["build"], 0);
+        assert_eq!(
+            schedule.critical_path,
+            vec!["design", "build", "test", "ship"]
+        );
+
+        let mut g = g;
+        g.add_edge(Some("ship"), Some("design"));
+        assert!(matches!(
+            critical_path(&g, &durations),
+            Err(GraphError::CycleDetected(_))
+        ));
+    }
+}
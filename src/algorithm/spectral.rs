@@ -0,0 +1,180 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{graph::Weighted, matrix::laplacian_matrix};
+use nalgebra::{DMatrix, SymmetricEigen};
+use std::collections::HashMap;
+
+const KMEANS_ITERATIONS: usize = 100;
+
+/// Split the nodes into at most `k` clusters by spectral clustering, in the
+/// style of Ng, Jordan and Weiss.
+///
+/// Every node is embedded by its entries in the eigenvectors of the `k`
+/// smallest eigenvalues of the normalized Laplacian `D^-1/2 (D - W) D^-1/2`,
+/// with edge directions ignored and weights as in
+/// [`laplacian_matrix`](crate::matrix::laplacian_matrix), so heavier edges
+/// pull their endpoints together. The embedded rows are scaled to unit length
+/// and grouped with k-means, seeded deterministically by farthest-point
+/// selection from the first node. Clusters are numbered in graph order of
+/// their first node. `k` is capped at the node count; with `k` of 0 no node
+/// is labelled and the result is empty.
+pub fn spectral_clustering<G: Weighted>(graph: &G, k: usize) -> HashMap<String, usize> {
+    let names: Vec<&str> = graph.node_names().collect();
+    let n = names.len();
+    if n == 0 || k == 0 {
+        return HashMap::new();
+    }
+    let k = k.min(n);
+
+    let laplacian = laplacian_matrix(graph, None).unwrap();
+    let scale: Vec<f64> = (0..n)
+        .map(|i| match laplacian[i][i] {
+            degree if degree > 0 => 1.0 / (degree as f64).sqrt(),
+            _ => 0.0,
+        })
+        .collect();
+    let normalized = DMatrix::from_fn(n, n, |i, j| laplacian[i][j] as f64 * scale[i] * scale[j]);
+
+    let eigen = SymmetricEigen::new(normalized);
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| eigen.eigenvalues[a].total_cmp(&eigen.eigenvalues[b]));
+    let points: Vec<Vec<f64>> = (0..n)
+        .map(|i| {
+            let row: Vec<f64> = order[..k]
+                .iter()
+                .map(|&c| eigen.eigenvectors[(i, c)])
+                .collect();
+            let norm = row.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm > 0.0 {
+                row.iter().map(|x| x / norm).collect()
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let labels = kmeans(&points, k);
+    // renumber clusters by first appearance
+    let mut renumber = HashMap::new();
+    names
+        .iter()
+        .zip(labels)
+        .map(|(&name, label)| {
+            let next = renumber.len();
+            (name.to_string(), *renumber.entry(label).or_insert(next))
+        })
+        .collect()
+}
+
+fn distance2(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn kmeans(points: &[Vec<f64>], k: usize) -> Vec<usize> {
+    // farthest-point seeding from the first point
+    let mut centers = vec![points[0].clone()];
+    while centers.len() < k {
+        let farthest = (0..points.len())
+            .max_by(|&a, &b| {
+                let da = centers
+                    .iter()
+                    .map(|c| distance2(&points[a], c))
+                    .fold(f64::INFINITY, f64::min);
+                let db = centers
+                    .iter()
+                    .map(|c| distance2(&points[b], c))
+                    .fold(f64::INFINITY, f64::min);
+                da.total_cmp(&db).then(b.cmp(&a))
+            })
+            .unwrap();
+        centers.push(points[farthest].clone());
+    }
+
+    let mut labels = vec![usize::MAX; points.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (i, point) in points.iter().enumerate() {
+            let nearest = (0..k)
+                .min_by(|&a, &b| {
+                    distance2(point, &centers[a]).total_cmp(&distance2(point, &centers[b]))
+                })
+                .unwrap();
+            if labels[i] != nearest {
+                labels[i] = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        for (c, center) in centers.iter_mut().enumerate() {
+            let members: Vec<&Vec<f64>> = points
+                .iter()
+                .zip(labels.iter())
+                .filter(|&(_, &label)| label == c)
+                .map(|(point, _)| point)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            for (d, value) in center.iter_mut().enumerate() {
+                *value = members.iter().map(|point| point[d]).sum::<f64>() / members.len() as f64;
+            }
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_spectral_clustering() {
+        // two heavy triangles joined by a light edge
+        let mut g = DiGraph::new(None);
+        for (from, to) in [
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("x", "y"),
+            ("y", "z"),
+            ("z", "x"),
+        ] {
+            g.add_weighted_edge(from, to, 10);
+        }
+        g.add_weighted_edge("c", "x", 1);
+
+        let labels = spectral_clustering(&g, 2);
+        assert_eq!(labels.len(), 6);
+        for name in ["a", "b", "c"] {
+            assert_eq!(labels[name], 0);
+        }
+        for name in ["x", "y", "z"] {
+            assert_eq!(labels[name], 1);
+        }
+
+        let labels = spectral_clustering(&g, 10);
+        assert!(labels.values().all(|&label| label < 6));
+        assert!(spectral_clustering(&DiGraph::new(None), 2).is_empty());
+    }
+
+    #[test]
+    fn test_spectral_clustering_no_clusters() {
+        let g: DiGraph = vec![("a", "b"), ("b", "c")].into_iter().collect();
+        assert!(spectral_clustering(&g, 0).is_empty());
+    }
+}
@@ -12,16 +12,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use super::path::Path;
+use crate::{
+    error::GraphError,
+    graph::{Attributed, DiGraph, DiNode, GraphBase, Node, Weighted},
+};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 
-pub fn dijkstra(graph: &impl SPGraph, source: &str) -> HashMap<String, usize> {
+/// Shortest distances from `source` to every node reachable from it.
+///
+/// Self-loops never shorten a path and are ignored. Edges without a weight
+/// count as 1, and a path longer than `usize::MAX` is left out. Fails with
+/// `GraphError::NotFoundNode` if `source` is not in the graph and with
+/// `GraphError::NegativeWeight` on reaching an edge of negative weight; use
+/// [`bellman_ford`] for those graphs.
+pub fn dijkstra<G: Weighted>(
+    graph: &G,
+    source: &str,
+) -> Result<HashMap<String, usize>, GraphError> {
+    if graph.node(source).is_none() {
+        return Err(GraphError::NotFoundNode(source.to_string()));
+    }
     // dist[i]: distance from source to i
     let mut dist = HashMap::new();
-    for name in graph.get_nodes().iter() {
+    for name in graph.node_names() {
         if name == source {
-            dist.insert(name.clone(), 0);
+            dist.insert(name.to_string(), 0);
         } else {
-            dist.insert(name.clone(), usize::MAX);
+            dist.insert(name.to_string(), usize::MAX);
         }
     }
 
@@ -29,28 +48,62 @@ pub fn dijkstra(graph: &impl SPGraph, source: &str) -> HashMap<String, usize> {
     let mut spt = HashMap::new();
     while spt.len() < graph.node_count() {
         let (name, distance) = min_distance(&dist);
+        if distance == usize::MAX {
+            // the remaining nodes are unreachable from source
+            break;
+        }
         dist.remove(name.as_str());
         if !spt.contains_key(name.as_str()) {
-            spt.insert(name.clone(), distance.clone());
+            spt.insert(name.clone(), distance);
 
             // update distance from source to each child v of node
-            let cnames = graph.get_successors(name.as_str());
-            if cnames.is_some() {
-                let cnames = cnames.unwrap();
-                for cname in cnames.iter() {
-                    if dist.contains_key(cname.as_str()) {
-                        let new_dist =
-                            distance + graph.get_edge_weight(name.as_str(), cname).unwrap();
-                        let cur_dist = dist.get_mut(cname).unwrap();
-                        if new_dist <= *cur_dist {
-                            *cur_dist = new_dist;
-                        }
+            let node = graph.node(name.as_str()).unwrap();
+            for cname in node.successor_names().filter(|&succ| succ != name) {
+                if dist.contains_key(cname) {
+                    let new_dist =
+                        match distance.checked_add(edge_length(graph, name.as_str(), cname)?) {
+                            Some(new_dist) => new_dist,
+                            None => continue,
+                        };
+                    let cur_dist = dist.get_mut(cname).unwrap();
+                    if new_dist <= *cur_dist {
+                        *cur_dist = new_dist;
                     }
                 }
             }
         }
     }
-    spt
+    Ok(spt)
+}
+
+/// Edges without a weight count as 1.
+fn edge_length<G: Weighted>(graph: &G, from: &str, to: &str) -> Result<usize, GraphError> {
+    edge_cost(graph, from, to).map(|weight| weight as usize)
+}
+
+/// Like [`edge_length`], for the searches that keep distances as `i64`.
+fn edge_cost<G: Weighted>(graph: &G, from: &str, to: &str) -> Result<i64, GraphError> {
+    match graph.edge_weight(from, to) {
+        Some(weight) if weight < 0 => Err(GraphError::NegativeWeight(
+            from.to_string(),
+            to.to_string(),
+            weight,
+        )),
+        Some(weight) => Ok(weight),
+        None => Ok(1),
+    }
+}
+
+/// Fail with `GraphError::NegativeWeight` on the first edge of negative
+/// weight, for searches that would rather check every edge up front.
+#[cfg(feature = "parallel")]
+pub(crate) fn check_nonnegative<G: Weighted>(graph: &G) -> Result<(), GraphError> {
+    for node in graph.nodes() {
+        for succ in node.successor_names() {
+            edge_cost(graph, node.name(), succ)?;
+        }
+    }
+    Ok(())
 }
 
 fn min_distance(dist: &HashMap<String, usize>) -> (String, usize) {
@@ -62,108 +115,429 @@ fn min_distance(dist: &HashMap<String, usize>) -> (String, usize) {
             name = key;
         }
     }
-    (name.clone(), d.clone())
+    (name.clone(), *d)
+}
+
+/// Number of edges on a shortest path from `source` to every node reachable
+/// from it, found by breadth-first search, so edge weights are ignored.
+///
+/// Nodes more than `cutoff` edges away are left out. An unknown `source`
+/// yields an empty map.
+pub fn single_source_shortest_path_length<G: GraphBase>(
+    graph: &G,
+    source: &str,
+    cutoff: Option<usize>,
+) -> HashMap<String, usize> {
+    let mut dist = HashMap::new();
+    if graph.node(source).is_none() {
+        return dist;
+    }
+    dist.insert(source.to_string(), 0);
+
+    let mut queue = VecDeque::new();
+    queue.push_back(source);
+    while let Some(name) = queue.pop_front() {
+        let level = dist[name] + 1;
+        if cutoff.is_some_and(|cutoff| level > cutoff) {
+            continue;
+        }
+        for succ in graph.node(name).unwrap().successor_names() {
+            if !dist.contains_key(succ) {
+                dist.insert(succ.to_string(), level);
+                queue.push_back(succ);
+            }
+        }
+    }
+    dist
 }
 
-pub trait SPGraph {
-    fn node_count(&self) -> usize;
-    fn get_nodes(&self) -> Vec<String>;
-    fn get_successors(&self, name: &str) -> Option<Vec<String>>;
-    fn get_edge_weight(&self, source: &str, target: &str) -> Option<usize>;
+/// Shortest distances from `source` to every node reachable from it, allowing
+/// negative edge weights. Edges without a weight count as 1, and a path whose
+/// length overflows `i64` is left out.
+///
+/// Fails with `GraphError::NegativeCycle` if a cycle of negative total weight
+/// is reachable from `source`, and with `GraphError::NotFoundNode` if `source`
+/// is not in the graph.
+pub fn bellman_ford<G: Weighted>(
+    graph: &G,
+    source: &str,
+) -> Result<HashMap<String, i64>, GraphError> {
+    if graph.node(source).is_none() {
+        return Err(GraphError::NotFoundNode(source.to_string()));
+    }
+    let mut dist: HashMap<String, i64> = HashMap::new();
+    let mut pred: HashMap<String, String> = HashMap::new();
+    dist.insert(source.to_string(), 0);
+
+    // returns the target of an edge that could still be relaxed, if any
+    let mut relax = |dist: &mut HashMap<String, i64>| {
+        let mut relaxed = None;
+        for node in graph.nodes() {
+            let from = node.name();
+            let base = match dist.get(from) {
+                Some(&base) => base,
+                None => continue,
+            };
+            for to in node.successor_names() {
+                let new_dist = match base.checked_add(graph.edge_weight(from, to).unwrap_or(1)) {
+                    Some(new_dist) => new_dist,
+                    None => continue,
+                };
+                if dist.get(to).is_none_or(|&cur| new_dist < cur) {
+                    dist.insert(to.to_string(), new_dist);
+                    pred.insert(to.to_string(), from.to_string());
+                    relaxed = Some(to.to_string());
+                }
+            }
+        }
+        relaxed
+    };
+
+    let mut relaxed = None;
+    for _ in 0..graph.node_count() {
+        relaxed = relax(&mut dist);
+        if relaxed.is_none() {
+            return Ok(dist);
+        }
+    }
+
+    // An edge still relaxes after |V| rounds, so its target is reachable from a
+    // negative cycle; walking back |V| predecessors lands on the cycle itself.
+    let mut name = relaxed.unwrap();
+    for _ in 0..graph.node_count() {
+        name = pred[&name].clone();
+    }
+    let mut cycle = vec![name.clone()];
+    let mut curr = pred[&name].clone();
+    while curr != name {
+        cycle.push(curr.clone());
+        curr = pred[&curr].clone();
+    }
+    cycle.reverse();
+    Err(GraphError::NegativeCycle(cycle))
 }
 
-pub struct MyGraph {
-    edges: HashMap<String, HashMap<String, Option<usize>>>,
+/// A cycle of negative total weight reachable from `source`, as the sequence of
+/// nodes along the cycle; the edge from the last node back to the first closes it.
+pub fn find_negative_cycle<G: Weighted>(graph: &G, source: &str) -> Option<Vec<String>> {
+    match bellman_ford(graph, source) {
+        Err(GraphError::NegativeCycle(cycle)) => Some(cycle),
+        _ => None,
+    }
 }
-impl MyGraph {
-    pub fn new() -> Self {
-        MyGraph {
-            edges: HashMap::new(),
+
+/// Shortest path from `source` to `target` through the nodes accepted by
+/// `node_filter` and the edges accepted by `edge_filter`, using at most
+/// `max_hops` edges if given. Its length is `Path::total_weight`.
+///
+/// Rejected nodes and edges are skipped during the search, so the graph is
+/// never copied. Edges without a weight count as 1, and a path longer than
+/// `i64::MAX` is left out. Returns `Ok(None)` if no such path exists,
+/// including when `source` or `target` is rejected. Fails with
+/// `GraphError::NotFoundNode` if either is not in the graph and with
+/// `GraphError::NegativeWeight` on reaching an accepted edge of negative weight.
+pub fn constrained_shortest_path<G, N, E>(
+    graph: &G,
+    source: &str,
+    target: &str,
+    node_filter: N,
+    edge_filter: E,
+    max_hops: Option<usize>,
+) -> Result<Option<Path>, GraphError>
+where
+    G: Weighted,
+    N: Fn(&str) -> bool,
+    E: Fn(&str, &str) -> bool,
+{
+    for name in [source, target] {
+        if graph.node(name).is_none() {
+            return Err(GraphError::NotFoundNode(name.to_string()));
         }
     }
-    pub fn add_edge(&mut self, source: &str, target: &str, weight: usize) {
-        if source == target {
-            panic!("Cannot add a self loop");
+    if !node_filter(source) || !node_filter(target) {
+        return Ok(None);
+    }
+
+    // labels[i]: (node, hops, parent label); a node is settled again only if
+    // it is reached with fewer hops than before, which matters under a limit
+    let mut labels: Vec<(&str, usize, Option<usize>)> = vec![(source, 0, None)];
+    let mut settled_hops: HashMap<&str, usize> = HashMap::new();
+    let mut heap = BinaryHeap::from([Reverse((0_i64, 0, 0))]);
+    while let Some(Reverse((distance, hops, label))) = heap.pop() {
+        let name = labels[label].0;
+        if settled_hops
+            .get(name)
+            .is_some_and(|&settled| settled <= hops)
+        {
+            continue;
+        }
+        settled_hops.insert(name, hops);
+
+        if name == target {
+            let mut path = Vec::new();
+            let mut curr = Some(label);
+            while let Some(label) = curr {
+                path.push(labels[label].0.to_string());
+                curr = labels[label].2;
+            }
+            path.reverse();
+            return Ok(Some(Path::from_nodes(graph, path)));
         }
-        if !self.edges.contains_key(source) {
-            self.edges.insert(source.to_string(), HashMap::new());
+        if max_hops.is_some_and(|max_hops| hops >= max_hops) {
+            continue;
         }
+        for succ in graph.node(name).unwrap().successor_names() {
+            if !node_filter(succ) || !edge_filter(name, succ) {
+                continue;
+            }
+            if settled_hops
+                .get(succ)
+                .is_some_and(|&settled| settled <= hops + 1)
+            {
+                continue;
+            }
+            let new_dist = match distance.checked_add(edge_cost(graph, name, succ)?) {
+                Some(new_dist) => new_dist,
+                None => continue,
+            };
+            labels.push((succ, hops + 1, Some(label)));
+            heap.push(Reverse((new_dist, hops + 1, labels.len() - 1)));
+        }
+    }
+    Ok(None)
+}
 
-        if !self.edges.contains_key(target) {
-            self.edges.insert(target.to_string(), HashMap::new());
+/// Path from `source` to `target` whose narrowest edge is as wide as possible,
+/// as `(width, path)`, where the width of a path is its smallest edge weight.
+///
+/// Edges without a weight have width 1. The path from `source` to itself has
+/// no edges and width `i64::MAX`. Returns `Ok(None)` if `target` is
+/// unreachable and `GraphError::NotFoundNode` if either node is not in the
+/// graph.
+pub fn widest_path<G: Weighted>(
+    graph: &G,
+    source: &str,
+    target: &str,
+) -> Result<Option<(i64, Path)>, GraphError> {
+    for name in [source, target] {
+        if graph.node(name).is_none() {
+            return Err(GraphError::NotFoundNode(name.to_string()));
         }
+    }
 
-        let map = self.edges.get_mut(source).unwrap();
-        map.entry(target.to_string())
-            .and_modify(|x| *x = Some(weight))
-            .or_insert(Some(weight));
+    // a modified Dijkstra settling nodes in order of decreasing width
+    let mut width: HashMap<&str, i64> = HashMap::from([(source, i64::MAX)]);
+    let mut pred: HashMap<&str, &str> = HashMap::new();
+    let mut settled = HashSet::new();
+    let mut heap = BinaryHeap::from([(i64::MAX, source)]);
+    while let Some((w, name)) = heap.pop() {
+        if !settled.insert(name) {
+            continue;
+        }
+        if name == target {
+            let mut path = vec![name.to_string()];
+            let mut curr = name;
+            while let Some(&prev) = pred.get(curr) {
+                path.push(prev.to_string());
+                curr = prev;
+            }
+            path.reverse();
+            return Ok(Some((w, Path::from_nodes(graph, path))));
+        }
+        for succ in graph.node(name).unwrap().successor_names() {
+            let new_width = w.min(graph.edge_weight(name, succ).unwrap_or(1));
+            if !settled.contains(succ) && width.get(succ).is_none_or(|&cur| new_width > cur) {
+                width.insert(succ, new_width);
+                pred.insert(succ, name);
+                heap.push((new_width, succ));
+            }
+        }
     }
+    Ok(None)
+}
+
+/// Distance from the nearest of `sources` to every node reachable from any of
+/// them, with that source, as `name -> (distance, source)`. A source is its
+/// own nearest source at distance 0; a tie goes to the source listed first.
+///
+/// Runs a single Dijkstra search seeded with every source. Edges without a
+/// weight count as 1, and a path longer than `i64::MAX` is left out as if
+/// unreachable. Fails with `GraphError::NotFoundNode` if a source is not in
+/// the graph and with `GraphError::NegativeWeight` on reaching an edge of
+/// negative weight.
+pub fn multi_source_dijkstra<G: Weighted>(
+    graph: &G,
+    sources: &[&str],
+) -> Result<HashMap<String, (i64, String)>, GraphError> {
+    Ok(nearest_sources(graph, sources)?
+        .into_iter()
+        .map(|(name, (distance, i, _))| (name.to_string(), (distance, sources[i].to_string())))
+        .collect())
 }
-impl SPGraph for MyGraph {
-    fn node_count(&self) -> usize {
-        self.edges.len()
+
+// (distance, index of the nearest source, predecessor)
+pub(crate) type SourceLabel<'a> = (i64, usize, Option<&'a str>);
+
+/// Dijkstra seeded with every source, labelling every reached node.
+pub(crate) fn nearest_sources<'a, G: Weighted>(
+    graph: &'a G,
+    sources: &[&'a str],
+) -> Result<HashMap<&'a str, SourceLabel<'a>>, GraphError> {
+    let mut best: HashMap<&str, SourceLabel> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    for (i, &source) in sources.iter().enumerate() {
+        if graph.node(source).is_none() {
+            return Err(GraphError::NotFoundNode(source.to_string()));
+        }
+        if !best.contains_key(source) {
+            best.insert(source, (0, i, None));
+            heap.push(Reverse((0, i, source)));
+        }
     }
-    fn get_nodes(&self) -> Vec<String> {
-        self.edges.keys().map(|x| x.clone()).collect()
+
+    while let Some(Reverse((distance, i, name))) = heap.pop() {
+        if (best[name].0, best[name].1) < (distance, i) {
+            continue;
+        }
+        for succ in graph.node(name).unwrap().successor_names() {
+            let new_dist = match distance.checked_add(edge_cost(graph, name, succ)?) {
+                Some(new_dist) => new_dist,
+                None => continue,
+            };
+            let label = (new_dist, i);
+            if best.get(succ).is_none_or(|&(d, j, _)| label < (d, j)) {
+                best.insert(succ, (label.0, i, Some(name)));
+                heap.push(Reverse((label.0, i, succ)));
+            }
+        }
     }
-    fn get_successors(&self, name: &str) -> Option<Vec<String>> {
-        let succs = self.edges.get(name);
-        if succs.is_none() {
-            return None;
+    Ok(best)
+}
+
+/// The shortest-path tree of `source` as a new `DiGraph`, to be serialized or
+/// rendered like any other graph: every node reachable from `source`, joined
+/// by the edge from its predecessor on a shortest path.
+///
+/// Nodes keep their weights and get their distance as the `distance`
+/// attribute; edges keep their weights. Edges without a weight count as 1.
+/// Fails with `GraphError::NotFoundNode` if `source` is not in the graph and
+/// with `GraphError::NegativeWeight` on reaching an edge of negative weight.
+pub fn shortest_path_tree<G: Weighted>(graph: &G, source: &str) -> Result<DiGraph, GraphError> {
+    let labels = nearest_sources(graph, &[source])?;
+    Ok(tree(
+        graph,
+        labels
+            .into_iter()
+            .map(|(name, (distance, _, pred))| (name, (distance, pred)))
+            .collect(),
+    ))
+}
+
+/// The breadth-first search tree of `source` as a new `DiGraph`, like
+/// [`shortest_path_tree`] with every edge counting as 1. Among shortest paths,
+/// the one through the earliest predecessor in graph order is kept.
+pub fn bfs_tree<G: Weighted>(graph: &G, source: &str) -> Result<DiGraph, GraphError> {
+    if graph.node(source).is_none() {
+        return Err(GraphError::NotFoundNode(source.to_string()));
+    }
+    let mut labels: HashMap<&str, (i64, Option<&str>)> = HashMap::from([(source, (0, None))]);
+    let mut queue = VecDeque::from([source]);
+    while let Some(name) = queue.pop_front() {
+        let level = labels[name].0 + 1;
+        for succ in graph.node(name).unwrap().successor_names() {
+            if !labels.contains_key(succ) {
+                labels.insert(succ, (level, Some(name)));
+                queue.push_back(succ);
+            }
         }
+    }
+    Ok(tree(graph, labels))
+}
 
-        let names: Vec<String> = succs
-            .unwrap()
-            .iter()
-            .filter(|&(key, val)| key.as_str() != name && val.is_some())
-            .map(|(x, _)| x.clone())
-            .collect();
-        if names.len() == 0 {
-            return None;
+/// Build a tree from `name -> (distance, predecessor)` labels, in graph order.
+fn tree<G: Weighted>(graph: &G, labels: HashMap<&str, (i64, Option<&str>)>) -> DiGraph {
+    let mut tree = DiGraph::new(None);
+    for node in graph.nodes() {
+        if let Some(&(distance, _)) = labels.get(node.name()) {
+            let mut copy = DiNode::new(node.name(), node.weight().map(String::from));
+            copy.set_attr("distance", distance);
+            tree.add_node(copy);
         }
-        Some(names)
     }
-    fn get_edge_weight(&self, source: &str, target: &str) -> Option<usize> {
-        let succs = self.edges.get(source);
-        if succs.is_none() {
-            return None;
+    for node in graph.nodes() {
+        if let Some(&(_, Some(pred))) = labels.get(node.name()) {
+            match graph.edge_weight(pred, node.name()) {
+                Some(weight) => tree.add_weighted_edge(pred, node.name(), weight),
+                None => tree.add_edge(Some(pred), Some(node.name())),
+            }
         }
+    }
+    tree
+}
 
-        let succs = succs.unwrap();
-        if !succs.contains_key(target) {
-            return None;
+/// Distance from every node that can reach `target` to it, with the next
+/// node on a shortest path, as `name -> (distance, next)`. Following `next`
+/// from any node leads to `target`, which maps to `(0, target)`.
+///
+/// The search runs against the edges, over the predecessors of every node, so
+/// the graph is never reversed. Edges without a weight count as 1, and a
+/// path longer than `i64::MAX` is left out as if unreachable. Fails with
+/// `GraphError::NotFoundNode` if `target` is not in the graph and with
+/// `GraphError::NegativeWeight` on reaching an edge of negative weight.
+pub fn single_target_shortest_path<G: Weighted>(
+    graph: &G,
+    target: &str,
+) -> Result<HashMap<String, (i64, String)>, GraphError> {
+    if graph.node(target).is_none() {
+        return Err(GraphError::NotFoundNode(target.to_string()));
+    }
+    let mut best: HashMap<&str, (i64, &str)> = HashMap::from([(target, (0, target))]);
+    let mut heap = BinaryHeap::from([Reverse((0, target))]);
+    while let Some(Reverse((distance, name))) = heap.pop() {
+        if best[name].0 < distance {
+            continue;
         }
-        let weight = succs.get(target).unwrap();
-        if weight.is_none() {
-            return None;
+        for pred in graph.node(name).unwrap().predecessor_names() {
+            let new_dist = match distance.checked_add(edge_cost(graph, pred, name)?) {
+                Some(new_dist) => new_dist,
+                None => continue,
+            };
+            if best.get(pred).is_none_or(|&(cur, _)| new_dist < cur) {
+                best.insert(pred, (new_dist, name));
+                heap.push(Reverse((new_dist, pred)));
+            }
         }
-        Some(weight.unwrap().clone())
     }
+    Ok(best
+        .into_iter()
+        .map(|(name, (distance, next))| (name.to_string(), (distance, next.to_string())))
+        .collect())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::graph::DiGraph;
 
     #[test]
     fn test_sssp_dijkstra() {
-        let mut g = MyGraph::new();
-        g.add_edge("0", "1", 4);
-        g.add_edge("0", "7", 8);
-        g.add_edge("1", "7", 11);
-        g.add_edge("1", "2", 8);
-        g.add_edge("2", "3", 7);
-        g.add_edge("2", "5", 4);
-        g.add_edge("2", "8", 2);
-        g.add_edge("3", "4", 9);
-        g.add_edge("3", "5", 14);
-        g.add_edge("4", "5", 10);
-        g.add_edge("5", "6", 2);
-        g.add_edge("6", "7", 1);
-        g.add_edge("6", "8", 6);
-        g.add_edge("7", "8", 7);
-
-        let actual = dijkstra(&g, "0");
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("0", "1", 4);
+        g.add_weighted_edge("0", "7", 8);
+        g.add_weighted_edge("1", "7", 11);
+        g.add_weighted_edge("1", "2", 8);
+        g.add_weighted_edge("2", "3", 7);
+        g.add_weighted_edge("2", "5", 4);
+        g.add_weighted_edge("2", "8", 2);
+        g.add_weighted_edge("3", "4", 9);
+        g.add_weighted_edge("3", "5", 14);
+        g.add_weighted_edge("4", "5", 10);
+        g.add_weighted_edge("5", "6", 2);
+        g.add_weighted_edge("6", "7", 1);
+        g.add_weighted_edge("6", "8", 6);
+        g.add_weighted_edge("7", "8", 7);
+
+        let actual = dijkstra(&g, "0").unwrap();
 
         let tuples = vec![
             ("7", 8),
@@ -182,4 +556,326 @@ mod tests {
             .collect();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_sssp_dijkstra_unreachable_and_self_loop() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("0", "1", 4);
+        g.add_edge(Some("1"), Some("2"));
+        g.add_weighted_edge("3", "0", 1);
+        g.add_weighted_edge("1", "1", 1);
+
+        let actual = dijkstra(&g, "0").unwrap();
+
+        let expected: HashMap<String, usize> = vec![("0", 0), ("1", 4), ("2", 5)]
+            .into_iter()
+            .map(|(x, y)| (x.to_string(), y))
+            .collect();
+        assert_eq!(expected, actual);
+
+        assert!(matches!(
+            dijkstra(&g, "9"),
+            Err(GraphError::NotFoundNode(name)) if name == "9"
+        ));
+    }
+
+    #[test]
+    fn test_sssp_dijkstra_negative_weight() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("0", "1", 4);
+        g.add_weighted_edge("1", "2", -3);
+        g.add_weighted_edge("3", "0", -1);
+        assert!(matches!(
+            dijkstra(&g, "0"),
+            Err(GraphError::NegativeWeight(from, to, -3)) if from == "1" && to == "2"
+        ));
+        // an unreachable negative edge is never looked at
+        assert_eq!(dijkstra(&g, "2").unwrap().len(), 1);
+        assert_eq!(bellman_ford(&g, "0").unwrap()["2"], 1);
+    }
+
+    #[test]
+    fn test_sssp_bfs_length() {
+        let g: DiGraph = vec![("0", "1"), ("1", "2"), ("0", "2"), ("2", "3"), ("4", "0")]
+            .into_iter()
+            .collect();
+
+        let expected: HashMap<String, usize> = vec![("0", 0), ("1", 1), ("2", 1), ("3", 2)]
+            .into_iter()
+            .map(|(x, y)| (x.to_string(), y))
+            .collect();
+        assert_eq!(single_source_shortest_path_length(&g, "0", None), expected);
+
+        let actual = single_source_shortest_path_length(&g, "0", Some(1));
+        assert_eq!(actual.len(), 3);
+        assert!(!actual.contains_key("3"));
+
+        assert!(single_source_shortest_path_length(&g, "9", None).is_empty());
+    }
+
+    #[test]
+    fn test_sssp_bellman_ford() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("0", "1", 4);
+        g.add_weighted_edge("0", "2", 5);
+        g.add_weighted_edge("2", "1", -3);
+        g.add_edge(Some("1"), Some("3"));
+        g.add_weighted_edge("4", "0", -7);
+
+        let expected: HashMap<String, i64> = vec![("0", 0), ("1", 2), ("2", 5), ("3", 3)]
+            .into_iter()
+            .map(|(x, y)| (x.to_string(), y))
+            .collect();
+        assert_eq!(bellman_ford(&g, "0").unwrap(), expected);
+        assert_eq!(find_negative_cycle(&g, "0"), None);
+        assert!(matches!(
+            bellman_ford(&g, "9"),
+            Err(GraphError::NotFoundNode(_))
+        ));
+    }
+
+    #[test]
+    fn test_sssp_find_negative_cycle() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("S", "A", 1);
+        g.add_weighted_edge("A", "B", 2);
+        g.add_weighted_edge("B", "C", -4);
+        g.add_weighted_edge("C", "A", 1);
+        g.add_weighted_edge("C", "D", 1);
+
+        let mut cycle = find_negative_cycle(&g, "S").unwrap();
+        assert_eq!(cycle.len(), 3);
+        let start = cycle.iter().position(|name| name == "A").unwrap();
+        cycle.rotate_left(start);
+        assert_eq!(cycle, vec!["A", "B", "C"]);
+
+        // not reachable from D
+        assert_eq!(find_negative_cycle(&g, "D"), None);
+
+        g.add_weighted_edge("D", "D", -1);
+        assert_eq!(find_negative_cycle(&g, "D"), Some(vec!["D".to_string()]));
+    }
+
+    #[test]
+    fn test_sssp_constrained_shortest_path() {
+        // the cheap route A-B-C-D is long; A-X-D is short but expensive
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 1);
+        g.add_weighted_edge("B", "C", 1);
+        g.add_weighted_edge("C", "D", 1);
+        g.add_weighted_edge("A", "X", 5);
+        g.add_weighted_edge("X", "D", 5);
+        let nodes = |path: Option<Path>| path.map(|path| (path.total_weight(), path.into_nodes()));
+        let path = |nodes: &[&str]| nodes.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let any = |_: &str| true;
+        let all_edges = |_: &str, _: &str| true;
+        assert_eq!(
+            nodes(constrained_shortest_path(&g, "A", "D", any, all_edges, None).unwrap()),
+            Some((3, path(&["A", "B", "C", "D"])))
+        );
+        assert_eq!(
+            nodes(constrained_shortest_path(&g, "A", "D", any, all_edges, Some(2)).unwrap()),
+            Some((10, path(&["A", "X", "D"])))
+        );
+        assert_eq!(
+            constrained_shortest_path(&g, "A", "D", |n: &str| n != "X", all_edges, Some(2))
+                .unwrap(),
+            None
+        );
+        let detour = constrained_shortest_path(
+            &g,
+            "A",
+            "D",
+            any,
+            |f: &str, t: &str| (f, t) != ("B", "C"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(nodes(detour), Some((10, path(&["A", "X", "D"]))));
+        assert!(matches!(
+            constrained_shortest_path(&g, "A", "Z", any, all_edges, None),
+            Err(GraphError::NotFoundNode(_))
+        ));
+
+        g.add_weighted_edge("C", "E", -5);
+        assert!(matches!(
+            constrained_shortest_path(&g, "A", "D", any, all_edges, None),
+            Err(GraphError::NegativeWeight(..))
+        ));
+        // a rejected negative edge is never looked at
+        let skip_negative = |f: &str, t: &str| (f, t) != ("C", "E");
+        assert_eq!(
+            nodes(constrained_shortest_path(&g, "A", "D", any, skip_negative, None).unwrap()),
+            Some((3, path(&["A", "B", "C", "D"])))
+        );
+    }
+
+    #[test]
+    fn test_sssp_widest_path() {
+        // A-B-D has bottleneck 3, A-C-D has bottleneck 4
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 10);
+        g.add_weighted_edge("B", "D", 3);
+        g.add_weighted_edge("A", "C", 4);
+        g.add_weighted_edge("C", "D", 7);
+        g.add_edge(Some("E"), None);
+
+        let (width, path) = widest_path(&g, "A", "D").unwrap().unwrap();
+        assert_eq!(width, 4);
+        assert_eq!(path.nodes(), ["A", "C", "D"]);
+        assert_eq!(path.edges().last(), Some(("C", "D", Some(7))));
+
+        let (width, path) = widest_path(&g, "A", "A").unwrap().unwrap();
+        assert_eq!(width, i64::MAX);
+        assert_eq!(path.nodes(), ["A"]);
+        assert_eq!(widest_path(&g, "A", "E").unwrap(), None);
+        assert!(widest_path(&g, "A", "Z").is_err());
+    }
+
+    #[test]
+    fn test_sssp_multi_source_dijkstra() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("depot1", "A", 2);
+        g.add_weighted_edge("A", "B", 2);
+        g.add_weighted_edge("depot2", "B", 1);
+        g.add_weighted_edge("B", "C", 5);
+        g.add_weighted_edge("depot2", "C", 7);
+        g.add_edge(Some("depot1"), Some("depot2"));
+        g.add_edge(Some("X"), Some("A"));
+
+        let nearest = multi_source_dijkstra(&g, &["depot1", "depot2"]).unwrap();
+        assert_eq!(nearest["A"], (2, "depot1".to_string()));
+        assert_eq!(nearest["B"], (1, "depot2".to_string()));
+        assert_eq!(nearest["C"], (6, "depot2".to_string()));
+        // a source is nearest to itself even when another source reaches it
+        assert_eq!(nearest["depot2"], (0, "depot2".to_string()));
+        assert!(!nearest.contains_key("X"));
+
+        // ties go to the source listed first
+        let mut g = DiGraph::new(None);
+        g.add_edge(Some("S1"), Some("M"));
+        g.add_edge(Some("S2"), Some("M"));
+        let nearest = multi_source_dijkstra(&g, &["S2", "S1"]).unwrap();
+        assert_eq!(nearest["M"], (1, "S2".to_string()));
+
+        assert!(multi_source_dijkstra(&g, &[]).unwrap().is_empty());
+        assert!(matches!(
+            multi_source_dijkstra(&g, &["S1", "Z"]),
+            Err(GraphError::NotFoundNode(_))
+        ));
+    }
+
+    #[test]
+    fn test_sssp_multi_source_dijkstra_bad_weights() {
+        // a negative cycle used to keep the search relaxing forever
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 1);
+        g.add_weighted_edge("B", "A", -3);
+        assert!(matches!(
+            multi_source_dijkstra(&g, &["A"]),
+            Err(GraphError::NegativeWeight(from, to, -3)) if from == "B" && to == "A"
+        ));
+
+        // C is only reachable over a path longer than i64::MAX
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", i64::MAX);
+        g.add_weighted_edge("B", "C", 1);
+        let nearest = multi_source_dijkstra(&g, &["A"]).unwrap();
+        assert_eq!(nearest["B"], (i64::MAX, "A".to_string()));
+        assert!(!nearest.contains_key("C"));
+    }
+
+    #[test]
+    fn test_sssp_single_target_shortest_path() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 1);
+        g.add_weighted_edge("B", "sink", 1);
+        g.add_weighted_edge("A", "sink", 5);
+        g.add_weighted_edge("C", "A", 2);
+        g.add_edge(Some("sink"), Some("D"));
+
+        let to_sink = single_target_shortest_path(&g, "sink").unwrap();
+        assert_eq!(to_sink["A"], (2, "B".to_string()));
+        assert_eq!(to_sink["C"], (4, "A".to_string()));
+        assert_eq!(to_sink["sink"], (0, "sink".to_string()));
+        assert!(!to_sink.contains_key("D"));
+
+        let mut path = vec!["C"];
+        while *path.last().unwrap() != "sink" {
+            path.push(to_sink[*path.last().unwrap()].1.as_str());
+        }
+        assert_eq!(path, vec!["C", "A", "B", "sink"]);
+        assert!(single_target_shortest_path(&g, "Z").is_err());
+    }
+
+    #[test]
+    fn test_sssp_single_target_shortest_path_bad_weights() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 1);
+        g.add_weighted_edge("B", "A", -3);
+        assert!(matches!(
+            single_target_shortest_path(&g, "A"),
+            Err(GraphError::NegativeWeight(from, to, -3)) if from == "B" && to == "A"
+        ));
+
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", i64::MAX);
+        g.add_weighted_edge("B", "C", 1);
+        let to_c = single_target_shortest_path(&g, "C").unwrap();
+        assert_eq!(to_c["B"], (1, "C".to_string()));
+        assert!(!to_c.contains_key("A"));
+    }
+
+    #[test]
+    fn test_sssp_shortest_path_tree() {
+        let mut g = DiGraph::new(None);
+        g.add_node(DiNode::new("S", Some("start".to_string())));
+        g.add_weighted_edge("S", "A", 4);
+        g.add_weighted_edge("S", "B", 1);
+        g.add_weighted_edge("B", "A", 2);
+        g.add_weighted_edge("A", "C", 1);
+        g.add_edge(Some("X"), Some("S"));
+
+        let tree = shortest_path_tree(&g, "S").unwrap();
+        assert_eq!(tree.get_nodes(), vec!["S", "A", "B", "C"]);
+        assert_eq!(
+            tree.edges().collect::<Vec<_>>(),
+            vec![("S", "B"), ("A", "C"), ("B", "A")]
+        );
+        assert_eq!(tree.get_edge_weight("B", "A"), Some(2));
+        assert_eq!(
+            tree.get_node("C").unwrap().get_attr_as::<i64>("distance"),
+            Some(4)
+        );
+        assert_eq!(
+            tree.get_node("S").unwrap().get_weight().as_deref(),
+            Some("start")
+        );
+
+        let tree = bfs_tree(&g, "S").unwrap();
+        assert_eq!(
+            tree.edges().collect::<Vec<_>>(),
+            vec![("S", "A"), ("S", "B"), ("A", "C")]
+        );
+        assert_eq!(
+            tree.get_node("C").unwrap().get_attr_as::<i64>("distance"),
+            Some(2)
+        );
+        assert!(bfs_tree(&g, "Z").is_err());
+        assert!(shortest_path_tree(&g, "Z").is_err());
+    }
+
+    #[test]
+    fn test_sssp_shortest_path_tree_negative_cycle() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 1);
+        g.add_weighted_edge("B", "A", -3);
+        assert!(matches!(
+            shortest_path_tree(&g, "A"),
+            Err(GraphError::NegativeWeight(from, to, -3)) if from == "B" && to == "A"
+        ));
+        // the BFS tree ignores weights
+        assert_eq!(bfs_tree(&g, "A").unwrap().node_count(), 2);
+    }
 }
@@ -0,0 +1,140 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    components::weakly_connected_components,
+    dag::topological_order,
+    metrics::{density, edge_count},
+};
+use crate::graph::{GraphBase, Node};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Percentiles of a degree distribution, by the nearest-rank method.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct DegreeDistribution {
+    pub min: usize,
+    pub median: usize,
+    pub p90: usize,
+    pub p99: usize,
+    pub max: usize,
+    pub mean: f64,
+}
+impl DegreeDistribution {
+    /// Describe a list of degrees; all fields are zero if it is empty.
+    pub fn new(mut degrees: Vec<usize>) -> Self {
+        if degrees.is_empty() {
+            return DegreeDistribution::default();
+        }
+        degrees.sort_unstable();
+        let n = degrees.len();
+        let percentile = |p: usize| degrees[(p * n).div_ceil(100).max(1) - 1];
+        DegreeDistribution {
+            min: degrees[0],
+            median: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: degrees[n - 1],
+            mean: degrees.iter().sum::<usize>() as f64 / n as f64,
+        }
+    }
+}
+
+/// Structural overview of a graph, as printed by `graphx stats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphSummary {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// Edges divided by the `n * (n - 1)` possible ones, or 0 with fewer than
+    /// two nodes.
+    pub density: f64,
+    pub in_degree: DegreeDistribution,
+    pub out_degree: DegreeDistribution,
+    /// Number of weakly connected components.
+    pub component_count: usize,
+    pub is_dag: bool,
+}
+impl GraphSummary {
+    pub fn new<G: GraphBase>(graph: &G) -> Self {
+        let node_count = graph.node_count();
+        let in_degrees: Vec<usize> = graph.nodes().map(|node| node.in_degree()).collect();
+        let out_degrees: Vec<usize> = graph.nodes().map(|node| node.out_degree()).collect();
+
+        GraphSummary {
+            node_count,
+            edge_count: edge_count(graph),
+            density: density(graph),
+            in_degree: DegreeDistribution::new(in_degrees),
+            out_degree: DegreeDistribution::new(out_degrees),
+            component_count: weakly_connected_components(graph).len(),
+            is_dag: topological_order(graph).is_ok(),
+        }
+    }
+}
+impl fmt::Display for GraphSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nodes:      {}", self.node_count)?;
+        writeln!(f, "edges:      {}", self.edge_count)?;
+        writeln!(f, "density:    {:.4}", self.density)?;
+        writeln!(f, "components: {}", self.component_count)?;
+        writeln!(f, "dag:        {}", self.is_dag)?;
+        writeln!(f, "degree      min  median  p90  p99  max  mean")?;
+        for (label, dist) in [("in", &self.in_degree), ("out", &self.out_degree)] {
+            writeln!(
+                f,
+                "  {:<9} {:>3}  {:>6}  {:>3}  {:>3}  {:>3}  {:.2}",
+                label, dist.min, dist.median, dist.p90, dist.p99, dist.max, dist.mean
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_graph_summary() {
+        // a star from A plus a separate 2-cycle
+        let g: DiGraph = vec![("A", "B"), ("A", "C"), ("A", "D"), ("E", "F"), ("F", "E")]
+            .into_iter()
+            .collect();
+
+        let summary = GraphSummary::new(&g);
+        assert_eq!(summary.node_count, 6);
+        assert_eq!(summary.edge_count, 5);
+        assert_eq!(summary.density, 5.0 / 30.0);
+        assert_eq!(summary.component_count, 2);
+        assert!(!summary.is_dag);
+        assert_eq!(
+            summary.out_degree,
+            DegreeDistribution {
+                min: 0,
+                median: 0,
+                p90: 3,
+                p99: 3,
+                max: 3,
+                mean: 5.0 / 6.0,
+            }
+        );
+        assert_eq!(summary.in_degree.median, 1);
+        assert!(summary.to_string().contains("components: 2"));
+
+        let empty = GraphSummary::new(&DiGraph::new(None));
+        assert_eq!(empty.density, 0.0);
+        assert!(empty.is_dag);
+    }
+}
@@ -12,31 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::graph::{GraphBase, Node};
 use std::collections::{HashMap, VecDeque};
-use std::hash::Hash;
 
 /// topological sort
-pub fn topsort(graph: &impl TSortGraph) -> Vec<String> {
+///
+/// Nodes that lie on a cycle, including nodes with a self-loop, and nodes
+/// reachable only through them are left out of the result.
+pub fn topsort<G: GraphBase>(graph: &G) -> Vec<String> {
     let mut map = HashMap::new();
-    for node in graph.get_nodes() {
+    for node in graph.nodes() {
         map.insert(node, node.in_degree());
     }
-    // HashMap<&<T as TSortGraph>::Node, usize>
+    // seed the queue in graph order so the result is deterministic
     let mut queue = VecDeque::new();
-    for (&key, val) in map.iter() {
-        if *val == 0 {
-            queue.push_back(key);
+    for node in graph.nodes() {
+        if map[node] == 0 {
+            queue.push_back(node);
         }
     }
 
     let mut names = Vec::new();
-    while queue.len() > 0 {
+    while !queue.is_empty() {
         let curr_node = queue.pop_front().unwrap();
-        names.push(curr_node.get_name().to_string());
-        for name in curr_node.get_successors() {
-            let succ = graph.get_node(name.as_str()).unwrap();
+        names.push(curr_node.name().to_string());
+        for name in curr_node.successor_names() {
+            let succ = graph.node(name).unwrap();
             let degree = map.get_mut(succ).unwrap();
-            *degree -= 1 as usize;
+            *degree -= 1_usize;
             if *degree == 0 {
                 queue.push_back(succ);
             }
@@ -46,18 +49,6 @@ pub fn topsort(graph: &impl TSortGraph) -> Vec<String> {
     names
 }
 
-pub trait TSortGraph {
-    type Node: TSortNode + Eq + Hash;
-    fn get_nodes(&self) -> Vec<&Self::Node>;
-    fn get_node(&self, name: &str) -> Option<&Self::Node>;
-}
-
-pub trait TSortNode {
-    fn get_name(&self) -> &str;
-    fn in_degree(&self) -> usize;
-    fn get_successors(&self) -> Vec<String>;
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,4 +91,17 @@ mod tests {
                 || sorted == vec!["H", "D", "A", "B", "C", "E", "F", "G", "I", "J"]
         );
     }
+
+    #[test]
+    fn test_topsort_self_loop() {
+        let mut g = DiGraph::new(None);
+        g.add_edge(Some("A"), Some("B"));
+        g.add_edge(Some("B"), Some("B"));
+        g.add_edge(Some("B"), Some("C"));
+        g.add_edge(Some("D"), None);
+
+        let mut names = topsort(&g);
+        names.sort();
+        assert_eq!(names, vec!["A", "D"]);
+    }
 }
@@ -0,0 +1,257 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Approximate tours for the traveling salesman problem on complete graphs.
+//!
+//! The cost between two nodes is the weight of the edge joining them in
+//! either direction, so directed graphs should carry the same weight both
+//! ways; edges without a weight cost 1. Both functions fail with
+//! `GraphError::NotFoundEdge` if some pair of nodes is not joined.
+
+use super::matching::max_weight_max_cardinality_matching;
+use crate::{
+    error::GraphError,
+    graph::{Directed, UnGraph, Weighted},
+};
+use std::collections::HashMap;
+
+/// A closed tour: `nodes` starts and ends at the same node and visits every
+/// other node once in between.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Tour {
+    pub nodes: Vec<String>,
+    /// Total cost of the edges along the tour.
+    pub cost: i64,
+}
+
+/// Christofides' algorithm: a minimum spanning tree plus a minimum weight
+/// perfect matching of its odd-degree nodes, walked as an Euler circuit with
+/// repeated nodes skipped. If the costs obey the triangle inequality, the tour
+/// costs at most 1.5 times the optimum. The tour starts at the first node in
+/// graph order.
+pub fn christofides<G: Directed + Weighted>(graph: &G) -> Result<Tour, GraphError> {
+    let costs = Costs::new(graph)?;
+    let n = costs.names.len();
+    if n <= 2 {
+        return Ok(costs.tour((0..n).collect()));
+    }
+
+    // Prim's algorithm on the dense cost matrix
+    let mut in_tree = vec![false; n];
+    let mut best: Vec<(i64, usize)> = (0..n).map(|v| (costs.cost(0, v), 0)).collect();
+    in_tree[0] = true;
+    let mut multigraph: Vec<(usize, usize)> = Vec::with_capacity(2 * n);
+    for _ in 1..n {
+        let v = (0..n)
+            .filter(|&v| !in_tree[v])
+            .min_by_key(|&v| best[v].0)
+            .unwrap();
+        in_tree[v] = true;
+        multigraph.push((best[v].1, v));
+        for w in 0..n {
+            if !in_tree[w] && costs.cost(v, w) < best[w].0 {
+                best[w] = (costs.cost(v, w), v);
+            }
+        }
+    }
+
+    // minimum weight perfect matching of the odd-degree nodes
+    let mut degree = vec![0; n];
+    for &(u, v) in multigraph.iter() {
+        degree[u] += 1;
+        degree[v] += 1;
+    }
+    let odd: Vec<usize> = (0..n).filter(|&v| degree[v] % 2 == 1).collect();
+    let mut odd_graph = UnGraph::new(None);
+    for (i, &u) in odd.iter().enumerate() {
+        for &v in odd[i + 1..].iter() {
+            let (a, b) = (u.to_string(), v.to_string());
+            odd_graph.add_weighted_edge(a.as_str(), b.as_str(), -costs.cost(u, v));
+        }
+    }
+    for (a, b) in max_weight_max_cardinality_matching(&odd_graph) {
+        multigraph.push((a.parse().unwrap(), b.parse().unwrap()));
+    }
+
+    // Hierholzer's algorithm; every degree is now even
+    let mut incident: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (k, &(u, v)) in multigraph.iter().enumerate() {
+        incident[u].push(k);
+        incident[v].push(k);
+    }
+    let mut used = vec![false; multigraph.len()];
+    let mut stack = vec![0];
+    let mut circuit = Vec::with_capacity(multigraph.len() + 1);
+    while let Some(&v) = stack.last() {
+        match incident[v].pop() {
+            Some(k) if used[k] => {}
+            Some(k) => {
+                used[k] = true;
+                let (a, b) = multigraph[k];
+                stack.push(if a == v { b } else { a });
+            }
+            None => circuit.push(stack.pop().unwrap()),
+        }
+    }
+
+    let mut visited = vec![false; n];
+    let order = circuit
+        .into_iter()
+        .rev()
+        .filter(|&v| !std::mem::replace(&mut visited[v], true))
+        .collect();
+    Ok(costs.tour(order))
+}
+
+/// Nearest-neighbor heuristic: start at `source`, or the first node in graph
+/// order, and always move to the cheapest unvisited node. Fails with
+/// `GraphError::NotFoundNode` if `source` is not in the graph.
+pub fn greedy_tsp<G: Directed + Weighted>(
+    graph: &G,
+    source: Option<&str>,
+) -> Result<Tour, GraphError> {
+    let costs = Costs::new(graph)?;
+    let n = costs.names.len();
+    let start = match source {
+        Some(source) => *costs
+            .index
+            .get(source)
+            .ok_or_else(|| GraphError::NotFoundNode(source.to_string()))?,
+        None if n == 0 => return Ok(Tour::default()),
+        None => 0,
+    };
+
+    let mut visited = vec![false; n];
+    visited[start] = true;
+    let mut order = vec![start];
+    let mut curr = start;
+    for _ in 1..n {
+        let next = (0..n)
+            .filter(|&v| !visited[v])
+            .min_by_key(|&v| costs.cost(curr, v))
+            .unwrap();
+        visited[next] = true;
+        order.push(next);
+        curr = next;
+    }
+    Ok(costs.tour(order))
+}
+
+/// Dense cost matrix of a complete graph.
+struct Costs<'a> {
+    names: Vec<&'a str>,
+    index: HashMap<&'a str, usize>,
+    matrix: Vec<Vec<i64>>,
+}
+
+impl<'a> Costs<'a> {
+    fn new<G: Directed + Weighted>(graph: &'a G) -> Result<Self, GraphError> {
+        let names: Vec<&str> = graph.node_names().collect();
+        let index = names
+            .iter()
+            .enumerate()
+            .map(|(i, &name)| (name, i))
+            .collect();
+        let n = names.len();
+        let mut matrix = vec![vec![0; n]; n];
+        for u in 0..n {
+            for v in u + 1..n {
+                let (a, b) = (names[u], names[v]);
+                let cost = if graph.contains_edge(a, b) {
+                    graph.edge_weight(a, b).unwrap_or(1)
+                } else if graph.contains_edge(b, a) {
+                    graph.edge_weight(b, a).unwrap_or(1)
+                } else {
+                    return Err(GraphError::NotFoundEdge(a.to_string(), b.to_string()));
+                };
+                matrix[u][v] = cost;
+                matrix[v][u] = cost;
+            }
+        }
+        Ok(Costs {
+            names,
+            index,
+            matrix,
+        })
+    }
+
+    fn cost(&self, u: usize, v: usize) -> i64 {
+        self.matrix[u][v]
+    }
+
+    /// Close a visiting order into a tour.
+    fn tour(&self, mut order: Vec<usize>) -> Tour {
+        if let Some(&first) = order.first() {
+            order.push(first);
+        }
+        Tour {
+            cost: order.windows(2).map(|w| self.cost(w[0], w[1])).sum(),
+            nodes: order
+                .into_iter()
+                .map(|v| self.names[v].to_string())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // points on a line at the given positions, with distances as costs
+    fn line(points: &[(&str, i64)]) -> UnGraph {
+        let mut g = UnGraph::new(None);
+        for (i, &(a, x)) in points.iter().enumerate() {
+            for &(b, y) in points[i + 1..].iter() {
+                g.add_weighted_edge(a, b, (x - y).abs());
+            }
+        }
+        g
+    }
+
+    #[test]
+    fn test_christofides() {
+        let g = line(&[("a", 0), ("c", 2), ("b", 1), ("d", 3), ("e", 7)]);
+        let tour = christofides(&g).unwrap();
+        assert_eq!(tour.nodes.len(), 6);
+        assert_eq!(tour.nodes.first(), tour.nodes.last());
+        // any tour of points on a line costs at least twice their span
+        assert!(tour.cost >= 14 && tour.cost <= 21);
+
+        let mut names = tour.nodes[1..].to_vec();
+        names.sort();
+        assert_eq!(names, vec!["a", "b", "c", "d", "e"]);
+
+        let mut g = g;
+        g.remove_edge("a", "e");
+        assert!(matches!(
+            christofides(&g),
+            Err(GraphError::NotFoundEdge(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_greedy_tsp() {
+        let g = line(&[("a", 0), ("c", 2), ("b", 1), ("d", 3)]);
+        let tour = greedy_tsp(&g, Some("a")).unwrap();
+        assert_eq!(tour.nodes, vec!["a", "b", "c", "d", "a"]);
+        assert_eq!(tour.cost, 6);
+
+        assert_eq!(
+            greedy_tsp(&UnGraph::new(None), None).unwrap(),
+            Tour::default()
+        );
+        assert!(greedy_tsp(&g, Some("z")).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Searches that follow edges from a start node and stop as soon as they find
+//! what they are looking for. Successors are visited in graph order.
+
+use super::path::Path;
+use crate::{
+    error::GraphError,
+    graph::{GraphBase, Node, Weighted},
+};
+use std::collections::{HashMap, VecDeque};
+
+/// Breadth-first search from `start` for the nearest node, by number of
+/// edges, accepted by `predicate`, returning the path to it. `start` itself is
+/// tested first.
+///
+/// Returns `Ok(None)` if no reachable node is accepted, and
+/// `GraphError::NotFoundNode` if `start` is not in the graph.
+pub fn bfs_find<G, P>(graph: &G, start: &str, mut predicate: P) -> Result<Option<Path>, GraphError>
+where
+    G: Weighted,
+    P: FnMut(&G::Node) -> bool,
+{
+    let node = graph
+        .node(start)
+        .ok_or_else(|| GraphError::NotFoundNode(start.to_string()))?;
+    if predicate(node) {
+        return Ok(Some(Path::from_nodes(graph, vec![start.to_string()])));
+    }
+
+    let mut parent: HashMap<&str, Option<&str>> = HashMap::from([(start, None)]);
+    let mut queue = VecDeque::from([node]);
+    while let Some(node) = queue.pop_front() {
+        for succ in node.successor_names() {
+            if parent.contains_key(succ) {
+                continue;
+            }
+            parent.insert(succ, Some(node.name()));
+            let succ_node = graph.node(succ).unwrap();
+            if predicate(succ_node) {
+                return Ok(Some(path_to(graph, &parent, succ)));
+            }
+            queue.push_back(succ_node);
+        }
+    }
+    Ok(None)
+}
+
+/// Depth-first search from `start` for the first node, in preorder, accepted
+/// by `predicate`, returning the path to it along the search tree. `start`
+/// itself is tested first.
+///
+/// Returns `Ok(None)` if no reachable node is accepted, and
+/// `GraphError::NotFoundNode` if `start` is not in the graph.
+pub fn dfs_find<G, P>(graph: &G, start: &str, mut predicate: P) -> Result<Option<Path>, GraphError>
+where
+    G: Weighted,
+    P: FnMut(&G::Node) -> bool,
+{
+    if graph.node(start).is_none() {
+        return Err(GraphError::NotFoundNode(start.to_string()));
+    }
+
+    let mut parent: HashMap<&str, Option<&str>> = HashMap::new();
+    // (node, parent), pushed in reverse so successors pop in graph order
+    let mut stack: Vec<(&str, Option<&str>)> = vec![(start, None)];
+    while let Some((name, up)) = stack.pop() {
+        if parent.contains_key(name) {
+            continue;
+        }
+        parent.insert(name, up);
+        let node = graph.node(name).unwrap();
+        if predicate(node) {
+            return Ok(Some(path_to(graph, &parent, name)));
+        }
+        let succs: Vec<&str> = node.successor_names().collect();
+        for &succ in succs.iter().rev() {
+            if !parent.contains_key(succ) {
+                stack.push((succ, Some(name)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Nodes reachable from `start` by a path of at most `limit` edges, in
+/// depth-first preorder of their first discovery, `start` first.
+///
+/// A node first reached by a long path is expanded again when a shorter one
+/// turns up, so the result matches a breadth-first search cut off at `limit`.
+/// Fails with `GraphError::NotFoundNode` if `start` is not in the graph.
+pub fn dls<G: GraphBase>(graph: &G, start: &str, limit: usize) -> Result<Vec<String>, GraphError> {
+    if graph.node(start).is_none() {
+        return Err(GraphError::NotFoundNode(start.to_string()));
+    }
+
+    let mut order = Vec::new();
+    let mut depth: HashMap<&str, usize> = HashMap::new();
+    let mut stack = vec![(start, 0)];
+    while let Some((name, d)) = stack.pop() {
+        match depth.get(name) {
+            Some(&seen) if seen <= d => continue,
+            Some(_) => {}
+            None => order.push(name.to_string()),
+        }
+        depth.insert(name, d);
+        if d == limit {
+            continue;
+        }
+        let succs: Vec<&str> = graph.node(name).unwrap().successor_names().collect();
+        for &succ in succs.iter().rev() {
+            if depth.get(succ).is_none_or(|&seen| seen > d + 1) {
+                stack.push((succ, d + 1));
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// Iterative deepening depth-first search: a shortest path, by number of
+/// edges, from `start` to `goal`, found by depth-limited searches with limits
+/// 0, 1, 2, ...
+///
+/// Only the current path is kept in memory, never a frontier or a visited
+/// set, at the price of revisiting nodes; on graphs with many cycles that
+/// can take exponential time. Returns `Ok(None)` once no simple path can be
+/// extended any further, and `GraphError::NotFoundNode` if either node is not
+/// in the graph.
+pub fn iddfs<G: Weighted>(graph: &G, start: &str, goal: &str) -> Result<Option<Path>, GraphError> {
+    for name in [start, goal] {
+        if graph.node(name).is_none() {
+            return Err(GraphError::NotFoundNode(name.to_string()));
+        }
+    }
+    for limit in 0.. {
+        match path_within(graph, start, goal, limit) {
+            Depth::Found(path) => return Ok(Some(Path::from_nodes(graph, path))),
+            Depth::Exhausted => return Ok(None),
+            Depth::CutOff => {}
+        }
+    }
+    unreachable!()
+}
+
+enum Depth {
+    Found(Vec<String>),
+    /// Some path was cut off at the limit, so a deeper search may succeed.
+    CutOff,
+    Exhausted,
+}
+
+/// Depth-first search for a simple path of at most `limit` edges.
+fn path_within<G: GraphBase>(graph: &G, start: &str, goal: &str, limit: usize) -> Depth {
+    // the current path, with the successors of every node still to try
+    let mut path = Vec::new();
+    let mut cut_off = false;
+    let mut next = Some(start);
+    loop {
+        if let Some(name) = next.take() {
+            if name == goal {
+                let mut found: Vec<String> = path
+                    .iter()
+                    .map(|(name, _): &(&str, _)| name.to_string())
+                    .collect();
+                found.push(name.to_string());
+                return Depth::Found(found);
+            }
+            let node = graph.node(name).unwrap();
+            if path.len() == limit {
+                cut_off |= node.out_degree() > 0;
+            } else {
+                path.push((name, node.successor_names()));
+            }
+        }
+        let (_, succs) = match path.last_mut() {
+            Some(top) => top,
+            None => break,
+        };
+        match succs.next() {
+            Some(succ) if path.iter().all(|(name, _)| *name != succ) => next = Some(succ),
+            Some(_) => {}
+            None => {
+                path.pop();
+            }
+        }
+    }
+    if cut_off {
+        Depth::CutOff
+    } else {
+        Depth::Exhausted
+    }
+}
+
+/// The path from the root of a search tree to `name`.
+fn path_to<G: Weighted>(graph: &G, parent: &HashMap<&str, Option<&str>>, name: &str) -> Path {
+    let mut path = vec![name.to_string()];
+    let mut curr = name;
+    while let Some(&Some(up)) = parent.get(curr) {
+        path.push(up.to_string());
+        curr = up;
+    }
+    path.reverse();
+    Path::from_nodes(graph, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, DiNode};
+
+    fn graph() -> DiGraph {
+        let mut g = DiGraph::new(None);
+        for name in ["T1", "T2"] {
+            g.add_node(DiNode::new(name, Some("target".to_string())));
+        }
+        g.add_edges_from(vec![
+            ("S", "A"),
+            ("A", "B"),
+            ("B", "T1"),
+            ("S", "C"),
+            ("C", "T2"),
+        ]);
+        g
+    }
+
+    #[test]
+    fn test_bfs_find() {
+        let g = graph();
+        let is_target = |node: &DiNode| node.get_weight().as_deref() == Some("target");
+        let path = bfs_find(&g, "S", is_target).unwrap().unwrap();
+        assert_eq!(path.nodes(), ["S", "C", "T2"]);
+        assert_eq!(bfs_find(&g, "A", is_target).unwrap().unwrap().len(), 2);
+        let path = bfs_find(&g, "T1", is_target).unwrap().unwrap();
+        assert_eq!((path.nodes(), path.len()), (&["T1".to_string()][..], 0));
+        assert_eq!(bfs_find(&g, "T1", |node| node.name() == "S").unwrap(), None);
+
+        // the search stops at the first hit
+        let mut visited = 0;
+        bfs_find(&g, "S", |_| {
+            visited += 1;
+            visited == 2
+        })
+        .unwrap();
+        assert_eq!(visited, 2);
+        assert!(bfs_find(&g, "Z", is_target).is_err());
+    }
+
+    #[test]
+    fn test_dfs_find() {
+        let g = graph();
+        let is_target = |node: &DiNode| node.get_weight().as_deref() == Some("target");
+        let path = dfs_find(&g, "S", is_target).unwrap().unwrap();
+        assert_eq!(path.nodes(), ["S", "A", "B", "T1"]);
+        assert_eq!(path.total_weight(), 3);
+
+        let mut order = Vec::new();
+        let found = dfs_find(&g, "S", |node| {
+            order.push(node.get_name());
+            false
+        })
+        .unwrap();
+        assert_eq!(found, None);
+        assert_eq!(order, vec!["S", "A", "B", "T1", "C", "T2"]);
+        assert!(dfs_find(&g, "Z", is_target).is_err());
+    }
+
+    #[test]
+    fn test_dls() {
+        // C is two edges away through B, but first found at depth 3 through X
+        let g: DiGraph = vec![
+            ("S", "X"),
+            ("X", "Y"),
+            ("Y", "C"),
+            ("S", "B"),
+            ("B", "C"),
+            ("C", "D"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(dls(&g, "S", 0).unwrap(), vec!["S"]);
+        assert_eq!(dls(&g, "S", 1).unwrap(), vec!["S", "X", "B"]);
+        assert_eq!(dls(&g, "S", 3).unwrap(), vec!["S", "X", "Y", "C", "B", "D"]);
+        assert!(dls(&g, "Z", 1).is_err());
+    }
+
+    #[test]
+    fn test_iddfs() {
+        let g: DiGraph = vec![
+            ("S", "X"),
+            ("X", "Y"),
+            ("Y", "G"),
+            ("S", "B"),
+            ("B", "G"),
+            ("G", "S"),
+        ]
+        .into_iter()
+        .collect();
+        let path = iddfs(&g, "S", "G").unwrap().unwrap();
+        assert_eq!(path.nodes(), ["S", "B", "G"]);
+        assert!(!path.contains("X"));
+        assert_eq!(iddfs(&g, "S", "S").unwrap().unwrap().nodes(), ["S"]);
+
+        let mut g = g;
+        g.add_edge(Some("island"), None);
+        assert_eq!(iddfs(&g, "S", "island").unwrap(), None);
+        assert!(iddfs(&g, "S", "Z").is_err());
+    }
+}
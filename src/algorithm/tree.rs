@@ -0,0 +1,250 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recognition of trees and forests, and helpers for trees hanging from a
+//! root. Except in [`is_arborescence`], edge direction is ignored and the two
+//! opposite arcs between a pair of nodes count as a single edge, so these work
+//! on an [`UnGraph`](crate::graph::UnGraph) as well as on directed graphs.
+
+use crate::{
+    error::GraphError,
+    graph::{GraphBase, Node},
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Whether the graph has no cycles. A self-loop is a cycle.
+pub fn is_forest<G: GraphBase>(graph: &G) -> bool {
+    undirected_edge_count(graph).is_some()
+}
+
+/// Whether the graph is a connected forest. The empty graph is not a tree.
+pub fn is_tree<G: GraphBase>(graph: &G) -> bool {
+    let n = graph.node_count();
+    n > 0 && undirected_edge_count(graph) == Some(n - 1)
+}
+
+/// Whether the graph is a tree whose edges all point away from a single root,
+/// so every other node has exactly one incoming edge.
+pub fn is_arborescence<G: GraphBase>(graph: &G) -> bool {
+    is_tree(graph)
+        && graph.nodes().all(|node| node.in_degree() <= 1)
+        && graph.nodes().map(|node| node.in_degree()).sum::<usize>() == graph.node_count() - 1
+}
+
+/// Number of edges of the undirected view, or `None` if it has a cycle.
+fn undirected_edge_count<G: GraphBase>(graph: &G) -> Option<usize> {
+    let index: HashMap<&str, usize> = graph
+        .node_names()
+        .enumerate()
+        .map(|(i, name)| (name, i))
+        .collect();
+    let mut parent: Vec<usize> = (0..index.len()).collect();
+    fn find(parent: &mut [usize], mut v: usize) -> usize {
+        while parent[v] != v {
+            parent[v] = parent[parent[v]];
+            v = parent[v];
+        }
+        v
+    }
+
+    let mut seen = HashSet::new();
+    for (v, name) in graph.node_names().enumerate() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            let w = index[succ];
+            if !seen.insert((v.min(w), v.max(w))) {
+                continue;
+            }
+            let (a, b) = (find(&mut parent, v), find(&mut parent, w));
+            if a == b {
+                return None;
+            }
+            parent[a] = b;
+        }
+    }
+    Some(seen.len())
+}
+
+/// The tree containing `root`, with every edge leading away from the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootedTree {
+    pub root: String,
+    /// Parent of every node other than the root.
+    pub parent: HashMap<String, String>,
+    /// Number of edges between every node and the root.
+    pub depth: HashMap<String, usize>,
+    /// Number of nodes in the subtree of every node, itself included.
+    pub subtree_size: HashMap<String, usize>,
+    /// Greatest depth of any node.
+    pub height: usize,
+}
+
+/// Hang the nodes connected to `root` from it, in `O(V + E)` time. Nodes in
+/// other components are left out.
+///
+/// Fails with `GraphError::NotFoundNode` if `root` is not in the graph, or
+/// with `GraphError::CycleDetected` and the nodes of a cycle, in path order,
+/// if the component of `root` is not a tree.
+pub fn rooted_tree<G: GraphBase>(graph: &G, root: &str) -> Result<RootedTree, GraphError> {
+    if graph.node(root).is_none() {
+        return Err(GraphError::NotFoundNode(root.to_string()));
+    }
+
+    let mut order = vec![root];
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    let mut depth: HashMap<&str, usize> = HashMap::from([(root, 0)]);
+    let mut queue = VecDeque::from([root]);
+    while let Some(name) = queue.pop_front() {
+        let node = graph.node(name).unwrap();
+        let mut nbrs: Vec<&str> = node
+            .successor_names()
+            .chain(node.predecessor_names())
+            .collect();
+        // opposite arcs and an undirected edge seen from both ends are one edge
+        let mut seen = HashSet::new();
+        nbrs.retain(|&nbr| seen.insert(nbr));
+        for nbr in nbrs {
+            if parent.get(name) == Some(&nbr) {
+                continue;
+            }
+            if depth.contains_key(nbr) {
+                return Err(GraphError::CycleDetected(cycle(&parent, &depth, name, nbr)));
+            }
+            parent.insert(nbr, name);
+            depth.insert(nbr, depth[name] + 1);
+            order.push(nbr);
+            queue.push_back(nbr);
+        }
+    }
+
+    let mut subtree_size: HashMap<&str, usize> = order.iter().map(|&name| (name, 1)).collect();
+    for name in order.iter().rev() {
+        if let Some(&up) = parent.get(name) {
+            let size = subtree_size[name];
+            *subtree_size.get_mut(up).unwrap() += size;
+        }
+    }
+
+    let owned = |map: HashMap<&str, usize>| {
+        map.into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
+    };
+    Ok(RootedTree {
+        root: root.to_string(),
+        height: depth.values().copied().max().unwrap_or(0),
+        parent: parent
+            .into_iter()
+            .map(|(child, up)| (child.to_string(), up.to_string()))
+            .collect(),
+        depth: owned(depth),
+        subtree_size: owned(subtree_size),
+    })
+}
+
+/// The cycle closed by the edge `u -- w` between two nodes already in the BFS
+/// tree: `u` up to the common ancestor and back down to `w`.
+fn cycle(
+    parent: &HashMap<&str, &str>,
+    depth: &HashMap<&str, usize>,
+    u: &str,
+    w: &str,
+) -> Vec<String> {
+    if u == w {
+        return vec![u.to_string()];
+    }
+    let (mut up, mut down) = (vec![u], vec![w]);
+    let (mut a, mut b) = (u, w);
+    while a != b {
+        if depth[a] >= depth[b] {
+            a = parent[a];
+            up.push(a);
+        } else {
+            b = parent[b];
+            down.push(b);
+        }
+    }
+    down.pop();
+    up.extend(down.into_iter().rev());
+    up.into_iter().map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn test_tree_predicates() {
+        let arborescence: DiGraph = vec![("A", "B"), ("A", "C"), ("C", "D")]
+            .into_iter()
+            .collect();
+        assert!(is_forest(&arborescence));
+        assert!(is_tree(&arborescence));
+        assert!(is_arborescence(&arborescence));
+
+        // a tree, but C has two parents
+        let polytree: DiGraph = vec![("A", "B"), ("A", "C"), ("D", "C")]
+            .into_iter()
+            .collect();
+        assert!(is_tree(&polytree));
+        assert!(!is_arborescence(&polytree));
+
+        // opposite arcs are one edge of the undirected view
+        let both_ways: DiGraph = vec![("A", "B"), ("B", "A")].into_iter().collect();
+        assert!(is_tree(&both_ways));
+        assert!(!is_arborescence(&both_ways));
+
+        let mut forest: UnGraph = vec![("A", "B"), ("C", "D")].into_iter().collect();
+        assert!(is_forest(&forest));
+        assert!(!is_tree(&forest));
+        forest.add_edge("B", "C");
+        assert!(is_tree(&forest));
+        forest.add_edge("D", "A");
+        assert!(!is_forest(&forest));
+
+        let self_loop: DiGraph = vec![("A", "A")].into_iter().collect();
+        assert!(!is_forest(&self_loop));
+        assert!(is_forest(&DiGraph::new(None)));
+        assert!(!is_tree(&DiGraph::new(None)));
+    }
+
+    #[test]
+    fn test_rooted_tree() {
+        let g: UnGraph = vec![("A", "B"), ("A", "C"), ("C", "D"), ("C", "E"), ("X", "Y")]
+            .into_iter()
+            .collect();
+        let tree = rooted_tree(&g, "C").unwrap();
+        assert_eq!(tree.parent.len(), 4);
+        assert_eq!(tree.parent["B"], "A");
+        assert_eq!(tree.parent["A"], "C");
+        assert_eq!(tree.depth["B"], 2);
+        assert_eq!(tree.subtree_size["C"], 5);
+        assert_eq!(tree.subtree_size["A"], 2);
+        assert_eq!(tree.subtree_size["D"], 1);
+        assert_eq!(tree.height, 2);
+        assert!(!tree.depth.contains_key("X"));
+
+        let g: DiGraph = vec![("R", "A"), ("A", "B"), ("B", "C"), ("R", "D"), ("D", "C")]
+            .into_iter()
+            .collect();
+        match rooted_tree(&g, "R") {
+            Err(GraphError::CycleDetected(cycle)) => {
+                assert_eq!(cycle.len(), 5);
+                assert!(cycle.contains(&"R".to_string()));
+            }
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+        assert!(rooted_tree(&g, "Z").is_err());
+    }
+}
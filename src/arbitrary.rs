@@ -0,0 +1,161 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Random graphs for property tests with `proptest`, enabled by the
+//! `proptest` feature.
+//!
+//! Nodes are named `n0`, `n1`, ... in graph order. Failing cases shrink
+//! towards fewer nodes and fewer edges.
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn topsort_orders_every_dag(g in graphx::arbitrary::dags(GraphParams::default())) {
+//!         prop_assert_eq!(topsort(&g).len(), g.node_count());
+//!     }
+//! }
+//! ```
+
+use crate::graph::{DiGraph, DiNode};
+use proptest::{
+    arbitrary::Arbitrary, collection::vec, prelude::*, sample::Index, strategy::BoxedStrategy,
+};
+
+/// Size limits of generated graphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphParams {
+    pub max_nodes: usize,
+    /// Edges drawn at most, before dropping duplicates and, for DAGs,
+    /// self-loops.
+    pub max_edges: usize,
+}
+impl Default for GraphParams {
+    fn default() -> Self {
+        GraphParams {
+            max_nodes: 8,
+            max_edges: 16,
+        }
+    }
+}
+
+impl Arbitrary for DiGraph {
+    type Parameters = GraphParams;
+    type Strategy = BoxedStrategy<DiGraph>;
+
+    fn arbitrary_with(params: GraphParams) -> Self::Strategy {
+        digraphs(params).boxed()
+    }
+}
+
+/// Any directed graph, self-loops included.
+pub fn digraphs(params: GraphParams) -> impl Strategy<Value = DiGraph> {
+    sized(params, 0, |n, edges| {
+        edges
+            .into_iter()
+            .map(|(a, b)| (a.index(n), b.index(n)))
+            .collect()
+    })
+}
+
+/// Directed acyclic graphs: every edge leads to a node later in graph order.
+pub fn dags(params: GraphParams) -> impl Strategy<Value = DiGraph> {
+    sized(params, 0, |n, edges| {
+        edges
+            .into_iter()
+            .map(|(a, b)| (a.index(n), b.index(n)))
+            .filter(|(a, b)| a != b)
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect()
+    })
+}
+
+/// Weakly connected graphs with at least one node: a random tree, with every
+/// node after the first joined to one before it, plus random edges.
+pub fn connected_digraphs(params: GraphParams) -> impl Strategy<Value = DiGraph> {
+    let params = GraphParams {
+        max_nodes: params.max_nodes.max(1),
+        ..params
+    };
+    (1..=params.max_nodes)
+        .prop_flat_map(move |n| (Just(n), vec(any::<Index>(), n - 1)))
+        .prop_flat_map(move |(n, parents)| {
+            sized(
+                GraphParams {
+                    max_nodes: n,
+                    ..params
+                },
+                n,
+                move |n, edges| {
+                    let tree = parents
+                        .iter()
+                        .enumerate()
+                        .map(|(i, parent)| (parent.index(i + 1), i + 1));
+                    tree.chain(edges.into_iter().map(|(a, b)| (a.index(n), b.index(n))))
+                        .collect()
+                },
+            )
+        })
+}
+
+/// Graphs with `min_nodes` to `params.max_nodes` nodes and the edges `edges`
+/// makes from up to `params.max_edges` random pairs.
+fn sized<F>(params: GraphParams, min_nodes: usize, edges: F) -> impl Strategy<Value = DiGraph>
+where
+    F: Fn(usize, Vec<(Index, Index)>) -> Vec<(usize, usize)> + Clone + 'static,
+{
+    (min_nodes..=params.max_nodes.max(min_nodes))
+        .prop_flat_map(move |n| {
+            let max_edges = if n == 0 { 0 } else { params.max_edges };
+            (Just(n), vec(any::<(Index, Index)>(), 0..=max_edges))
+        })
+        .prop_map(move |(n, pairs)| {
+            let mut graph = DiGraph::new(None);
+            for i in 0..n {
+                graph.add_node(DiNode::new(&format!("n{}", i), None));
+            }
+            for (a, b) in edges(n, pairs) {
+                graph.add_edge(
+                    Some(format!("n{}", a).as_str()),
+                    Some(format!("n{}", b).as_str()),
+                );
+            }
+            graph
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{components::weakly_connected_components, topsort::topsort};
+
+    proptest! {
+        #[test]
+        fn test_arbitrary_digraph(g in any::<DiGraph>()) {
+            prop_assert!(g.node_count() <= 8);
+            prop_assert!(g.edge_count() <= 16);
+            prop_assert!(g.validate().is_ok());
+        }
+
+        #[test]
+        fn test_dags(g in dags(GraphParams { max_nodes: 12, max_edges: 30 })) {
+            prop_assert_eq!(topsort(&g).len(), g.node_count());
+        }
+
+        #[test]
+        fn test_connected_digraphs(g in connected_digraphs(GraphParams::default())) {
+            prop_assert!(g.node_count() >= 1);
+            prop_assert_eq!(weakly_connected_components(&g).len(), 1);
+        }
+    }
+}
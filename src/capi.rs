@@ -0,0 +1,225 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! C bindings, enabled by the `capi` feature and declared in `include/graphx.h`.
+//!
+//! Graphs are opaque `GraphxDiGraph` handles owned by the caller and released
+//! with `graphx_digraph_free`. Strings are NUL-terminated UTF-8. Functions
+//! returning `int` report `0` on success and `-1` on invalid arguments.
+
+use crate::{
+    algorithm::isomorphism::DiGraphMatcher,
+    graph::{DiGraph, DiNode},
+};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// Matching mode for `graphx_subgraph_match_count`: induced subgraph isomorphism.
+pub const GRAPHX_MATCH_SUBGRAPH: c_int = 0;
+/// Matching mode for `graphx_subgraph_match_count`: subgraph monomorphism.
+pub const GRAPHX_MATCH_MONO: c_int = 1;
+
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+/// Create an empty graph.
+#[no_mangle]
+pub extern "C" fn graphx_digraph_new() -> *mut DiGraph {
+    Box::into_raw(Box::new(DiGraph::new(None)))
+}
+
+/// Parse a graph from its JSON form, or return null if `json` is invalid.
+///
+/// # Safety
+///
+/// `json` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn graphx_digraph_from_json(json: *const c_char) -> *mut DiGraph {
+    match to_str(json).and_then(|json| DiGraph::from_json(json).ok()) {
+        Some(graph) => Box::into_raw(Box::new(graph)),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Release a graph. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `graph` must be null or a handle returned by this library that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn graphx_digraph_free(graph: *mut DiGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// Add a node with an optional (nullable) weight.
+///
+/// # Safety
+///
+/// `graph` must be a live handle; `name` and `weight` must be null or valid
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn graphx_digraph_add_node(
+    graph: *mut DiGraph,
+    name: *const c_char,
+    weight: *const c_char,
+) -> c_int {
+    match (graph.as_mut(), to_str(name)) {
+        (Some(graph), Some(name)) => {
+            let weight = to_str(weight).map(|weight| weight.to_string());
+            graph.add_node(DiNode::new(name, weight));
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Add an edge, creating missing endpoints.
+///
+/// # Safety
+///
+/// `graph` must be a live handle; `from` and `to` must be null or valid
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn graphx_digraph_add_edge(
+    graph: *mut DiGraph,
+    from: *const c_char,
+    to: *const c_char,
+) -> c_int {
+    match (graph.as_mut(), to_str(from), to_str(to)) {
+        (Some(graph), Some(from), Some(to)) => {
+            graph.add_edge(Some(from), Some(to));
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Add an edge with an integer weight, creating missing endpoints.
+///
+/// # Safety
+///
+/// Same as `graphx_digraph_add_edge`.
+#[no_mangle]
+pub unsafe extern "C" fn graphx_digraph_add_weighted_edge(
+    graph: *mut DiGraph,
+    from: *const c_char,
+    to: *const c_char,
+    weight: i64,
+) -> c_int {
+    match (graph.as_mut(), to_str(from), to_str(to)) {
+        (Some(graph), Some(from), Some(to)) => {
+            graph.add_weighted_edge(from, to, weight);
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Number of nodes, or 0 for a null handle.
+///
+/// # Safety
+///
+/// `graph` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn graphx_digraph_node_count(graph: *const DiGraph) -> usize {
+    graph.as_ref().map_or(0, |graph| graph.node_count())
+}
+
+/// Number of edges, or 0 for a null handle.
+///
+/// # Safety
+///
+/// `graph` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn graphx_digraph_edge_count(graph: *const DiGraph) -> usize {
+    graph.as_ref().map_or(0, |graph| graph.edge_count())
+}
+
+/// Count the subgraphs of `host` matching `pattern` with the given mode,
+/// stopping once `limit` matches are found (`0` means no limit). Returns
+/// `-1` on invalid arguments.
+///
+/// # Safety
+///
+/// `host` and `pattern` must be null or live handles.
+#[no_mangle]
+pub unsafe extern "C" fn graphx_subgraph_match_count(
+    host: *const DiGraph,
+    pattern: *const DiGraph,
+    mode: c_int,
+    limit: usize,
+) -> i64 {
+    let (host, pattern) = match (host.as_ref(), pattern.as_ref()) {
+        (Some(host), Some(pattern)) => (host, pattern),
+        _ => return -1,
+    };
+
+    let mut matcher = DiGraphMatcher::new(host, pattern);
+    matcher.limit = if limit == 0 { None } else { Some(limit) };
+    let mut mapping = Vec::new();
+    match mode {
+        GRAPHX_MATCH_SUBGRAPH => matcher.subgraph_isomorphism_iter(&mut mapping),
+        GRAPHX_MATCH_MONO => matcher.subgraph_monomorphism_iter(&mut mapping),
+        _ => return -1,
+    }
+    mapping.len() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn capi_match_count_test() {
+        let name = |s: &str| CString::new(s).unwrap();
+        unsafe {
+            let host = graphx_digraph_new();
+            graphx_digraph_add_edge(host, name("A").as_ptr(), name("B").as_ptr());
+            graphx_digraph_add_edge(host, name("B").as_ptr(), name("C").as_ptr());
+            graphx_digraph_add_edge(host, name("A").as_ptr(), name("C").as_ptr());
+            assert_eq!(graphx_digraph_node_count(host), 3);
+            assert_eq!(graphx_digraph_edge_count(host), 3);
+
+            let json = name("{\"name\":null,\"nodes\":{}}");
+            let pattern = graphx_digraph_from_json(json.as_ptr());
+            assert!(!pattern.is_null());
+            graphx_digraph_add_node(pattern, name("1").as_ptr(), ptr::null());
+            graphx_digraph_add_weighted_edge(pattern, name("1").as_ptr(), name("2").as_ptr(), 4);
+            graphx_digraph_add_edge(pattern, name("2").as_ptr(), name("3").as_ptr());
+
+            let count = |mode, limit| graphx_subgraph_match_count(host, pattern, mode, limit);
+            assert_eq!(count(GRAPHX_MATCH_SUBGRAPH, 0), 0);
+            assert_eq!(count(GRAPHX_MATCH_MONO, 0), 1);
+            assert_eq!(count(7, 0), -1);
+            assert_eq!(graphx_subgraph_match_count(host, ptr::null(), 0, 0), -1);
+            assert_eq!(
+                graphx_digraph_add_edge(host, ptr::null(), name("A").as_ptr()),
+                -1
+            );
+            assert!(graphx_digraph_from_json(name("{").as_ptr()).is_null());
+
+            graphx_digraph_free(pattern);
+            graphx_digraph_free(host);
+        }
+    }
+}
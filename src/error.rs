@@ -18,4 +18,119 @@ use thiserror::Error;
 pub enum GraphError {
     #[error("Not found node: {0}")]
     NotFoundNode(String),
+    #[error("Duplicate node: {0}")]
+    DuplicateNode(String),
+    #[error("Not found edge: {0} -> {1}")]
+    NotFoundEdge(String, String),
+    #[error("Dangling edge: {0} -> {1}")]
+    DanglingEdge(String, String),
+    #[error("Edge recorded at one endpoint only: {0} -> {1}")]
+    OneSidedEdge(String, String),
+    #[error("Node stored under another name: {0} holds {1}")]
+    MisnamedNode(String, String),
+    #[error("Not found port: {0}.{1}")]
+    NotFoundPort(String, String),
+    #[error("Port already fed: {0}.{1} by {2}")]
+    PortInUse(String, usize, String),
+    #[error("Edge joins two nodes on the same side: {0} -- {1}")]
+    SameSide(String, String),
+    #[error("Result too large: {0} nodes need more than {1} entries")]
+    TooLarge(usize, usize),
+    #[error("Too many nodes: {0}, at most {1} supported")]
+    TooManyNodes(usize, usize),
+    #[error("Cycle detected among nodes: {0:?}")]
+    CycleDetected(Vec<String>),
+    #[error("Negative cycle detected: {0:?}")]
+    NegativeCycle(Vec<String>),
+    #[error("Negative edge weight: {0} -> {1} weighs {2}")]
+    NegativeWeight(String, String, i64),
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+    #[error("Parse error at line {line}, column {column}: {message}")]
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// An error with what was being done when it occurred, e.g. the file being
+    /// read or the edge being added. The error itself is its `source()`.
+    #[error("{context}")]
+    Context {
+        context: String,
+        #[source]
+        source: Box<GraphError>,
+    },
+}
+impl GraphError {
+    /// Wrap the error with what was being done when it occurred.
+    pub fn context(self, context: impl Into<String>) -> GraphError {
+        GraphError::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// The error under all its context, to match on its kind.
+    pub fn root_cause(&self) -> &GraphError {
+        match self {
+            GraphError::Context { source, .. } => source.root_cause(),
+            err => err,
+        }
+    }
+
+    /// The error and all its context in one line, outermost first, e.g.
+    /// `reading g.tgf: line 3: Not found node: 7`.
+    pub fn report(&self) -> String {
+        match self {
+            GraphError::Context { context, source } => format!("{}: {}", context, source.report()),
+            err => err.to_string(),
+        }
+    }
+}
+
+/// Adding context to the error of a `Result`.
+pub trait ResultExt<T> {
+    fn context(self, context: impl Into<String>) -> Result<T, GraphError>;
+
+    /// Like [`ResultExt::context`], building the context only on error.
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> Result<T, GraphError>;
+}
+impl<T> ResultExt<T> for Result<T, GraphError> {
+    fn context(self, context: impl Into<String>) -> Result<T, GraphError> {
+        self.map_err(|err| err.context(context))
+    }
+
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> Result<T, GraphError> {
+        self.map_err(|err| err.context(context()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_error_context() {
+        let found: Result<(), GraphError> = Err(GraphError::NotFoundNode("7".to_string()));
+        let err = found
+            .context("line 3")
+            .with_context(|| format!("reading {}", "g.tgf"))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "reading g.tgf");
+        assert_eq!(err.report(), "reading g.tgf: line 3: Not found node: 7");
+        assert!(matches!(err.root_cause(), GraphError::NotFoundNode(name) if name == "7"));
+
+        let source = err.source().unwrap();
+        assert_eq!(source.to_string(), "line 3");
+        assert_eq!(source.source().unwrap().to_string(), "Not found node: 7");
+        assert!(source.source().unwrap().source().is_none());
+
+        let parse = GraphError::Parse {
+            line: 2,
+            column: 5,
+            message: "expected a number".to_string(),
+        };
+        assert_eq!(parse.root_cause().report(), parse.to_string());
+    }
 }
@@ -12,8 +12,213 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod attr;
+mod bipartite;
+mod builder;
 mod digraph;
+#[cfg(feature = "mmap")]
+mod disk;
+mod edge;
+mod frozen;
+mod imdigraph;
+mod interner;
+mod listener;
+mod minor;
 mod node;
+mod temporal;
+mod ungraph;
+mod walker;
 
-pub use digraph::DiGraph;
+pub use attr::{Attributed, Attributes};
+pub use bipartite::{BipartiteGraph, Side};
+pub use builder::DiGraphBuilder;
+pub use digraph::{DiGraph, NodeEntry, NodeOrder, WeightPolicy};
+#[cfg(feature = "mmap")]
+pub use disk::DiskDiGraph;
+pub use edge::EdgeData;
+pub use frozen::{FrozenDiGraph, FrozenNode};
+pub use imdigraph::{GraphVersion, ImDiGraph, ImDiNode, VersionDiff};
+pub use interner::{NodeId, NodeInterner};
+pub use listener::{GraphEvent, ListenerId};
+pub use minor::MinorOp;
 pub use node::DiNode;
+pub use temporal::{TemporalDiGraph, TemporalWindow, WindowNode};
+pub use ungraph::{UnGraph, UnNode};
+pub use walker::{NeighborWalker, NodeWalker};
+
+use std::hash::Hash;
+
+/// A node of a `Graph`.
+pub trait Node {
+    fn name(&self) -> &str;
+    fn weight(&self) -> Option<&str>;
+    fn predecessor_names(&self) -> impl Iterator<Item = &str>;
+    fn successor_names(&self) -> impl Iterator<Item = &str>;
+    fn in_degree(&self) -> usize;
+    fn out_degree(&self) -> usize;
+
+    /// Names of nodes connected to this node by an edge in either direction.
+    fn neighbors(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.successor_names().collect();
+        for name in self.predecessor_names() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Whether two nodes are interchangeable for the isomorphism matcher.
+    fn semantic_equal(&self, other: &Self) -> bool {
+        self.weight() == other.weight()
+    }
+}
+
+/// Nodes looked up by name: the part of a graph every algorithm needs.
+///
+/// Together with [`Directed`], [`Weighted`] and [`Ported`], whose methods all
+/// have defaults, this is all a graph type implements to work with every
+/// algorithm in `crate::algorithm`; those traits combine into [`Graph`]. Each
+/// algorithm asks only for the traits whose methods it calls.
+pub trait GraphBase {
+    type Node: Node + Eq + Hash;
+
+    fn node_count(&self) -> usize;
+    fn node(&self, name: &str) -> Option<&Self::Node>;
+    fn nodes(&self) -> impl Iterator<Item = &Self::Node>;
+
+    fn node_names(&self) -> impl Iterator<Item = &str> {
+        self.nodes().map(|node| node.name())
+    }
+
+    /// Names of the nodes that may be semantically equal to `other`, if the graph
+    /// can narrow them down cheaply. `None` means every node is a candidate.
+    fn semantic_candidates(&self, _other: &Self::Node) -> Option<Vec<String>> {
+        None
+    }
+}
+
+/// Edges as ordered pairs of nodes. An undirected graph has every edge in
+/// both directions.
+pub trait Directed: GraphBase {
+    fn contains_edge(&self, from: &str, to: &str) -> bool {
+        self.node(from)
+            .is_some_and(|node| node.successor_names().any(|name| name == to))
+    }
+}
+
+/// Edges with optional integer weights.
+pub trait Weighted: GraphBase {
+    /// Weight of the edge from `from` to `to`, if the edge exists and has one.
+    fn edge_weight(&self, _from: &str, _to: &str) -> Option<i64> {
+        None
+    }
+}
+
+/// Edges feeding numbered input ports of their target.
+pub trait Ported: Directed {
+    /// Input port of `to` that the edge from `from` feeds, if the edge exists
+    /// and is connected to a port.
+    fn edge_port(&self, _from: &str, _to: &str) -> Option<usize> {
+        None
+    }
+}
+
+/// Every graph type: implemented for all types implementing [`GraphBase`],
+/// [`Directed`], [`Weighted`] and [`Ported`].
+pub trait Graph: Directed + Weighted + Ported {}
+impl<G: Directed + Weighted + Ported> Graph for G {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{isomorphism::DiGraphMatcher, sssp::dijkstra, topsort::topsort};
+    use std::collections::HashMap;
+
+    // minimal adjacency-list graph implementing only the required methods
+    #[derive(PartialEq, Eq, Hash)]
+    struct AdjNode {
+        name: String,
+        preds: Vec<String>,
+        succs: Vec<String>,
+    }
+    impl Node for AdjNode {
+        fn name(&self) -> &str {
+            self.name.as_str()
+        }
+
+        fn weight(&self) -> Option<&str> {
+            None
+        }
+
+        fn predecessor_names(&self) -> impl Iterator<Item = &str> {
+            self.preds.iter().map(|name| name.as_str())
+        }
+
+        fn successor_names(&self) -> impl Iterator<Item = &str> {
+            self.succs.iter().map(|name| name.as_str())
+        }
+
+        fn in_degree(&self) -> usize {
+            self.preds.len()
+        }
+
+        fn out_degree(&self) -> usize {
+            self.succs.len()
+        }
+    }
+
+    struct AdjGraph {
+        nodes: HashMap<String, AdjNode>,
+    }
+    impl AdjGraph {
+        fn from_edges(edges: &[(&str, &str)]) -> Self {
+            let mut nodes: HashMap<String, AdjNode> = HashMap::new();
+            for &(from, to) in edges {
+                for name in [from, to].iter() {
+                    nodes.entry(name.to_string()).or_insert_with(|| AdjNode {
+                        name: name.to_string(),
+                        preds: Vec::new(),
+                        succs: Vec::new(),
+                    });
+                }
+                nodes.get_mut(from).unwrap().succs.push(to.to_string());
+                nodes.get_mut(to).unwrap().preds.push(from.to_string());
+            }
+            AdjGraph { nodes }
+        }
+    }
+    impl GraphBase for AdjGraph {
+        type Node = AdjNode;
+
+        fn node_count(&self) -> usize {
+            self.nodes.len()
+        }
+
+        fn node(&self, name: &str) -> Option<&AdjNode> {
+            self.nodes.get(name)
+        }
+
+        fn nodes(&self) -> impl Iterator<Item = &AdjNode> {
+            self.nodes.values()
+        }
+    }
+    impl Directed for AdjGraph {}
+    impl Weighted for AdjGraph {}
+    impl Ported for AdjGraph {}
+
+    #[test]
+    fn test_graph_trait_algorithms() {
+        let g = AdjGraph::from_edges(&[("A", "B"), ("B", "C"), ("A", "C")]);
+
+        assert_eq!(topsort(&g), vec!["A", "B", "C"]);
+        assert_eq!(dijkstra(&g, "A").unwrap().get("C"), Some(&1));
+        assert_eq!(g.node("A").unwrap().neighbors().len(), 2);
+
+        let pattern = AdjGraph::from_edges(&[("1", "2")]);
+        let mut matcher = DiGraphMatcher::new(&g, &pattern);
+        let mut mapping = Vec::new();
+        matcher.subgraph_isomorphism_iter(&mut mapping);
+        assert_eq!(mapping.len(), 3);
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Arbitrary key-value attributes attached to a graph, a node or an edge.
+pub type Attributes = HashMap<String, Value>;
+
+/// Typed access to the attributes of a graph element.
+pub trait Attributed {
+    fn attrs(&self) -> &Attributes;
+    fn attrs_mut(&mut self) -> &mut Attributes;
+
+    fn get_attr(&self, key: &str) -> Option<&Value> {
+        self.attrs().get(key)
+    }
+
+    /// Get an attribute converted to `T`, or `None` if it is missing or has another type.
+    fn get_attr_as<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.get_attr(key)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    fn set_attr(&mut self, key: &str, value: impl Into<Value>) {
+        self.attrs_mut().insert(key.to_string(), value.into());
+    }
+
+    fn remove_attr(&mut self, key: &str) -> Option<Value> {
+        self.attrs_mut().remove(key)
+    }
+}
@@ -0,0 +1,259 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Directed, GraphBase, Ported, UnGraph, UnNode, Weighted};
+use crate::error::GraphError;
+use indexmap::{IndexMap, IndexSet};
+
+/// One of the two node sets of a [`BipartiteGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Left,
+    Right,
+}
+impl Side {
+    pub fn other(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+/// An undirected graph whose nodes are split into a left and a right set, with
+/// every edge joining a left node to a right node.
+#[derive(Debug, Default)]
+pub struct BipartiteGraph {
+    graph: UnGraph,
+    left: IndexSet<String>,
+    right: IndexSet<String>,
+}
+impl BipartiteGraph {
+    pub fn new(name: Option<String>) -> Self {
+        BipartiteGraph {
+            graph: UnGraph::new(name),
+            ..BipartiteGraph::default()
+        }
+    }
+
+    /// The whole graph, ignoring sides.
+    pub fn graph(&self) -> &UnGraph {
+        &self.graph
+    }
+
+    /// Add a node to `side`, or replace the weight and attributes of a node
+    /// already there. Fails with `GraphError::DuplicateNode` if the node is on
+    /// the other side.
+    pub fn add_node(&mut self, side: Side, node: UnNode) -> Result<(), GraphError> {
+        if self.side(node.name()) == Some(side.other()) {
+            return Err(GraphError::DuplicateNode(node.get_name()));
+        }
+        self.set(side).insert(node.get_name());
+        self.graph.add_node(node);
+        Ok(())
+    }
+
+    /// Add an edge between the left node `left` and the right node `right`,
+    /// creating missing nodes on their sides. Fails with
+    /// `GraphError::SameSide` if either node is on the wrong side.
+    pub fn add_edge(&mut self, left: &str, right: &str) -> Result<(), GraphError> {
+        if self.side(left) == Some(Side::Right) || self.side(right) == Some(Side::Left) {
+            return Err(GraphError::SameSide(left.to_string(), right.to_string()));
+        }
+        self.left.insert(left.to_string());
+        self.right.insert(right.to_string());
+        self.graph.add_edge(left, right);
+        Ok(())
+    }
+
+    pub fn add_weighted_edge(
+        &mut self,
+        left: &str,
+        right: &str,
+        weight: i64,
+    ) -> Result<(), GraphError> {
+        self.add_edge(left, right)?;
+        self.graph.add_weighted_edge(left, right, weight);
+        Ok(())
+    }
+
+    /// Remove the edge between `a` and `b`, returning whether it existed.
+    pub fn remove_edge(&mut self, a: &str, b: &str) -> bool {
+        self.graph.remove_edge(a, b)
+    }
+
+    /// Remove a node and its edges, returning the node if it existed.
+    pub fn remove_node(&mut self, name: &str) -> Option<UnNode> {
+        self.left.shift_remove(name);
+        self.right.shift_remove(name);
+        self.graph.remove_node(name)
+    }
+
+    /// The side of a node, or `None` if the node is missing.
+    pub fn side(&self, name: &str) -> Option<Side> {
+        if self.left.contains(name) {
+            Some(Side::Left)
+        } else if self.right.contains(name) {
+            Some(Side::Right)
+        } else {
+            None
+        }
+    }
+
+    /// Names of the nodes on `side`, in insertion order.
+    pub fn side_names(&self, side: Side) -> impl Iterator<Item = &str> {
+        let set = match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        };
+        set.iter().map(|name| name.as_str())
+    }
+
+    fn set(&mut self, side: Side) -> &mut IndexSet<String> {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+
+    /// The co-occurrence graph of the nodes on `side`: two of them are joined
+    /// when they share a neighbor on the other side, and the weight of the
+    /// edge is the number of neighbors they share. Node weights are kept.
+    pub fn project_onto(&self, side: Side) -> UnGraph {
+        let mut projection = UnGraph::new(self.graph.get_name());
+        for name in self.side_names(side) {
+            let node = self.graph.get_node(name).unwrap();
+            projection.add_node(UnNode::new(name, node.get_weight()));
+        }
+
+        // (first, second) in the order of `side` -> number of shared neighbors
+        let index: IndexMap<&str, usize> = self
+            .side_names(side)
+            .enumerate()
+            .map(|(i, name)| (name, i))
+            .collect();
+        let mut shared: IndexMap<(usize, usize), i64> = IndexMap::new();
+        for middle in self.side_names(side.other()) {
+            let mut nbrs: Vec<usize> = self
+                .graph
+                .get_node(middle)
+                .unwrap()
+                .neighbor_names()
+                .map(|name| index[name])
+                .collect();
+            nbrs.sort_unstable();
+            for (i, &a) in nbrs.iter().enumerate() {
+                for &b in &nbrs[i + 1..] {
+                    *shared.entry((a, b)).or_insert(0) += 1;
+                }
+            }
+        }
+        for ((a, b), count) in shared {
+            let (a, _) = index.get_index(a).unwrap();
+            let (b, _) = index.get_index(b).unwrap();
+            projection.add_weighted_edge(a, b, count);
+        }
+        projection
+    }
+}
+impl GraphBase for BipartiteGraph {
+    type Node = UnNode;
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn node(&self, name: &str) -> Option<&UnNode> {
+        self.graph.get_node(name)
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &UnNode> {
+        self.graph.nodes()
+    }
+}
+impl Directed for BipartiteGraph {
+    fn contains_edge(&self, from: &str, to: &str) -> bool {
+        self.graph.contains_edge(from, to)
+    }
+}
+impl Weighted for BipartiteGraph {
+    fn edge_weight(&self, from: &str, to: &str) -> Option<i64> {
+        self.graph.get_edge_weight(from, to)
+    }
+}
+impl Ported for BipartiteGraph {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bipartite_membership() {
+        let mut g = BipartiteGraph::new(None);
+        g.add_edge("task1", "cpu").unwrap();
+        g.add_weighted_edge("task2", "cpu", 3).unwrap();
+        g.add_node(Side::Right, UnNode::new("gpu", Some("fast".to_string())))
+            .unwrap();
+        assert_eq!(g.side("task1"), Some(Side::Left));
+        assert_eq!(g.side("gpu"), Some(Side::Right));
+        assert_eq!(g.side("disk"), None);
+        assert_eq!(g.edge_weight("cpu", "task2"), Some(3));
+
+        assert!(matches!(
+            g.add_edge("task1", "task2"),
+            Err(GraphError::SameSide(..))
+        ));
+        assert!(matches!(
+            g.add_edge("cpu", "gpu"),
+            Err(GraphError::SameSide(..))
+        ));
+        assert!(matches!(
+            g.add_node(Side::Left, UnNode::new("cpu", None)),
+            Err(GraphError::DuplicateNode(_))
+        ));
+        assert_eq!(g.graph().edge_count(), 2);
+
+        g.remove_node("cpu");
+        assert_eq!(g.side_names(Side::Right).collect::<Vec<_>>(), vec!["gpu"]);
+        g.add_edge("cpu", "task3").unwrap();
+        assert_eq!(g.side("cpu"), Some(Side::Left));
+    }
+
+    #[test]
+    fn test_bipartite_projection() {
+        let mut g = BipartiteGraph::new(None);
+        for (task, resource) in [
+            ("t1", "cpu"),
+            ("t1", "gpu"),
+            ("t2", "cpu"),
+            ("t2", "gpu"),
+            ("t3", "gpu"),
+            ("t4", "disk"),
+        ] {
+            g.add_edge(task, resource).unwrap();
+        }
+
+        let tasks = g.project_onto(Side::Left);
+        assert_eq!(tasks.node_count(), 4);
+        assert_eq!(tasks.edge_count(), 3);
+        assert_eq!(tasks.get_edge_weight("t1", "t2"), Some(2));
+        assert_eq!(tasks.get_edge_weight("t3", "t2"), Some(1));
+        assert!(!tasks.contains_edge("t4", "t1"));
+
+        let resources = g.project_onto(Side::Right);
+        assert_eq!(resources.edge_count(), 1);
+        assert_eq!(resources.get_edge_weight("cpu", "gpu"), Some(2));
+        assert_eq!(resources.degree("disk").unwrap(), 0);
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{digraph::DiGraph, node::DiNode};
+use crate::{algorithm::dag::topological_order, error::GraphError};
+use std::collections::HashSet;
+
+/// Accumulates nodes and edges, and validates them before producing a `DiGraph`.
+///
+/// Unlike `DiGraph::add_edge`, edges never create nodes implicitly: every edge
+/// endpoint must be declared with `node` first.
+#[derive(Debug, Default)]
+pub struct DiGraphBuilder {
+    name: Option<String>,
+    nodes: Vec<DiNode>,
+    edges: Vec<(String, String)>,
+    acyclic: bool,
+}
+impl DiGraphBuilder {
+    pub fn new() -> Self {
+        DiGraphBuilder::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn node(mut self, name: &str, weight: Option<String>) -> Self {
+        self.nodes.push(DiNode::new(name, weight));
+        self
+    }
+
+    pub fn edge(mut self, from: &str, to: &str) -> Self {
+        self.edges.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Require the resulting graph to be a DAG.
+    pub fn acyclic(mut self, acyclic: bool) -> Self {
+        self.acyclic = acyclic;
+        self
+    }
+
+    /// Check all constraints and build the graph, or return every violation found.
+    pub fn build(self) -> Result<DiGraph, Vec<GraphError>> {
+        let mut violations = Vec::new();
+
+        // unique names
+        let mut names = HashSet::new();
+        for node in self.nodes.iter() {
+            if !names.insert(node.get_name()) {
+                violations.push(GraphError::DuplicateNode(node.get_name()));
+            }
+        }
+
+        // no dangling references
+        for (from, to) in self.edges.iter() {
+            if !names.contains(from) || !names.contains(to) {
+                violations.push(GraphError::DanglingEdge(from.clone(), to.clone()));
+            }
+        }
+
+        if !violations.is_empty() {
+            return Err(violations);
+        }
+
+        let mut graph = DiGraph::new(self.name);
+        for node in self.nodes {
+            graph.add_node(node);
+        }
+        for (from, to) in self.edges.iter() {
+            graph.add_edge(Some(from.as_str()), Some(to.as_str()));
+        }
+
+        if self.acyclic {
+            topological_order(&graph).map_err(|err| vec![err])?;
+        }
+
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_build() {
+        let g = DiGraphBuilder::new()
+            .name("g")
+            .node("A", None)
+            .node("B", Some("B".to_string()))
+            .edge("A", "B")
+            .acyclic(true)
+            .build()
+            .unwrap();
+
+        let mut expected = DiGraph::new(Some("g".to_string()));
+        expected.add_node(DiNode::new("A", None));
+        expected.add_node(DiNode::new("B", Some("B".to_string())));
+        expected.add_edge(Some("A"), Some("B"));
+
+        assert_eq!(expected, g);
+    }
+
+    #[test]
+    fn test_builder_violations() {
+        let violations = DiGraphBuilder::new()
+            .node("A", None)
+            .node("A", None)
+            .edge("A", "B")
+            .build()
+            .unwrap_err();
+        assert_eq!(violations.len(), 2);
+        assert!(matches!(&violations[0], GraphError::DuplicateNode(name) if name == "A"));
+        assert!(
+            matches!(&violations[1], GraphError::DanglingEdge(from, to) if from == "A" && to == "B")
+        );
+
+        let violations = DiGraphBuilder::new()
+            .node("A", None)
+            .node("B", None)
+            .node("C", None)
+            .edge("A", "B")
+            .edge("B", "C")
+            .edge("C", "B")
+            .node("D", None)
+            .edge("C", "D")
+            .node("E", None)
+            .edge("E", "E")
+            .acyclic(true)
+            .build()
+            .unwrap_err();
+        assert_eq!(violations.len(), 1);
+        // D cannot be ordered either, but it is not on a cycle
+        assert!(
+            matches!(&violations[0], GraphError::CycleDetected(names) if names == &vec!["B".to_string(), "C".to_string(), "E".to_string()])
+        );
+    }
+}
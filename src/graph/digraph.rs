@@ -12,24 +12,115 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::node::DiNode;
-use crate::{
-    algorithm::{isomorphism::GMGraph, topsort::TSortGraph},
-    error::GraphError,
+use super::{
+    attr::{Attributed, Attributes},
+    edge::{default_edge_data, EdgeData},
+    interner::{NodeId, NodeInterner},
+    listener::{GraphEvent, ListenerId, Listeners},
+    node::DiNode,
+    ungraph::{UnGraph, UnNode},
 };
+use super::{Directed, GraphBase, Ported, Weighted};
+use crate::error::GraphError;
+use indexmap::{IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::iter::FromIterator;
 
-#[derive(Debug, Eq, PartialEq, Deserialize, Serialize)]
+/// Iteration order of the nodes, neighbors and edges of a [`DiGraph`].
+///
+/// Either way the order is deterministic, so algorithms such as topsort and
+/// the matcher return the same results on every run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeOrder {
+    /// The order in which nodes and edges were first added.
+    #[default]
+    Insertion,
+    /// Sorted by node name.
+    Sorted,
+}
+
+/// How [`DiGraph::to_undirected`] combines the weights of two opposite arcs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightPolicy {
+    Min,
+    Max,
+    Sum,
+}
+impl WeightPolicy {
+    fn combine(self, a: i64, b: i64) -> i64 {
+        match self {
+            WeightPolicy::Min => a.min(b),
+            WeightPolicy::Max => a.max(b),
+            WeightPolicy::Sum => a + b,
+        }
+    }
+}
+
+/// A directed graph whose nodes are identified by name.
+///
+/// Self-loops are allowed: a self-loop is an ordinary edge whose source and
+/// target are the same node, so it adds one to both the in-degree and the
+/// out-degree of that node (two to its `all_degree`), and the node is its own
+/// predecessor, successor and neighbor.
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DiGraph {
     name: Option<String>,
-    nodes: HashMap<String, DiNode>,
+    nodes: IndexMap<String, DiNode>,
+    // edge data keyed by source and then target; edges without data are not stored
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    edges: IndexMap<String, IndexMap<String, EdgeData>>,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    attrs: Attributes,
+    // optional secondary index: node weight -> names of nodes with that weight
+    #[serde(skip)]
+    weight_index: Option<IndexMap<Option<String>, IndexSet<String>>>,
+    // not serialized: a deserialized graph keeps the order of its source
+    #[serde(skip)]
+    order: NodeOrder,
+    #[serde(skip)]
+    listeners: Listeners,
 }
 impl DiGraph {
     pub fn new(name: Option<String>) -> Self {
+        DiGraph::with_order(name, NodeOrder::default())
+    }
+
+    pub fn with_order(name: Option<String>, order: NodeOrder) -> Self {
         DiGraph {
             name,
-            nodes: HashMap::new(),
+            nodes: IndexMap::new(),
+            edges: IndexMap::new(),
+            attrs: Attributes::new(),
+            weight_index: None,
+            order,
+            listeners: Listeners::default(),
+        }
+    }
+
+    pub fn order(&self) -> NodeOrder {
+        self.order
+    }
+
+    /// Switch the iteration order. Switching to `Sorted` sorts the existing
+    /// nodes and edges; switching back keeps the current order for them.
+    pub fn set_order(&mut self, order: NodeOrder) {
+        self.order = order;
+        if order == NodeOrder::Sorted {
+            self.nodes.sort_keys();
+            for node in self.nodes.values_mut() {
+                node.sort_neighbors();
+            }
+            self.edges.sort_keys();
+            for targets in self.edges.values_mut() {
+                targets.sort_keys();
+            }
+            if let Some(index) = self.weight_index.as_mut() {
+                for names in index.values_mut() {
+                    names.sort();
+                }
+            }
         }
     }
 
@@ -45,55 +136,802 @@ impl DiGraph {
     }
 
     pub fn add_node(&mut self, node: DiNode) {
-        self.nodes.insert(node.get_name().clone(), node);
+        let name = node.get_name();
+        let old = self.nodes.get(node.name()).map(|old| old.get_weight());
+        self.reindex_weight(node.name(), old.clone(), node.get_weight());
+        match self.order {
+            NodeOrder::Insertion => self.nodes.insert(node.get_name(), node),
+            NodeOrder::Sorted => self.nodes.insert_sorted(node.get_name(), node).1,
+        };
+        if old.is_none() {
+            self.listeners.emit(GraphEvent::NodeAdded(name.as_str()));
+        }
+    }
+
+    /// Get the node with the given name for in-place insertion or modification,
+    /// looking it up only once.
+    pub fn node_entry(&mut self, name: &str) -> NodeEntry<'_> {
+        let index = self.nodes.get_index_of(name);
+        NodeEntry {
+            graph: self,
+            name: name.to_string(),
+            index,
+        }
+    }
+
+    /// Move a node in the weight index from its old weight, if it had one, to
+    /// its new weight.
+    fn reindex_weight(&mut self, name: &str, old: Option<Option<String>>, new: Option<String>) {
+        let index = match self.weight_index.as_mut() {
+            Some(index) => index,
+            None => return,
+        };
+        if let Some(names) = old.and_then(|old| index.get_mut(&old)) {
+            names.shift_remove(name);
+        }
+        let names = index.entry(new).or_default();
+        match self.order {
+            NodeOrder::Insertion => names.insert(name.to_string()),
+            NodeOrder::Sorted => names.insert_sorted(name.to_string()).1,
+        };
     }
 
     pub fn add_edge(&mut self, from: Option<&str>, to: Option<&str>) {
-        if from.is_some() {
+        if let Some(name) = from {
             // create a new node
-            let name = from.unwrap();
             if !self.contains_node(name) {
-                self.nodes
-                    .entry(name.to_string())
-                    .or_insert(DiNode::new(name, None));
+                self.add_node(DiNode::new(name, None));
             }
         }
 
-        if to.is_some() {
+        if let Some(name) = to {
             // create a new node
-            let name = to.unwrap();
             if !self.contains_node(name) {
-                self.nodes
-                    .entry(name.to_string())
-                    .or_insert(DiNode::new(name, None));
+                self.add_node(DiNode::new(name, None));
             }
         }
 
-        if from.is_some() && to.is_some() {
+        if let (Some(from), Some(to)) = (from, to) {
             // update predecessors and successros of new nodes
 
-            let source = self.nodes.get_mut(from.unwrap()).unwrap();
-            source.add_successor(to.unwrap());
+            let sorted = self.order == NodeOrder::Sorted;
+
+            let source = self.nodes.get_mut(from).unwrap();
+            if source.has_successor(to) {
+                return;
+            }
+            if sorted {
+                source.add_successor_sorted(to);
+            } else {
+                source.add_successor(to);
+            }
+
+            let target = self.nodes.get_mut(to).unwrap();
+            if sorted {
+                target.add_predecessor_sorted(from);
+            } else {
+                target.add_predecessor(from);
+            }
+            self.listeners.emit(GraphEvent::EdgeAdded(from, to));
+        }
+    }
+
+    pub fn add_weighted_edge(&mut self, from: &str, to: &str, weight: i64) {
+        self.add_edge(Some(from), Some(to));
+        self.edges
+            .entry(from.to_string())
+            .or_default()
+            .entry(to.to_string())
+            .or_default()
+            .set_weight(Some(weight));
+    }
+
+    /// Add many nodes at once, with room for them reserved up front. Unlike
+    /// `add_node`, a node whose name is already in the graph is skipped, so the
+    /// existing node keeps its edges.
+    pub fn add_nodes_from<I: IntoIterator<Item = DiNode>>(&mut self, nodes: I) {
+        let nodes = nodes.into_iter();
+        self.nodes.reserve(nodes.size_hint().0);
+        self.batch(|graph| {
+            for node in nodes {
+                if !graph.nodes.contains_key(node.name()) {
+                    graph.add_node(node);
+                }
+            }
+        });
+    }
+
+    /// Add many edges at once, creating missing nodes, with room reserved up
+    /// front from the size hint of `edges`. Unweighted edges are kept in the
+    /// adjacency of their nodes, so the room is for new nodes.
+    pub fn add_edges_from<'a, I: IntoIterator<Item = (&'a str, &'a str)>>(&mut self, edges: I) {
+        let edges = edges.into_iter();
+        self.nodes.reserve(edges.size_hint().0);
+        self.batch(|graph| {
+            for (from, to) in edges {
+                graph.add_edge(Some(from), Some(to));
+            }
+        });
+    }
+
+    /// Add many `(from, to, weight)` edges at once, creating missing nodes,
+    /// with room for new nodes and edge weights reserved up front from the
+    /// size hint of `edges`.
+    pub fn add_weighted_edges_from<'a, I>(&mut self, edges: I)
+    where
+        I: IntoIterator<Item = (&'a str, &'a str, i64)>,
+    {
+        let edges = edges.into_iter();
+        let hint = edges.size_hint().0;
+        self.nodes.reserve(hint);
+        self.edges.reserve(hint);
+        self.batch(|graph| {
+            for (from, to, weight) in edges {
+                graph.add_weighted_edge(from, to, weight);
+            }
+        });
+    }
+
+    /// Remove many nodes and their edges at once, returning how many existed.
+    pub fn remove_nodes_from<'a, I: IntoIterator<Item = &'a str>>(&mut self, names: I) -> usize {
+        names
+            .into_iter()
+            .filter(|name| self.remove_node(name).is_some())
+            .count()
+    }
+
+    /// Run a bulk insertion in insertion order, then sort once at the end if
+    /// the graph is sorted, instead of on every insertion.
+    fn batch(&mut self, insert: impl FnOnce(&mut Self)) {
+        let order = self.order;
+        self.order = NodeOrder::Insertion;
+        insert(self);
+        self.set_order(order);
+    }
+
+    /// Remove the edge from `from` to `to` with its data, returning whether it existed.
+    pub fn remove_edge(&mut self, from: &str, to: &str) -> bool {
+        if !self.contains_edge(from, to) {
+            return false;
+        }
+        self.nodes.get_mut(from).unwrap().remove_successor(to);
+        self.nodes.get_mut(to).unwrap().remove_predecessor(from);
+        self.take_edge_data(from, to);
+        self.listeners.emit(GraphEvent::EdgeRemoved(from, to));
+        true
+    }
+
+    /// Remove a node and its edges, returning the node if it existed.
+    pub fn remove_node(&mut self, name: &str) -> Option<DiNode> {
+        let node = self.nodes.shift_remove(name)?;
+        for succ in node.successor_names() {
+            if succ != name {
+                self.nodes.get_mut(succ).unwrap().remove_predecessor(name);
+            }
+            self.listeners.emit(GraphEvent::EdgeRemoved(name, succ));
+        }
+        for pred in node.predecessor_names() {
+            if pred != name {
+                self.nodes.get_mut(pred).unwrap().remove_successor(name);
+                self.take_edge_data(pred, name);
+                self.listeners.emit(GraphEvent::EdgeRemoved(pred, name));
+            }
+        }
+        self.edges.shift_remove(name);
+        if let Some(index) = self.weight_index.as_mut() {
+            if let Some(names) = index.get_mut(&node.get_weight()) {
+                names.shift_remove(name);
+            }
+        }
+        self.listeners.emit(GraphEvent::NodeRemoved(name));
+        Some(node)
+    }
+
+    /// Merge `to` into `from` along the edge between them.
+    ///
+    /// Edges between the two nodes disappear, and every other edge of `to`,
+    /// including a self-loop, moves to `from` with its data. Where `from`
+    /// already has the same edge, that edge and its data are kept. `from` keeps
+    /// its weight and attributes. Contracting a self-loop just removes it.
+    pub fn contract_edge(&mut self, from: &str, to: &str) -> Result<(), GraphError> {
+        if !self.contains_edge(from, to) {
+            return Err(GraphError::NotFoundEdge(from.to_string(), to.to_string()));
+        }
+        if from == to {
+            self.remove_edge(from, to);
+            return Ok(());
+        }
+
+        let node = self.nodes.get(to).unwrap();
+        let mut moved = Vec::new();
+        for succ in node.successor_names() {
+            if succ != from {
+                let target = if succ == to { from } else { succ };
+                moved.push((from.to_string(), target.to_string(), to, succ));
+            }
+        }
+        for pred in node.predecessor_names() {
+            if pred != from && pred != to {
+                moved.push((pred.to_string(), from.to_string(), pred, to));
+            }
+        }
+        let moved: Vec<(String, String, Option<EdgeData>)> = moved
+            .into_iter()
+            .map(|(a, b, old_from, old_to)| (a, b, self.get_edge_data(old_from, old_to).cloned()))
+            .collect();
+
+        self.remove_node(to);
+        for (a, b, data) in moved {
+            if self.contains_edge(a.as_str(), b.as_str()) {
+                continue;
+            }
+            self.add_edge(Some(a.as_str()), Some(b.as_str()));
+            if let Some(data) = data {
+                *self.get_edge_data_mut(a.as_str(), b.as_str()).unwrap() = data;
+            }
+        }
+        Ok(())
+    }
+
+    /// Split `name` into `in_part`, which takes over its incoming edges, and
+    /// `out_part`, which takes over its outgoing edges, joined by an edge from
+    /// `in_part` to `out_part`, as when modeling node capacities in a flow
+    /// network. A self-loop becomes an edge from `out_part` back to `in_part`.
+    ///
+    /// Edges keep their data. Both parts get the weight and attributes of the
+    /// node, and `in_part` its ports. Either part may reuse `name`; otherwise
+    /// fails with `GraphError::DuplicateNode` if a part is already in the graph
+    /// or both parts have the same name.
+    pub fn split_node(
+        &mut self,
+        name: &str,
+        in_part: &str,
+        out_part: &str,
+    ) -> Result<(), GraphError> {
+        if !self.contains_node(name) {
+            return Err(GraphError::NotFoundNode(name.to_string()));
+        }
+        if in_part == out_part {
+            return Err(GraphError::DuplicateNode(out_part.to_string()));
+        }
+        for part in [in_part, out_part] {
+            if part != name && self.contains_node(part) {
+                return Err(GraphError::DuplicateNode(part.to_string()));
+            }
+        }
+
+        let node = self.nodes.get(name).unwrap();
+        let mut moved = Vec::new();
+        for pred in node.predecessor_names() {
+            let source = if pred == name { out_part } else { pred };
+            moved.push((source.to_string(), in_part, pred, name));
+        }
+        for succ in node.successor_names() {
+            if succ != name {
+                moved.push((out_part.to_string(), succ, name, succ));
+            }
+        }
+        let moved: Vec<(String, String, Option<EdgeData>)> = moved
+            .into_iter()
+            .map(|(a, b, old_from, old_to)| {
+                let data = self.get_edge_data(old_from, old_to).cloned();
+                (a, b.to_string(), data)
+            })
+            .collect();
+
+        let node = self.remove_node(name).unwrap();
+        for part in [in_part, out_part] {
+            let mut copy = DiNode::new(part, node.get_weight());
+            *copy.attrs_mut() = node.attrs().clone();
+            if part == in_part {
+                copy.set_ports(node.ports().iter().cloned());
+            }
+            self.add_node(copy);
+        }
+        self.add_edge(Some(in_part), Some(out_part));
+        for (a, b, data) in moved {
+            self.add_edge(Some(a.as_str()), Some(b.as_str()));
+            if let Some(data) = data {
+                *self.get_edge_data_mut(a.as_str(), b.as_str()).unwrap() = data;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the graph is internally consistent, which it always is unless
+    /// it was deserialized from edited data, or report every problem found:
+    ///
+    /// * `MisnamedNode` for a node stored under a name other than its own,
+    /// * `DanglingEdge` for a predecessor or successor that is not a node,
+    /// * `OneSidedEdge` for an edge listed by only one of its endpoints,
+    /// * `NotFoundEdge` for edge data without an edge.
+    pub fn validate(&self) -> Result<(), Vec<GraphError>> {
+        let mut problems = Vec::new();
+        for (name, node) in self.nodes.iter() {
+            if node.name() != name {
+                problems.push(GraphError::MisnamedNode(name.clone(), node.get_name()));
+            }
+            for succ in node.successor_names() {
+                match self.nodes.get(succ) {
+                    None => problems.push(GraphError::DanglingEdge(name.clone(), succ.to_string())),
+                    Some(target) if !target.has_predecessor(name) => {
+                        problems.push(GraphError::OneSidedEdge(name.clone(), succ.to_string()))
+                    }
+                    _ => {}
+                }
+            }
+            for pred in node.predecessor_names() {
+                match self.nodes.get(pred) {
+                    None => problems.push(GraphError::DanglingEdge(pred.to_string(), name.clone())),
+                    Some(source) if !source.has_successor(name) => {
+                        problems.push(GraphError::OneSidedEdge(pred.to_string(), name.clone()))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for (from, targets) in self.edges.iter() {
+            for to in targets.keys() {
+                if !self.recorded_edge(from, to) {
+                    problems.push(GraphError::NotFoundEdge(from.clone(), to.clone()));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Fix every problem reported by [`DiGraph::validate`] and return them.
+    ///
+    /// A misnamed node takes the name it is stored under, dangling names are
+    /// dropped, one-sided edges are completed and edge data without an edge is
+    /// dropped.
+    pub fn repair(&mut self) -> Vec<GraphError> {
+        let problems = match self.validate() {
+            Ok(()) => return Vec::new(),
+            Err(problems) => problems,
+        };
+
+        let mut edges = Vec::new();
+        for (name, node) in self.nodes.iter() {
+            for succ in node.successor_names() {
+                edges.push((name.clone(), succ.to_string()));
+            }
+            for pred in node.predecessor_names() {
+                edges.push((pred.to_string(), name.clone()));
+            }
+        }
+        let names: HashSet<String> = self.nodes.keys().cloned().collect();
+        for (name, node) in self.nodes.iter_mut() {
+            node.set_name(name);
+            let dangling: Vec<String> = node
+                .successor_names()
+                .chain(node.predecessor_names())
+                .filter(|other| !names.contains(*other))
+                .map(String::from)
+                .collect();
+            for other in dangling.iter() {
+                node.remove_successor(other);
+                node.remove_predecessor(other);
+            }
+        }
+        for (from, to) in edges.iter() {
+            if names.contains(from) && names.contains(to) {
+                self.nodes.get_mut(from).unwrap().add_successor(to);
+                self.nodes.get_mut(to).unwrap().add_predecessor(from);
+            }
+        }
+
+        let stray: Vec<(String, String)> = self
+            .edges
+            .iter()
+            .flat_map(|(from, targets)| targets.keys().map(move |to| (from.clone(), to.clone())))
+            .filter(|(from, to)| !self.contains_edge(from, to))
+            .collect();
+        for (from, to) in stray.iter() {
+            self.take_edge_data(from, to);
+        }
+
+        if self.order == NodeOrder::Sorted {
+            self.set_order(NodeOrder::Sorted);
+        }
+        if self.weight_index.is_some() {
+            self.enable_weight_index();
+        }
+        problems
+    }
+
+    /// Whether either endpoint lists the edge from `from` to `to`.
+    fn recorded_edge(&self, from: &str, to: &str) -> bool {
+        self.contains_edge(from, to)
+            || self
+                .nodes
+                .get(to)
+                .is_some_and(|node| node.has_predecessor(from))
+    }
+
+    /// Convert to an undirected graph with an edge wherever there is an arc in
+    /// either direction.
+    ///
+    /// The weights of two opposite arcs are combined with `policy`; if only one
+    /// of them has a weight, the edge gets that weight. Their attributes are
+    /// merged, the arc that comes first in edge order winning on conflicts. The
+    /// name, attributes and nodes are copied as they are.
+    pub fn to_undirected(&self, policy: WeightPolicy) -> UnGraph {
+        let mut graph = UnGraph::new(self.name.clone());
+        *graph.attrs_mut() = self.attrs.clone();
+        for node in self.nodes.values() {
+            let mut copy = UnNode::new(node.name(), node.get_weight());
+            *copy.attrs_mut() = node.attrs().clone();
+            graph.add_node(copy);
+        }
+        for (from, to) in self.edges() {
+            let reciprocal = graph.contains_edge(from, to);
+            graph.add_edge(from, to);
+            let data = match self.get_edge_data(from, to) {
+                Some(data) => data,
+                None => continue,
+            };
+            let merged = graph.get_edge_data_mut(from, to).unwrap();
+            for (key, value) in data.attrs().iter() {
+                merged
+                    .attrs_mut()
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
+            }
+            let weight = match (merged.get_weight(), data.get_weight()) {
+                (Some(a), Some(b)) if reciprocal => Some(policy.combine(a, b)),
+                (a, b) => a.or(b),
+            };
+            merged.set_weight(weight);
+        }
+        graph
+    }
+
+    fn take_edge_data(&mut self, from: &str, to: &str) -> Option<EdgeData> {
+        let targets = self.edges.get_mut(from)?;
+        let data = targets.shift_remove(to);
+        if targets.is_empty() {
+            self.edges.shift_remove(from);
+        }
+        data
+    }
+
+    pub fn get_edge_data(&self, from: &str, to: &str) -> Option<&EdgeData> {
+        self.edges.get(from).and_then(|targets| targets.get(to))
+    }
+
+    /// Mutable access to the data of an existing edge, creating it on first use.
+    pub fn get_edge_data_mut(&mut self, from: &str, to: &str) -> Option<&mut EdgeData> {
+        if !self.contains_edge(from, to) {
+            return None;
+        }
+        Some(
+            self.edges
+                .entry(from.to_string())
+                .or_default()
+                .entry(to.to_string())
+                .or_default(),
+        )
+    }
 
-            let target = self.nodes.get_mut(to.unwrap()).unwrap();
-            target.add_predecessor(from.unwrap());
+    pub fn get_edge_weight(&self, from: &str, to: &str) -> Option<i64> {
+        self.get_edge_data(from, to)
+            .and_then(|data| data.get_weight())
+    }
+
+    /// Add an edge feeding input port `port` of `to`, creating missing nodes.
+    /// The port of an existing edge is replaced.
+    ///
+    /// Fails with `GraphError::PortInUse` if another edge already feeds the
+    /// port, leaving the graph unchanged.
+    pub fn add_port_edge(&mut self, from: &str, to: &str, port: usize) -> Result<(), GraphError> {
+        if let Some(node) = self.get_node(to) {
+            let feeder = node
+                .predecessor_names()
+                .find(|&pred| pred != from && self.get_edge_port(pred, to) == Some(port));
+            if let Some(feeder) = feeder {
+                return Err(GraphError::PortInUse(
+                    to.to_string(),
+                    port,
+                    feeder.to_string(),
+                ));
+            }
         }
+        self.add_edge(Some(from), Some(to));
+        self.get_edge_data_mut(from, to)
+            .unwrap()
+            .set_port(Some(port));
+        Ok(())
+    }
+
+    /// Add an edge feeding the input port of `to` named `port`, creating
+    /// `from` if it is missing.
+    ///
+    /// Fails with `GraphError::NotFoundNode` if `to` is missing, with
+    /// `GraphError::NotFoundPort` if `to` has no such port, or with
+    /// `GraphError::PortInUse` if another edge already feeds it.
+    pub fn add_named_port_edge(
+        &mut self,
+        from: &str,
+        to: &str,
+        port: &str,
+    ) -> Result<(), GraphError> {
+        let index = self
+            .get_node(to)
+            .ok_or_else(|| GraphError::NotFoundNode(to.to_string()))?
+            .port_index(port)
+            .ok_or_else(|| GraphError::NotFoundPort(to.to_string(), port.to_string()))?;
+        self.add_port_edge(from, to, index)
+    }
+
+    pub fn get_edge_port(&self, from: &str, to: &str) -> Option<usize> {
+        self.get_edge_data(from, to)
+            .and_then(|data| data.get_port())
+    }
+
+    /// The producers of a node's operands, indexed by port. The list covers
+    /// every declared port and every port in use; a port nothing feeds is
+    /// `None`. Edges without a port are left out. Returns `None` if the node
+    /// is missing. `add_port_edge` keeps a port to one edge; should edited edge
+    /// data feed a port twice, the last predecessor wins.
+    pub fn operands(&self, name: &str) -> Option<Vec<Option<&str>>> {
+        let node = self.get_node(name)?;
+        let ports: Vec<(usize, &str)> = node
+            .predecessor_names()
+            .filter_map(|pred| self.get_edge_port(pred, name).map(|port| (port, pred)))
+            .collect();
+        let len = ports
+            .iter()
+            .map(|&(port, _)| port + 1)
+            .max()
+            .unwrap_or(0)
+            .max(node.ports().len());
+        let mut operands = vec![None; len];
+        for (port, pred) in ports {
+            operands[port] = Some(pred);
+        }
+        Some(operands)
+    }
+
+    /// Iterate over all edges as `(source, target)` pairs.
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.nodes.values().flat_map(|node| {
+            let from = node.name();
+            node.successor_names().map(move |to| (from, to))
+        })
+    }
+
+    /// Iterate over all edges as `(source, target, weight)` triples.
+    pub fn weighted_edges(&self) -> impl Iterator<Item = (&str, &str, Option<i64>)> {
+        self.edges()
+            .map(move |(from, to)| (from, to, self.get_edge_weight(from, to)))
+    }
+
+    /// Iterate over the outgoing edges of a node as `(source, target, data)` triples.
+    pub fn out_edges(
+        &self,
+        name: &str,
+    ) -> Result<impl Iterator<Item = (&str, &str, &EdgeData)>, GraphError> {
+        let node = self
+            .nodes
+            .get(name)
+            .ok_or_else(|| GraphError::NotFoundNode(String::from(name)))?;
+        let from = node.name();
+        Ok(node.successor_names().map(move |to| {
+            let data = self.get_edge_data(from, to).unwrap_or(default_edge_data());
+            (from, to, data)
+        }))
+    }
+
+    /// Iterate over the incoming edges of a node as `(source, target, data)` triples.
+    pub fn in_edges(
+        &self,
+        name: &str,
+    ) -> Result<impl Iterator<Item = (&str, &str, &EdgeData)>, GraphError> {
+        let node = self
+            .nodes
+            .get(name)
+            .ok_or_else(|| GraphError::NotFoundNode(String::from(name)))?;
+        let to = node.name();
+        Ok(node.predecessor_names().map(move |from| {
+            let data = self.get_edge_data(from, to).unwrap_or(default_edge_data());
+            (from, to, data)
+        }))
+    }
+
+    /// Total number of edges in the graph.
+    pub fn edge_count(&self) -> usize {
+        self.nodes.values().map(|node| node.out_degree()).sum()
     }
 
     pub fn get_node(&self, name: &str) -> Option<&DiNode> {
         self.nodes.get(name)
     }
 
+    /// Mutable access to a node. Changing its weight this way bypasses the
+    /// weight index; use [`DiGraph::node_entry`] for that.
     pub fn get_node_mut(&mut self, name: &str) -> Option<&mut DiNode> {
         self.nodes.get_mut(name)
     }
 
+    /// The id of a node, its position in graph order, as in
+    /// [`DiGraph::interner`]. Adding or removing nodes may shift ids.
+    pub fn node_id(&self, name: &str) -> Option<NodeId> {
+        self.nodes.get_index_of(name).map(NodeId)
+    }
+
+    pub fn node_by_id(&self, id: NodeId) -> Option<&DiNode> {
+        self.nodes.get_index(id.index()).map(|(_, node)| node)
+    }
+
+    /// Intern the node names, with id-based adjacency lists for hot loops.
+    pub fn interner(&self) -> NodeInterner {
+        NodeInterner::new(self)
+    }
+
     pub fn get_nodes(&self) -> Vec<String> {
-        let mut names = Vec::new();
-        for name in self.nodes.keys() {
-            names.push(name.clone());
+        self.node_names().map(String::from).collect()
+    }
+
+    /// Iterate over all nodes without allocating.
+    pub fn nodes(&self) -> impl Iterator<Item = &DiNode> {
+        self.nodes.values()
+    }
+
+    /// Iterate over all node names without allocating.
+    pub fn node_names(&self) -> impl Iterator<Item = &str> {
+        self.nodes.keys().map(|name| name.as_str())
+    }
+
+    /// Run `edit` on a staged copy of the graph, and replace the graph with it
+    /// only if `edit` returns `Ok`; on `Err` the graph is left untouched.
+    ///
+    /// Listeners are not called while editing. On commit they see the net
+    /// change instead: removed edges, removed nodes, added nodes and added
+    /// edges, each in graph order.
+    pub fn transaction<T, E>(
+        &mut self,
+        edit: impl FnOnce(&mut DiGraph) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut staged = self.clone();
+        let value = edit(&mut staged)?;
+        staged.listeners = std::mem::take(&mut self.listeners);
+        let old = std::mem::replace(self, staged);
+        self.emit_changes_since(&old);
+        Ok(value)
+    }
+
+    fn emit_changes_since(&mut self, old: &DiGraph) {
+        if self.listeners.is_empty() {
+            return;
+        }
+        let (nodes, listeners) = (&self.nodes, &mut self.listeners);
+        let has_edge =
+            |from: &str, to: &str| nodes.get(from).is_some_and(|node| node.has_successor(to));
+        for (from, to) in old.edges() {
+            if !has_edge(from, to) {
+                listeners.emit(GraphEvent::EdgeRemoved(from, to));
+            }
+        }
+        for name in old.node_names() {
+            if !nodes.contains_key(name) {
+                listeners.emit(GraphEvent::NodeRemoved(name));
+            }
+        }
+        for name in nodes.keys() {
+            if !old.contains_node(name) {
+                listeners.emit(GraphEvent::NodeAdded(name));
+            }
+        }
+        for node in nodes.values() {
+            for to in node.successor_names() {
+                if !old.contains_edge(node.name(), to) {
+                    listeners.emit(GraphEvent::EdgeAdded(node.name(), to));
+                }
+            }
+        }
+    }
+
+    /// Register a callback for every change to the nodes and edges made
+    /// through the mutation methods, including bulk ones and those of
+    /// [`DiGraph::node_entry`]. Adding a node or edge that already exists is
+    /// not a change. Listeners are neither serialized nor cloned.
+    pub fn subscribe(
+        &mut self,
+        listener: impl FnMut(&GraphEvent<'_>) + Send + Sync + 'static,
+    ) -> ListenerId {
+        self.listeners.add(Box::new(listener))
+    }
+
+    /// Remove a listener, returning whether it was registered.
+    pub fn unsubscribe(&mut self, id: ListenerId) -> bool {
+        self.listeners.remove(id)
+    }
+
+    pub fn on_node_added(
+        &mut self,
+        mut listener: impl FnMut(&str) + Send + Sync + 'static,
+    ) -> ListenerId {
+        self.subscribe(move |event| {
+            if let GraphEvent::NodeAdded(name) = event {
+                listener(name);
+            }
+        })
+    }
+
+    pub fn on_node_removed(
+        &mut self,
+        mut listener: impl FnMut(&str) + Send + Sync + 'static,
+    ) -> ListenerId {
+        self.subscribe(move |event| {
+            if let GraphEvent::NodeRemoved(name) = event {
+                listener(name);
+            }
+        })
+    }
+
+    pub fn on_edge_added(
+        &mut self,
+        mut listener: impl FnMut(&str, &str) + Send + Sync + 'static,
+    ) -> ListenerId {
+        self.subscribe(move |event| {
+            if let GraphEvent::EdgeAdded(from, to) = event {
+                listener(from, to);
+            }
+        })
+    }
+
+    pub fn on_edge_removed(
+        &mut self,
+        mut listener: impl FnMut(&str, &str) + Send + Sync + 'static,
+    ) -> ListenerId {
+        self.subscribe(move |event| {
+            if let GraphEvent::EdgeRemoved(from, to) = event {
+                listener(from, to);
+            }
+        })
+    }
+
+    /// Build an index from node weight to node names, kept up to date by later
+    /// mutations. The index is not serialized.
+    pub fn enable_weight_index(&mut self) {
+        let mut index: IndexMap<Option<String>, IndexSet<String>> = IndexMap::new();
+        for node in self.nodes.values() {
+            index
+                .entry(node.get_weight())
+                .or_default()
+                .insert(node.get_name());
+        }
+        self.weight_index = Some(index);
+    }
+
+    pub fn disable_weight_index(&mut self) {
+        self.weight_index = None;
+    }
+
+    /// Nodes with the given weight, answered from the weight index if enabled.
+    pub fn nodes_with_weight(&self, weight: Option<&str>) -> Vec<&DiNode> {
+        match self.weight_index.as_ref() {
+            Some(index) => index
+                .get(&weight.map(String::from))
+                .map(|names| {
+                    names
+                        .iter()
+                        .map(|name| self.nodes.get(name).unwrap())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            None => self
+                .nodes
+                .values()
+                .filter(|node| node.get_weight().as_deref() == weight)
+                .collect(),
         }
-        names
     }
 
     pub fn node_count(&self) -> usize {
@@ -108,7 +946,7 @@ impl DiGraph {
         let node = self
             .nodes
             .get(name)
-            .expect(format!("Not found node with name: {}", name).as_str());
+            .unwrap_or_else(|| panic!("Not found node with name: {}", name));
         Ok(node
             .get_predecessors()
             .iter()
@@ -123,7 +961,7 @@ impl DiGraph {
 
         let node = self
             .get_node(name)
-            .expect(format!("Not found node with name: {}", name).as_str());
+            .unwrap_or_else(|| panic!("Not found node with name: {}", name));
         Ok(node
             .get_successors()
             .iter()
@@ -131,6 +969,19 @@ impl DiGraph {
             .collect())
     }
 
+    /// Union of the predecessors and successors of a node.
+    pub fn neighbors(&self, name: &str) -> Result<Vec<&DiNode>, GraphError> {
+        let node = self
+            .nodes
+            .get(name)
+            .ok_or_else(|| GraphError::NotFoundNode(String::from(name)))?;
+        Ok(node
+            .get_neighbors()
+            .iter()
+            .map(|name| self.nodes.get(name.as_str()).unwrap())
+            .collect())
+    }
+
     pub fn in_degree(&self, name: &str) -> Result<usize, GraphError> {
         if !self.nodes.contains_key(name) {
             return Err(GraphError::NotFoundNode(String::from(name)));
@@ -149,101 +1000,222 @@ impl DiGraph {
         Ok(node.out_degree())
     }
 
-    pub fn edge_count(&self, from: &str, to: &str) -> usize {
-        let mut count = 0 as usize;
-        let result_succ = self.successors(from);
-        match result_succ {
-            Ok(successor_vec) => {
-                for succ in successor_vec {
-                    if succ.get_name() == to {
-                        count += 1;
-                    }
-                }
-            }
-            Err(err) => panic!("{}", err),
+    /// Sum of in-degree and out-degree; a self-loop counts twice.
+    pub fn all_degree(&self, name: &str) -> Result<usize, GraphError> {
+        if !self.nodes.contains_key(name) {
+            return Err(GraphError::NotFoundNode(String::from(name)));
         }
-        count
+
+        let node = self.nodes.get(name).unwrap();
+        Ok(node.all_degree())
+    }
+
+    /// Iterate over `(name, all_degree)` for every node.
+    pub fn degrees(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.nodes
+            .values()
+            .map(|node| (node.name(), node.all_degree()))
+    }
+
+    /// Iterate over `(name, in_degree)` for every node.
+    pub fn in_degrees(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.nodes
+            .values()
+            .map(|node| (node.name(), node.in_degree()))
+    }
+
+    /// Iterate over `(name, out_degree)` for every node.
+    pub fn out_degrees(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.nodes
+            .values()
+            .map(|node| (node.name(), node.out_degree()))
+    }
+
+    /// The node with the largest `all_degree` and that degree, the first in
+    /// graph order on ties, or `None` for an empty graph.
+    pub fn max_degree_node(&self) -> Option<(&str, usize)> {
+        self.degrees()
+            .reduce(|best, cur| if cur.1 > best.1 { cur } else { best })
+    }
+
+    /// The node with the smallest `all_degree` and that degree, the first in
+    /// graph order on ties, or `None` for an empty graph.
+    pub fn min_degree_node(&self) -> Option<(&str, usize)> {
+        self.degrees()
+            .reduce(|best, cur| if cur.1 < best.1 { cur } else { best })
+    }
+
+    /// Number of edges from `from` to `to`, 0 if either node is missing.
+    pub fn edge_count_between(&self, from: &str, to: &str) -> usize {
+        self.get_node(from).map_or(0, |node| {
+            node.successor_names().filter(|&succ| succ == to).count()
+        })
     }
 
     pub fn contains_node(&self, name: &str) -> bool {
         self.nodes.contains_key(name)
     }
-}
-impl GMGraph for DiGraph {
-    type Node = DiNode;
 
-    fn node_count(&self) -> usize {
-        self.nodes.len()
+    /// Check whether there is an edge from `from` to `to`.
+    pub fn contains_edge(&self, from: &str, to: &str) -> bool {
+        self.nodes
+            .get(from)
+            .is_some_and(|node| node.has_successor(to))
+    }
+}
+/// A node of a [`DiGraph`] that may not exist yet, from [`DiGraph::node_entry`].
+pub struct NodeEntry<'a> {
+    graph: &'a mut DiGraph,
+    name: String,
+    index: Option<usize>,
+}
+impl<'a> NodeEntry<'a> {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
     }
 
-    fn edge_count(&self, from: &str, to: &str) -> usize {
-        let mut count = 0 as usize;
-        let result_succ = self.successors(from);
-        match result_succ {
-            Ok(successor_vec) => {
-                for succ in successor_vec {
-                    if succ.get_name() == to {
-                        count += 1;
-                    }
-                }
+    /// Modify the node if it exists. A changed weight is kept up to date in
+    /// the weight index.
+    pub fn and_modify(self, f: impl FnOnce(&mut DiNode)) -> Self {
+        if let Some(index) = self.index {
+            let node = self.graph.nodes.get_index_mut(index).unwrap().1;
+            let old = node.get_weight();
+            f(node);
+            let new = node.get_weight();
+            if old != new {
+                self.graph
+                    .reindex_weight(self.name.as_str(), Some(old), new);
             }
-            Err(err) => panic!("{}", err),
         }
-        count
+        self
     }
 
-    fn get_node(&self, name: &str) -> Option<&DiNode> {
-        self.nodes.get(name)
+    /// The node, inserted with the given weight if it does not exist.
+    /// Changing its weight through the returned reference bypasses the weight
+    /// index, as with [`DiGraph::get_node_mut`]; use
+    /// [`NodeEntry::and_modify`] for that.
+    pub fn or_insert(self, weight: Option<String>) -> &'a mut DiNode {
+        self.or_insert_with(|| weight)
     }
 
-    fn get_nodes(&self) -> Vec<String> {
-        let mut names = Vec::new();
-        for name in self.nodes.keys() {
-            names.push(name.clone());
-        }
-        names
+    /// The node, inserted with the weight returned by `weight` if it does not
+    /// exist. Like [`NodeEntry::or_insert`], changing its weight through the
+    /// returned reference bypasses the weight index.
+    pub fn or_insert_with(self, weight: impl FnOnce() -> Option<String>) -> &'a mut DiNode {
+        let index = match self.index {
+            Some(index) => index,
+            None => {
+                self.graph
+                    .add_node(DiNode::new(self.name.as_str(), weight()));
+                self.graph.nodes.get_index_of(self.name.as_str()).unwrap()
+            }
+        };
+        self.graph.nodes.get_index_mut(index).unwrap().1
     }
+}
 
-    fn predecessors(&self, name: &str) -> Result<Vec<&DiNode>, GraphError> {
-        if !self.nodes.contains_key(name) {
-            return Err(GraphError::NotFoundNode(String::from(name)));
-        }
-
-        let node = self
-            .nodes
-            .get(name)
-            .expect(format!("Not found node with name: {}", name).as_str());
-        Ok(node
-            .get_predecessors()
-            .iter()
-            .map(|name| self.nodes.get(name.as_str()).unwrap())
-            .collect())
+impl PartialEq for DiGraph {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.nodes == other.nodes
+            && self.edges == other.edges
+            && self.attrs == other.attrs
+    }
+}
+impl Eq for DiGraph {}
+impl Attributed for DiGraph {
+    fn attrs(&self) -> &Attributes {
+        &self.attrs
     }
 
-    fn successors(&self, name: &str) -> Result<Vec<&DiNode>, GraphError> {
-        if !self.nodes.contains_key(name) {
-            return Err(GraphError::NotFoundNode(String::from(name)));
+    fn attrs_mut(&mut self) -> &mut Attributes {
+        &mut self.attrs
+    }
+}
+impl fmt::Display for DiGraph {
+    /// One line per node in name order, e.g. `A (weight) -> [B: 3, C]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = &self.name {
+            writeln!(f, "{}:", name)?;
         }
 
-        let node = GMGraph::get_node(self, name)
-            .expect(format!("Not found node with name: {}", name).as_str());
-        Ok(node
-            .get_successors()
-            .iter()
-            .map(|name| self.nodes.get(name.as_str()).unwrap())
-            .collect())
+        let mut names = self.get_nodes();
+        names.sort();
+        for name in names.iter() {
+            let node = self.nodes.get(name).unwrap();
+            let mut successors = node.get_successors();
+            successors.sort();
+            let successors: Vec<String> = successors
+                .into_iter()
+                .map(|succ| match self.get_edge_weight(name, succ.as_str()) {
+                    Some(weight) => format!("{}: {}", succ, weight),
+                    None => succ,
+                })
+                .collect();
+            writeln!(f, "{} -> [{}]", node, successors.join(", "))?;
+        }
+        Ok(())
+    }
+}
+impl<'a> FromIterator<(&'a str, &'a str)> for DiGraph {
+    fn from_iter<I: IntoIterator<Item = (&'a str, &'a str)>>(iter: I) -> Self {
+        let mut graph = DiGraph::new(None);
+        for (from, to) in iter {
+            graph.add_edge(Some(from), Some(to));
+        }
+        graph
+    }
+}
+impl Extend<(String, String)> for DiGraph {
+    fn extend<I: IntoIterator<Item = (String, String)>>(&mut self, iter: I) {
+        for (from, to) in iter {
+            self.add_edge(Some(from.as_str()), Some(to.as_str()));
+        }
     }
 }
-impl TSortGraph for DiGraph {
+impl GraphBase for DiGraph {
     type Node = DiNode;
 
-    fn get_nodes(&self) -> Vec<&DiNode> {
-        self.nodes.values().map(|x| x).collect()
+    fn node_count(&self) -> usize {
+        self.nodes.len()
     }
 
-    fn get_node(&self, name: &str) -> Option<&DiNode> {
+    fn node(&self, name: &str) -> Option<&DiNode> {
         self.nodes.get(name)
     }
+
+    fn nodes(&self) -> impl Iterator<Item = &DiNode> {
+        self.nodes.values()
+    }
+
+    fn node_names(&self) -> impl Iterator<Item = &str> {
+        self.nodes.keys().map(|name| name.as_str())
+    }
+
+    fn semantic_candidates(&self, other: &DiNode) -> Option<Vec<String>> {
+        // DiNode::semantic_equal compares weights only
+        Some(
+            self.nodes_with_weight(other.get_weight().as_deref())
+                .iter()
+                .map(|node| node.get_name())
+                .collect(),
+        )
+    }
+}
+impl Directed for DiGraph {
+    fn contains_edge(&self, from: &str, to: &str) -> bool {
+        DiGraph::contains_edge(self, from, to)
+    }
+}
+impl Weighted for DiGraph {
+    fn edge_weight(&self, from: &str, to: &str) -> Option<i64> {
+        self.get_edge_weight(from, to)
+    }
+}
+impl Ported for DiGraph {
+    fn edge_port(&self, from: &str, to: &str) -> Option<usize> {
+        self.get_edge_port(from, to)
+    }
 }
 
 #[cfg(test)]
@@ -271,4 +1243,588 @@ mod tests {
 
         assert_eq!(g, actual);
     }
+
+    #[test]
+    fn test_digraph_display() {
+        let mut g = DiGraph::new(Some("g".to_string()));
+        g.add_node(DiNode::new("A", Some("a".to_string())));
+        g.add_edge(Some("A"), Some("C"));
+        g.add_edge(Some("A"), Some("B"));
+        g.add_edge(Some("B"), Some("C"));
+
+        assert_eq!(g.to_string(), "g:\nA (a) -> [B, C]\nB -> [C]\nC -> []\n");
+    }
+
+    #[test]
+    fn test_digraph_node_iters() {
+        let g: DiGraph = vec![("A", "B"), ("B", "C")].into_iter().collect();
+
+        let mut names: Vec<&str> = g.node_names().collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["A", "B", "C"]);
+
+        let mut degrees: Vec<(&str, usize)> = g
+            .nodes()
+            .map(|node| (node.name(), node.out_degree()))
+            .collect();
+        degrees.sort_unstable();
+        assert_eq!(degrees, vec![("A", 1), ("B", 1), ("C", 0)]);
+    }
+
+    #[test]
+    fn test_digraph_neighbors() {
+        let g: DiGraph = vec![("A", "B"), ("B", "C"), ("C", "B"), ("D", "D")]
+            .into_iter()
+            .collect();
+
+        let mut names: Vec<String> = g
+            .neighbors("B")
+            .unwrap()
+            .iter()
+            .map(|node| node.get_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["A", "C"]);
+        assert_eq!(g.all_degree("B").unwrap(), 3);
+
+        // a self-loop makes a node its own neighbor and counts twice
+        assert_eq!(g.neighbors("D").unwrap().len(), 1);
+        assert_eq!(g.all_degree("D").unwrap(), 2);
+
+        assert!(g.neighbors("E").is_err());
+        assert!(g.all_degree("E").is_err());
+    }
+
+    #[test]
+    fn test_digraph_degrees() {
+        let g: DiGraph = vec![("A", "B"), ("A", "C"), ("B", "C"), ("D", "D")]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            g.in_degrees().collect::<Vec<_>>(),
+            vec![("A", 0), ("B", 1), ("C", 2), ("D", 1)]
+        );
+        assert_eq!(
+            g.out_degrees().collect::<Vec<_>>(),
+            vec![("A", 2), ("B", 1), ("C", 0), ("D", 1)]
+        );
+        assert_eq!(g.degrees().map(|(_, degree)| degree).sum::<usize>(), 8);
+        assert_eq!(g.max_degree_node(), Some(("A", 2)));
+        assert_eq!(g.min_degree_node(), Some(("A", 2)));
+
+        let mut g = g;
+        g.add_edge(Some("E"), None);
+        assert_eq!(g.min_degree_node(), Some(("E", 0)));
+        assert_eq!(DiGraph::new(None).max_degree_node(), None);
+    }
+
+    #[test]
+    fn test_digraph_contains_edge() {
+        let g: DiGraph = vec![("A", "B"), ("C", "C")].into_iter().collect();
+
+        assert!(g.contains_edge("A", "B"));
+        assert!(!g.contains_edge("B", "A"));
+        assert!(g.contains_edge("C", "C"));
+        assert!(!g.contains_edge("D", "A"));
+    }
+
+    #[test]
+    fn test_digraph_edges() {
+        let mut g = DiGraph::new(None);
+        g.add_edge(Some("A"), Some("B"));
+        g.add_weighted_edge("B", "C", 3);
+        g.add_edge(Some("C"), Some("C"));
+        g.add_edge(Some("D"), None);
+
+        let mut edges: Vec<(&str, &str)> = g.edges().collect();
+        edges.sort();
+        assert_eq!(edges, vec![("A", "B"), ("B", "C"), ("C", "C")]);
+
+        let mut edges: Vec<(&str, &str, Option<i64>)> = g.weighted_edges().collect();
+        edges.sort();
+        assert_eq!(
+            edges,
+            vec![("A", "B", None), ("B", "C", Some(3)), ("C", "C", None)]
+        );
+
+        assert_eq!(g.edge_count(), 3);
+        assert_eq!(g.edge_count_between("B", "C"), 1);
+        assert_eq!(g.edge_count_between("C", "B"), 0);
+        assert_eq!(g.edge_count_between("Z", "B"), 0);
+        assert_eq!(g.edge_count_between("B", "Z"), 0);
+        assert_eq!(g.to_string(), "A -> [B]\nB -> [C: 3]\nC -> [C]\nD -> []\n");
+    }
+
+    #[test]
+    fn test_digraph_in_out_edges() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 1);
+        g.add_edge(Some("C"), Some("B"));
+        g.add_weighted_edge("B", "D", 2);
+
+        let mut edges: Vec<(&str, &str, Option<i64>)> = g
+            .in_edges("B")
+            .unwrap()
+            .map(|(from, to, data)| (from, to, data.get_weight()))
+            .collect();
+        edges.sort();
+        assert_eq!(edges, vec![("A", "B", Some(1)), ("C", "B", None)]);
+
+        let edges: Vec<(&str, &str, Option<i64>)> = g
+            .out_edges("B")
+            .unwrap()
+            .map(|(from, to, data)| (from, to, data.get_weight()))
+            .collect();
+        assert_eq!(edges, vec![("B", "D", Some(2))]);
+
+        assert!(g.out_edges("E").is_err());
+        assert!(g.in_edges("E").is_err());
+    }
+
+    #[test]
+    fn test_digraph_remove_and_contract() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 1);
+        g.add_weighted_edge("B", "C", 2);
+        g.add_weighted_edge("A", "C", 5);
+        g.add_edge(Some("D"), Some("B"));
+        g.add_edge(Some("B"), Some("A"));
+        g.add_edge(Some("B"), Some("B"));
+        g.get_edge_data_mut("D", "B")
+            .unwrap()
+            .set_attr("tensor", "x");
+
+        // B's edges move to A, except those between A and B; A -> C keeps its weight
+        g.contract_edge("A", "B").unwrap();
+        assert!(!g.contains_node("B"));
+        assert_eq!(g.to_string(), "A -> [A, C: 5]\nC -> []\nD -> [A]\n");
+        assert_eq!(
+            g.get_edge_data("D", "A")
+                .unwrap()
+                .get_attr_as::<String>("tensor"),
+            Some("x".to_string())
+        );
+        assert!(g.contract_edge("C", "A").is_err());
+
+        g.contract_edge("A", "A").unwrap();
+        assert!(g.remove_edge("A", "C"));
+        assert!(!g.remove_edge("A", "C"));
+        assert_eq!(g.get_edge_weight("A", "C"), None);
+        assert!(g.remove_node("D").is_some());
+        assert!(g.remove_node("D").is_none());
+        assert_eq!(g.edge_count(), 0);
+        assert_eq!(g.get_nodes(), vec!["A", "C"]);
+    }
+
+    #[test]
+    fn test_digraph_split_node() {
+        let mut g = DiGraph::new(None);
+        g.add_node(DiNode::new("V", Some("relay".to_string())));
+        g.add_weighted_edge("S", "V", 4);
+        g.add_weighted_edge("V", "T", 2);
+        g.add_edge(Some("V"), Some("V"));
+        g.get_node_mut("V").unwrap().set_attr("capacity", 3);
+
+        g.split_node("V", "V", "V'").unwrap();
+        assert_eq!(g.get_edge_weight("S", "V"), Some(4));
+        assert_eq!(g.get_edge_weight("V'", "T"), Some(2));
+        assert!(g.contains_edge("V", "V'") && g.contains_edge("V'", "V"));
+        assert!(!g.contains_edge("V", "T") && !g.contains_edge("V", "V"));
+        assert_eq!(g.edge_count(), 4);
+        let out = g.get_node("V'").unwrap();
+        assert_eq!(out.get_weight(), Some("relay".to_string()));
+        assert_eq!(out.get_attr_as::<i64>("capacity"), Some(3));
+        assert!(g.validate().is_ok());
+
+        assert!(matches!(
+            g.split_node("T", "S", "T'"),
+            Err(GraphError::DuplicateNode(_))
+        ));
+        assert!(matches!(
+            g.split_node("T", "X", "X"),
+            Err(GraphError::DuplicateNode(_))
+        ));
+        assert!(matches!(
+            g.split_node("Z", "Z1", "Z2"),
+            Err(GraphError::NotFoundNode(_))
+        ));
+    }
+
+    #[test]
+    fn test_digraph_batch_mutation() {
+        let mut g = DiGraph::with_order(None, NodeOrder::Sorted);
+        g.add_nodes_from(vec![
+            DiNode::new("E", None),
+            DiNode::new("A", Some("a".to_string())),
+        ]);
+        g.add_edges_from(vec![("C", "B"), ("C", "A"), ("B", "D")]);
+        g.add_weighted_edges_from(vec![("A", "D", 2), ("D", "C", 3)]);
+
+        assert_eq!(g.get_nodes(), vec!["A", "B", "C", "D", "E"]);
+        assert_eq!(g.get_node("C").unwrap().get_successors(), vec!["A", "B"]);
+        assert_eq!(g.get_node("D").unwrap().get_predecessors(), vec!["A", "B"]);
+        assert_eq!(g.get_edge_weight("D", "C"), Some(3));
+        assert_eq!(g.edge_count(), 5);
+
+        assert_eq!(g.remove_nodes_from(vec!["B", "E", "Z"]), 2);
+        assert_eq!(g.get_nodes(), vec!["A", "C", "D"]);
+        assert_eq!(g.edge_count(), 3);
+
+        // a node already in the graph keeps its edges
+        g.add_nodes_from(vec![DiNode::new("A", None), DiNode::new("F", None)]);
+        assert_eq!(g.get_node("A").unwrap().get_weight().as_deref(), Some("a"));
+        assert_eq!(g.get_node("A").unwrap().get_successors(), vec!["D"]);
+        assert_eq!(g.get_nodes(), vec!["A", "C", "D", "F"]);
+        assert!(g.validate().is_ok());
+    }
+
+    #[test]
+    fn test_digraph_to_undirected() {
+        let mut g = DiGraph::new(Some("g".to_string()));
+        g.add_weighted_edge("A", "B", 2);
+        g.add_weighted_edge("B", "A", 5);
+        g.add_edge(Some("B"), Some("C"));
+        g.add_weighted_edge("C", "B", 1);
+        g.add_edge(Some("C"), Some("C"));
+        g.get_node_mut("A").unwrap().set_attr("layer", 1);
+        g.get_edge_data_mut("A", "B").unwrap().set_attr("k", "ab");
+        g.get_edge_data_mut("B", "A").unwrap().set_attr("k", "ba");
+        g.get_edge_data_mut("B", "A").unwrap().set_attr("x", 0);
+
+        let u = g.to_undirected(WeightPolicy::Sum);
+        assert_eq!(u.get_name(), Some("g".to_string()));
+        assert_eq!(u.edge_count(), 3);
+        assert_eq!(u.get_edge_weight("A", "B"), Some(7));
+        assert_eq!(u.get_edge_weight("B", "C"), Some(1));
+        assert_eq!(u.get_edge_weight("C", "C"), None);
+        let data = u.get_edge_data("B", "A").unwrap();
+        assert_eq!(data.get_attr_as::<String>("k"), Some("ab".to_string()));
+        assert_eq!(data.get_attr_as::<i64>("x"), Some(0));
+        assert_eq!(
+            u.get_node("A").unwrap().get_attr_as::<i64>("layer"),
+            Some(1)
+        );
+
+        assert_eq!(
+            g.to_undirected(WeightPolicy::Min).get_edge_weight("A", "B"),
+            Some(2)
+        );
+        assert_eq!(
+            g.to_undirected(WeightPolicy::Max).get_edge_weight("A", "B"),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_digraph_validate_and_repair() {
+        // B is stored as "b", A lists a missing successor Z, C -> A is only known
+        // to A, and there is data for a missing edge A -> C
+        let json_str = r#"{"name":null,"nodes":{
+            "A":{"name":"A","inputs":["C"],"outputs":["B","Z"],"weight":null},
+            "B":{"name":"b","inputs":["A"],"outputs":[],"weight":null},
+            "C":{"name":"C","inputs":[],"outputs":[],"weight":null}},
+            "edges":{"A":{"C":{"weight":1}},"C":{"A":{"weight":2}}}}"#;
+        let mut g: DiGraph = serde_json::from_str(json_str).unwrap();
+
+        let problems = g.validate().unwrap_err();
+        assert_eq!(problems.len(), 4);
+        assert!(
+            matches!(&problems[0], GraphError::DanglingEdge(from, to) if from == "A" && to == "Z")
+        );
+        assert!(
+            matches!(&problems[1], GraphError::OneSidedEdge(from, to) if from == "C" && to == "A")
+        );
+        assert!(
+            matches!(&problems[2], GraphError::MisnamedNode(key, name) if key == "B" && name == "b")
+        );
+        assert!(
+            matches!(&problems[3], GraphError::NotFoundEdge(from, to) if from == "A" && to == "C")
+        );
+
+        assert_eq!(g.repair().len(), 4);
+        assert!(g.validate().is_ok());
+        assert!(g.repair().is_empty());
+        assert_eq!(g.to_string(), "A -> [B]\nB -> []\nC -> [A: 2]\n");
+    }
+
+    #[test]
+    fn test_digraph_listeners() {
+        use std::sync::{Arc, Mutex};
+
+        let mut g = DiGraph::new(None);
+        g.add_edge(Some("A"), Some("B"));
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = log.clone();
+        let all = g.subscribe(move |event| sink.lock().unwrap().push(format!("{:?}", event)));
+        let added = Arc::new(Mutex::new(Vec::new()));
+        let sink = added.clone();
+        g.on_edge_added(move |from, to| sink.lock().unwrap().push(format!("{}{}", from, to)));
+
+        g.add_edge(Some("A"), Some("B"));
+        g.add_edge(Some("B"), Some("C"));
+        g.add_edge(Some("C"), Some("C"));
+        g.remove_node("C");
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                r#"NodeAdded("C")"#,
+                r#"EdgeAdded("B", "C")"#,
+                r#"EdgeAdded("C", "C")"#,
+                r#"EdgeRemoved("C", "C")"#,
+                r#"EdgeRemoved("B", "C")"#,
+                r#"NodeRemoved("C")"#,
+            ]
+        );
+        assert_eq!(*added.lock().unwrap(), vec!["BC", "CC"]);
+
+        assert!(g.unsubscribe(all));
+        assert!(!g.unsubscribe(all));
+        g.clone().add_edge(Some("B"), Some("A"));
+        g.add_node(DiNode::new("D", None));
+        assert_eq!(log.lock().unwrap().len(), 6);
+        assert_eq!(added.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_digraph_transaction() {
+        use std::sync::{Arc, Mutex};
+
+        let mut g: DiGraph = vec![("A", "B"), ("B", "C")].into_iter().collect();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = log.clone();
+        g.subscribe(move |event| sink.lock().unwrap().push(format!("{:?}", event)));
+
+        let result: Result<(), GraphError> = g.transaction(|txn| {
+            txn.add_weighted_edge("C", "D", 1);
+            txn.remove_node("A");
+            txn.contract_edge("D", "C")
+        });
+        assert!(matches!(result, Err(GraphError::NotFoundEdge(_, _))));
+        assert_eq!(g.to_string(), "A -> [B]\nB -> [C]\nC -> []\n");
+        assert!(log.lock().unwrap().is_empty());
+
+        let count = g
+            .transaction(|txn| {
+                txn.add_weighted_edge("C", "D", 1);
+                txn.remove_node("A");
+                txn.contract_edge("C", "D")?;
+                Ok::<usize, GraphError>(txn.node_count())
+            })
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(g.to_string(), "B -> [C]\nC -> []\n");
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![r#"EdgeRemoved("A", "B")"#, r#"NodeRemoved("A")"#]
+        );
+    }
+
+    #[test]
+    fn test_weighted_digraph_json() {
+        let mut g = DiGraph::new(None);
+        g.add_weighted_edge("A", "B", 2);
+
+        let json_str = serde_json::to_string(&g).unwrap();
+        assert!(json_str.contains(r#""edges":{"A":{"B":{"weight":2}}}"#));
+
+        let actual: DiGraph = serde_json::from_str(json_str.as_str()).unwrap();
+        assert_eq!(g, actual);
+    }
+
+    #[test]
+    fn test_digraph_weight_index() {
+        let mut g = DiGraph::new(None);
+        g.add_node(DiNode::new("A", Some("conv".to_string())));
+        g.add_node(DiNode::new("B", Some("relu".to_string())));
+        g.enable_weight_index();
+        g.add_node(DiNode::new("C", Some("conv".to_string())));
+        g.add_edge(Some("C"), Some("D"));
+        // replacing a node moves it to its new weight
+        g.add_node(DiNode::new("B", Some("conv".to_string())));
+
+        let mut names: Vec<String> = g
+            .nodes_with_weight(Some("conv"))
+            .iter()
+            .map(|node| node.get_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["A", "B", "C"]);
+        assert!(g.nodes_with_weight(Some("relu")).is_empty());
+        assert_eq!(g.nodes_with_weight(None).len(), 1);
+
+        g.disable_weight_index();
+        assert_eq!(g.nodes_with_weight(Some("conv")).len(), 3);
+    }
+
+    #[test]
+    fn test_digraph_node_entry() {
+        let mut g = DiGraph::with_order(None, NodeOrder::Sorted);
+        g.add_edge(Some("B"), Some("C"));
+        g.enable_weight_index();
+
+        g.node_entry("A")
+            .and_modify(|_| panic!("A does not exist yet"))
+            .or_insert(Some("conv".to_string()))
+            .set_attr("layer", 1);
+        assert_eq!(g.get_nodes(), vec!["A", "B", "C"]);
+
+        for name in ["A", "B"] {
+            g.node_entry(name)
+                .and_modify(|node| node.set_weight(Some("relu".to_string())))
+                .or_insert_with(|| unreachable!());
+        }
+        assert_eq!(g.get_node("B").unwrap().get_successors(), vec!["C"]);
+        assert_eq!(
+            g.get_node("A").unwrap().get_attr_as::<i64>("layer"),
+            Some(1)
+        );
+        assert!(g.nodes_with_weight(Some("conv")).is_empty());
+        assert_eq!(g.nodes_with_weight(Some("relu")).len(), 2);
+        assert_eq!(g.node_entry("D").name(), "D");
+    }
+
+    #[test]
+    fn test_digraph_attrs() {
+        let mut g = DiGraph::new(None);
+        g.add_edge(Some("A"), Some("B"));
+        g.set_attr("model", "resnet");
+        g.get_node_mut("A").unwrap().set_attr("layer", 1);
+        g.get_edge_data_mut("A", "B")
+            .unwrap()
+            .set_attr("tensor", "x");
+        assert!(g.get_edge_data_mut("B", "A").is_none());
+
+        let json_str = serde_json::to_string(&g).unwrap();
+        let actual: DiGraph = serde_json::from_str(json_str.as_str()).unwrap();
+        assert_eq!(g, actual);
+        assert_eq!(
+            actual.get_attr_as::<String>("model"),
+            Some("resnet".to_string())
+        );
+        assert_eq!(
+            actual.get_node("A").unwrap().get_attr_as::<i64>("layer"),
+            Some(1)
+        );
+        assert_eq!(
+            actual
+                .get_edge_data("A", "B")
+                .unwrap()
+                .get_attr_as::<String>("tensor"),
+            Some("x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_digraph_from_iter() {
+        let actual: DiGraph = vec![("A", "B"), ("B", "C")].into_iter().collect();
+
+        let mut g = DiGraph::new(None);
+        g.add_edge(Some("A"), Some("B"));
+        g.add_edge(Some("B"), Some("C"));
+
+        assert_eq!(g, actual);
+    }
+
+    #[test]
+    fn test_digraph_extend() {
+        let mut actual = DiGraph::new(None);
+        actual.add_edge(Some("A"), Some("B"));
+        actual.extend(vec![
+            ("B".to_string(), "C".to_string()),
+            ("C".to_string(), "A".to_string()),
+        ]);
+
+        let mut g = DiGraph::new(None);
+        g.add_edge(Some("A"), Some("B"));
+        g.add_edge(Some("B"), Some("C"));
+        g.add_edge(Some("C"), Some("A"));
+
+        assert_eq!(g, actual);
+    }
+
+    #[test]
+    fn test_digraph_order() {
+        let edges = [("C", "A"), ("B", "A"), ("C", "B")];
+
+        let g: DiGraph = edges.iter().cloned().collect();
+        assert_eq!(g.order(), NodeOrder::Insertion);
+        assert_eq!(g.get_nodes(), vec!["C", "A", "B"]);
+        assert_eq!(g.get_node("A").unwrap().get_predecessors(), vec!["C", "B"]);
+        assert_eq!(crate::algorithm::topsort::topsort(&g), vec!["C", "B", "A"]);
+
+        let mut sorted = DiGraph::with_order(None, NodeOrder::Sorted);
+        for (from, to) in edges.iter() {
+            sorted.add_edge(Some(from), Some(to));
+        }
+        assert_eq!(sorted.get_nodes(), vec!["A", "B", "C"]);
+        assert_eq!(
+            sorted.get_node("A").unwrap().get_predecessors(),
+            vec!["B", "C"]
+        );
+        assert_eq!(
+            sorted.edges().collect::<Vec<_>>(),
+            vec![("B", "A"), ("C", "A"), ("C", "B")]
+        );
+        assert_eq!(sorted, g);
+
+        let mut g = g;
+        g.set_order(NodeOrder::Sorted);
+        assert_eq!(g.get_nodes(), vec!["A", "B", "C"]);
+        assert_eq!(
+            g.edges().collect::<Vec<_>>(),
+            sorted.edges().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_digraph_ports() {
+        let mut g = DiGraph::new(None);
+        let mut select = DiNode::new("select", None);
+        select.set_ports(["cond", "then", "else"]);
+        g.add_node(select);
+        g.add_named_port_edge("y", "select", "else").unwrap();
+        g.add_named_port_edge("c", "select", "cond").unwrap();
+        g.add_edge(Some("ctl"), Some("select"));
+        assert_eq!(g.get_edge_port("y", "select"), Some(2));
+        assert_eq!(g.get_edge_port("ctl", "select"), None);
+        assert_eq!(
+            g.operands("select").unwrap(),
+            vec![Some("c"), None, Some("y")]
+        );
+
+        assert!(matches!(
+            g.add_named_port_edge("x", "select", "other"),
+            Err(GraphError::NotFoundPort(..))
+        ));
+        assert!(matches!(
+            g.add_named_port_edge("x", "missing", "then"),
+            Err(GraphError::NotFoundNode(_))
+        ));
+        assert!(!g.contains_node("x"));
+
+        assert!(matches!(
+            g.add_named_port_edge("x", "select", "cond"),
+            Err(GraphError::PortInUse(to, 0, from)) if to == "select" && from == "c"
+        ));
+        assert!(!g.contains_node("x"));
+        // an edge may be re-added to the port it already feeds, or moved
+        g.add_port_edge("c", "select", 0).unwrap();
+        g.add_port_edge("c", "select", 1).unwrap();
+        assert_eq!(
+            g.operands("select").unwrap(),
+            vec![None, Some("c"), Some("y")]
+        );
+
+        g.add_port_edge("x", "select", 4).unwrap();
+        assert_eq!(g.operands("select").unwrap().len(), 5);
+        assert_eq!(g.operands("c").unwrap(), Vec::<Option<&str>>::new());
+        assert!(g.operands("missing").is_none());
+
+        let json = serde_json::to_string(&g).unwrap();
+        let back: DiGraph = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, g);
+        assert_eq!(back.get_node("select").unwrap().ports().len(), 3);
+    }
 }
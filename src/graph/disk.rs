@@ -0,0 +1,124 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Directed, FrozenDiGraph, FrozenNode, GraphBase, Ported, Weighted};
+use crate::error::{GraphError, ResultExt};
+use std::fs;
+use std::path::Path;
+
+/// A read-only directed graph kept in a file in the layout of
+/// [`FrozenDiGraph`], mapped into memory so the operating system pages edge
+/// lists in and out as algorithms touch them. It implements [`Graph`], so
+/// every algorithm runs on graphs larger than RAM.
+///
+/// Memory use is a small handle per node. Opening checks the name, weight and
+/// offset tables but not the edge lists, so it takes time in proportion to the
+/// nodes only; a corrupt edge list then shows up as missing edges rather than
+/// an error, unless [`DiskDiGraph::verify`] is called. The file must not be
+/// changed while the graph is open.
+#[derive(Debug, Clone)]
+pub struct DiskDiGraph {
+    graph: FrozenDiGraph,
+}
+impl DiskDiGraph {
+    /// Write any graph to `path`, replacing the file.
+    pub fn create<G: Weighted>(path: impl AsRef<Path>, graph: &G) -> Result<(), GraphError> {
+        let bytes = FrozenDiGraph::encode(graph)?;
+        fs::write(path.as_ref(), bytes)
+            .map_err(|err| GraphError::Serialization(err.to_string()))
+            .with_context(|| format!("writing {}", path.as_ref().display()))
+    }
+
+    /// Open a file written by [`DiskDiGraph::create`] or
+    /// [`FrozenDiGraph::encode`].
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, GraphError> {
+        let graph = FrozenDiGraph::map_file(path.as_ref(), false)?;
+        Ok(DiskDiGraph { graph })
+    }
+
+    /// Check every edge list, reading the whole file once.
+    pub fn verify(&self) -> Result<(), GraphError> {
+        self.graph.check_edges()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+}
+impl GraphBase for DiskDiGraph {
+    type Node = FrozenNode;
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn node(&self, name: &str) -> Option<&Self::Node> {
+        self.graph.node(name)
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &Self::Node> {
+        self.graph.nodes()
+    }
+}
+impl Directed for DiskDiGraph {
+    fn contains_edge(&self, from: &str, to: &str) -> bool {
+        self.graph.contains_edge(from, to)
+    }
+}
+impl Weighted for DiskDiGraph {
+    fn edge_weight(&self, from: &str, to: &str) -> Option<i64> {
+        self.graph.edge_weight(from, to)
+    }
+}
+impl Ported for DiskDiGraph {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithm::sssp::dijkstra,
+        graph::{DiGraph, Node},
+    };
+    use std::env;
+
+    #[test]
+    fn test_disk_digraph() {
+        let mut g: DiGraph = vec![("A", "B"), ("B", "C"), ("A", "D")]
+            .into_iter()
+            .collect();
+        g.add_weighted_edge("D", "C", 5);
+        let path = env::temp_dir().join("graphx_disk_test.bin");
+        DiskDiGraph::create(&path, &g).unwrap();
+
+        let disk = DiskDiGraph::open(&path).unwrap();
+        assert!(disk.verify().is_ok());
+        assert_eq!((disk.node_count(), disk.edge_count()), (4, 4));
+        assert_eq!(disk.edge_weight("D", "C"), Some(5));
+        assert_eq!(dijkstra(&disk, "A").unwrap(), dijkstra(&g, "A").unwrap());
+        drop(disk);
+
+        // a broken predecessor list is only found by verify
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() = 0x80;
+        let corrupt = env::temp_dir().join("graphx_disk_test_corrupt.bin");
+        fs::write(&corrupt, bytes).unwrap();
+        let disk = DiskDiGraph::open(&corrupt).unwrap();
+        assert!(disk.verify().is_err());
+        assert_eq!(disk.node("D").unwrap().predecessor_names().count(), 0);
+        let missing = path.with_extension("missing");
+        let err = DiskDiGraph::open(&missing).unwrap_err();
+        assert_eq!(err.to_string(), format!("mapping {}", missing.display()));
+        assert!(matches!(err.root_cause(), GraphError::Serialization(_)));
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::attr::{Attributed, Attributes};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+static DEFAULT_EDGE_DATA: OnceLock<EdgeData> = OnceLock::new();
+
+/// Shared by all edges that have no data of their own.
+pub(crate) fn default_edge_data() -> &'static EdgeData {
+    DEFAULT_EDGE_DATA.get_or_init(EdgeData::default)
+}
+
+/// Data attached to a single edge of a `DiGraph`.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Deserialize, Serialize)]
+pub struct EdgeData {
+    weight: Option<i64>,
+    // index of the input port of the target that this edge feeds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    port: Option<usize>,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    attrs: Attributes,
+}
+impl EdgeData {
+    pub fn new(weight: Option<i64>) -> Self {
+        EdgeData {
+            weight,
+            port: None,
+            attrs: Attributes::new(),
+        }
+    }
+
+    pub fn get_weight(&self) -> Option<i64> {
+        self.weight
+    }
+
+    pub fn set_weight(&mut self, weight: Option<i64>) {
+        self.weight = weight;
+    }
+
+    /// The input port of the target node that the edge is connected to.
+    pub fn get_port(&self) -> Option<usize> {
+        self.port
+    }
+
+    pub fn set_port(&mut self, port: Option<usize>) {
+        self.port = port;
+    }
+}
+impl Attributed for EdgeData {
+    fn attrs(&self) -> &Attributes {
+        &self.attrs
+    }
+
+    fn attrs_mut(&mut self) -> &mut Attributes {
+        &mut self.attrs
+    }
+}
@@ -0,0 +1,752 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Immutable graphs stored in a compact binary layout that is read in place.
+//!
+//! The layout is a magic number, the node, edge and label counts, and the
+//! start of every section, followed by the sections, all little-endian:
+//!
+//! * node names as a string table: `n + 1` `u64` offsets into the name bytes;
+//! * the node ids as `u32`, sorted by name, to look names up by binary search;
+//! * the distinct node weights as a string table, so each is stored once;
+//! * the weight of every node as a `u32`, the weight's index plus one or 0;
+//! * the successors of every node as `n + 1` `u64` offsets into the edge
+//!   bytes, where each list is the degree as a varint and then, per edge in
+//!   order of ids, the difference to the previous id shifted left by one, its
+//!   low bit set if a zigzag varint weight follows;
+//! * the predecessors in the same way, without weights.
+//!
+//! Node ids are positions in graph order. Loading checks the whole layout once
+//! in `O(V + E)` time and builds no adjacency of its own, so it is far faster
+//! than parsing JSON, and with the `mmap` feature the file is mapped rather
+//! than copied into memory.
+
+use super::{Directed, GraphBase, Node, Ported, Weighted};
+use crate::error::GraphError;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+const MAGIC: &[u8; 8] = b"graphx\0\x01";
+
+const NAME_OFFSETS: usize = 0;
+const NAMES: usize = 1;
+const SORTED: usize = 2;
+const LABEL_OFFSETS: usize = 3;
+const LABELS: usize = 4;
+const WEIGHTS: usize = 5;
+const OUT_OFFSETS: usize = 6;
+const OUT_EDGES: usize = 7;
+const IN_OFFSETS: usize = 8;
+const IN_EDGES: usize = 9;
+const SECTIONS: usize = 10;
+
+/// The magic number, three counts, and the start of every section and of the
+/// end.
+const HEADER_LEN: usize = MAGIC.len() + 8 * (3 + SECTIONS + 1);
+
+/// An immutable directed graph in the compact layout described in the module
+/// docs, shared cheaply between threads and clones.
+///
+/// Build one with [`FrozenDiGraph::from_graph`], or load bytes made by
+/// [`FrozenDiGraph::encode`]. It implements [`Graph`], so every algorithm runs
+/// on it. Successors and predecessors come in graph order.
+#[derive(Clone)]
+pub struct FrozenDiGraph {
+    layout: Arc<Layout>,
+    nodes: Vec<FrozenNode>,
+}
+impl FrozenDiGraph {
+    /// Freeze any graph. Fails with `GraphError::TooManyNodes` if it has more
+    /// than `u32::MAX` nodes.
+    pub fn from_graph<G: Weighted>(graph: &G) -> Result<Self, GraphError> {
+        FrozenDiGraph::from_bytes(FrozenDiGraph::encode(graph)?)
+    }
+
+    /// Encode any graph in the frozen layout, e.g. to write it to a file.
+    pub fn encode<G: Weighted>(graph: &G) -> Result<Vec<u8>, GraphError> {
+        let names: Vec<&str> = graph.node_names().collect();
+        let n = names.len();
+        if u32::try_from(n).is_err() {
+            return Err(GraphError::TooManyNodes(n, u32::MAX as usize));
+        }
+        let index: HashMap<&str, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, &name)| (name, i))
+            .collect();
+
+        let mut sections = vec![Vec::new(); SECTIONS];
+        let (offsets, data) = string_table(&names);
+        sections[NAME_OFFSETS] = offsets;
+        sections[NAMES] = data;
+
+        let mut sorted: Vec<usize> = (0..n).collect();
+        sorted.sort_unstable_by_key(|&i| names[i]);
+        for i in sorted {
+            sections[SORTED].extend_from_slice(&(i as u32).to_le_bytes());
+        }
+
+        let mut labels: Vec<&str> = Vec::new();
+        let mut label_index: HashMap<&str, usize> = HashMap::new();
+        for &name in names.iter() {
+            let code = match graph.node(name).unwrap().weight() {
+                Some(weight) => {
+                    let next = labels.len();
+                    let label = *label_index.entry(weight).or_insert(next);
+                    if label == next {
+                        labels.push(weight);
+                    }
+                    label as u32 + 1
+                }
+                None => 0,
+            };
+            sections[WEIGHTS].extend_from_slice(&code.to_le_bytes());
+        }
+        let (offsets, data) = string_table(&labels);
+        sections[LABEL_OFFSETS] = offsets;
+        sections[LABELS] = data;
+
+        let mut edge_count = 0;
+        for &name in names.iter() {
+            let node = graph.node(name).unwrap();
+            let mut succs: Vec<usize> = node.successor_names().map(|s| index[s]).collect();
+            succs.sort_unstable();
+            succs.dedup();
+            edge_count += succs.len();
+            let mut list = Vec::new();
+            write_varint(&mut list, succs.len() as u64);
+            let mut prev = 0;
+            for succ in succs {
+                let weight = graph.edge_weight(name, names[succ]);
+                write_varint(
+                    &mut list,
+                    ((succ - prev) as u64) << 1 | u64::from(weight.is_some()),
+                );
+                if let Some(weight) = weight {
+                    write_varint(&mut list, ((weight << 1) ^ (weight >> 63)) as u64);
+                }
+                prev = succ;
+            }
+            push_list(&mut sections, OUT_OFFSETS, OUT_EDGES, &list);
+
+            let mut preds: Vec<usize> = node.predecessor_names().map(|p| index[p]).collect();
+            preds.sort_unstable();
+            preds.dedup();
+            let mut list = Vec::new();
+            write_varint(&mut list, preds.len() as u64);
+            let mut prev = 0;
+            for pred in preds {
+                write_varint(&mut list, ((pred - prev) as u64) << 1);
+                prev = pred;
+            }
+            push_list(&mut sections, IN_OFFSETS, IN_EDGES, &list);
+        }
+        let out_len = sections[OUT_EDGES].len() as u64;
+        sections[OUT_OFFSETS].extend_from_slice(&out_len.to_le_bytes());
+        let in_len = sections[IN_EDGES].len() as u64;
+        sections[IN_OFFSETS].extend_from_slice(&in_len.to_le_bytes());
+
+        let mut bytes =
+            Vec::with_capacity(HEADER_LEN + sections.iter().map(Vec::len).sum::<usize>());
+        bytes.extend_from_slice(MAGIC);
+        for count in [n, edge_count, labels.len()] {
+            bytes.extend_from_slice(&(count as u64).to_le_bytes());
+        }
+        let mut start = HEADER_LEN;
+        for section in sections.iter() {
+            bytes.extend_from_slice(&(start as u64).to_le_bytes());
+            start += section.len();
+        }
+        bytes.extend_from_slice(&(start as u64).to_le_bytes());
+        for section in sections {
+            bytes.extend(section);
+        }
+        Ok(bytes)
+    }
+
+    /// Load a graph from bytes made by [`FrozenDiGraph::encode`]. Fails with
+    /// `GraphError::Serialization` if they are not a valid layout.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, GraphError> {
+        FrozenDiGraph::load(Storage::Owned(bytes), true)
+    }
+
+    /// Load a graph from a file made by [`FrozenDiGraph::encode`] by mapping it
+    /// into memory, so its pages are read only when used and shared with other
+    /// processes mapping the same file.
+    ///
+    /// The file must not be changed while the graph is alive.
+    #[cfg(feature = "mmap")]
+    pub fn map(path: impl AsRef<std::path::Path>) -> Result<Self, GraphError> {
+        FrozenDiGraph::map_file(path.as_ref(), true)
+    }
+
+    /// Map a file, checking its edge lists only if `check_edges` is set.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn map_file(path: &std::path::Path, check_edges: bool) -> Result<Self, GraphError> {
+        use crate::error::ResultExt;
+
+        let mapped = std::fs::File::open(path).and_then(|file| {
+            // SAFETY: the map is read-only, and callers keep the file unchanged
+            // while the graph is alive, as the public constructors document.
+            unsafe { memmap2::Mmap::map(&file) }
+        });
+        mapped
+            .map_err(|err| GraphError::Serialization(err.to_string()))
+            .and_then(|map| FrozenDiGraph::load(Storage::Mapped(map), check_edges))
+            .with_context(|| format!("mapping {}", path.display()))
+    }
+
+    fn load(bytes: Storage, check_edges: bool) -> Result<Self, GraphError> {
+        let layout = Layout::new(bytes)?;
+        if check_edges {
+            layout.check_edge_lists()?;
+        }
+        let layout = Arc::new(layout);
+        let nodes = (0..layout.node_count)
+            .map(|id| FrozenNode {
+                layout: layout.clone(),
+                id,
+            })
+            .collect();
+        Ok(FrozenDiGraph { layout, nodes })
+    }
+
+    /// The graph in the frozen layout.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.layout.bytes
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.layout.edge_count
+    }
+
+    /// Check the edge lists of a graph loaded without checking them.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn check_edges(&self) -> Result<(), GraphError> {
+        self.layout.check_edge_lists()
+    }
+}
+impl fmt::Debug for FrozenDiGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrozenDiGraph")
+            .field("node_count", &self.layout.node_count)
+            .field("edge_count", &self.layout.edge_count)
+            .finish()
+    }
+}
+impl GraphBase for FrozenDiGraph {
+    type Node = FrozenNode;
+
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node(&self, name: &str) -> Option<&Self::Node> {
+        self.layout.id(name).map(|id| &self.nodes[id])
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &Self::Node> {
+        self.nodes.iter()
+    }
+}
+impl Directed for FrozenDiGraph {
+    fn contains_edge(&self, from: &str, to: &str) -> bool {
+        self.edge_weight_of(from, to).is_some()
+    }
+}
+impl Weighted for FrozenDiGraph {
+    fn edge_weight(&self, from: &str, to: &str) -> Option<i64> {
+        self.edge_weight_of(from, to).flatten()
+    }
+}
+impl Ported for FrozenDiGraph {}
+impl FrozenDiGraph {
+    /// `Some(weight)` if the edge exists.
+    fn edge_weight_of(&self, from: &str, to: &str) -> Option<Option<i64>> {
+        let (from, to) = (self.layout.id(from)?, self.layout.id(to)?);
+        self.layout
+            .edges(OUT_OFFSETS, OUT_EDGES, from)
+            .find(|&(id, _)| id == to)
+            .map(|(_, weight)| weight)
+    }
+}
+
+/// A node of a [`FrozenDiGraph`], reading its name and edges from the layout.
+#[derive(Clone)]
+pub struct FrozenNode {
+    layout: Arc<Layout>,
+    id: usize,
+}
+impl PartialEq for FrozenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && Arc::ptr_eq(&self.layout, &other.layout)
+    }
+}
+impl Eq for FrozenNode {}
+impl Hash for FrozenNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+impl fmt::Debug for FrozenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrozenNode")
+            .field("name", &self.name())
+            .field("weight", &self.weight())
+            .finish()
+    }
+}
+impl Node for FrozenNode {
+    fn name(&self) -> &str {
+        self.layout.name(self.id)
+    }
+
+    fn weight(&self) -> Option<&str> {
+        match self.layout.u32_at(WEIGHTS, self.id) {
+            0 => None,
+            label => Some(self.layout.string(LABEL_OFFSETS, LABELS, label - 1)),
+        }
+    }
+
+    fn predecessor_names(&self) -> impl Iterator<Item = &str> {
+        self.layout
+            .edges(IN_OFFSETS, IN_EDGES, self.id)
+            .map(move |(id, _)| self.layout.name(id))
+    }
+
+    fn successor_names(&self) -> impl Iterator<Item = &str> {
+        self.layout
+            .edges(OUT_OFFSETS, OUT_EDGES, self.id)
+            .map(move |(id, _)| self.layout.name(id))
+    }
+
+    fn in_degree(&self) -> usize {
+        self.layout.edges(IN_OFFSETS, IN_EDGES, self.id).len()
+    }
+
+    fn out_degree(&self) -> usize {
+        self.layout.edges(OUT_OFFSETS, OUT_EDGES, self.id).len()
+    }
+}
+
+enum Storage {
+    Owned(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+impl Deref for Storage {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Storage::Owned(bytes) => bytes,
+            #[cfg(feature = "mmap")]
+            Storage::Mapped(map) => map,
+        }
+    }
+}
+
+/// The bytes of a graph, checked to be a valid layout, so reading them never
+/// fails.
+struct Layout {
+    bytes: Storage,
+    node_count: usize,
+    edge_count: usize,
+    starts: [usize; SECTIONS + 1],
+}
+impl Layout {
+    fn new(bytes: Storage) -> Result<Self, GraphError> {
+        if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(invalid("bad header"));
+        }
+        let header = |i: usize| read_u64(&bytes, MAGIC.len() + 8 * i);
+        let (node_count, edge_count, label_count) = (header(0), header(1), header(2));
+        let mut starts = [0; SECTIONS + 1];
+        for (i, start) in starts.iter_mut().enumerate() {
+            *start = header(3 + i);
+        }
+        if starts[0] != HEADER_LEN
+            || starts.windows(2).any(|w| w[0] > w[1])
+            || starts[SECTIONS] != bytes.len()
+        {
+            return Err(invalid("bad section bounds"));
+        }
+        let layout = Layout {
+            bytes,
+            node_count,
+            edge_count,
+            starts,
+        };
+
+        let fixed = [
+            (NAME_OFFSETS, node_count.checked_add(1), 8),
+            (SORTED, Some(node_count), 4),
+            (LABEL_OFFSETS, label_count.checked_add(1), 8),
+            (WEIGHTS, Some(node_count), 4),
+            (OUT_OFFSETS, node_count.checked_add(1), 8),
+            (IN_OFFSETS, node_count.checked_add(1), 8),
+        ];
+        for (section, count, width) in fixed {
+            if count.and_then(|count| count.checked_mul(width)) != Some(layout.len(section)) {
+                return Err(invalid("bad section size"));
+            }
+        }
+
+        for (offsets, data, count) in [
+            (NAME_OFFSETS, NAMES, node_count),
+            (LABEL_OFFSETS, LABELS, label_count),
+        ] {
+            layout
+                .check_offsets(offsets, data, count)
+                .ok_or_else(|| invalid("bad string offsets"))?;
+            for i in 0..count {
+                let (start, end) = layout.range(offsets, data, i);
+                std::str::from_utf8(&layout.bytes[start..end])
+                    .map_err(|_| invalid("bad string"))?;
+            }
+        }
+        let mut prev: Option<&str> = None;
+        for i in 0..node_count {
+            let id = layout.u32_at(SORTED, i);
+            if id >= node_count {
+                return Err(invalid("bad node id"));
+            }
+            let name = layout.name(id);
+            if prev.is_some_and(|prev| prev >= name) {
+                return Err(invalid("names unsorted or duplicated"));
+            }
+            prev = Some(name);
+        }
+        if (0..node_count).any(|id| layout.u32_at(WEIGHTS, id) > label_count) {
+            return Err(invalid("bad node weight"));
+        }
+
+        for (offsets, data) in [(OUT_OFFSETS, OUT_EDGES), (IN_OFFSETS, IN_EDGES)] {
+            layout
+                .check_offsets(offsets, data, node_count)
+                .ok_or_else(|| invalid("bad edge offsets"))?;
+        }
+        Ok(layout)
+    }
+
+    /// Check every edge list, in `O(V + E)` time.
+    fn check_edge_lists(&self) -> Result<(), GraphError> {
+        for (offsets, data) in [(OUT_OFFSETS, OUT_EDGES), (IN_OFFSETS, IN_EDGES)] {
+            let mut edges = 0;
+            for id in 0..self.node_count {
+                edges += self
+                    .check_edges(offsets, data, id)
+                    .ok_or_else(|| invalid("bad edge list"))?;
+            }
+            if edges != self.edge_count {
+                return Err(invalid("bad edge count"));
+            }
+        }
+        // walking the sources in order, each edge must be the next one in the
+        // predecessors of its target
+        let mut preds: Vec<Edges> = (0..self.node_count)
+            .map(|id| self.edges(IN_OFFSETS, IN_EDGES, id))
+            .collect();
+        for id in 0..self.node_count {
+            for (succ, _) in self.edges(OUT_OFFSETS, OUT_EDGES, id) {
+                if preds[succ].next().map(|(pred, _)| pred) != Some(id) {
+                    return Err(invalid("predecessors and successors differ"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn len(&self, section: usize) -> usize {
+        self.starts[section + 1] - self.starts[section]
+    }
+
+    fn u64_at(&self, section: usize, i: usize) -> usize {
+        read_u64(&self.bytes, self.starts[section] + 8 * i)
+    }
+
+    fn u32_at(&self, section: usize, i: usize) -> usize {
+        let at = self.starts[section] + 4 * i;
+        let mut word = [0; 4];
+        word.copy_from_slice(&self.bytes[at..at + 4]);
+        u32::from_le_bytes(word) as usize
+    }
+
+    /// Absolute byte range of entry `i` of a table.
+    fn range(&self, offsets: usize, data: usize, i: usize) -> (usize, usize) {
+        let start = self.starts[data];
+        (
+            start + self.u64_at(offsets, i),
+            start + self.u64_at(offsets, i + 1),
+        )
+    }
+
+    /// Whether `count + 1` offsets start at 0, never decrease, and end at the
+    /// end of `data`.
+    fn check_offsets(&self, offsets: usize, data: usize, count: usize) -> Option<()> {
+        let mut prev = 0;
+        for i in 0..=count {
+            let offset = self.u64_at(offsets, i);
+            if (i == 0 && offset != 0) || offset < prev {
+                return None;
+            }
+            prev = offset;
+        }
+        (prev == self.len(data)).then_some(())
+    }
+
+    /// The degree of a node if its edge list is well-formed and fills its
+    /// range exactly.
+    fn check_edges(&self, offsets: usize, data: usize, id: usize) -> Option<usize> {
+        let (start, end) = self.range(offsets, data, id);
+        let list = &self.bytes[start..end];
+        let mut pos = 0;
+        let degree = usize::try_from(read_varint(list, &mut pos)?).ok()?;
+        let mut prev = 0usize;
+        for i in 0..degree {
+            let code = read_varint(list, &mut pos)?;
+            let delta = usize::try_from(code >> 1).ok()?;
+            if i > 0 && delta == 0 {
+                return None;
+            }
+            prev = prev.checked_add(delta).filter(|&id| id < self.node_count)?;
+            if code & 1 == 1 {
+                if offsets == IN_OFFSETS {
+                    return None;
+                }
+                read_varint(list, &mut pos)?;
+            }
+        }
+        (pos == list.len()).then_some(degree)
+    }
+
+    fn string(&self, offsets: usize, data: usize, i: usize) -> &str {
+        let (start, end) = self.range(offsets, data, i);
+        std::str::from_utf8(&self.bytes[start..end]).unwrap_or_default()
+    }
+
+    fn name(&self, id: usize) -> &str {
+        self.string(NAME_OFFSETS, NAMES, id)
+    }
+
+    fn id(&self, name: &str) -> Option<usize> {
+        let (mut lo, mut hi) = (0, self.node_count);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let id = self.u32_at(SORTED, mid);
+            match self.name(id).cmp(name) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return Some(id),
+            }
+        }
+        None
+    }
+
+    fn edges(&self, offsets: usize, data: usize, id: usize) -> Edges<'_> {
+        let (start, end) = self.range(offsets, data, id);
+        let list = &self.bytes[start..end];
+        let mut pos = 0;
+        let remaining = read_varint(list, &mut pos).unwrap_or(0) as usize;
+        Edges {
+            list,
+            pos,
+            remaining,
+            prev: 0,
+            node_count: self.node_count,
+        }
+    }
+}
+
+/// The ids and weights of an edge list. A malformed list, possible only in an
+/// unchecked layout, ends early.
+struct Edges<'a> {
+    list: &'a [u8],
+    pos: usize,
+    remaining: usize,
+    prev: usize,
+    node_count: usize,
+}
+impl Edges<'_> {
+    fn decode(&mut self) -> Option<(usize, Option<i64>)> {
+        let code = read_varint(self.list, &mut self.pos)?;
+        let delta = usize::try_from(code >> 1).ok()?;
+        self.prev = self
+            .prev
+            .checked_add(delta)
+            .filter(|&id| id < self.node_count)?;
+        let weight = if code & 1 == 1 {
+            let zigzag = read_varint(self.list, &mut self.pos)?;
+            Some((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64))
+        } else {
+            None
+        };
+        Some((self.prev, weight))
+    }
+}
+impl Iterator for Edges<'_> {
+    type Item = (usize, Option<i64>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let item = self.decode();
+        self.remaining = if item.is_some() {
+            self.remaining - 1
+        } else {
+            0
+        };
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl ExactSizeIterator for Edges<'_> {}
+
+fn invalid(what: &str) -> GraphError {
+    GraphError::Serialization(format!("Invalid frozen graph: {}", what))
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> usize {
+    let mut word = [0; 8];
+    word.copy_from_slice(&bytes[at..at + 8]);
+    usize::try_from(u64::from_le_bytes(word)).unwrap_or(usize::MAX)
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// The offsets and the bytes of a string table.
+fn string_table(strings: &[&str]) -> (Vec<u8>, Vec<u8>) {
+    let mut offsets = 0u64.to_le_bytes().to_vec();
+    let mut data = Vec::new();
+    for string in strings {
+        data.extend_from_slice(string.as_bytes());
+        offsets.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    }
+    (offsets, data)
+}
+
+fn push_list(sections: &mut [Vec<u8>], offsets: usize, data: usize, list: &[u8]) {
+    let offset = sections[data].len() as u64;
+    sections[offsets].extend_from_slice(&offset.to_le_bytes());
+    sections[data].extend_from_slice(list);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithm::topsort::topsort,
+        graph::{DiGraph, DiNode},
+    };
+
+    fn sample() -> DiGraph {
+        let mut g = DiGraph::new(None);
+        g.add_node(DiNode::new("conv", Some("Conv".to_string())));
+        g.add_node(DiNode::new("relu", Some("Relu".to_string())));
+        g.add_node(DiNode::new("input", None));
+        g.add_node(DiNode::new("pool", Some("Conv".to_string())));
+        g.add_edge(Some("input"), Some("conv"));
+        g.add_weighted_edge("conv", "relu", -3);
+        g.add_weighted_edge("relu", "pool", i64::MIN);
+        g.add_edge(Some("pool"), Some("pool"));
+        g
+    }
+
+    #[test]
+    fn test_frozen_digraph() {
+        let g = sample();
+        let frozen = FrozenDiGraph::from_graph(&g).unwrap();
+        assert_eq!(frozen.node_count(), 4);
+        assert_eq!(frozen.edge_count(), 4);
+        assert_eq!(
+            frozen.node_names().collect::<Vec<_>>(),
+            vec!["conv", "relu", "input", "pool"]
+        );
+        let pool = frozen.node("pool").unwrap();
+        assert_eq!(pool.weight(), Some("Conv"));
+        assert_eq!(
+            pool.predecessor_names().collect::<Vec<_>>(),
+            vec!["relu", "pool"]
+        );
+        assert_eq!((pool.in_degree(), pool.out_degree()), (2, 1));
+        assert_eq!(frozen.node("input").unwrap().weight(), None);
+        assert!(frozen.node("nope").is_none());
+
+        assert_eq!(frozen.edge_weight("conv", "relu"), Some(-3));
+        assert_eq!(frozen.edge_weight("relu", "pool"), Some(i64::MIN));
+        assert_eq!(frozen.edge_weight("input", "conv"), None);
+        assert!(frozen.contains_edge("input", "conv"));
+        assert!(!frozen.contains_edge("conv", "input"));
+        assert_eq!(topsort(&frozen), topsort(&g));
+
+        let copy = FrozenDiGraph::from_bytes(frozen.as_bytes().to_vec()).unwrap();
+        assert_eq!(copy.as_bytes(), frozen.as_bytes());
+        assert_eq!(
+            FrozenDiGraph::from_graph(&copy).unwrap().as_bytes(),
+            frozen.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_frozen_digraph_rejects_corrupt_bytes() {
+        let bytes = FrozenDiGraph::encode(&sample()).unwrap();
+        for len in 0..bytes.len() {
+            assert!(FrozenDiGraph::from_bytes(bytes[..len].to_vec()).is_err());
+        }
+        for i in 0..bytes.len() {
+            let mut corrupt = bytes.clone();
+            corrupt[i] ^= 0xff;
+            // any outcome but a panic is fine: some bytes are free to change
+            let _ = FrozenDiGraph::from_bytes(corrupt).map(|g| topsort(&g));
+        }
+        assert!(FrozenDiGraph::from_graph(&DiGraph::new(None)).is_ok());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_frozen_digraph_map() {
+        let path = std::env::temp_dir().join("graphx_frozen_test.bin");
+        std::fs::write(&path, FrozenDiGraph::encode(&sample()).unwrap()).unwrap();
+        let frozen = FrozenDiGraph::map(&path).unwrap();
+        assert_eq!(frozen.edge_weight("conv", "relu"), Some(-3));
+        assert!(FrozenDiGraph::map(path.with_extension("missing")).is_err());
+    }
+}
@@ -0,0 +1,458 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{DiGraph, Directed, GraphBase, Node, Ported, Weighted};
+use archery::ArcTK;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+
+// a fixed hasher keeps iteration order the same on every run
+type Hamt<K, V> = rpds::HashTrieMap<K, V, ArcTK, BuildHasherDefault<DefaultHasher>>;
+type HamtSet<T> = rpds::HashTrieSet<T, ArcTK, BuildHasherDefault<DefaultHasher>>;
+
+/// A node of an [`ImDiGraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImDiNode {
+    name: String,
+    weight: Option<String>,
+    inputs: HamtSet<String>,
+    // successor name -> edge weight
+    outputs: Hamt<String, Option<i64>>,
+}
+impl ImDiNode {
+    fn new(name: &str, weight: Option<String>) -> Self {
+        ImDiNode {
+            name: name.to_string(),
+            weight,
+            inputs: HamtSet::new_with_hasher_with_ptr_kind(Default::default()),
+            outputs: Hamt::new_with_hasher_and_ptr_kind(Default::default()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn get_weight(&self) -> Option<String> {
+        self.weight.clone()
+    }
+
+    pub fn has_predecessor(&self, name: &str) -> bool {
+        self.inputs.contains(name)
+    }
+
+    pub fn has_successor(&self, name: &str) -> bool {
+        self.outputs.contains_key(name)
+    }
+}
+impl Hash for ImDiNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+impl Node for ImDiNode {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn weight(&self) -> Option<&str> {
+        self.weight.as_deref()
+    }
+
+    fn predecessor_names(&self) -> impl Iterator<Item = &str> {
+        self.inputs.iter().map(|name| name.as_str())
+    }
+
+    fn successor_names(&self) -> impl Iterator<Item = &str> {
+        self.outputs.keys().map(|name| name.as_str())
+    }
+
+    fn in_degree(&self) -> usize {
+        self.inputs.size()
+    }
+
+    fn out_degree(&self) -> usize {
+        self.outputs.size()
+    }
+}
+
+/// A persistent directed graph.
+///
+/// Every mutation returns a new graph and leaves `self` untouched. The new
+/// graph shares all unchanged nodes with the old one through hash array mapped
+/// tries, so keeping many versions around (e.g. an undo stack) is cheap and
+/// cloning is O(1).
+///
+/// Nodes are iterated in the order of the hashes of their names, which is the
+/// same on every run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImDiGraph {
+    nodes: Hamt<String, ImDiNode>,
+    edge_count: usize,
+}
+impl Default for ImDiGraph {
+    fn default() -> Self {
+        ImDiGraph::new()
+    }
+}
+impl ImDiGraph {
+    pub fn new() -> Self {
+        ImDiGraph {
+            nodes: Hamt::new_with_hasher_and_ptr_kind(Default::default()),
+            edge_count: 0,
+        }
+    }
+
+    /// Add a node, or change the weight of an existing one.
+    pub fn add_node(&self, name: &str, weight: Option<String>) -> Self {
+        let node = match self.nodes.get(name) {
+            Some(node) => ImDiNode {
+                weight,
+                ..node.clone()
+            },
+            None => ImDiNode::new(name, weight),
+        };
+        ImDiGraph {
+            nodes: self.nodes.insert(name.to_string(), node),
+            edge_count: self.edge_count,
+        }
+    }
+
+    /// Add an edge without weight, creating missing endpoints.
+    pub fn add_edge(&self, from: &str, to: &str) -> Self {
+        self.insert_edge(from, to, None)
+    }
+
+    /// Add an edge with a weight, creating missing endpoints. The weight of an
+    /// existing edge is replaced.
+    pub fn add_weighted_edge(&self, from: &str, to: &str, weight: i64) -> Self {
+        self.insert_edge(from, to, Some(weight))
+    }
+
+    fn insert_edge(&self, from: &str, to: &str, weight: Option<i64>) -> Self {
+        let mut graph = self.clone();
+        for name in [from, to] {
+            if !graph.contains_node(name) {
+                graph = graph.add_node(name, None);
+            }
+        }
+        if !graph.contains_edge(from, to) {
+            graph.edge_count += 1;
+        }
+
+        let mut source = graph.nodes.get(from).unwrap().clone();
+        source.outputs = source.outputs.insert(to.to_string(), weight);
+        graph.nodes = graph.nodes.insert(from.to_string(), source);
+
+        let mut target = graph.nodes.get(to).unwrap().clone();
+        target.inputs = target.inputs.insert(from.to_string());
+        graph.nodes = graph.nodes.insert(to.to_string(), target);
+        graph
+    }
+
+    /// Remove an edge. Removing a missing edge returns an equal graph.
+    pub fn remove_edge(&self, from: &str, to: &str) -> Self {
+        if !self.contains_edge(from, to) {
+            return self.clone();
+        }
+        let mut graph = self.clone();
+        graph.edge_count -= 1;
+
+        let mut source = graph.nodes.get(from).unwrap().clone();
+        source.outputs = source.outputs.remove(to);
+        graph.nodes = graph.nodes.insert(from.to_string(), source);
+
+        let mut target = graph.nodes.get(to).unwrap().clone();
+        target.inputs = target.inputs.remove(from);
+        graph.nodes = graph.nodes.insert(to.to_string(), target);
+        graph
+    }
+
+    /// Remove a node and all of its edges. Removing a missing node returns an
+    /// equal graph.
+    pub fn remove_node(&self, name: &str) -> Self {
+        let node = match self.nodes.get(name) {
+            Some(node) => node,
+            None => return self.clone(),
+        };
+        let mut graph = self.clone();
+        for succ in node.outputs.keys() {
+            graph = graph.remove_edge(name, succ);
+        }
+        for pred in node.inputs.iter() {
+            graph = graph.remove_edge(pred, name);
+        }
+        graph.nodes = graph.nodes.remove(name);
+        graph
+    }
+
+    pub fn get_node(&self, name: &str) -> Option<&ImDiNode> {
+        self.nodes.get(name)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &ImDiNode> {
+        self.nodes.values()
+    }
+
+    /// Iterate over all edges as `(source, target, weight)` triples.
+    pub fn weighted_edges(&self) -> impl Iterator<Item = (&str, &str, Option<i64>)> {
+        self.nodes.values().flat_map(|node| {
+            let from = node.name();
+            node.outputs
+                .iter()
+                .map(move |(to, weight)| (from, to.as_str(), *weight))
+        })
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.size()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    pub fn contains_node(&self, name: &str) -> bool {
+        self.nodes.contains_key(name)
+    }
+
+    pub fn contains_edge(&self, from: &str, to: &str) -> bool {
+        self.nodes
+            .get(from)
+            .is_some_and(|node| node.has_successor(to))
+    }
+
+    pub fn get_edge_weight(&self, from: &str, to: &str) -> Option<i64> {
+        self.nodes
+            .get(from)
+            .and_then(|node| node.outputs.get(to).copied().flatten())
+    }
+
+    /// Save the current state in O(1). The version shares all of its nodes
+    /// with the graph, so an editor can take one on every change and keep a
+    /// whole undo history around.
+    pub fn snapshot(&self) -> GraphVersion {
+        GraphVersion(self.clone())
+    }
+
+    /// Return to a saved state in O(1). Restoring an older version after
+    /// restoring a newer one is redo.
+    pub fn restore(&mut self, version: &GraphVersion) {
+        *self = version.0.clone();
+    }
+
+    /// What changed from version `a` to version `b`.
+    pub fn diff_versions(a: &GraphVersion, b: &GraphVersion) -> VersionDiff {
+        let (a, b) = (&a.0, &b.0);
+        let mut diff = VersionDiff::default();
+        for node in b.nodes.values() {
+            match a.nodes.get(node.name()) {
+                None => diff.added_nodes.push(node.name.clone()),
+                Some(old) if old.weight != node.weight => {
+                    diff.reweighted_nodes.push(node.name.clone())
+                }
+                _ => {}
+            }
+        }
+        for node in a.nodes.values() {
+            if !b.contains_node(node.name()) {
+                diff.removed_nodes.push(node.name.clone());
+            }
+        }
+        for (from, to, weight) in b.weighted_edges() {
+            let edge = (from.to_string(), to.to_string());
+            match a.nodes.get(from).and_then(|node| node.outputs.get(to)) {
+                None => diff.added_edges.push(edge),
+                Some(old) if *old != weight => diff.reweighted_edges.push(edge),
+                _ => {}
+            }
+        }
+        for (from, to, _) in a.weighted_edges() {
+            if !b.contains_edge(from, to) {
+                diff.removed_edges.push((from.to_string(), to.to_string()));
+            }
+        }
+        diff.sort();
+        diff
+    }
+
+    /// Copy into a mutable `DiGraph`.
+    pub fn to_digraph(&self) -> DiGraph {
+        let mut graph = DiGraph::new(None);
+        for node in self.nodes.values() {
+            graph.add_node(super::DiNode::new(node.name(), node.get_weight()));
+        }
+        for (from, to, weight) in self.weighted_edges() {
+            match weight {
+                Some(weight) => graph.add_weighted_edge(from, to, weight),
+                None => graph.add_edge(Some(from), Some(to)),
+            }
+        }
+        graph
+    }
+}
+/// A saved state of an [`ImDiGraph`], taken with [`ImDiGraph::snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphVersion(ImDiGraph);
+impl GraphVersion {
+    /// The graph as it was when the version was taken.
+    pub fn graph(&self) -> &ImDiGraph {
+        &self.0
+    }
+}
+
+/// The changes between two [`GraphVersion`]s, each list sorted by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionDiff {
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    /// Nodes in both versions whose weight differs.
+    pub reweighted_nodes: Vec<String>,
+    pub added_edges: Vec<(String, String)>,
+    pub removed_edges: Vec<(String, String)>,
+    /// Edges in both versions whose weight differs.
+    pub reweighted_edges: Vec<(String, String)>,
+}
+impl VersionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.reweighted_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.reweighted_edges.is_empty()
+    }
+
+    fn sort(&mut self) {
+        self.added_nodes.sort();
+        self.removed_nodes.sort();
+        self.reweighted_nodes.sort();
+        self.added_edges.sort();
+        self.removed_edges.sort();
+        self.reweighted_edges.sort();
+    }
+}
+
+impl From<&DiGraph> for ImDiGraph {
+    fn from(graph: &DiGraph) -> Self {
+        let mut im = ImDiGraph::new();
+        for node in graph.nodes() {
+            im = im.add_node(node.name(), node.get_weight());
+        }
+        for (from, to, weight) in graph.weighted_edges() {
+            im = im.insert_edge(from, to, weight);
+        }
+        im
+    }
+}
+impl GraphBase for ImDiGraph {
+    type Node = ImDiNode;
+
+    fn node_count(&self) -> usize {
+        self.nodes.size()
+    }
+
+    fn node(&self, name: &str) -> Option<&Self::Node> {
+        self.nodes.get(name)
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &Self::Node> {
+        self.nodes.values()
+    }
+}
+impl Directed for ImDiGraph {
+    fn contains_edge(&self, from: &str, to: &str) -> bool {
+        ImDiGraph::contains_edge(self, from, to)
+    }
+}
+impl Weighted for ImDiGraph {
+    fn edge_weight(&self, from: &str, to: &str) -> Option<i64> {
+        self.get_edge_weight(from, to)
+    }
+}
+impl Ported for ImDiGraph {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::topsort::topsort;
+
+    #[test]
+    fn test_imdigraph_persistence() {
+        let v0 = ImDiGraph::new()
+            .add_edge("A", "B")
+            .add_weighted_edge("B", "C", 2);
+        let v1 = v0.remove_node("B");
+        let v2 = v1.add_edge("A", "C");
+
+        assert_eq!((v0.node_count(), v0.edge_count()), (3, 2));
+        assert_eq!(topsort(&v0), vec!["A", "B", "C"]);
+        assert_eq!(v0.get_edge_weight("B", "C"), Some(2));
+
+        assert_eq!((v1.node_count(), v1.edge_count()), (2, 0));
+        assert!(!v1.get_node("C").unwrap().has_predecessor("B"));
+
+        assert_eq!((v2.node_count(), v2.edge_count()), (2, 1));
+        assert!(v2.contains_edge("A", "C") && !v0.contains_edge("A", "C"));
+        assert_eq!(v2.remove_edge("A", "C"), v1);
+        assert_eq!(v2.remove_edge("C", "A"), v2);
+    }
+
+    #[test]
+    fn test_imdigraph_versions() {
+        let mut g = ImDiGraph::new().add_edge("A", "B").add_node("C", None);
+        let v0 = g.snapshot();
+        g = g
+            .remove_node("C")
+            .add_weighted_edge("A", "B", 3)
+            .add_edge("B", "D")
+            .add_node("A", Some("conv".to_string()));
+        let v1 = g.snapshot();
+
+        let diff = ImDiGraph::diff_versions(&v0, &v1);
+        assert_eq!(diff.added_nodes, vec!["D"]);
+        assert_eq!(diff.removed_nodes, vec!["C"]);
+        assert_eq!(diff.reweighted_nodes, vec!["A"]);
+        assert_eq!(diff.added_edges, vec![("B".to_string(), "D".to_string())]);
+        assert!(diff.removed_edges.is_empty());
+        assert_eq!(
+            diff.reweighted_edges,
+            vec![("A".to_string(), "B".to_string())]
+        );
+        assert!(ImDiGraph::diff_versions(&v1, &v1).is_empty());
+
+        g.restore(&v0);
+        assert_eq!(&g, v0.graph());
+        assert!(g.contains_node("C") && !g.contains_node("D"));
+        g.restore(&v1);
+        assert_eq!(g.get_edge_weight("A", "B"), Some(3));
+        assert_eq!(ImDiGraph::diff_versions(&v1, &v0).added_nodes, vec!["C"]);
+    }
+
+    #[test]
+    fn test_imdigraph_digraph_roundtrip() {
+        let mut g = DiGraph::new(None);
+        g.add_node(crate::graph::DiNode::new("A", Some("conv".to_string())));
+        g.add_weighted_edge("A", "B", 5);
+        g.add_edge(Some("B"), Some("B"));
+
+        let im = ImDiGraph::from(&g);
+        assert_eq!(im.edge_count(), 2);
+        assert_eq!(
+            im.get_node("A").unwrap().get_weight().as_deref(),
+            Some("conv")
+        );
+        assert_eq!(im.to_digraph(), g);
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{GraphBase, Node};
+use std::collections::HashMap;
+
+/// Dense id of a node: its position in graph order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub(crate) usize);
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Node names interned as [`NodeId`]s, with adjacency lists of ids, so hot
+/// loops can follow edges without hashing names.
+///
+/// It is a snapshot of the graph it was built from: later changes to the
+/// graph are not reflected, and may shift the ids of its nodes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NodeInterner {
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    succs: Vec<Vec<NodeId>>,
+    preds: Vec<Vec<NodeId>>,
+}
+impl NodeInterner {
+    pub fn new<G: GraphBase>(graph: &G) -> Self {
+        let names: Vec<String> = graph.node_names().map(String::from).collect();
+        let ids: HashMap<String, NodeId> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.clone(), NodeId(i)))
+            .collect();
+        let mut succs = Vec::with_capacity(names.len());
+        let mut preds = Vec::with_capacity(names.len());
+        for node in graph.nodes() {
+            succs.push(node.successor_names().map(|name| ids[name]).collect());
+            preds.push(node.predecessor_names().map(|name| ids[name]).collect());
+        }
+        NodeInterner {
+            names,
+            ids,
+            succs,
+            preds,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    /// The name behind an id. Panics if the id is not from this interner.
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id.0]
+    }
+
+    /// All ids, in graph order.
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.names.len()).map(NodeId)
+    }
+
+    /// Successors of a node, in graph order of its edges.
+    pub fn successors_by_id(&self, id: NodeId) -> &[NodeId] {
+        &self.succs[id.0]
+    }
+
+    pub fn predecessors_by_id(&self, id: NodeId) -> &[NodeId] {
+        &self.preds[id.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_node_interner() {
+        let g: DiGraph = vec![("A", "B"), ("A", "C"), ("C", "B")]
+            .into_iter()
+            .collect();
+        let interner = g.interner();
+        let (a, b, c) = (
+            g.node_id("A").unwrap(),
+            g.node_id("B").unwrap(),
+            g.node_id("C").unwrap(),
+        );
+        assert_eq!(interner.id("C"), Some(c));
+        assert_eq!(c.index(), 2);
+        assert_eq!(interner.name(b), "B");
+        assert_eq!(interner.successors_by_id(a), [b, c]);
+        assert_eq!(interner.predecessors_by_id(b), [a, c]);
+        assert_eq!(interner.ids().collect::<Vec<_>>(), vec![a, b, c]);
+        assert_eq!(interner.len(), 3);
+        assert_eq!(g.node_by_id(c).unwrap().name(), "C");
+        assert_eq!(g.node_id("Z"), None);
+        assert!(NodeInterner::new(&DiGraph::new(None)).is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+/// A change to the structure of a [`DiGraph`](super::DiGraph), passed to its
+/// listeners right after it happens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphEvent<'a> {
+    NodeAdded(&'a str),
+    /// Sent after the `EdgeRemoved` events of the node's edges.
+    NodeRemoved(&'a str),
+    EdgeAdded(&'a str, &'a str),
+    EdgeRemoved(&'a str, &'a str),
+}
+
+/// Handle of a registered listener, used to remove it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(usize);
+
+type Callback = Box<dyn FnMut(&GraphEvent<'_>) + Send + Sync>;
+
+/// The listeners of one graph. A clone of a graph starts without listeners.
+#[derive(Default)]
+pub(crate) struct Listeners {
+    next_id: usize,
+    callbacks: Vec<(ListenerId, Callback)>,
+}
+impl Listeners {
+    pub(crate) fn add(&mut self, callback: Callback) -> ListenerId {
+        let id = ListenerId(self.next_id);
+        self.next_id += 1;
+        self.callbacks.push((id, callback));
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: ListenerId) -> bool {
+        let len = self.callbacks.len();
+        self.callbacks.retain(|(other, _)| *other != id);
+        self.callbacks.len() < len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.callbacks.is_empty()
+    }
+
+    /// Call every listener in registration order.
+    pub(crate) fn emit(&mut self, event: GraphEvent<'_>) {
+        for (_, callback) in self.callbacks.iter_mut() {
+            callback(&event);
+        }
+    }
+}
+impl Clone for Listeners {
+    fn clone(&self) -> Self {
+        Listeners::default()
+    }
+}
+impl fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Listeners({})", self.callbacks.len())
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::digraph::DiGraph;
+use crate::error::GraphError;
+
+/// One step towards a minor of a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MinorOp {
+    DeleteNode(String),
+    DeleteEdge(String, String),
+    /// Merge the second node into the first, see [`DiGraph::contract_edge`].
+    ContractEdge(String, String),
+}
+
+impl DiGraph {
+    /// Apply `ops` in order to a copy of the graph, leaving the graph itself
+    /// untouched. Fails with `GraphError::NotFoundNode` or
+    /// `GraphError::NotFoundEdge` at the first step whose node or edge is
+    /// missing by then.
+    pub fn minor(&self, ops: &[MinorOp]) -> Result<DiGraph, GraphError> {
+        let mut graph = self.clone();
+        for op in ops.iter() {
+            match op {
+                MinorOp::DeleteNode(name) => {
+                    graph
+                        .remove_node(name)
+                        .ok_or_else(|| GraphError::NotFoundNode(name.clone()))?;
+                }
+                MinorOp::DeleteEdge(from, to) => {
+                    if !graph.remove_edge(from, to) {
+                        return Err(GraphError::NotFoundEdge(from.clone(), to.clone()));
+                    }
+                }
+                MinorOp::ContractEdge(from, to) => graph.contract_edge(from, to)?,
+            }
+        }
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::planarity::is_planar;
+
+    #[test]
+    fn test_digraph_minor() {
+        // a 6-cycle with chords 0-3, 1-4 and 2-5 is K3,3; contracting one
+        // cycle edge leaves a planar graph on 5 nodes
+        let mut g = DiGraph::new(None);
+        for i in 0..6 {
+            let (a, b) = (i.to_string(), ((i + 1) % 6).to_string());
+            g.add_edge(Some(a.as_str()), Some(b.as_str()));
+        }
+        for i in 0..3 {
+            let (a, b) = (i.to_string(), (i + 3).to_string());
+            g.add_edge(Some(a.as_str()), Some(b.as_str()));
+        }
+        assert!(!is_planar(&g).is_planar());
+
+        let minor = g
+            .minor(&[MinorOp::ContractEdge("0".to_string(), "1".to_string())])
+            .unwrap();
+        assert_eq!(minor.node_count(), 5);
+        assert_eq!(minor.edge_count(), 8);
+        assert!(is_planar(&minor).is_planar());
+        assert_eq!(g.node_count(), 6);
+
+        let ops = [
+            MinorOp::DeleteEdge("0".to_string(), "3".to_string()),
+            MinorOp::DeleteNode("5".to_string()),
+            MinorOp::DeleteEdge("0".to_string(), "3".to_string()),
+        ];
+        assert!(matches!(
+            g.minor(&ops),
+            Err(GraphError::NotFoundEdge(from, to)) if from == "0" && to == "3"
+        ));
+        assert_eq!(g.minor(&ops[..2]).unwrap().edge_count(), 5);
+    }
+}
@@ -12,25 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::algorithm::{isomorphism::GMNode, topsort::TSortNode};
+use super::{
+    attr::{Attributed, Attributes},
+    Node,
+};
+use indexmap::IndexSet;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::fmt;
 use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
 pub struct DiNode {
     name: String,
-    inputs: HashSet<String>,
-    outputs: HashSet<String>,
+    inputs: IndexSet<String>,
+    outputs: IndexSet<String>,
     weight: Option<String>,
+    // names of the ordered input ports
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ports: Vec<String>,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    attrs: Attributes,
 }
 impl DiNode {
     pub fn new(name: &str, weight: Option<String>) -> Self {
         DiNode {
             name: name.to_string(),
-            inputs: HashSet::new(),
-            outputs: HashSet::new(),
+            inputs: IndexSet::new(),
+            outputs: IndexSet::new(),
             weight,
+            ports: Vec::new(),
+            attrs: Attributes::new(),
         }
     }
 
@@ -38,12 +49,24 @@ impl DiNode {
         self.name.clone()
     }
 
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
     pub fn set_name(&mut self, name: &str) {
         self.name = name.to_string();
     }
 
     pub fn get_predecessors(&self) -> Vec<String> {
-        self.inputs.iter().map(|name| name.clone()).collect()
+        self.inputs.iter().cloned().collect()
+    }
+
+    pub fn predecessor_names(&self) -> impl Iterator<Item = &str> {
+        self.inputs.iter().map(|name| name.as_str())
+    }
+
+    pub fn has_predecessor(&self, name: &str) -> bool {
+        self.inputs.contains(name)
     }
 
     pub fn add_predecessor(&mut self, name: &str) {
@@ -51,11 +74,19 @@ impl DiNode {
     }
 
     pub fn remove_predecessor(&mut self, name: &str) {
-        self.inputs.remove(name);
+        self.inputs.shift_remove(name);
     }
 
     pub fn get_successors(&self) -> Vec<String> {
-        self.outputs.iter().map(|name| name.clone()).collect()
+        self.outputs.iter().cloned().collect()
+    }
+
+    pub fn successor_names(&self) -> impl Iterator<Item = &str> {
+        self.outputs.iter().map(|name| name.as_str())
+    }
+
+    pub fn has_successor(&self, name: &str) -> bool {
+        self.outputs.contains(name)
     }
 
     pub fn add_successor(&mut self, name: &str) {
@@ -63,7 +94,44 @@ impl DiNode {
     }
 
     pub fn remove_successor(&mut self, name: &str) {
-        self.outputs.remove(name);
+        self.outputs.shift_remove(name);
+    }
+
+    pub(crate) fn predecessor_index(&self, name: &str) -> Option<usize> {
+        self.inputs.get_index_of(name)
+    }
+
+    pub(crate) fn predecessor_at(&self, index: usize) -> Option<&str> {
+        self.inputs.get_index(index).map(|name| name.as_str())
+    }
+
+    pub(crate) fn successor_index(&self, name: &str) -> Option<usize> {
+        self.outputs.get_index_of(name)
+    }
+
+    pub(crate) fn successor_at(&self, index: usize) -> Option<&str> {
+        self.outputs.get_index(index).map(|name| name.as_str())
+    }
+
+    /// Add a predecessor and a successor at their place in sorted neighbors,
+    /// for graphs that keep them sorted.
+    pub(crate) fn add_predecessor_sorted(&mut self, name: &str) {
+        self.inputs.insert_sorted(name.to_string());
+    }
+
+    pub(crate) fn add_successor_sorted(&mut self, name: &str) {
+        self.outputs.insert_sorted(name.to_string());
+    }
+
+    /// Sort predecessors and successors by name.
+    pub(crate) fn sort_neighbors(&mut self) {
+        self.inputs.sort();
+        self.outputs.sort();
+    }
+
+    /// Names of nodes connected to this node by an edge in either direction.
+    pub fn get_neighbors(&self) -> Vec<String> {
+        self.inputs.union(&self.outputs).cloned().collect()
     }
 
     pub fn in_degree(&self) -> usize {
@@ -74,57 +142,81 @@ impl DiNode {
         self.outputs.len()
     }
 
+    /// Sum of in-degree and out-degree.
+    pub fn all_degree(&self) -> usize {
+        self.inputs.len() + self.outputs.len()
+    }
+
     pub fn get_weight(&self) -> Option<String> {
         if self.weight.is_some() {
             return self.weight.clone();
         }
         None
     }
+
+    pub fn set_weight(&mut self, weight: Option<String>) {
+        self.weight = weight;
+    }
+
+    /// Names of the input ports, in operand order.
+    pub fn ports(&self) -> &[String] {
+        &self.ports
+    }
+
+    /// Declare the input ports, e.g. `["lhs", "rhs"]` for a binary operator.
+    pub fn set_ports<S: Into<String>>(&mut self, ports: impl IntoIterator<Item = S>) {
+        self.ports = ports.into_iter().map(Into::into).collect();
+    }
+
+    pub fn port_index(&self, port: &str) -> Option<usize> {
+        self.ports.iter().position(|name| name == port)
+    }
+}
+impl fmt::Display for DiNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.weight {
+            Some(weight) => write!(f, "{} ({})", self.name, weight),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+impl Attributed for DiNode {
+    fn attrs(&self) -> &Attributes {
+        &self.attrs
+    }
+
+    fn attrs_mut(&mut self) -> &mut Attributes {
+        &mut self.attrs
+    }
 }
 impl Hash for DiNode {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.name.hash(state);
     }
 }
-impl GMNode for DiNode {
-    fn get_name(&self) -> String {
-        self.name.clone()
+impl Node for DiNode {
+    fn name(&self) -> &str {
+        self.name.as_str()
     }
 
-    fn get_weight(&self) -> Option<String> {
-        if self.weight.is_some() {
-            return self.weight.clone();
-        }
-        None
+    fn weight(&self) -> Option<&str> {
+        self.weight.as_deref()
     }
 
-    fn semantic_equal(&self, other: &DiNode) -> bool {
-        let weight1 = self.get_weight();
-        let weight2 = other.get_weight();
-
-        if weight1.is_some() && weight2.is_some() {
-            let value1 = weight1.unwrap();
-            let value2 = weight2.unwrap();
-            if value1 != value2 {
-                return false;
-            }
-        } else if weight1.is_some() || weight2.is_some() {
-            return false;
-        }
-        true
+    fn predecessor_names(&self) -> impl Iterator<Item = &str> {
+        self.inputs.iter().map(|name| name.as_str())
     }
-}
-impl TSortNode for DiNode {
-    fn get_name(&self) -> &str {
-        self.name.as_str()
+
+    fn successor_names(&self) -> impl Iterator<Item = &str> {
+        self.outputs.iter().map(|name| name.as_str())
     }
 
     fn in_degree(&self) -> usize {
         self.inputs.len()
     }
 
-    fn get_successors(&self) -> Vec<String> {
-        self.outputs.iter().map(|x| x.clone()).collect()
+    fn out_degree(&self) -> usize {
+        self.outputs.len()
     }
 }
 
@@ -162,4 +254,35 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_node_attrs() {
+        let mut node = DiNode::new("A", None);
+        node.set_attr("layer", 3);
+        node.set_attr("shape", vec![1, 224, 224]);
+        node.set_attr("dtype", "f32");
+
+        assert_eq!(node.get_attr_as::<i64>("layer"), Some(3));
+        assert_eq!(
+            node.get_attr_as::<Vec<usize>>("shape"),
+            Some(vec![1, 224, 224])
+        );
+        assert_eq!(node.get_attr_as::<String>("dtype"), Some("f32".to_string()));
+        assert_eq!(node.get_attr_as::<bool>("dtype"), None);
+        assert_eq!(node.get_attr_as::<i64>("missing"), None);
+
+        let serialized = serde_json::to_string(&node.attrs).unwrap();
+        let actual: DiNode = serde_json::from_str(
+            format!(
+                r#"{{"name":"A","inputs":[],"outputs":[],"weight":null,"attrs":{}}}"#,
+                serialized
+            )
+            .as_str(),
+        )
+        .unwrap();
+        assert_eq!(node, actual);
+
+        assert!(node.remove_attr("layer").is_some());
+        assert_eq!(node.get_attr("layer"), None);
+    }
 }
@@ -0,0 +1,246 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{DiGraph, DiNode, Directed, GraphBase, Node, Ported, Weighted};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+/// A directed graph whose edges may be active only during a closed time
+/// interval. Edges added without a time are active at all times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemporalDiGraph {
+    graph: DiGraph,
+    // (source, target) -> (start, end)
+    intervals: HashMap<(String, String), (i64, i64)>,
+}
+impl Default for TemporalDiGraph {
+    fn default() -> Self {
+        TemporalDiGraph::new()
+    }
+}
+impl TemporalDiGraph {
+    pub fn new() -> Self {
+        TemporalDiGraph {
+            graph: DiGraph::new(None),
+            intervals: HashMap::new(),
+        }
+    }
+
+    /// All nodes and edges, regardless of time.
+    pub fn graph(&self) -> &DiGraph {
+        &self.graph
+    }
+
+    pub fn add_node(&mut self, node: DiNode) {
+        self.graph.add_node(node);
+    }
+
+    /// Add an edge active at all times, creating missing endpoints.
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        self.graph.add_edge(Some(from), Some(to));
+        self.intervals.remove(&(from.to_string(), to.to_string()));
+    }
+
+    /// Add an edge active from `start` to `end`, both included, creating missing
+    /// endpoints. Adding an existing edge replaces its interval. An edge whose
+    /// `start` is after its `end` is never active.
+    pub fn add_timed_edge(&mut self, from: &str, to: &str, start: i64, end: i64) {
+        self.graph.add_edge(Some(from), Some(to));
+        self.intervals
+            .insert((from.to_string(), to.to_string()), (start, end));
+    }
+
+    /// Like `add_timed_edge`, with a weight.
+    pub fn add_weighted_timed_edge(
+        &mut self,
+        from: &str,
+        to: &str,
+        weight: i64,
+        start: i64,
+        end: i64,
+    ) {
+        self.graph.add_weighted_edge(from, to, weight);
+        self.intervals
+            .insert((from.to_string(), to.to_string()), (start, end));
+    }
+
+    /// The interval of an edge, or `None` if the edge is missing or always
+    /// active.
+    pub fn interval(&self, from: &str, to: &str) -> Option<(i64, i64)> {
+        self.intervals
+            .get(&(from.to_string(), to.to_string()))
+            .copied()
+    }
+
+    /// Remove an edge with its interval, returning whether it existed.
+    pub fn remove_edge(&mut self, from: &str, to: &str) -> bool {
+        self.intervals.remove(&(from.to_string(), to.to_string()));
+        self.graph.remove_edge(from, to)
+    }
+
+    /// Remove a node with its edges and their intervals.
+    pub fn remove_node(&mut self, name: &str) -> Option<DiNode> {
+        let node = self.graph.remove_node(name)?;
+        self.intervals
+            .retain(|(from, to), _| from != name && to != name);
+        Some(node)
+    }
+
+    /// The graph as of the time range from `t0` to `t1`, both included: every
+    /// node, and the edges active at some point in the range. The view
+    /// implements [`Graph`], so any algorithm can run on it.
+    pub fn window(&self, t0: i64, t1: i64) -> TemporalWindow<'_> {
+        let active = |from: &str, to: &str| match self.interval(from, to) {
+            Some((start, end)) => start <= t1 && t0 <= end && start <= end,
+            None => true,
+        };
+        let mut nodes: IndexMap<&str, WindowNode> = self
+            .graph
+            .nodes()
+            .map(|node| {
+                let window_node = WindowNode {
+                    name: node.name(),
+                    weight: node.weight(),
+                    preds: Vec::new(),
+                    succs: Vec::new(),
+                };
+                (node.name(), window_node)
+            })
+            .collect();
+        for node in self.graph.nodes() {
+            for to in node.successor_names() {
+                if active(node.name(), to) {
+                    nodes[node.name()].succs.push(to);
+                    nodes[to].preds.push(node.name());
+                }
+            }
+        }
+        TemporalWindow {
+            graph: &self.graph,
+            nodes,
+        }
+    }
+
+    /// The graph as of the single instant `t`.
+    pub fn at(&self, t: i64) -> TemporalWindow<'_> {
+        self.window(t, t)
+    }
+}
+
+/// A node of a [`TemporalWindow`], with only the edges active in the window.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct WindowNode<'a> {
+    name: &'a str,
+    weight: Option<&'a str>,
+    preds: Vec<&'a str>,
+    succs: Vec<&'a str>,
+}
+impl Node for WindowNode<'_> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn weight(&self) -> Option<&str> {
+        self.weight
+    }
+
+    fn predecessor_names(&self) -> impl Iterator<Item = &str> {
+        self.preds.iter().copied()
+    }
+
+    fn successor_names(&self) -> impl Iterator<Item = &str> {
+        self.succs.iter().copied()
+    }
+
+    fn in_degree(&self) -> usize {
+        self.preds.len()
+    }
+
+    fn out_degree(&self) -> usize {
+        self.succs.len()
+    }
+}
+
+/// A time-windowed view of a [`TemporalDiGraph`], made by
+/// [`TemporalDiGraph::window`].
+#[derive(Debug)]
+pub struct TemporalWindow<'a> {
+    graph: &'a DiGraph,
+    nodes: IndexMap<&'a str, WindowNode<'a>>,
+}
+impl<'a> GraphBase for TemporalWindow<'a> {
+    type Node = WindowNode<'a>;
+
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node(&self, name: &str) -> Option<&Self::Node> {
+        self.nodes.get(name)
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &Self::Node> {
+        self.nodes.values()
+    }
+}
+impl<'a> Directed for TemporalWindow<'a> {}
+impl<'a> Weighted for TemporalWindow<'a> {
+    fn edge_weight(&self, from: &str, to: &str) -> Option<i64> {
+        if self.contains_edge(from, to) {
+            self.graph.get_edge_weight(from, to)
+        } else {
+            None
+        }
+    }
+}
+impl<'a> Ported for TemporalWindow<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{components::weakly_connected_components, sssp::bellman_ford};
+
+    #[test]
+    fn test_temporal_window() {
+        let mut g = TemporalDiGraph::new();
+        g.add_weighted_timed_edge("A", "B", 1, 0, 10);
+        g.add_weighted_timed_edge("B", "C", 1, 5, 5);
+        g.add_weighted_timed_edge("A", "C", 7, 0, 3);
+        g.add_edge("C", "D");
+        g.add_timed_edge("D", "E", 9, 2);
+        assert_eq!(g.interval("B", "C"), Some((5, 5)));
+        assert_eq!(g.interval("C", "D"), None);
+
+        let early = g.window(0, 4);
+        assert_eq!(early.node_count(), 5);
+        assert!(early.contains_edge("A", "C") && !early.contains_edge("B", "C"));
+        assert!(!early.contains_edge("D", "E"));
+        assert_eq!(bellman_ford(&early, "A").unwrap()["C"], 7);
+        assert_eq!(early.node("C").unwrap().in_degree(), 1);
+
+        let late = g.at(5);
+        assert!(!late.contains_edge("A", "C"));
+        assert_eq!(late.edge_weight("A", "C"), None);
+        assert_eq!(bellman_ford(&late, "A").unwrap()["D"], 3);
+
+        let all = g.window(i64::MIN, i64::MAX);
+        assert_eq!(bellman_ford(&all, "A").unwrap()["C"], 2);
+        assert_eq!(weakly_connected_components(&g.at(20)).len(), 4);
+
+        g.remove_node("B");
+        assert_eq!(g.interval("A", "B"), None);
+        assert!(g.remove_edge("A", "C"));
+        assert_eq!(g.interval("A", "C"), None);
+    }
+}
@@ -0,0 +1,502 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    attr::{Attributed, Attributes},
+    digraph::DiGraph,
+    edge::EdgeData,
+    node::DiNode,
+    Directed, GraphBase, Node, Ported, Weighted,
+};
+use crate::error::GraphError;
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+
+/// A node of an [`UnGraph`].
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+pub struct UnNode {
+    name: String,
+    neighbors: IndexSet<String>,
+    weight: Option<String>,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    attrs: Attributes,
+}
+impl UnNode {
+    pub fn new(name: &str, weight: Option<String>) -> Self {
+        UnNode {
+            name: name.to_string(),
+            neighbors: IndexSet::new(),
+            weight,
+            attrs: Attributes::new(),
+        }
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn get_neighbors(&self) -> Vec<String> {
+        self.neighbors.iter().cloned().collect()
+    }
+
+    pub fn neighbor_names(&self) -> impl Iterator<Item = &str> {
+        self.neighbors.iter().map(|name| name.as_str())
+    }
+
+    pub fn has_neighbor(&self, name: &str) -> bool {
+        self.neighbors.contains(name)
+    }
+
+    /// Number of edges at this node; a self-loop counts twice.
+    pub fn degree(&self) -> usize {
+        self.neighbors.len() + usize::from(self.has_neighbor(self.name.as_str()))
+    }
+
+    pub fn get_weight(&self) -> Option<String> {
+        self.weight.clone()
+    }
+}
+impl fmt::Display for UnNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.weight {
+            Some(weight) => write!(f, "{} ({})", self.name, weight),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+impl Attributed for UnNode {
+    fn attrs(&self) -> &Attributes {
+        &self.attrs
+    }
+
+    fn attrs_mut(&mut self) -> &mut Attributes {
+        &mut self.attrs
+    }
+}
+impl Hash for UnNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+/// Every neighbor is both a predecessor and a successor, so algorithms written
+/// for directed graphs see each undirected edge as a pair of opposite arcs.
+impl Node for UnNode {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn weight(&self) -> Option<&str> {
+        self.weight.as_deref()
+    }
+
+    fn predecessor_names(&self) -> impl Iterator<Item = &str> {
+        self.neighbor_names()
+    }
+
+    fn successor_names(&self) -> impl Iterator<Item = &str> {
+        self.neighbor_names()
+    }
+
+    fn in_degree(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    fn out_degree(&self) -> usize {
+        self.neighbors.len()
+    }
+}
+
+/// An undirected graph whose nodes are identified by name.
+///
+/// Nodes, neighbors and edges iterate in insertion order. A self-loop makes a
+/// node its own neighbor and adds two to its degree.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UnGraph {
+    name: Option<String>,
+    nodes: IndexMap<String, UnNode>,
+    // edge data keyed by the smaller and then the larger endpoint name; edges
+    // without data are not stored
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    edges: IndexMap<String, IndexMap<String, EdgeData>>,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    attrs: Attributes,
+}
+impl UnGraph {
+    pub fn new(name: Option<String>) -> Self {
+        UnGraph {
+            name,
+            ..UnGraph::default()
+        }
+    }
+
+    pub fn get_name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    pub fn set_name(&mut self, name: Option<&str>) {
+        self.name = name.map(String::from);
+    }
+
+    /// Add a node, replacing the weight and attributes of an existing node of
+    /// the same name but keeping its edges.
+    pub fn add_node(&mut self, mut node: UnNode) {
+        match self.nodes.get_mut(node.name()) {
+            Some(old) => {
+                old.weight = node.weight.take();
+                old.attrs = std::mem::take(&mut node.attrs);
+            }
+            None => {
+                node.neighbors.clear();
+                self.nodes.insert(node.get_name(), node);
+            }
+        }
+    }
+
+    /// Add an edge between `a` and `b`, creating missing nodes.
+    pub fn add_edge(&mut self, a: &str, b: &str) {
+        for (from, to) in [(a, b), (b, a)] {
+            self.nodes
+                .entry(from.to_string())
+                .or_insert_with(|| UnNode::new(from, None))
+                .neighbors
+                .insert(to.to_string());
+        }
+    }
+
+    pub fn add_weighted_edge(&mut self, a: &str, b: &str, weight: i64) {
+        self.add_edge(a, b);
+        self.get_edge_data_mut(a, b)
+            .unwrap()
+            .set_weight(Some(weight));
+    }
+
+    /// Remove the edge between `a` and `b`, returning whether it existed.
+    pub fn remove_edge(&mut self, a: &str, b: &str) -> bool {
+        if !self.contains_edge(a, b) {
+            return false;
+        }
+        self.nodes.get_mut(a).unwrap().neighbors.shift_remove(b);
+        self.nodes.get_mut(b).unwrap().neighbors.shift_remove(a);
+        let (first, second) = key(a, b);
+        if let Some(targets) = self.edges.get_mut(first) {
+            targets.shift_remove(second);
+            if targets.is_empty() {
+                self.edges.shift_remove(first);
+            }
+        }
+        true
+    }
+
+    /// Remove a node and its edges, returning the node if it existed.
+    pub fn remove_node(&mut self, name: &str) -> Option<UnNode> {
+        let node = self.nodes.shift_remove(name)?;
+        for nbr in node.neighbor_names() {
+            if nbr != name {
+                self.nodes
+                    .get_mut(nbr)
+                    .unwrap()
+                    .neighbors
+                    .shift_remove(name);
+                let (first, second) = key(name, nbr);
+                if let Some(targets) = self.edges.get_mut(first) {
+                    targets.shift_remove(second);
+                }
+            }
+        }
+        self.edges.shift_remove(name);
+        self.edges.retain(|_, targets| !targets.is_empty());
+        Some(node)
+    }
+
+    pub fn get_edge_data(&self, a: &str, b: &str) -> Option<&EdgeData> {
+        let (first, second) = key(a, b);
+        self.edges
+            .get(first)
+            .and_then(|targets| targets.get(second))
+    }
+
+    /// Mutable access to the data of an existing edge, creating it on first use.
+    pub fn get_edge_data_mut(&mut self, a: &str, b: &str) -> Option<&mut EdgeData> {
+        if !self.contains_edge(a, b) {
+            return None;
+        }
+        let (first, second) = key(a, b);
+        Some(
+            self.edges
+                .entry(first.to_string())
+                .or_default()
+                .entry(second.to_string())
+                .or_default(),
+        )
+    }
+
+    pub fn get_edge_weight(&self, a: &str, b: &str) -> Option<i64> {
+        self.get_edge_data(a, b).and_then(|data| data.get_weight())
+    }
+
+    /// Iterate over all edges once each, as `(a, b)` pairs with `a` added to
+    /// the graph no later than `b`.
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.nodes.values().enumerate().flat_map(move |(i, node)| {
+            let a = node.name();
+            node.neighbor_names()
+                .filter(move |&b| self.nodes.get_index_of(b).unwrap() >= i)
+                .map(move |b| (a, b))
+        })
+    }
+
+    /// Iterate over all edges once each as `(a, b, weight)` triples.
+    pub fn weighted_edges(&self) -> impl Iterator<Item = (&str, &str, Option<i64>)> {
+        self.edges()
+            .map(move |(a, b)| (a, b, self.get_edge_weight(a, b)))
+    }
+
+    /// Total number of edges in the graph.
+    pub fn edge_count(&self) -> usize {
+        self.nodes.values().map(|node| node.degree()).sum::<usize>() / 2
+    }
+
+    pub fn get_node(&self, name: &str) -> Option<&UnNode> {
+        self.nodes.get(name)
+    }
+
+    /// Mutable access to a node's weight and attributes.
+    pub fn get_node_mut(&mut self, name: &str) -> Option<&mut UnNode> {
+        self.nodes.get_mut(name)
+    }
+
+    pub fn get_nodes(&self) -> Vec<String> {
+        self.nodes.keys().cloned().collect()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &UnNode> {
+        self.nodes.values()
+    }
+
+    pub fn node_names(&self) -> impl Iterator<Item = &str> {
+        self.nodes.keys().map(|name| name.as_str())
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn neighbors(&self, name: &str) -> Result<Vec<&UnNode>, GraphError> {
+        let node = self
+            .nodes
+            .get(name)
+            .ok_or_else(|| GraphError::NotFoundNode(String::from(name)))?;
+        Ok(node
+            .neighbor_names()
+            .map(|nbr| self.nodes.get(nbr).unwrap())
+            .collect())
+    }
+
+    /// Number of edges at a node; a self-loop counts twice.
+    pub fn degree(&self, name: &str) -> Result<usize, GraphError> {
+        self.nodes
+            .get(name)
+            .map(|node| node.degree())
+            .ok_or_else(|| GraphError::NotFoundNode(String::from(name)))
+    }
+
+    pub fn contains_node(&self, name: &str) -> bool {
+        self.nodes.contains_key(name)
+    }
+
+    pub fn contains_edge(&self, a: &str, b: &str) -> bool {
+        self.nodes.get(a).is_some_and(|node| node.has_neighbor(b))
+    }
+
+    /// Convert to a directed graph with an arc in both directions for every
+    /// edge, each carrying a copy of the edge's data; a self-loop becomes a
+    /// single arc. The name, attributes and nodes are copied as they are.
+    pub fn to_directed(&self) -> DiGraph {
+        let mut graph = DiGraph::new(self.name.clone());
+        *graph.attrs_mut() = self.attrs.clone();
+        for node in self.nodes.values() {
+            let mut copy = DiNode::new(node.name(), node.get_weight());
+            *copy.attrs_mut() = node.attrs.clone();
+            graph.add_node(copy);
+        }
+        for (a, b) in self.edges() {
+            for (from, to) in [(a, b), (b, a)] {
+                graph.add_edge(Some(from), Some(to));
+                if let Some(data) = self.get_edge_data(a, b) {
+                    *graph.get_edge_data_mut(from, to).unwrap() = data.clone();
+                }
+            }
+        }
+        graph
+    }
+}
+
+/// The endpoints of an edge in the order its data is stored under.
+fn key<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+impl PartialEq for UnGraph {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.nodes == other.nodes
+            && self.edges == other.edges
+            && self.attrs == other.attrs
+    }
+}
+impl Eq for UnGraph {}
+impl Attributed for UnGraph {
+    fn attrs(&self) -> &Attributes {
+        &self.attrs
+    }
+
+    fn attrs_mut(&mut self) -> &mut Attributes {
+        &mut self.attrs
+    }
+}
+impl fmt::Display for UnGraph {
+    /// One line per node in name order, e.g. `A (weight) -- [B: 3, C]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = &self.name {
+            writeln!(f, "{}:", name)?;
+        }
+
+        let mut names = self.get_nodes();
+        names.sort();
+        for name in names.iter() {
+            let node = self.nodes.get(name).unwrap();
+            let mut neighbors = node.get_neighbors();
+            neighbors.sort();
+            let neighbors: Vec<String> = neighbors
+                .into_iter()
+                .map(|nbr| match self.get_edge_weight(name, nbr.as_str()) {
+                    Some(weight) => format!("{}: {}", nbr, weight),
+                    None => nbr,
+                })
+                .collect();
+            writeln!(f, "{} -- [{}]", node, neighbors.join(", "))?;
+        }
+        Ok(())
+    }
+}
+impl<'a> FromIterator<(&'a str, &'a str)> for UnGraph {
+    fn from_iter<I: IntoIterator<Item = (&'a str, &'a str)>>(iter: I) -> Self {
+        let mut graph = UnGraph::new(None);
+        for (a, b) in iter {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+}
+impl GraphBase for UnGraph {
+    type Node = UnNode;
+
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node(&self, name: &str) -> Option<&UnNode> {
+        self.nodes.get(name)
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &UnNode> {
+        self.nodes.values()
+    }
+
+    fn node_names(&self) -> impl Iterator<Item = &str> {
+        self.nodes.keys().map(|name| name.as_str())
+    }
+}
+impl Directed for UnGraph {
+    fn contains_edge(&self, from: &str, to: &str) -> bool {
+        UnGraph::contains_edge(self, from, to)
+    }
+}
+impl Weighted for UnGraph {
+    fn edge_weight(&self, from: &str, to: &str) -> Option<i64> {
+        self.get_edge_weight(from, to)
+    }
+}
+impl Ported for UnGraph {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ungraph_edges() {
+        let mut g: UnGraph = vec![("A", "B"), ("C", "B"), ("C", "C")]
+            .into_iter()
+            .collect();
+        g.add_weighted_edge("B", "A", 4);
+
+        assert_eq!(g.node_count(), 3);
+        assert_eq!(g.edge_count(), 3);
+        assert!(g.contains_edge("B", "C"));
+        assert_eq!(g.get_edge_weight("A", "B"), Some(4));
+        assert_eq!(
+            g.edges().collect::<Vec<_>>(),
+            vec![("A", "B"), ("B", "C"), ("C", "C")]
+        );
+        assert_eq!(g.degree("C").unwrap(), 3);
+        assert!(g.degree("D").is_err());
+        assert_eq!(g.to_string(), "A -- [B: 4]\nB -- [A: 4, C]\nC -- [B, C]\n");
+
+        assert!(g.remove_edge("B", "A"));
+        assert!(!g.remove_edge("B", "A"));
+        assert_eq!(g.get_edge_weight("A", "B"), None);
+        assert!(g.remove_node("C").is_some());
+        assert_eq!(g.edge_count(), 0);
+        assert_eq!(g.get_node("B").unwrap().degree(), 0);
+    }
+
+    #[test]
+    fn test_ungraph_to_directed() {
+        let mut g: UnGraph = vec![("A", "B"), ("C", "C")].into_iter().collect();
+        g.add_weighted_edge("B", "C", 3);
+        g.add_node(UnNode::new("D", Some("d".to_string())));
+
+        let d = g.to_directed();
+        assert_eq!(d.edge_count(), 5);
+        assert_eq!(d.get_edge_weight("B", "C"), Some(3));
+        assert_eq!(d.get_edge_weight("C", "B"), Some(3));
+        assert!(d.contains_edge("B", "A"));
+        assert_eq!(d.get_node("D").unwrap().get_weight(), Some("d".to_string()));
+
+        // and back again
+        assert_eq!(d.to_undirected(crate::graph::WeightPolicy::Max), g);
+    }
+
+    #[test]
+    fn test_ungraph_json() {
+        let mut g = UnGraph::new(Some("g".to_string()));
+        g.add_node(UnNode::new("A", Some("a".to_string())));
+        g.add_weighted_edge("A", "B", 2);
+
+        let json = serde_json::to_string(&g).unwrap();
+        let actual: UnGraph = serde_json::from_str(json.as_str()).unwrap();
+        assert_eq!(g, actual);
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Walkers: iterators that take the graph at every step instead of borrowing
+//! it, so it can be changed in between, e.g. to remove the nodes or edges
+//! walked over.
+
+use super::{interner::NodeId, DiGraph};
+
+impl DiGraph {
+    /// Walk all nodes in graph order.
+    pub fn node_walker(&self) -> NodeWalker {
+        NodeWalker {
+            cursor: Cursor::default(),
+        }
+    }
+
+    /// Walk the successors of a node in order. The walk ends early if the node
+    /// is removed.
+    pub fn successor_walker(&self, name: &str) -> NeighborWalker {
+        NeighborWalker {
+            node: name.to_string(),
+            outgoing: true,
+            cursor: Cursor::default(),
+        }
+    }
+
+    /// Walk the predecessors of a node in order. The walk ends early if the
+    /// node is removed.
+    pub fn predecessor_walker(&self, name: &str) -> NeighborWalker {
+        NeighborWalker {
+            node: name.to_string(),
+            outgoing: false,
+            cursor: Cursor::default(),
+        }
+    }
+}
+
+/// Walks the nodes of a [`DiGraph`], made by [`DiGraph::node_walker`].
+///
+/// Every step goes on after the node returned last, in the graph as it is at
+/// that step, so nodes removed before being reached are skipped and nodes
+/// added at the end are visited. If the node returned last was removed, the
+/// walk goes on from where it stood, which is exact as long as no node before
+/// it was removed too.
+#[derive(Debug, Clone)]
+pub struct NodeWalker {
+    cursor: Cursor,
+}
+impl NodeWalker {
+    pub fn walk_next(&mut self, graph: &DiGraph) -> Option<String> {
+        self.cursor.step(
+            |name| graph.node_id(name).map(NodeId::index),
+            |i| graph.node_by_id(NodeId(i)).map(|node| node.name()),
+        )
+    }
+}
+
+/// Walks the successors or predecessors of a node, made by
+/// [`DiGraph::successor_walker`] or [`DiGraph::predecessor_walker`], with the
+/// same guarantees as a [`NodeWalker`].
+#[derive(Debug, Clone)]
+pub struct NeighborWalker {
+    node: String,
+    outgoing: bool,
+    cursor: Cursor,
+}
+impl NeighborWalker {
+    pub fn walk_next(&mut self, graph: &DiGraph) -> Option<String> {
+        let node = graph.get_node(&self.node)?;
+        if self.outgoing {
+            self.cursor
+                .step(|name| node.successor_index(name), |i| node.successor_at(i))
+        } else {
+            self.cursor.step(
+                |name| node.predecessor_index(name),
+                |i| node.predecessor_at(i),
+            )
+        }
+    }
+}
+
+/// The name returned last and where it stood.
+#[derive(Debug, Clone, Default)]
+struct Cursor {
+    last: Option<String>,
+    position: usize,
+}
+impl Cursor {
+    fn step<'a>(
+        &mut self,
+        index_of: impl Fn(&str) -> Option<usize>,
+        at: impl Fn(usize) -> Option<&'a str>,
+    ) -> Option<String> {
+        let position = match self.last.as_deref() {
+            // if the last one is gone, the next one has moved into its place
+            Some(last) => index_of(last).map_or(self.position, |i| i + 1),
+            None => 0,
+        };
+        let name = at(position)?.to_string();
+        self.position = position;
+        self.last = Some(name.clone());
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{DiGraph, DiNode};
+
+    #[test]
+    fn test_successor_walker() {
+        let mut g: DiGraph = vec![("a", "b"), ("a", "c"), ("a", "d"), ("a", "e")]
+            .into_iter()
+            .collect();
+        let mut walker = g.successor_walker("a");
+        let mut seen = Vec::new();
+        while let Some(succ) = walker.walk_next(&g) {
+            if succ == "b" || succ == "c" {
+                g.remove_edge("a", &succ);
+            }
+            seen.push(succ);
+        }
+        assert_eq!(seen, vec!["b", "c", "d", "e"]);
+        assert_eq!(g.get_node("a").unwrap().get_successors(), vec!["d", "e"]);
+
+        let mut walker = g.predecessor_walker("e");
+        assert_eq!(walker.walk_next(&g), Some("a".to_string()));
+        assert_eq!(walker.walk_next(&g), None);
+
+        let mut walker = g.successor_walker("a");
+        walker.walk_next(&g);
+        g.remove_node("a");
+        assert_eq!(walker.walk_next(&g), None);
+    }
+
+    #[test]
+    fn test_node_walker() {
+        let mut g: DiGraph = vec![("a", "b"), ("b", "c"), ("c", "d")]
+            .into_iter()
+            .collect();
+        let mut walker = g.node_walker();
+        let mut seen = Vec::new();
+        while let Some(name) = walker.walk_next(&g) {
+            match name.as_str() {
+                "a" => g.add_node(DiNode::new("e", None)),
+                "b" => {
+                    g.remove_node("b");
+                    g.remove_node("d");
+                }
+                _ => {}
+            }
+            seen.push(name);
+        }
+        assert_eq!(seen, vec!["a", "b", "c", "e"]);
+        assert_eq!(g.get_nodes(), vec!["a", "c", "e"]);
+    }
+}
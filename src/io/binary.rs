@@ -0,0 +1,117 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compact binary encoding of graphs in MessagePack, with optional gzip
+//! (`gzip` feature) and zstd (`zstd` feature) compression.
+
+use crate::{error::GraphError, graph::DiGraph};
+
+impl DiGraph {
+    /// Encode the graph as MessagePack. Like the JSON form, the weight index
+    /// is not stored.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, GraphError> {
+        rmp_serde::to_vec_named(self).map_err(|err| GraphError::Serialization(err.to_string()))
+    }
+
+    /// Decode a graph produced by [`DiGraph::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<DiGraph, GraphError> {
+        rmp_serde::from_slice(bytes).map_err(|err| GraphError::Serialization(err.to_string()))
+    }
+}
+
+/// Encode the graph as gzip-compressed MessagePack.
+#[cfg(feature = "gzip")]
+pub fn to_gzip(graph: &DiGraph) -> Result<Vec<u8>, GraphError> {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(graph.to_bytes()?.as_slice())
+        .and_then(|_| encoder.finish())
+        .map_err(|err| GraphError::Serialization(err.to_string()))
+}
+
+/// Decode a graph produced by [`to_gzip`].
+#[cfg(feature = "gzip")]
+pub fn from_gzip(bytes: &[u8]) -> Result<DiGraph, GraphError> {
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    let mut decoded = Vec::new();
+    GzDecoder::new(bytes)
+        .read_to_end(&mut decoded)
+        .map_err(|err| GraphError::Serialization(err.to_string()))?;
+    DiGraph::from_bytes(decoded.as_slice())
+}
+
+/// Encode the graph as zstd-compressed MessagePack.
+#[cfg(feature = "zstd")]
+pub fn to_zstd(graph: &DiGraph) -> Result<Vec<u8>, GraphError> {
+    zstd::encode_all(graph.to_bytes()?.as_slice(), 0)
+        .map_err(|err| GraphError::Serialization(err.to_string()))
+}
+
+/// Decode a graph produced by [`to_zstd`].
+#[cfg(feature = "zstd")]
+pub fn from_zstd(bytes: &[u8]) -> Result<DiGraph, GraphError> {
+    let decoded =
+        zstd::decode_all(bytes).map_err(|err| GraphError::Serialization(err.to_string()))?;
+    DiGraph::from_bytes(decoded.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Attributed, DiNode};
+
+    fn sample() -> DiGraph {
+        let mut g = DiGraph::new(Some("sample".to_string()));
+        g.add_node(DiNode::new("A", Some("conv".to_string())));
+        g.add_weighted_edge("A", "B", -3);
+        g.add_edge(Some("B"), Some("C"));
+        g.set_attr("version", 2);
+        g.get_node_mut("C").unwrap().set_attr("tags", vec!["out"]);
+        g
+    }
+
+    #[test]
+    fn binary_roundtrip_test() {
+        let g = sample();
+        let bytes = g.to_bytes().unwrap();
+        assert_eq!(DiGraph::from_bytes(bytes.as_slice()).unwrap(), g);
+        assert!(bytes.len() < serde_json::to_vec(&g).unwrap().len());
+
+        assert!(matches!(
+            DiGraph::from_bytes(&[0xc1]),
+            Err(GraphError::Serialization(_))
+        ));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_roundtrip_test() {
+        let g = sample();
+        assert_eq!(from_gzip(to_gzip(&g).unwrap().as_slice()).unwrap(), g);
+        assert!(from_gzip(&[1, 2, 3]).is_err());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_roundtrip_test() {
+        let g = sample();
+        assert_eq!(from_zstd(to_zstd(&g).unwrap().as_slice()).unwrap(), g);
+        assert!(from_zstd(&[1, 2, 3]).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    error::{GraphError, ResultExt},
+    graph::{DiGraph, DiNode},
+};
+
+/// Most nodes a DIMACS file may declare, as they are all created up front.
+pub const MAX_DIMACS_NODES: usize = 1 << 24;
+
+/// Parse an undirected graph in the DIMACS format of the clique and coloring
+/// benchmarks.
+///
+/// `c` lines are comments; the `p edge <n> <m>` line (or `p col`) declares
+/// nodes `1..=n`, named by their id, and every `e <u> <v>` line adds an edge
+/// in both directions. Fails with `GraphError::NotFoundNode` for edges to ids
+/// outside `1..=n`, with the line as context, and `GraphError::Parse` for
+/// malformed lines and for `n` above [`MAX_DIMACS_NODES`].
+pub fn from_dimacs(dimacs: &str) -> Result<DiGraph, GraphError> {
+    let mut graph = DiGraph::new(None);
+    let mut n = None;
+
+    for (i, line) in dimacs.lines().enumerate() {
+        let invalid = || GraphError::Parse {
+            line: i + 1,
+            column: 1,
+            message: format!("invalid DIMACS line {:?}", line),
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [] | ["c", ..] => {}
+            ["p", _, field, _] if n.is_none() => {
+                let count: usize = field.parse().map_err(|_| invalid())?;
+                if count > MAX_DIMACS_NODES {
+                    return Err(GraphError::Parse {
+                        line: i + 1,
+                        column: field.as_ptr() as usize - line.as_ptr() as usize + 1,
+                        message: format!(
+                            "{} nodes declared, more than the {} supported",
+                            count, MAX_DIMACS_NODES
+                        ),
+                    });
+                }
+                for v in 1..=count {
+                    graph.add_node(DiNode::new(v.to_string().as_str(), None));
+                }
+                n = Some(count);
+            }
+            ["e", u, v] | ["e", u, v, _] => {
+                let n = n.ok_or_else(invalid)?;
+                for id in [u, v] {
+                    match id.parse::<usize>() {
+                        Ok(id) if (1..=n).contains(&id) => {}
+                        Ok(_) => {
+                            return Err(GraphError::NotFoundNode(id.to_string()))
+                                .context(format!("line {}", i + 1))
+                        }
+                        Err(_) => return Err(invalid()),
+                    }
+                }
+                graph.add_edge(Some(u), Some(v));
+                graph.add_edge(Some(v), Some(u));
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_dimacs() {
+        let dimacs = "c a triangle\np edge 3 3\ne 1 2\ne 2 3\ne 3 1\n";
+        let g = from_dimacs(dimacs).unwrap();
+        assert_eq!(g.node_count(), 3);
+        assert_eq!(g.edge_count(), 6);
+        assert!(g.contains_edge("2", "1"));
+
+        let err = from_dimacs("p edge 2 1\ne 1 3\n").unwrap_err();
+        assert!(matches!(err.root_cause(), GraphError::NotFoundNode(id) if id == "3"));
+        assert_eq!(err.to_string(), "line 2");
+        assert!(matches!(
+            from_dimacs("c\ne 1 2\n"),
+            Err(GraphError::Parse { line: 2, .. })
+        ));
+
+        // the declared nodes are created up front, so their count is bounded
+        assert!(matches!(
+            from_dimacs(
+                "p edge 18446744073709551615 1
+e 1 2
+"
+            ),
+            Err(GraphError::Parse {
+                line: 1,
+                column: 8,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{graph::DiGraph, layout::Position};
+use std::collections::HashMap;
+
+// points per layout unit
+const POINTS_PER_UNIT: f64 = 72.0;
+
+/// Render a graph in the Graphviz DOT language.
+///
+/// Nodes and edges are emitted in name order so the output is stable. Node
+/// weights and edge weights become labels.
+pub fn to_dot(graph: &DiGraph) -> String {
+    render(graph, None)
+}
+
+/// Like [`to_dot`], but pin every node with a position to it, one layout unit
+/// per inch, so `neato -n` draws the graph without computing its own layout.
+pub fn to_dot_with_positions(graph: &DiGraph, positions: &HashMap<String, Position>) -> String {
+    render(graph, Some(positions))
+}
+
+fn render(graph: &DiGraph, positions: Option<&HashMap<String, Position>>) -> String {
+    let mut dot = String::new();
+    match graph.get_name() {
+        Some(name) => dot.push_str(format!("digraph {} {{\n", quote(name.as_str())).as_str()),
+        None => dot.push_str("digraph {\n"),
+    }
+
+    let mut nodes: Vec<_> = graph.nodes().collect();
+    nodes.sort_by(|a, b| a.name().cmp(b.name()));
+    for node in nodes {
+        let mut attrs = Vec::new();
+        if let Some(weight) = node.get_weight() {
+            attrs.push(format!(
+                "label={}",
+                quote(format!("{} ({})", node.name(), weight).as_str())
+            ));
+        }
+        if let Some(position) = positions.and_then(|positions| positions.get(node.name())) {
+            // Graphviz puts the origin at the bottom left; `0.0 - y` avoids printing -0
+            attrs.push(format!(
+                "pos=\"{},{}!\"",
+                position.x * POINTS_PER_UNIT,
+                0.0 - position.y * POINTS_PER_UNIT
+            ));
+        }
+        match attrs.is_empty() {
+            true => dot.push_str(format!("    {};\n", quote(node.name())).as_str()),
+            false => dot
+                .push_str(format!("    {} [{}];\n", quote(node.name()), attrs.join(", ")).as_str()),
+        }
+    }
+
+    let mut edges: Vec<_> = graph.weighted_edges().collect();
+    edges.sort();
+    for (from, to, weight) in edges {
+        match weight {
+            Some(weight) => dot.push_str(
+                format!(
+                    "    {} -> {} [label=\"{}\"];\n",
+                    quote(from),
+                    quote(to),
+                    weight
+                )
+                .as_str(),
+            ),
+            None => dot.push_str(format!("    {} -> {};\n", quote(from), quote(to)).as_str()),
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiNode;
+
+    #[test]
+    fn test_to_dot() {
+        let mut g = DiGraph::new(Some("g".to_string()));
+        g.add_node(DiNode::new("A", Some("conv".to_string())));
+        g.add_weighted_edge("A", "B", 3);
+        g.add_edge(Some("B"), Some("say \"hi\""));
+
+        let expected = r#"digraph "g" {
+    "A" [label="A (conv)"];
+    "B";
+    "say \"hi\"";
+    "A" -> "B" [label="3"];
+    "B" -> "say \"hi\"";
+}
+"#;
+        assert_eq!(to_dot(&g), expected);
+    }
+
+    #[test]
+    fn test_to_dot_with_positions() {
+        let mut g = DiGraph::new(None);
+        g.add_node(DiNode::new("A", Some("conv".to_string())));
+        g.add_edge(Some("A"), Some("B"));
+        let layout = crate::layout::layered(&g).unwrap();
+
+        let expected = r#"digraph {
+    "A" [label="A (conv)", pos="0,0!"];
+    "B" [pos="0,-72!"];
+    "A" -> "B";
+}
+"#;
+        assert_eq!(to_dot_with_positions(&g, &layout.positions), expected);
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    graph::{Attributed, DiGraph},
+    layout::{fruchterman_reingold, layered},
+};
+use serde_json::json;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const FORCE_ITERATIONS: usize = 300;
+
+/// Write a graph as a standalone HTML page with an interactive viewer.
+pub fn export(graph: &DiGraph, path: impl AsRef<Path>) -> io::Result<()> {
+    fs::write(path, to_html(graph))
+}
+
+/// Render a graph as a standalone HTML page that needs nothing but a browser.
+///
+/// The page embeds the graph as node-link JSON with precomputed positions,
+/// from the layered layout for DAGs and the force-directed layout otherwise,
+/// and a small script to drag nodes, pan, zoom, and inspect weights and
+/// attributes.
+pub fn to_html(graph: &DiGraph) -> String {
+    let positions = match layered(graph) {
+        Ok(layout) => layout.positions,
+        Err(_) => fruchterman_reingold(graph, FORCE_ITERATIONS, 0),
+    };
+
+    let nodes: Vec<_> = graph
+        .nodes()
+        .map(|node| {
+            let position = positions[node.name()];
+            json!({
+                "id": node.name(),
+                "weight": node.get_weight(),
+                "attrs": node.attrs(),
+                "x": position.x,
+                "y": position.y,
+            })
+        })
+        .collect();
+    let links: Vec<_> = graph
+        .weighted_edges()
+        .map(|(from, to, weight)| json!({"source": from, "target": to, "weight": weight}))
+        .collect();
+    let data = json!({
+        "name": graph.get_name(),
+        "nodes": nodes,
+        "links": links,
+    });
+    // keep the JSON from closing the script element
+    let data = data.to_string().replace("</", "<\\/");
+
+    let title = escape(graph.get_name().as_deref().unwrap_or("graphx"));
+    fill(
+        TEMPLATE,
+        &[("title", title.as_str()), ("data", data.as_str())],
+    )
+}
+
+/// Fill the `{{key}}` placeholders of a template in a single pass, so that
+/// placeholders inside the values are left as they are.
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut html = String::with_capacity(
+        template.len() + values.iter().map(|(_, value)| value.len()).sum::<usize>(),
+    );
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        html.push_str(&rest[..start]);
+        let placeholder = &rest[start + 2..];
+        let found = values.iter().find(|(key, _)| {
+            placeholder
+                .strip_prefix(key)
+                .is_some_and(|tail| tail.starts_with("}}"))
+        });
+        match found {
+            Some((key, value)) => {
+                html.push_str(value);
+                rest = &placeholder[key.len() + 2..];
+            }
+            None => {
+                html.push_str("{{");
+                rest = placeholder;
+            }
+        }
+    }
+    html.push_str(rest);
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+const TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{{title}}</title>
+<style>
+  body { margin: 0; font: 13px sans-serif; }
+  svg { width: 100vw; height: 100vh; cursor: grab; }
+  line { stroke: #999; }
+  circle { fill: #4a90d9; stroke: #fff; cursor: move; }
+  text { pointer-events: none; }
+  #info { position: fixed; top: 8px; left: 8px; background: #fffd; padding: 4px 8px; white-space: pre; }
+</style>
+</head>
+<body>
+<div id="info">drag nodes, drag the background to pan, scroll to zoom</div>
+<svg id="view">
+  <defs><marker id="arrow" viewBox="0 0 10 10" refX="18" refY="5" markerWidth="6" markerHeight="6" orient="auto"><path d="M0,0L10,5L0,10z" fill="#999"/></marker></defs>
+  <g id="scene"></g>
+</svg>
+<script id="graph" type="application/json">{{data}}</script>
+<script>
+const graph = JSON.parse(document.getElementById("graph").textContent);
+const NS = "http://www.w3.org/2000/svg", UNIT = 80;
+const svg = document.getElementById("view"), scene = document.getElementById("scene");
+const info = document.getElementById("info");
+const make = (tag, attrs) => {
+  const el = document.createElementNS(NS, tag);
+  for (const k in attrs) el.setAttribute(k, attrs[k]);
+  return el;
+};
+const byId = {};
+graph.nodes.forEach(n => { n.x *= UNIT; n.y *= UNIT; byId[n.id] = n; });
+graph.links.forEach(l => {
+  l.el = scene.appendChild(make("line", {"marker-end": "url(#arrow)"}));
+  if (l.weight !== null) l.label = scene.appendChild(make("text", {"fill": "#666"}));
+  if (l.label) l.label.textContent = l.weight;
+});
+graph.nodes.forEach(n => {
+  n.el = scene.appendChild(make("circle", {r: 8}));
+  n.label = scene.appendChild(make("text", {dx: 10, dy: 4}));
+  n.label.textContent = n.weight === null ? n.id : n.id + " (" + n.weight + ")";
+  n.el.addEventListener("mousedown", e => { e.stopPropagation(); dragging = n; });
+  n.el.addEventListener("mouseenter", () => {
+    info.textContent = n.id + (n.weight === null ? "" : " (" + n.weight + ")") +
+      (n.attrs && Object.keys(n.attrs).length ? "\n" + JSON.stringify(n.attrs, null, 2) : "");
+  });
+});
+function draw() {
+  graph.links.forEach(l => {
+    const s = byId[l.source], t = byId[l.target];
+    l.el.setAttribute("x1", s.x); l.el.setAttribute("y1", s.y);
+    l.el.setAttribute("x2", t.x); l.el.setAttribute("y2", t.y);
+    if (l.label) { l.label.setAttribute("x", (s.x + t.x) / 2); l.label.setAttribute("y", (s.y + t.y) / 2); }
+  });
+  graph.nodes.forEach(n => {
+    n.el.setAttribute("cx", n.x); n.el.setAttribute("cy", n.y);
+    n.label.setAttribute("x", n.x); n.label.setAttribute("y", n.y);
+  });
+}
+let view = {x: svg.clientWidth / 2, y: 60, k: 1}, dragging = null, panning = null;
+const apply = () => scene.setAttribute("transform", `translate(${view.x},${view.y}) scale(${view.k})`);
+svg.addEventListener("mousedown", e => { panning = {x: e.clientX - view.x, y: e.clientY - view.y}; });
+window.addEventListener("mousemove", e => {
+  if (dragging) {
+    dragging.x = (e.clientX - view.x) / view.k; dragging.y = (e.clientY - view.y) / view.k; draw();
+  } else if (panning) {
+    view.x = e.clientX - panning.x; view.y = e.clientY - panning.y; apply();
+  }
+});
+window.addEventListener("mouseup", () => { dragging = null; panning = null; });
+svg.addEventListener("wheel", e => {
+  e.preventDefault();
+  const f = e.deltaY < 0 ? 1.1 : 1 / 1.1;
+  view.x = e.clientX - (e.clientX - view.x) * f; view.y = e.clientY - (e.clientY - view.y) * f; view.k *= f;
+  apply();
+});
+draw(); apply();
+</script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiNode;
+
+    #[test]
+    fn test_to_html() {
+        let mut g = DiGraph::new(Some("a <b>".to_string()));
+        g.add_node(DiNode::new("A", Some("</script>".to_string())));
+        g.add_weighted_edge("A", "B", 3);
+
+        let html = to_html(&g);
+        assert!(html.contains("<title>a &lt;b&gt;</title>"));
+        assert_eq!(html.matches("</script>").count(), 2);
+
+        let start = html.find("application/json\">").unwrap() + "application/json\">".len();
+        let end = start + html[start..].find("</script>").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&html[start..end]).unwrap();
+        assert_eq!(data["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(data["nodes"][0]["weight"], "</script>");
+        assert_eq!(data["links"][0]["weight"], 3);
+
+        // a cyclic graph falls back to the force-directed layout
+        g.add_edge(Some("B"), Some("A"));
+        assert!(to_html(&g).contains("\"source\":\"B\""));
+    }
+
+    #[test]
+    fn test_to_html_placeholder_in_title() {
+        let g = DiGraph::new(Some("{{data}} & \"{{title}}\"".to_string()));
+        let html = to_html(&g);
+        assert!(html.contains("<title>{{data}} &amp; &quot;{{title}}&quot;</title>"));
+        assert_eq!(html.matches("\"nodes\":[]").count(), 1);
+    }
+}
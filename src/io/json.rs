@@ -0,0 +1,283 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSON wrapped in a versioned envelope, `{"version":2,"graph":{...}}`, so
+//! stored graphs can still be read after the layout of `DiGraph` changes.
+//!
+//! Version 1 is the bare serde form of a `DiGraph` written before the
+//! envelope existed. Reading upgrades older layouts step by step to the
+//! current one.
+//!
+//! Decoding checks the graph as well as the JSON: [`DiGraph::from_json`]
+//! refuses a graph with duplicate or inconsistent nodes, and
+//! [`DiGraph::from_json_lossy`] repairs it and reports what it fixed.
+
+use crate::{error::GraphError, graph::DiGraph};
+use serde::{
+    de::{IgnoredAny, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+
+/// Version written by [`DiGraph::to_json`].
+pub const SCHEMA_VERSION: u64 = 2;
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u64,
+    graph: &'a DiGraph,
+}
+
+impl DiGraph {
+    /// Encode the graph as JSON in the current versioned envelope.
+    pub fn to_json(&self) -> Result<String, GraphError> {
+        let envelope = Envelope {
+            version: SCHEMA_VERSION,
+            graph: self,
+        };
+        serde_json::to_string(&envelope).map_err(|err| GraphError::Serialization(err.to_string()))
+    }
+
+    /// Decode a graph from an envelope of any version up to the current one,
+    /// or from the bare version 1 form. Fails with `GraphError::Parse` for
+    /// invalid JSON, `GraphError::Serialization` for JSON that is not a graph
+    /// or a version newer than this library, and with the
+    /// first problem found for a graph that is not internally consistent:
+    /// `DuplicateNode` for a name listed twice, or any problem reported by
+    /// [`DiGraph::validate`].
+    pub fn from_json(text: &str) -> Result<DiGraph, GraphError> {
+        let (graph, duplicates) = decode(text)?;
+        if let Some(name) = duplicates.into_iter().next() {
+            return Err(GraphError::DuplicateNode(name));
+        }
+        graph
+            .validate()
+            .map_err(|mut problems| problems.remove(0))?;
+        Ok(graph)
+    }
+
+    /// Decode a graph like [`DiGraph::from_json`], but repair an inconsistent
+    /// graph with [`DiGraph::repair`] instead of failing, so dangling edges and
+    /// edge data without an edge are dropped. Of nodes listed twice, the last
+    /// one is kept. Returns the graph and the problems fixed, as warnings.
+    pub fn from_json_lossy(text: &str) -> Result<(DiGraph, Vec<GraphError>), GraphError> {
+        let (mut graph, duplicates) = decode(text)?;
+        let mut warnings: Vec<GraphError> = duplicates
+            .into_iter()
+            .map(GraphError::DuplicateNode)
+            .collect();
+        warnings.extend(graph.repair());
+        Ok((graph, warnings))
+    }
+}
+
+/// Decode a graph as it is, with the names of nodes listed more than once.
+fn decode(text: &str) -> Result<(DiGraph, Vec<String>), GraphError> {
+    let value: Value = serde_json::from_str(text).map_err(parse_error)?;
+    let (version, graph) = match value {
+        Value::Object(mut object) if object.contains_key("version") => {
+            let version = object
+                .get("version")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| GraphError::Serialization("Invalid version".to_string()))?;
+            let graph = object
+                .remove("graph")
+                .ok_or_else(|| GraphError::Serialization("Missing graph".to_string()))?;
+            (version, graph)
+        }
+        bare => (1, bare),
+    };
+    let graph = upgrade(version, graph)?;
+
+    // a JSON value keeps only the last of repeated keys, so look for
+    // repeated node names in the text itself
+    let scan: Scan = serde_json::from_str(text).map_err(parse_error)?;
+    let names = scan.graph.and_then(|inner| inner.nodes).or(scan.nodes);
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    for name in names.map_or_else(Vec::new, |names| names.0) {
+        if !seen.insert(name.clone()) && !duplicates.contains(&name) {
+            duplicates.push(name);
+        }
+    }
+
+    let graph =
+        serde_json::from_value(graph).map_err(|err| GraphError::Serialization(err.to_string()))?;
+    Ok((graph, duplicates))
+}
+
+/// A JSON error at a position in the text as `GraphError::Parse`.
+fn parse_error(err: serde_json::Error) -> GraphError {
+    if err.line() == 0 {
+        return GraphError::Serialization(err.to_string());
+    }
+    let position = format!(" at line {} column {}", err.line(), err.column());
+    GraphError::Parse {
+        line: err.line(),
+        column: err.column(),
+        message: err.to_string().trim_end_matches(&position).to_string(),
+    }
+}
+
+/// The node names of an enveloped or bare graph, read without building it.
+#[derive(Deserialize)]
+struct Scan {
+    graph: Option<ScanGraph>,
+    nodes: Option<NodeNames>,
+}
+
+#[derive(Deserialize)]
+struct ScanGraph {
+    nodes: Option<NodeNames>,
+}
+
+/// Keys of a map of nodes in the order written, repeats included.
+struct NodeNames(Vec<String>);
+impl<'de> Deserialize<'de> for NodeNames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Names;
+        impl<'de> Visitor<'de> for Names {
+            type Value = NodeNames;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of nodes")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<NodeNames, A::Error> {
+                let mut names = Vec::new();
+                while let Some((name, IgnoredAny)) = map.next_entry::<String, IgnoredAny>()? {
+                    names.push(name);
+                }
+                Ok(NodeNames(names))
+            }
+        }
+        deserializer.deserialize_map(Names)
+    }
+}
+
+/// Bring the graph layout of `version` up to [`SCHEMA_VERSION`].
+fn upgrade(version: u64, graph: Value) -> Result<Value, GraphError> {
+    match version {
+        // the envelope was the only change from version 1 to 2
+        1 | 2 => Ok(graph),
+        _ => Err(GraphError::Serialization(format!(
+            "Unsupported schema version: {}",
+            version
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versioned_json_test() {
+        let mut g = DiGraph::new(Some("g".to_string()));
+        g.add_weighted_edge("A", "B", 2);
+
+        let json = g.to_json().unwrap();
+        assert!(json.starts_with(r#"{"version":2,"graph":{"name":"g","#));
+        assert_eq!(DiGraph::from_json(json.as_str()).unwrap(), g);
+
+        // bare version 1 output, with and without weights
+        let bare = serde_json::to_string(&g).unwrap();
+        assert_eq!(DiGraph::from_json(bare.as_str()).unwrap(), g);
+        let bare = r#"{"nodes":{"B":{"name":"B","inputs":["A"],"outputs":[]},"A":{"name":"A","inputs":[],"outputs":["B"]}}}"#;
+        assert_eq!(DiGraph::from_json(bare).unwrap().edge_count(), 1);
+
+        let envelope = r#"{"version":1,"graph":{"nodes":{}}}"#;
+        assert_eq!(DiGraph::from_json(envelope).unwrap().node_count(), 0);
+        for invalid in [
+            r#"{"version":3,"graph":{"nodes":{}}}"#,
+            r#"{"version":"2","graph":{"nodes":{}}}"#,
+            r#"{"version":2}"#,
+        ] {
+            assert!(matches!(
+                DiGraph::from_json(invalid),
+                Err(GraphError::Serialization(_))
+            ));
+        }
+        match DiGraph::from_json("{\n  \"nodes\": {]\n}") {
+            Err(GraphError::Parse {
+                line,
+                column,
+                message,
+            }) => {
+                assert_eq!((line, column), (2, 13));
+                assert_eq!(message, "key must be a string");
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn checked_json_test() {
+        // a successor missing from the nodes
+        let dangling = r#"{"nodes":{"A":{"name":"A","inputs":[],"outputs":["Z"]}}}"#;
+        assert!(matches!(
+            DiGraph::from_json(dangling),
+            Err(GraphError::DanglingEdge(from, to)) if from == "A" && to == "Z"
+        ));
+        let (g, warnings) = DiGraph::from_json_lossy(dangling).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(g.edge_count(), 0);
+        assert!(g.successors("A").unwrap().is_empty());
+
+        // a node listed twice; the last one wins when lossy
+        let duplicate = r#"{"version":2,"graph":{"nodes":{
+            "A":{"name":"A","inputs":[],"outputs":["B"]},
+            "B":{"name":"B","inputs":["A"],"outputs":[]},
+            "A":{"name":"A","inputs":[],"outputs":[]}}}}"#;
+        assert!(matches!(
+            DiGraph::from_json(duplicate),
+            Err(GraphError::DuplicateNode(name)) if name == "A"
+        ));
+        let (g, warnings) = DiGraph::from_json_lossy(duplicate).unwrap();
+        assert!(matches!(&warnings[0], GraphError::DuplicateNode(name) if name == "A"));
+        assert!(
+            matches!(&warnings[1], GraphError::OneSidedEdge(from, to) if from == "A" && to == "B")
+        );
+        assert!(g.contains_edge("A", "B"));
+        assert!(g.validate().is_ok());
+
+        // a weighted self-loop only its predecessors know of
+        let self_loop = r#"{"nodes":{"A":{"name":"A","inputs":["A"],"outputs":[]}},
+            "edges":{"A":{"A":{"weight":3}}}}"#;
+        assert!(matches!(
+            DiGraph::from_json(self_loop),
+            Err(GraphError::OneSidedEdge(from, to)) if from == "A" && to == "A"
+        ));
+        let (g, _) = DiGraph::from_json_lossy(self_loop).unwrap();
+        assert_eq!(g.get_edge_weight("A", "A"), Some(3));
+        assert_eq!(g.predecessors("A").unwrap().len(), 1);
+
+        // edge data without an edge
+        let stray = r#"{"nodes":{"A":{"name":"A","inputs":[],"outputs":[]}},
+            "edges":{"A":{"B":{"weight":1}}}}"#;
+        assert!(matches!(
+            DiGraph::from_json(stray),
+            Err(GraphError::NotFoundEdge(_, _))
+        ));
+        let (g, warnings) = DiGraph::from_json_lossy(stray).unwrap();
+        assert_eq!((g.node_count(), g.edge_count(), warnings.len()), (1, 0, 1));
+
+        assert!(matches!(
+            DiGraph::from_json_lossy("{"),
+            Err(GraphError::Parse { .. })
+        ));
+    }
+}
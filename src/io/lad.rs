@@ -0,0 +1,198 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    error::{GraphError, ResultExt},
+    graph::{DiGraph, DiNode},
+};
+
+/// Parse an undirected graph in the LAD format of the subgraph isomorphism
+/// benchmark suites.
+///
+/// The first number is the node count `n`; then, for each node `0..n`, comes
+/// its degree followed by that many neighbor ids. Nodes are named by their id,
+/// and every edge is added in both directions, so each neighbor may be listed
+/// at either or both endpoints.
+pub fn from_lad(lad: &str) -> Result<DiGraph, GraphError> {
+    parse(lad, false, true)
+}
+
+/// Like [`from_lad`], but keep every listed edge as a single directed edge
+/// from the node to its neighbor.
+pub fn from_directed_lad(lad: &str) -> Result<DiGraph, GraphError> {
+    parse(lad, false, false)
+}
+
+/// Like [`from_lad`], but every node's degree is preceded by its label, which
+/// becomes the node weight so that only nodes with equal labels match.
+pub fn from_vertex_labelled_lad(lad: &str) -> Result<DiGraph, GraphError> {
+    parse(lad, true, true)
+}
+
+fn parse(lad: &str, labelled: bool, undirected: bool) -> Result<DiGraph, GraphError> {
+    let mut tokens = Tokens::new(lad);
+    let (n, _, _) = tokens.next("node count")?;
+    let mut graph = DiGraph::new(None);
+    // the counts are untrusted, but every node and neighbor takes a token
+    let mut adjacency = Vec::with_capacity(n.min(tokens.remaining));
+    for v in 0..n {
+        let weight = if labelled {
+            Some(tokens.next("label")?.0.to_string())
+        } else {
+            None
+        };
+        graph.add_node(DiNode::new(v.to_string().as_str(), weight));
+        let (degree, _, _) = tokens.next("degree")?;
+        let mut nbrs = Vec::with_capacity(degree.min(tokens.remaining));
+        for _ in 0..degree {
+            let (w, line, column) = tokens.next("neighbor")?;
+            if w >= n {
+                return Err(GraphError::NotFoundNode(w.to_string()))
+                    .with_context(|| format!("line {}, column {}", line, column));
+            }
+            nbrs.push(w);
+        }
+        adjacency.push(nbrs);
+    }
+
+    for (v, nbrs) in adjacency.iter().enumerate() {
+        let from = v.to_string();
+        for w in nbrs {
+            let to = w.to_string();
+            graph.add_edge(Some(from.as_str()), Some(to.as_str()));
+            if undirected {
+                graph.add_edge(Some(to.as_str()), Some(from.as_str()));
+            }
+        }
+    }
+    Ok(graph)
+}
+
+/// The numbers of a LAD file with their line and column.
+struct Tokens<'a> {
+    tokens: Box<dyn Iterator<Item = (usize, usize, &'a str)> + 'a>,
+    // where a missing token would have been
+    end: (usize, usize),
+    remaining: usize,
+}
+impl<'a> Tokens<'a> {
+    fn new(lad: &'a str) -> Self {
+        let tokens = lad.lines().enumerate().flat_map(|(i, line)| {
+            line.split_whitespace().map(move |token| {
+                let column = token.as_ptr() as usize - line.as_ptr() as usize + 1;
+                (i + 1, column, token)
+            })
+        });
+        let end = (
+            lad.lines().count().max(1),
+            lad.lines().last().map_or(0, str::len) + 1,
+        );
+        Tokens {
+            tokens: Box::new(tokens),
+            end,
+            remaining: lad.split_whitespace().count(),
+        }
+    }
+
+    /// The next number, with its line and column.
+    fn next(&mut self, what: &str) -> Result<(usize, usize, usize), GraphError> {
+        let (line, column, token) = self.tokens.next().ok_or(GraphError::Parse {
+            line: self.end.0,
+            column: self.end.1,
+            message: format!("missing {}", what),
+        })?;
+        self.remaining -= 1;
+        let value = token.parse().map_err(|_| GraphError::Parse {
+            line,
+            column,
+            message: format!("invalid {} {:?}", what, token),
+        })?;
+        Ok((value, line, column))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_lad() {
+        // a path 0 - 1 - 2, with 1 - 2 listed at both ends
+        let lad = "3\n1 1\n2 0 2\n1 1\n";
+        let g = from_lad(lad).unwrap();
+        assert_eq!(g.node_count(), 3);
+        assert_eq!(g.edge_count(), 4);
+        assert!(g.contains_edge("1", "0"));
+
+        let g = from_directed_lad("3\n1 1\n1 2\n0\n").unwrap();
+        assert_eq!(g.edge_count(), 2);
+        assert!(!g.contains_edge("1", "0"));
+
+        let g = from_vertex_labelled_lad("2\n7 1 1\n8 0\n").unwrap();
+        assert_eq!(g.get_node("1").unwrap().get_weight(), Some("8".to_string()));
+        assert!(g.contains_edge("1", "0"));
+
+        let err = from_lad("2\n1 5\n0\n").unwrap_err();
+        assert!(matches!(err.root_cause(), GraphError::NotFoundNode(id) if id == "5"));
+        assert_eq!(err.report(), "line 2, column 3: Not found node: 5");
+        assert!(matches!(
+            from_lad("2\n1"),
+            Err(GraphError::Parse {
+                line: 2,
+                column: 2,
+                ..
+            })
+        ));
+        assert!(matches!(
+            from_lad("2\n1  x"),
+            Err(GraphError::Parse {
+                line: 2,
+                column: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_from_lad_malformed_header() {
+        // counts far beyond the input are reported, not reserved
+        assert!(matches!(
+            from_lad(
+                "18446744073709551615
+0
+"
+            ),
+            Err(GraphError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            from_lad(
+                "1
+18446744073709551615 0
+"
+            ),
+            Err(GraphError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            from_lad(
+                "18446744073709551616
+"
+            ),
+            Err(GraphError::Parse {
+                line: 1,
+                column: 1,
+                ..
+            })
+        ));
+    }
+}
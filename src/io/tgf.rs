@@ -0,0 +1,173 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    error::{GraphError, ResultExt},
+    graph::{Attributed, DiGraph, DiNode},
+};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Render a graph in the Trivial Graph Format.
+///
+/// Nodes are numbered from 1 in graph order and labeled with their names.
+/// An edge is labeled with its weight, or else with its `label` attribute if
+/// that is a string.
+pub fn to_tgf(graph: &DiGraph) -> String {
+    let mut tgf = String::new();
+    let mut ids = HashMap::new();
+    for (i, name) in graph.node_names().enumerate() {
+        ids.insert(name, i + 1);
+        tgf.push_str(format!("{} {}\n", i + 1, name).as_str());
+    }
+    tgf.push_str("#\n");
+    for (from, to) in graph.edges() {
+        let label = graph.get_edge_data(from, to).and_then(|data| {
+            match (data.get_weight(), data.attrs().get("label")) {
+                (Some(weight), _) => Some(weight.to_string()),
+                (None, Some(Value::String(label))) => Some(label.clone()),
+                _ => None,
+            }
+        });
+        match label {
+            Some(label) => tgf.push_str(format!("{} {} {}\n", ids[from], ids[to], label).as_str()),
+            None => tgf.push_str(format!("{} {}\n", ids[from], ids[to]).as_str()),
+        }
+    }
+    tgf
+}
+
+/// Parse a graph from the Trivial Graph Format.
+///
+/// Every node line is an id followed by an optional label, which becomes the
+/// node name; unlabeled nodes are named by their id. After the `#` line, every
+/// edge line is two node ids followed by an optional label: integer labels
+/// become edge weights and any other label is kept as the `label` attribute.
+/// Blank lines are skipped. Fails with `GraphError::DuplicateNode` if two
+/// nodes share an id or a name and `GraphError::NotFoundNode` if an edge refers
+/// to an unknown id, both with the line as context, and with
+/// `GraphError::Parse` for malformed lines.
+pub fn from_tgf(tgf: &str) -> Result<DiGraph, GraphError> {
+    let mut graph = DiGraph::new(None);
+    let mut names: HashMap<&str, &str> = HashMap::new();
+    let mut lines = tgf
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()));
+
+    for (number, line) in lines.by_ref() {
+        if line == "#" {
+            break;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        let (id, label) = split_token(line);
+        let name = if label.is_empty() { id } else { label };
+        if names.contains_key(id) || graph.contains_node(name) {
+            return Err(GraphError::DuplicateNode(name.to_string()))
+                .context(format!("line {}", number));
+        }
+        names.insert(id, name);
+        graph.add_node(DiNode::new(name, None));
+    }
+
+    for (number, line) in lines {
+        if line.is_empty() {
+            continue;
+        }
+        let (from, rest) = split_token(line);
+        let (to, label) = split_token(rest);
+        if to.is_empty() {
+            return Err(GraphError::Parse {
+                line: number,
+                column: 1,
+                message: format!("expected two node ids, found {:?}", line),
+            });
+        }
+        let from = *names
+            .get(from)
+            .ok_or_else(|| GraphError::NotFoundNode(from.to_string()))
+            .with_context(|| format!("line {}", number))?;
+        let to = *names
+            .get(to)
+            .ok_or_else(|| GraphError::NotFoundNode(to.to_string()))
+            .with_context(|| format!("line {}", number))?;
+
+        match label.parse::<i64>() {
+            Ok(weight) => graph.add_weighted_edge(from, to, weight),
+            Err(_) => {
+                graph.add_edge(Some(from), Some(to));
+                if !label.is_empty() {
+                    graph
+                        .get_edge_data_mut(from, to)
+                        .unwrap()
+                        .attrs_mut()
+                        .insert("label".to_string(), Value::from(label));
+                }
+            }
+        }
+    }
+    Ok(graph)
+}
+
+/// Split off the first whitespace-separated token of a trimmed line.
+fn split_token(line: &str) -> (&str, &str) {
+    match line.split_once(char::is_whitespace) {
+        Some((token, rest)) => (token, rest.trim()),
+        None => (line, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tgf_round_trip() {
+        let tgf = "1 January\n2 March\n3\n\n#\n1 2 5\n2 3 next month\n3 1\n";
+        let g = from_tgf(tgf).unwrap();
+        assert_eq!(g.get_nodes(), vec!["January", "March", "3"]);
+        assert_eq!(g.get_edge_weight("January", "March"), Some(5));
+        assert_eq!(
+            g.get_edge_data("March", "3").unwrap().attrs()["label"],
+            "next month"
+        );
+        assert!(g.contains_edge("3", "January"));
+
+        // unlabeled nodes are written back with their names as labels
+        assert_eq!(
+            to_tgf(&g),
+            "1 January\n2 March\n3 3\n#\n1 2 5\n2 3 next month\n3 1\n"
+        );
+        assert_eq!(
+            from_tgf(to_tgf(&g).as_str()).unwrap().get_nodes(),
+            g.get_nodes()
+        );
+    }
+
+    #[test]
+    fn test_tgf_errors() {
+        let err = from_tgf("1 A\n2 A\n#\n").unwrap_err();
+        assert!(matches!(err.root_cause(), GraphError::DuplicateNode(name) if name == "A"));
+        assert_eq!(err.report(), "line 2: Duplicate node: A");
+        let err = from_tgf("1 A\n#\n1 2\n").unwrap_err();
+        assert!(matches!(err.root_cause(), GraphError::NotFoundNode(id) if id == "2"));
+        assert_eq!(err.to_string(), "line 3");
+        assert!(matches!(
+            from_tgf("1 A\n#\n1\n"),
+            Err(GraphError::Parse { line: 3, .. })
+        ));
+    }
+}
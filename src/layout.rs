@@ -0,0 +1,31 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Node placement for drawing graphs. Coordinates are in abstract units with
+//! neighboring nodes about one unit apart and `y` growing downwards.
+
+mod force;
+mod layered;
+
+pub use force::fruchterman_reingold;
+pub use layered::{layered, LayeredLayout};
+
+use serde::{Deserialize, Serialize};
+
+/// A point in the plane.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
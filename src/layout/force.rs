@@ -0,0 +1,150 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::Position;
+use crate::graph::{GraphBase, Node};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// Lay out any graph, including cyclic ones, with the force-directed algorithm
+/// of Fruchterman and Reingold.
+///
+/// Nodes start at random positions drawn from `seed`, then every iteration
+/// all pairs of nodes repel each other and the endpoints of every edge attract
+/// each other, with moves capped by a temperature that cools linearly. Edge
+/// direction and weight are ignored. The result is centered on the origin and
+/// scaled so the ideal edge length is one unit; the same graph, iteration
+/// count and seed always give the same layout.
+pub fn fruchterman_reingold<G: GraphBase>(
+    graph: &G,
+    iterations: usize,
+    seed: u64,
+) -> HashMap<String, Position> {
+    let names: Vec<&str> = graph.node_names().collect();
+    let n = names.len();
+    if n == 0 {
+        return HashMap::new();
+    }
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+    let mut edges = Vec::new();
+    for (v, &name) in names.iter().enumerate() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            if succ != name {
+                edges.push((v, index[succ]));
+            }
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut pos: Vec<(f64, f64)> = (0..n)
+        .map(|_| (rng.random::<f64>(), rng.random::<f64>()))
+        .collect();
+
+    // optimal distance between nodes in the unit square
+    let k = (1.0 / n as f64).sqrt();
+    let mut temperature = 0.1;
+    let cooling = temperature / (iterations as f64 + 1.0);
+    for _ in 0..iterations {
+        let mut disp = vec![(0.0, 0.0); n];
+        for v in 0..n {
+            for w in v + 1..n {
+                let (dx, dy) = (pos[v].0 - pos[w].0, pos[v].1 - pos[w].1);
+                let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+                let force = k * k / distance;
+                let (fx, fy) = (dx / distance * force, dy / distance * force);
+                disp[v].0 += fx;
+                disp[v].1 += fy;
+                disp[w].0 -= fx;
+                disp[w].1 -= fy;
+            }
+        }
+        for &(v, w) in edges.iter() {
+            let (dx, dy) = (pos[v].0 - pos[w].0, pos[v].1 - pos[w].1);
+            let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+            let force = distance * distance / k;
+            let (fx, fy) = (dx / distance * force, dy / distance * force);
+            disp[v].0 -= fx;
+            disp[v].1 -= fy;
+            disp[w].0 += fx;
+            disp[w].1 += fy;
+        }
+        for v in 0..n {
+            let length = (disp[v].0 * disp[v].0 + disp[v].1 * disp[v].1).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature) / length;
+                pos[v].0 += disp[v].0 * step;
+                pos[v].1 += disp[v].1 * step;
+            }
+        }
+        temperature -= cooling;
+    }
+
+    let cx = pos.iter().map(|p| p.0).sum::<f64>() / n as f64;
+    let cy = pos.iter().map(|p| p.1).sum::<f64>() / n as f64;
+    names
+        .iter()
+        .zip(pos)
+        .map(|(&name, (x, y))| {
+            let position = Position {
+                x: (x - cx) / k,
+                y: (y - cy) / k,
+            };
+            (name.to_string(), position)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    fn distance(a: Position, b: Position) -> f64 {
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_fruchterman_reingold() {
+        // two triangles joined by a single edge
+        let g: DiGraph = vec![
+            ("A", "B"),
+            ("B", "C"),
+            ("C", "A"),
+            ("C", "D"),
+            ("D", "E"),
+            ("E", "F"),
+            ("F", "D"),
+        ]
+        .into_iter()
+        .collect();
+
+        let positions = fruchterman_reingold(&g, 100, 7);
+        assert_eq!(positions.len(), 6);
+        assert_eq!(positions, fruchterman_reingold(&g, 100, 7));
+
+        // nodes of the same triangle end up closer than nodes of different ones
+        let near = distance(positions["A"], positions["B"]);
+        let far = distance(positions["A"], positions["E"]);
+        assert!(near < far);
+
+        let cx: f64 = positions.values().map(|p| p.x).sum();
+        assert!(cx.abs() < 1e-9);
+
+        assert!(fruchterman_reingold(&DiGraph::new(None), 10, 0).is_empty());
+    }
+}
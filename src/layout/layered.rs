@@ -0,0 +1,222 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::Position;
+use crate::{
+    algorithm::dag::topological_order,
+    error::GraphError,
+    graph::{GraphBase, Node},
+};
+use std::collections::HashMap;
+
+// barycenter sweeps; each sweep goes down and then up once
+const SWEEPS: usize = 8;
+
+/// Result of [`layered`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayeredLayout {
+    /// Nodes of each layer, left to right.
+    pub layers: Vec<Vec<String>>,
+    /// Position of every node: `y` is the layer and `x` the slot within the
+    /// layer, with each layer centered on `x = 0`.
+    pub positions: HashMap<String, Position>,
+    /// Number of edge crossings between adjacent layers in the final order.
+    pub crossings: usize,
+}
+
+/// Lay out a DAG top to bottom in the style of Sugiyama et al.
+///
+/// Every node is put on the layer after its deepest predecessor. Edges that
+/// span several layers get hidden dummy nodes, then the order within layers is
+/// improved with barycenter sweeps, keeping the order with the fewest
+/// crossings. Fails with `GraphError::CycleDetected` if the graph has a cycle.
+pub fn layered<G: GraphBase>(dag: &G) -> Result<LayeredLayout, GraphError> {
+    let order = topological_order(dag)?;
+    let index: HashMap<&str, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), i))
+        .collect();
+
+    // layer assignment by longest path from the sources
+    let mut rank = vec![0; order.len()];
+    for (v, name) in order.iter().enumerate() {
+        rank[v] = dag
+            .node(name)
+            .unwrap()
+            .predecessor_names()
+            .map(|pred| rank[index[pred]] + 1)
+            .max()
+            .unwrap_or(0);
+    }
+
+    // split long edges with dummy nodes, numbered after the real ones
+    let mut below: Vec<Vec<usize>> = vec![Vec::new(); order.len()];
+    let mut above: Vec<Vec<usize>> = vec![Vec::new(); order.len()];
+    for (v, name) in order.iter().enumerate() {
+        for succ in dag.node(name).unwrap().successor_names() {
+            let target = index[succ];
+            let mut prev = v;
+            for r in rank[v] + 1..rank[target] {
+                let dummy = rank.len();
+                rank.push(r);
+                below.push(Vec::new());
+                above.push(vec![prev]);
+                below[prev].push(dummy);
+                prev = dummy;
+            }
+            below[prev].push(target);
+            above[target].push(prev);
+        }
+    }
+
+    let depth = rank.iter().max().map_or(0, |&r| r + 1);
+    let mut layers: Vec<Vec<usize>> = vec![Vec::new(); depth];
+    for (v, &r) in rank.iter().enumerate() {
+        layers[r].push(v);
+    }
+
+    let mut best = layers.clone();
+    let mut best_crossings = crossings(&layers, &below, rank.len());
+    for _ in 0..SWEEPS {
+        if best_crossings == 0 {
+            break;
+        }
+        for r in 1..depth {
+            sort_by_barycenter(&mut layers, r, r - 1, &above, rank.len());
+        }
+        for r in (0..depth.saturating_sub(1)).rev() {
+            sort_by_barycenter(&mut layers, r, r + 1, &below, rank.len());
+        }
+        let count = crossings(&layers, &below, rank.len());
+        if count < best_crossings {
+            best = layers.clone();
+            best_crossings = count;
+        }
+    }
+
+    let mut positions = HashMap::new();
+    for (r, layer) in best.iter().enumerate() {
+        let offset = (layer.len() as f64 - 1.0) / 2.0;
+        for (i, &v) in layer.iter().enumerate() {
+            if v < order.len() {
+                let position = Position {
+                    x: i as f64 - offset,
+                    y: r as f64,
+                };
+                positions.insert(order[v].clone(), position);
+            }
+        }
+    }
+
+    Ok(LayeredLayout {
+        layers: best
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .filter(|&&v| v < order.len())
+                    .map(|&v| order[v].clone())
+                    .collect()
+            })
+            .collect(),
+        positions,
+        crossings: best_crossings,
+    })
+}
+
+/// Reorder `layers[r]` by the mean slot of each node's neighbors in
+/// `layers[fixed]`; nodes without such neighbors keep their slot.
+fn sort_by_barycenter(
+    layers: &mut [Vec<usize>],
+    r: usize,
+    fixed: usize,
+    nbrs: &[Vec<usize>],
+    n: usize,
+) {
+    let mut slot = vec![0.0; n];
+    for (i, &v) in layers[fixed].iter().enumerate() {
+        slot[v] = i as f64;
+    }
+    let mut keyed: Vec<(f64, usize)> = layers[r]
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            if nbrs[v].is_empty() {
+                (i as f64, v)
+            } else {
+                let sum: f64 = nbrs[v].iter().map(|&w| slot[w]).sum();
+                (sum / nbrs[v].len() as f64, v)
+            }
+        })
+        .collect();
+    keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    layers[r] = keyed.into_iter().map(|(_, v)| v).collect();
+}
+
+fn crossings(layers: &[Vec<usize>], below: &[Vec<usize>], n: usize) -> usize {
+    let mut slot = vec![0; n];
+    for layer in layers.iter() {
+        for (i, &v) in layer.iter().enumerate() {
+            slot[v] = i;
+        }
+    }
+
+    let mut count = 0;
+    for layer in layers.iter() {
+        let edges: Vec<(usize, usize)> = layer
+            .iter()
+            .flat_map(|&v| below[v].iter().map(move |&w| (v, w)))
+            .map(|(v, w)| (slot[v], slot[w]))
+            .collect();
+        for (i, &(a1, b1)) in edges.iter().enumerate() {
+            for &(a2, b2) in edges[i + 1..].iter() {
+                if (a1 < a2 && b1 > b2) || (a1 > a2 && b1 < b2) {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_layered_layout() {
+        // the initial order crosses A->D with B->C
+        let g: DiGraph = vec![("A", "D"), ("B", "C"), ("A", "E"), ("C", "E")]
+            .into_iter()
+            .collect();
+
+        let layout = layered(&g).unwrap();
+        assert_eq!(layout.layers.len(), 3);
+        assert_eq!(layout.layers[2], vec!["E"]);
+        assert_eq!(layout.crossings, 0);
+        assert_eq!(layout.positions.len(), 5);
+        assert_eq!(layout.positions["E"], Position { x: 0.0, y: 2.0 });
+        assert_eq!(layout.positions["A"].y, 0.0);
+        assert_eq!(
+            layout.positions["A"].x < layout.positions["B"].x,
+            layout.positions["D"].x < layout.positions["C"].x
+        );
+
+        let mut g = g;
+        g.add_edge(Some("E"), Some("A"));
+        assert!(matches!(layered(&g), Err(GraphError::CycleDetected(_))));
+    }
+}
@@ -13,5 +13,16 @@
 // limitations under the License.
 
 pub mod algorithm;
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+#[cfg(feature = "capi")]
+pub mod capi;
 pub mod error;
 pub mod graph;
+pub mod io;
+pub mod layout;
+pub mod matrix;
+#[cfg(feature = "graphx-py")]
+pub mod python;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,190 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use graphx::{
+    algorithm::{isomorphism::DiGraphMatcher, summary::GraphSummary},
+    graph::DiGraph,
+    io::{dimacs, dot, html, lad, tgf},
+};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::{env, fs, process};
+
+const USAGE: &str = "Usage:
+    graphx match <pattern.json> <host.json> [--mode subgraph|mono] [--limit N]
+    graphx render <graph.json> [-o <graph.dot|graph.svg|graph.html>]
+    graphx stats <graph.json> [--json]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(|arg| arg.as_str()) {
+        Some("match") => run_match(&args[1..]),
+        Some("render") => run_render(&args[1..]),
+        Some("stats") => run_stats(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+/// Read a graph, picking the format from the file extension: `.lad`, `.dimacs`,
+/// `.tgf`, or JSON otherwise.
+fn read_graph(path: &str) -> Result<DiGraph, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("Cannot read {}: {}", path, err))?;
+    let graph = match path.rsplit('.').next() {
+        Some("lad") => lad::from_lad(text.as_str()),
+        Some("dimacs") => dimacs::from_dimacs(text.as_str()),
+        Some("tgf") => tgf::from_tgf(text.as_str()),
+        _ => DiGraph::from_json(text.as_str()),
+    };
+    graph.map_err(|err| format!("Cannot parse {}: {}", path, err.report()))
+}
+
+/// Print every mapping from pattern nodes to host nodes as one JSON object per line.
+fn run_match(args: &[String]) -> Result<(), String> {
+    let mut paths = Vec::new();
+    let mut mode = "subgraph";
+    let mut limit = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--mode" => {
+                mode = iter
+                    .next()
+                    .map(|value| value.as_str())
+                    .ok_or_else(|| USAGE.to_string())?;
+            }
+            "--limit" => {
+                let value = iter.next().ok_or_else(|| USAGE.to_string())?;
+                limit = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid limit: {}", value))?,
+                );
+            }
+            _ => paths.push(arg.as_str()),
+        }
+    }
+    if paths.len() != 2 {
+        return Err(USAGE.to_string());
+    }
+
+    let pattern = read_graph(paths[0])?;
+    let host = read_graph(paths[1])?;
+
+    let mut matcher = DiGraphMatcher::new(&host, &pattern);
+    matcher.limit = limit;
+    let mut mapping = Vec::new();
+    match mode {
+        "subgraph" => matcher.subgraph_isomorphism_iter(&mut mapping),
+        "mono" => matcher.subgraph_monomorphism_iter(&mut mapping),
+        _ => return Err(format!("Invalid mode: {}", mode)),
+    }
+
+    for found in mapping {
+        let sorted: BTreeMap<String, String> = found.into_iter().collect();
+        println!("{}", serde_json::to_string(&sorted).unwrap());
+    }
+    Ok(())
+}
+
+/// Write a graph as DOT, or as SVG through Graphviz when the output ends with `.svg`.
+fn run_render(args: &[String]) -> Result<(), String> {
+    let mut path = None;
+    let mut output = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                output = Some(iter.next().ok_or_else(|| USAGE.to_string())?.as_str());
+            }
+            _ if path.is_none() => path = Some(arg.as_str()),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let path = path.ok_or_else(|| USAGE.to_string())?;
+
+    let graph = read_graph(path)?;
+    let dot_str = dot::to_dot(&graph);
+
+    match output {
+        Some(output) if output.ends_with(".html") => {
+            html::export(&graph, output).map_err(|err| format!("Cannot write {}: {}", output, err))
+        }
+        Some(output) if output.ends_with(".svg") => {
+            let svg = layout_svg(dot_str.as_str())?;
+            fs::write(output, svg).map_err(|err| format!("Cannot write {}: {}", output, err))
+        }
+        Some(output) => {
+            fs::write(output, dot_str).map_err(|err| format!("Cannot write {}: {}", output, err))
+        }
+        None => {
+            print!("{}", dot_str);
+            Ok(())
+        }
+    }
+}
+
+/// Lay out a DOT document with the Graphviz `dot` command.
+fn layout_svg(dot_str: &str) -> Result<Vec<u8>, String> {
+    let mut child = Command::new("dot")
+        .arg("-Tsvg")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Cannot run Graphviz dot: {}", err))?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(dot_str.as_bytes())
+        .map_err(|err| format!("Cannot run Graphviz dot: {}", err))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Cannot run Graphviz dot: {}", err))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    Ok(output.stdout)
+}
+
+/// Print counts, density, degree percentiles, components and acyclicity.
+fn run_stats(args: &[String]) -> Result<(), String> {
+    let mut path = None;
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ if path.is_none() => path = Some(arg.as_str()),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let path = path.ok_or_else(|| USAGE.to_string())?;
+
+    let summary = GraphSummary::new(&read_graph(path)?);
+    if json {
+        println!("{}", serde_json::to_string(&summary).unwrap());
+    } else {
+        print!("{}", summary);
+    }
+    Ok(())
+}
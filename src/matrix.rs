@@ -0,0 +1,81 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Matrix representations of graphs for linear-algebra crates. Rows and
+//! columns follow a node order given by the caller, or the graph's own node
+//! order (insertion order for a `DiGraph`) if none is given.
+
+mod adjacency;
+mod laplacian;
+#[cfg(feature = "sparse")]
+mod sparse;
+
+pub use adjacency::{
+    from_sparse_adjacency_matrix, to_adjacency_matrix, to_sparse_adjacency_matrix,
+};
+pub use laplacian::{
+    incidence_matrix, laplacian_matrix, sparse_incidence_matrix, sparse_laplacian_matrix,
+};
+#[cfg(feature = "sparse")]
+pub use sparse::{
+    adjacency_csmat, adjacency_csr, from_csmat, from_csr, laplacian_csmat, laplacian_csr,
+};
+
+use crate::{error::GraphError, graph::GraphBase};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The `(source, target)` edge behind each column of an incidence matrix.
+pub type ColumnEdges = Vec<(String, String)>;
+
+/// A sparse matrix in coordinate form: `(row, column, value)` triplets in
+/// row-major order, without zeros.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub struct TripletMatrix {
+    pub rows: usize,
+    pub cols: usize,
+    pub entries: Vec<(usize, usize, i64)>,
+}
+impl TripletMatrix {
+    /// Expand into a dense row-major matrix.
+    pub fn to_dense(&self) -> Vec<Vec<i64>> {
+        let mut dense = vec![vec![0; self.cols]; self.rows];
+        for &(row, col, value) in self.entries.iter() {
+            dense[row][col] = value;
+        }
+        dense
+    }
+}
+
+/// Map node names to matrix indices. `node_order` may list any subset of the
+/// nodes, each at most once; the matrix then covers only those nodes.
+fn node_index<'a, G: GraphBase>(
+    graph: &'a G,
+    node_order: Option<&[&'a str]>,
+) -> Result<HashMap<&'a str, usize>, GraphError> {
+    let names: Vec<&str> = match node_order {
+        Some(order) => order.to_vec(),
+        None => graph.node_names().collect(),
+    };
+    let mut index = HashMap::with_capacity(names.len());
+    for (i, name) in names.into_iter().enumerate() {
+        if graph.node(name).is_none() {
+            return Err(GraphError::NotFoundNode(name.to_string()));
+        }
+        if index.insert(name, i).is_some() {
+            return Err(GraphError::DuplicateNode(name.to_string()));
+        }
+    }
+    Ok(index)
+}
@@ -0,0 +1,136 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{node_index, TripletMatrix};
+use crate::{
+    error::GraphError,
+    graph::{DiGraph, DiNode, Node, Weighted},
+};
+
+/// Build the dense adjacency matrix: entry `[i][j]` is the weight of the edge
+/// from node `i` to node `j`, 1 for an unweighted edge, and 0 without an edge.
+///
+/// Fails with `GraphError::NotFoundNode` or `GraphError::DuplicateNode` if
+/// `node_order` names an unknown node or repeats one.
+pub fn to_adjacency_matrix<G: Weighted>(
+    graph: &G,
+    node_order: Option<&[&str]>,
+) -> Result<Vec<Vec<i64>>, GraphError> {
+    Ok(to_sparse_adjacency_matrix(graph, node_order)?.to_dense())
+}
+
+/// Like [`to_adjacency_matrix`], but as sparse triplets.
+pub fn to_sparse_adjacency_matrix<G: Weighted>(
+    graph: &G,
+    node_order: Option<&[&str]>,
+) -> Result<TripletMatrix, GraphError> {
+    let index = node_index(graph, node_order)?;
+    let mut entries = Vec::new();
+    for (&name, &row) in index.iter() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            if let Some(&col) = index.get(succ) {
+                let weight = graph.edge_weight(name, succ).unwrap_or(1);
+                if weight != 0 {
+                    entries.push((row, col, weight));
+                }
+            }
+        }
+    }
+    entries.sort_unstable();
+    Ok(TripletMatrix {
+        rows: index.len(),
+        cols: index.len(),
+        entries,
+    })
+}
+
+/// Build a graph from a sparse adjacency matrix, naming the node of row and
+/// column `i` after `names[i]`. Every entry becomes an edge weighted by its
+/// value; nodes appear in the order of `names`.
+///
+/// Fails with `GraphError::NotFoundNode` naming the first index that has no
+/// name, and with `GraphError::DuplicateNode` if `names` repeats a name.
+pub fn from_sparse_adjacency_matrix(
+    matrix: &TripletMatrix,
+    names: &[&str],
+) -> Result<DiGraph, GraphError> {
+    let size = matrix.rows.max(matrix.cols);
+    if names.len() < size {
+        return Err(GraphError::NotFoundNode(names.len().to_string()));
+    }
+    let mut graph = DiGraph::new(None);
+    for &name in names {
+        if graph.contains_node(name) {
+            return Err(GraphError::DuplicateNode(name.to_string()));
+        }
+        graph.add_node(DiNode::new(name, None));
+    }
+    for &(row, col, value) in matrix.entries.iter() {
+        graph.add_weighted_edge(names[row], names[col], value);
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_adjacency_matrix() {
+        let mut g: DiGraph = vec![("A", "B"), ("B", "C"), ("C", "C")]
+            .into_iter()
+            .collect();
+        g.add_weighted_edge("A", "C", 5);
+
+        assert_eq!(
+            to_adjacency_matrix(&g, None).unwrap(),
+            vec![vec![0, 1, 5], vec![0, 0, 1], vec![0, 0, 1]]
+        );
+        assert_eq!(
+            to_adjacency_matrix(&g, Some(&["C", "A"])).unwrap(),
+            vec![vec![1, 0], vec![5, 0]]
+        );
+
+        let sparse = to_sparse_adjacency_matrix(&g, None).unwrap();
+        assert_eq!(
+            sparse.entries,
+            vec![(0, 1, 1), (0, 2, 5), (1, 2, 1), (2, 2, 1)]
+        );
+        assert_eq!((sparse.rows, sparse.cols), (3, 3));
+
+        let back = from_sparse_adjacency_matrix(&sparse, &["A", "B", "C"]).unwrap();
+        assert_eq!(back.get_nodes(), vec!["A", "B", "C"]);
+        assert_eq!(back.get_edge_weight("A", "C"), Some(5));
+        assert_eq!(back.get_edge_weight("C", "C"), Some(1));
+        assert_eq!(back.edge_count(), 4);
+        assert!(matches!(
+            from_sparse_adjacency_matrix(&sparse, &["A", "B"]),
+            Err(GraphError::NotFoundNode(_))
+        ));
+        assert!(matches!(
+            from_sparse_adjacency_matrix(&sparse, &["A", "B", "A"]),
+            Err(GraphError::DuplicateNode(_))
+        ));
+
+        assert!(matches!(
+            to_adjacency_matrix(&g, Some(&["A", "X"])),
+            Err(GraphError::NotFoundNode(_))
+        ));
+        assert!(matches!(
+            to_adjacency_matrix(&g, Some(&["A", "A"])),
+            Err(GraphError::DuplicateNode(_))
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{node_index, ColumnEdges, TripletMatrix};
+use crate::{
+    error::GraphError,
+    graph::{GraphBase, Node, Weighted},
+};
+use std::collections::BTreeMap;
+
+/// Build the dense oriented incidence matrix, with a row per node and a
+/// column per edge: `-1` at the source and `1` at the target. Weights are
+/// ignored and self-loops give empty columns.
+///
+/// Columns list the edges between nodes of `node_order` by source row, then
+/// in the graph's successor order; the returned edges name each column.
+pub fn incidence_matrix<G: GraphBase>(
+    graph: &G,
+    node_order: Option<&[&str]>,
+) -> Result<(Vec<Vec<i64>>, ColumnEdges), GraphError> {
+    let (matrix, edges) = sparse_incidence_matrix(graph, node_order)?;
+    Ok((matrix.to_dense(), edges))
+}
+
+/// Like [`incidence_matrix`], but as sparse triplets.
+pub fn sparse_incidence_matrix<G: GraphBase>(
+    graph: &G,
+    node_order: Option<&[&str]>,
+) -> Result<(TripletMatrix, ColumnEdges), GraphError> {
+    let index = node_index(graph, node_order)?;
+    let mut rows: Vec<(&str, usize)> = index.iter().map(|(&name, &i)| (name, i)).collect();
+    rows.sort_unstable_by_key(|&(_, i)| i);
+
+    let mut edges = Vec::new();
+    let mut entries = Vec::new();
+    for (name, row) in rows {
+        for succ in graph.node(name).unwrap().successor_names() {
+            if let Some(&target) = index.get(succ) {
+                let col = edges.len();
+                if target != row {
+                    entries.push((row, col, -1));
+                    entries.push((target, col, 1));
+                }
+                edges.push((name.to_string(), succ.to_string()));
+            }
+        }
+    }
+    entries.sort_unstable();
+    Ok((
+        TripletMatrix {
+            rows: index.len(),
+            cols: edges.len(),
+            entries,
+        },
+        edges,
+    ))
+}
+
+/// Build the dense Laplacian `D - W` of the graph with edge directions
+/// ignored, where `W[i][j]` sums the weights of the edges between nodes `i`
+/// and `j` in either direction (1 for an unweighted edge) and `D` is the
+/// diagonal of row sums of `W`. Self-loops are ignored, so the matrix is
+/// symmetric and its rows sum to zero.
+pub fn laplacian_matrix<G: Weighted>(
+    graph: &G,
+    node_order: Option<&[&str]>,
+) -> Result<Vec<Vec<i64>>, GraphError> {
+    Ok(sparse_laplacian_matrix(graph, node_order)?.to_dense())
+}
+
+/// Like [`laplacian_matrix`], but as sparse triplets.
+pub fn sparse_laplacian_matrix<G: Weighted>(
+    graph: &G,
+    node_order: Option<&[&str]>,
+) -> Result<TripletMatrix, GraphError> {
+    let index = node_index(graph, node_order)?;
+    let mut values: BTreeMap<(usize, usize), i64> = BTreeMap::new();
+    for (&name, &row) in index.iter() {
+        for succ in graph.node(name).unwrap().successor_names() {
+            match index.get(succ) {
+                Some(&col) if col != row => {
+                    let weight = graph.edge_weight(name, succ).unwrap_or(1);
+                    *values.entry((row, col)).or_default() -= weight;
+                    *values.entry((col, row)).or_default() -= weight;
+                    *values.entry((row, row)).or_default() += weight;
+                    *values.entry((col, col)).or_default() += weight;
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(TripletMatrix {
+        rows: index.len(),
+        cols: index.len(),
+        entries: values
+            .into_iter()
+            .filter(|&(_, value)| value != 0)
+            .map(|((row, col), value)| (row, col, value))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn test_incidence_matrix() {
+        let g: DiGraph = vec![("A", "B"), ("A", "C"), ("C", "C")]
+            .into_iter()
+            .collect();
+
+        let (matrix, edges) = incidence_matrix(&g, None).unwrap();
+        assert_eq!(matrix, vec![vec![-1, -1, 0], vec![1, 0, 0], vec![0, 1, 0]]);
+        assert_eq!(edges[2], ("C".to_string(), "C".to_string()));
+
+        let (sparse, _) = sparse_incidence_matrix(&g, Some(&["C", "A"])).unwrap();
+        assert_eq!((sparse.rows, sparse.cols), (2, 2));
+        assert_eq!(sparse.entries, vec![(0, 1, 1), (1, 1, -1)]);
+    }
+
+    #[test]
+    fn test_laplacian_matrix() {
+        // A <-> B counts twice, B -> C has weight 3
+        let mut g: DiGraph = vec![("A", "B"), ("B", "A"), ("A", "A")]
+            .into_iter()
+            .collect();
+        g.add_weighted_edge("B", "C", 3);
+
+        let laplacian = laplacian_matrix(&g, None).unwrap();
+        assert_eq!(
+            laplacian,
+            vec![vec![2, -2, 0], vec![-2, 5, -3], vec![0, -3, 3]]
+        );
+        for row in laplacian.iter() {
+            assert_eq!(row.iter().sum::<i64>(), 0);
+        }
+        assert_eq!(
+            sparse_laplacian_matrix(&g, None).unwrap().to_dense(),
+            laplacian
+        );
+    }
+}
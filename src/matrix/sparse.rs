@@ -0,0 +1,157 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compressed sparse row matrices of `sprs` and `nalgebra-sparse`, built from
+//! the triplets of [`to_sparse_adjacency_matrix`] and
+//! [`sparse_laplacian_matrix`].
+
+use super::{
+    from_sparse_adjacency_matrix, sparse_laplacian_matrix, to_sparse_adjacency_matrix,
+    TripletMatrix,
+};
+use crate::{
+    error::GraphError,
+    graph::{DiGraph, Weighted},
+};
+use nalgebra_sparse::{CooMatrix, CsrMatrix};
+use sprs::{CsMat, TriMat};
+
+/// The adjacency matrix, see [`to_sparse_adjacency_matrix`], as a `sprs`
+/// CSR matrix.
+pub fn adjacency_csmat<G: Weighted>(
+    graph: &G,
+    node_order: Option<&[&str]>,
+) -> Result<CsMat<i64>, GraphError> {
+    Ok(to_csmat(&to_sparse_adjacency_matrix(graph, node_order)?))
+}
+
+/// The Laplacian matrix, see [`sparse_laplacian_matrix`], as a `sprs` CSR
+/// matrix.
+pub fn laplacian_csmat<G: Weighted>(
+    graph: &G,
+    node_order: Option<&[&str]>,
+) -> Result<CsMat<i64>, GraphError> {
+    Ok(to_csmat(&sparse_laplacian_matrix(graph, node_order)?))
+}
+
+/// The adjacency matrix, see [`to_sparse_adjacency_matrix`], as a
+/// `nalgebra-sparse` CSR matrix.
+pub fn adjacency_csr<G: Weighted>(
+    graph: &G,
+    node_order: Option<&[&str]>,
+) -> Result<CsrMatrix<i64>, GraphError> {
+    Ok(to_csr(&to_sparse_adjacency_matrix(graph, node_order)?))
+}
+
+/// The Laplacian matrix, see [`sparse_laplacian_matrix`], as a
+/// `nalgebra-sparse` CSR matrix.
+pub fn laplacian_csr<G: Weighted>(
+    graph: &G,
+    node_order: Option<&[&str]>,
+) -> Result<CsrMatrix<i64>, GraphError> {
+    Ok(to_csr(&sparse_laplacian_matrix(graph, node_order)?))
+}
+
+/// Build a graph from a `sprs` adjacency matrix, see
+/// [`from_sparse_adjacency_matrix`]. Explicitly stored zeros are skipped.
+pub fn from_csmat(matrix: &CsMat<i64>, names: &[&str]) -> Result<DiGraph, GraphError> {
+    let mut entries: Vec<(usize, usize, i64)> = matrix
+        .iter()
+        .filter(|(&value, _)| value != 0)
+        .map(|(&value, (row, col))| (row, col, value))
+        .collect();
+    entries.sort_unstable();
+    let triplets = TripletMatrix {
+        rows: matrix.rows(),
+        cols: matrix.cols(),
+        entries,
+    };
+    from_sparse_adjacency_matrix(&triplets, names)
+}
+
+/// Build a graph from a `nalgebra-sparse` adjacency matrix, see
+/// [`from_sparse_adjacency_matrix`]. Explicitly stored zeros are skipped.
+pub fn from_csr(matrix: &CsrMatrix<i64>, names: &[&str]) -> Result<DiGraph, GraphError> {
+    let triplets = TripletMatrix {
+        rows: matrix.nrows(),
+        cols: matrix.ncols(),
+        entries: matrix
+            .triplet_iter()
+            .filter(|&(_, _, &value)| value != 0)
+            .map(|(row, col, &value)| (row, col, value))
+            .collect(),
+    };
+    from_sparse_adjacency_matrix(&triplets, names)
+}
+
+fn to_csmat(matrix: &TripletMatrix) -> CsMat<i64> {
+    let mut triplets = TriMat::with_capacity((matrix.rows, matrix.cols), matrix.entries.len());
+    for &(row, col, value) in matrix.entries.iter() {
+        triplets.add_triplet(row, col, value);
+    }
+    triplets.to_csr()
+}
+
+fn to_csr(matrix: &TripletMatrix) -> CsrMatrix<i64> {
+    let mut coo = CooMatrix::new(matrix.rows, matrix.cols);
+    for &(row, col, value) in matrix.entries.iter() {
+        coo.push(row, col, value);
+    }
+    CsrMatrix::from(&coo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> DiGraph {
+        let mut g: DiGraph = vec![("A", "B"), ("B", "C")].into_iter().collect();
+        g.add_weighted_edge("A", "C", 5);
+        g
+    }
+
+    #[test]
+    fn test_sprs_round_trip() {
+        let g = sample();
+        let adjacency = adjacency_csmat(&g, None).unwrap();
+        assert_eq!(adjacency.shape(), (3, 3));
+        assert_eq!(adjacency.nnz(), 3);
+        assert_eq!(adjacency.get(0, 2), Some(&5));
+        assert_eq!(adjacency.get(1, 0), None);
+
+        let laplacian = laplacian_csmat(&g, None).unwrap();
+        assert_eq!(laplacian.to_dense().row(2).to_vec(), vec![-5, -1, 6]);
+
+        let back = from_csmat(&adjacency, &["x", "y", "z"]).unwrap();
+        assert_eq!(back.get_edge_weight("x", "z"), Some(5));
+        assert_eq!(back.edge_count(), 3);
+        assert!(from_csmat(&adjacency, &["x"]).is_err());
+    }
+
+    #[test]
+    fn test_nalgebra_sparse_round_trip() {
+        let g = sample();
+        let adjacency = adjacency_csr(&g, Some(&["C", "A"])).unwrap();
+        assert_eq!((adjacency.nrows(), adjacency.ncols()), (2, 2));
+        assert_eq!(
+            adjacency.triplet_iter().collect::<Vec<_>>(),
+            vec![(1, 0, &5)]
+        );
+        assert_eq!(laplacian_csr(&g, None).unwrap().nnz(), 9);
+
+        let back = from_csr(&adjacency_csr(&g, None).unwrap(), &["A", "B", "C"]).unwrap();
+        assert_eq!(back.get_edge_weight("A", "C"), Some(5));
+        assert_eq!(back.get_edge_weight("A", "B"), Some(1));
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Python bindings built with `pyo3`, enabled by the `graphx-py` feature.
+//!
+//! Graphs are exchanged as networkx-style dict-of-dicts,
+//! `{source: {target: {"weight": w}}}`, as produced by `nx.to_dict_of_dicts`.
+
+use crate::{
+    algorithm::{isomorphism::DiGraphMatcher, sssp::dijkstra, topsort::topsort},
+    graph::{DiGraph, DiNode},
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+
+#[pyclass(name = "DiGraph")]
+pub struct PyDiGraph {
+    graph: DiGraph,
+}
+#[pymethods]
+impl PyDiGraph {
+    #[new]
+    #[pyo3(signature = (name=None))]
+    fn new(name: Option<String>) -> Self {
+        PyDiGraph {
+            graph: DiGraph::new(name),
+        }
+    }
+
+    /// Build a graph from `{source: {target: {"weight": w}}}`.
+    #[staticmethod]
+    fn from_dict_of_dicts(adjacency: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let mut graph = DiGraph::new(None);
+        for (from, targets) in adjacency.iter() {
+            let from: String = from.extract()?;
+            graph.add_edge(Some(from.as_str()), None);
+            for (to, attrs) in targets.cast::<PyDict>()?.iter() {
+                let to: String = to.extract()?;
+                let weight = match attrs.cast::<PyDict>()?.get_item("weight")? {
+                    Some(weight) => Some(weight.extract::<i64>()?),
+                    None => None,
+                };
+                match weight {
+                    Some(weight) => graph.add_weighted_edge(from.as_str(), to.as_str(), weight),
+                    None => graph.add_edge(Some(from.as_str()), Some(to.as_str())),
+                }
+            }
+        }
+        Ok(PyDiGraph { graph })
+    }
+
+    /// Export the graph as `{source: {target: {"weight": w}}}`.
+    fn to_dict_of_dicts<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let adjacency = PyDict::new(py);
+        for name in self.graph.node_names() {
+            adjacency.set_item(name, PyDict::new(py))?;
+        }
+        for (from, to, weight) in self.graph.weighted_edges() {
+            let attrs = PyDict::new(py);
+            if let Some(weight) = weight {
+                attrs.set_item("weight", weight)?;
+            }
+            adjacency.get_item(from)?.unwrap().set_item(to, attrs)?;
+        }
+        Ok(adjacency)
+    }
+
+    #[staticmethod]
+    fn from_json(json_str: &str) -> PyResult<Self> {
+        let graph =
+            DiGraph::from_json(json_str).map_err(|err| PyValueError::new_err(err.report()))?;
+        Ok(PyDiGraph { graph })
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        self.graph
+            .to_json()
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    #[pyo3(signature = (name, weight=None))]
+    fn add_node(&mut self, name: &str, weight: Option<String>) {
+        self.graph.add_node(DiNode::new(name, weight));
+    }
+
+    #[pyo3(signature = (from, to, weight=None))]
+    fn add_edge(&mut self, from: &str, to: &str, weight: Option<i64>) {
+        match weight {
+            Some(weight) => self.graph.add_weighted_edge(from, to, weight),
+            None => self.graph.add_edge(Some(from), Some(to)),
+        }
+    }
+
+    fn number_of_nodes(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn number_of_edges(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DiGraph(nodes={}, edges={})",
+            self.graph.node_count(),
+            self.graph.edge_count()
+        )
+    }
+}
+
+#[pyfunction(name = "topsort")]
+fn py_topsort(graph: &PyDiGraph) -> Vec<String> {
+    topsort(&graph.graph)
+}
+
+#[pyfunction(name = "dijkstra")]
+fn py_dijkstra(graph: &PyDiGraph, source: &str) -> PyResult<HashMap<String, usize>> {
+    dijkstra(&graph.graph, source).map_err(|err| PyValueError::new_err(err.report()))
+}
+
+/// Like networkx's `DiGraphMatcher.subgraph_isomorphisms_iter`, each mapping
+/// goes from `host` nodes to `pattern` nodes. `mode` is `subgraph` or `mono`.
+#[pyfunction]
+#[pyo3(signature = (host, pattern, mode="subgraph", limit=None))]
+fn subgraph_isomorphisms_iter(
+    host: &PyDiGraph,
+    pattern: &PyDiGraph,
+    mode: &str,
+    limit: Option<usize>,
+) -> PyResult<Vec<HashMap<String, String>>> {
+    let mut matcher = DiGraphMatcher::new(&host.graph, &pattern.graph);
+    matcher.limit = limit;
+    let mut mapping = Vec::new();
+    match mode {
+        "subgraph" => matcher.subgraph_isomorphism_iter(&mut mapping),
+        "mono" => matcher.subgraph_monomorphism_iter(&mut mapping),
+        _ => return Err(PyValueError::new_err(format!("Invalid mode: {}", mode))),
+    }
+
+    Ok(mapping
+        .into_iter()
+        .map(|found| found.into_iter().map(|(g2, g1)| (g1, g2)).collect())
+        .collect())
+}
+
+#[pymodule]
+fn graphx(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDiGraph>()?;
+    m.add_function(wrap_pyfunction!(py_topsort, m)?)?;
+    m.add_function(wrap_pyfunction!(py_dijkstra, m)?)?;
+    m.add_function(wrap_pyfunction!(subgraph_isomorphisms_iter, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,117 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JavaScript bindings built with `wasm-bindgen`, enabled by the `wasm` feature.
+//!
+//! Maps and mappings cross the boundary as JSON strings.
+
+use crate::{
+    algorithm::{isomorphism::DiGraphMatcher, sssp::dijkstra, topsort::topsort},
+    graph::{DiGraph, DiNode},
+};
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(js_name = DiGraph)]
+pub struct WasmDiGraph {
+    graph: DiGraph,
+}
+#[wasm_bindgen(js_class = DiGraph)]
+impl WasmDiGraph {
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: Option<String>) -> Self {
+        WasmDiGraph {
+            graph: DiGraph::new(name),
+        }
+    }
+
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json_str: &str) -> Result<WasmDiGraph, JsValue> {
+        let graph = DiGraph::from_json(json_str).map_err(|err| err.report())?;
+        Ok(WasmDiGraph { graph })
+    }
+
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsValue> {
+        Ok(self.graph.to_json().map_err(|err| err.to_string())?)
+    }
+
+    #[wasm_bindgen(js_name = addNode)]
+    pub fn add_node(&mut self, name: &str, weight: Option<String>) {
+        self.graph.add_node(DiNode::new(name, weight));
+    }
+
+    #[wasm_bindgen(js_name = addEdge)]
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        self.graph.add_edge(Some(from), Some(to));
+    }
+
+    /// JavaScript numbers are truncated to integer weights.
+    #[wasm_bindgen(js_name = addWeightedEdge)]
+    pub fn add_weighted_edge(&mut self, from: &str, to: &str, weight: f64) {
+        self.graph.add_weighted_edge(from, to, weight as i64);
+    }
+
+    #[wasm_bindgen(js_name = nodeCount)]
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    #[wasm_bindgen(js_name = edgeCount)]
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    pub fn topsort(&self) -> Vec<String> {
+        topsort(&self.graph)
+    }
+
+    /// Distances from `source` as a JSON object.
+    pub fn dijkstra(&self, source: &str) -> Result<String, JsValue> {
+        let dist: BTreeMap<String, usize> = dijkstra(&self.graph, source)
+            .map_err(|err| err.report())?
+            .into_iter()
+            .collect();
+        Ok(serde_json::to_string(&dist).unwrap())
+    }
+}
+
+/// Find subgraphs of `host` matching `pattern`, returned as a JSON array of
+/// objects mapping pattern nodes to host nodes. `mode` is `subgraph` or `mono`.
+#[wasm_bindgen(js_name = subgraphMatches)]
+pub fn subgraph_matches(
+    host: &WasmDiGraph,
+    pattern: &WasmDiGraph,
+    mode: &str,
+    limit: Option<usize>,
+) -> Result<String, JsValue> {
+    let mut matcher = DiGraphMatcher::new(&host.graph, &pattern.graph);
+    matcher.limit = limit;
+    let mut mapping = Vec::new();
+    match mode {
+        "subgraph" => matcher.subgraph_isomorphism_iter(&mut mapping),
+        "mono" => matcher.subgraph_monomorphism_iter(&mut mapping),
+        _ => {
+            return Err(JsValue::from_str(
+                format!("Invalid mode: {}", mode).as_str(),
+            ))
+        }
+    }
+
+    let sorted: Vec<BTreeMap<String, String>> = mapping
+        .into_iter()
+        .map(|found| found.into_iter().collect())
+        .collect();
+    Ok(serde_json::to_string(&sorted).unwrap())
+}
@@ -0,0 +1,118 @@
+// Copyright 2021 apepkuss
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use graphx::graph::DiGraph;
+use std::path::PathBuf;
+use std::process::Command;
+use std::{env, fs};
+
+fn write_graph(file_name: &str, edges: &[(&str, &str)]) -> PathBuf {
+    let graph: DiGraph = edges.iter().cloned().collect();
+    let path = env::temp_dir().join(format!("graphx_cli_{}", file_name));
+    fs::write(&path, serde_json::to_string(&graph).unwrap()).unwrap();
+    path
+}
+
+fn graphx(args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_graphx"))
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn cli_match_test() {
+    let pattern = write_graph("match_pattern.json", &[("1", "2"), ("2", "3")]);
+    let host = write_graph("match_host.json", &[("A", "B"), ("B", "C"), ("A", "C")]);
+    let pattern = pattern.to_str().unwrap();
+    let host = host.to_str().unwrap();
+
+    let (ok, stdout) = graphx(&["match", pattern, host]);
+    assert!(ok);
+    assert_eq!(stdout, "");
+
+    let (ok, stdout) = graphx(&["match", pattern, host, "--mode", "mono", "--limit", "5"]);
+    assert!(ok);
+    assert_eq!(stdout, "{\"1\":\"A\",\"2\":\"B\",\"3\":\"C\"}\n");
+
+    let (ok, _) = graphx(&["match", pattern, host, "--mode", "induced"]);
+    assert!(!ok);
+    let (ok, _) = graphx(&["match", pattern]);
+    assert!(!ok);
+}
+
+#[test]
+fn cli_match_lad_test() {
+    // an undirected edge into an undirected triangle
+    let pattern = env::temp_dir().join("graphx_cli_pattern.lad");
+    let host = env::temp_dir().join("graphx_cli_host.dimacs");
+    fs::write(&pattern, "2\n1 1\n1 0\n").unwrap();
+    fs::write(&host, "p edge 3 3\ne 1 2\ne 2 3\ne 3 1\n").unwrap();
+
+    let (ok, stdout) = graphx(&[
+        "match",
+        pattern.to_str().unwrap(),
+        host.to_str().unwrap(),
+        "--mode",
+        "mono",
+    ]);
+    assert!(ok);
+    assert_eq!(stdout.lines().count(), 6);
+}
+
+#[test]
+fn cli_render_test() {
+    let path = write_graph("render.json", &[("A", "B")]);
+    let path = path.to_str().unwrap();
+
+    let (ok, stdout) = graphx(&["render", path]);
+    assert!(ok);
+    assert_eq!(
+        stdout,
+        "digraph {\n    \"A\";\n    \"B\";\n    \"A\" -> \"B\";\n}\n"
+    );
+
+    let output = env::temp_dir().join("graphx_cli_render.dot");
+    let (ok, _) = graphx(&["render", path, "-o", output.to_str().unwrap()]);
+    assert!(ok);
+    assert_eq!(fs::read_to_string(output).unwrap(), stdout);
+
+    let output = env::temp_dir().join("graphx_cli_render.html");
+    let (ok, _) = graphx(&["render", path, "-o", output.to_str().unwrap()]);
+    assert!(ok);
+    assert!(fs::read_to_string(output)
+        .unwrap()
+        .contains("\"source\":\"A\",\"target\":\"B\""));
+}
+
+#[test]
+fn cli_stats_test() {
+    let path = write_graph("stats.json", &[("A", "B"), ("B", "C"), ("D", "E")]);
+    let path = path.to_str().unwrap();
+
+    let (ok, stdout) = graphx(&["stats", path]);
+    assert!(ok);
+    assert!(stdout.contains("nodes:      5"));
+    assert!(stdout.contains("components: 2"));
+
+    let (ok, stdout) = graphx(&["stats", path, "--json"]);
+    assert!(ok);
+    let summary: serde_json::Value = serde_json::from_str(stdout.as_str()).unwrap();
+    assert_eq!(summary["edge_count"], 3);
+    assert_eq!(summary["is_dag"], true);
+}
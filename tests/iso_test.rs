@@ -13,9 +13,11 @@
 // limitations under the License.
 
 use graphx::{
-    algorithm::isomorphism as iso,
+    algorithm::{invariants, isomorphism as iso},
+    error::GraphError,
     graph::{DiGraph, DiNode},
 };
+use std::sync::Arc;
 
 #[test]
 fn iso_digraph_weight_test() {
@@ -84,3 +86,406 @@ fn iso_digraph_test() {
 
     assert_eq!(mapping.len(), 2);
 }
+
+#[test]
+fn iso_digraph_weight_index_test() {
+    let mut g1 = DiGraph::new(None);
+    g1.add_node(DiNode::new("A", Some("conv".to_string())));
+    g1.add_node(DiNode::new("B", Some("relu".to_string())));
+    g1.add_node(DiNode::new("C", Some("conv".to_string())));
+    g1.add_node(DiNode::new("D", Some("relu".to_string())));
+    g1.add_edge(Some("A"), Some("B"));
+    g1.add_edge(Some("B"), Some("C"));
+    g1.add_edge(Some("C"), Some("D"));
+    g1.enable_weight_index();
+
+    let mut g2 = DiGraph::new(None);
+    g2.add_node(DiNode::new("1", Some("conv".to_string())));
+    g2.add_node(DiNode::new("2", Some("relu".to_string())));
+    g2.add_edge(Some("1"), Some("2"));
+
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    let mut mapping = Vec::new();
+    matcher.subgraph_isomorphism_iter(&mut mapping);
+
+    assert_eq!(mapping.len(), 2);
+}
+
+#[test]
+fn iso_digraph_self_loop_test() {
+    let mut g1 = DiGraph::new(None);
+    g1.add_edge(Some("A"), Some("B"));
+    g1.add_edge(Some("B"), Some("B"));
+    g1.add_edge(Some("C"), Some("D"));
+
+    let mut g2 = DiGraph::new(None);
+    g2.add_edge(Some("1"), Some("2"));
+    g2.add_edge(Some("2"), Some("2"));
+
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    let mut mapping = Vec::new();
+    matcher.subgraph_isomorphism_iter(&mut mapping);
+
+    assert_eq!(mapping.len(), 1);
+    assert_eq!(mapping[0].get("1").unwrap(), "A");
+    assert_eq!(mapping[0].get("2").unwrap(), "B");
+}
+
+#[test]
+fn mono_digraph_test() {
+    // the transitive triangle A -> B -> C, A -> C contains no induced directed
+    // path of length two, but exactly one monomorphic one, A -> B -> C
+    let mut g1 = DiGraph::new(None);
+    g1.add_edge(Some("A"), Some("B"));
+    g1.add_edge(Some("B"), Some("C"));
+    g1.add_edge(Some("A"), Some("C"));
+
+    let mut g2 = DiGraph::new(None);
+    g2.add_edge(Some("1"), Some("2"));
+    g2.add_edge(Some("2"), Some("3"));
+
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    let mut mapping = Vec::new();
+    matcher.subgraph_isomorphism_iter(&mut mapping);
+    assert_eq!(mapping.len(), 0);
+
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    let mut mapping = Vec::new();
+    matcher.subgraph_monomorphism_iter(&mut mapping);
+    assert_eq!(mapping.len(), 1);
+    assert_eq!(mapping[0].get("1").unwrap(), "A");
+    assert_eq!(mapping[0].get("2").unwrap(), "B");
+    assert_eq!(mapping[0].get("3").unwrap(), "C");
+}
+
+#[test]
+fn iso_digraph_limit_test() {
+    let mut g1 = DiGraph::new(None);
+    g1.add_edge(Some("A"), Some("B"));
+    g1.add_edge(Some("C"), Some("D"));
+    g1.add_edge(Some("E"), Some("F"));
+
+    let mut g2 = DiGraph::new(None);
+    g2.add_edge(Some("1"), Some("2"));
+
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    matcher.limit = Some(2);
+    let mut mapping = Vec::new();
+    matcher.subgraph_isomorphism_iter(&mut mapping);
+    assert_eq!(mapping.len(), 2);
+}
+
+#[test]
+fn iso_digraph_ports_test() {
+    // a - b, with the operands in port order
+    let mut g1 = DiGraph::new(None);
+    g1.add_node(DiNode::new("sub", Some("sub".to_string())));
+    g1.add_port_edge("a", "sub", 0).unwrap();
+    g1.add_port_edge("b", "sub", 1).unwrap();
+
+    let mut g2 = DiGraph::new(None);
+    g2.add_node(DiNode::new("op", Some("sub".to_string())));
+    g2.add_port_edge("rhs", "op", 1).unwrap();
+    g2.add_port_edge("lhs", "op", 0).unwrap();
+
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    let mut mapping = Vec::new();
+    matcher.subgraph_isomorphism_iter(&mut mapping);
+    assert_eq!(mapping.len(), 2);
+
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    matcher.match_ports = true;
+    let mut mapping = Vec::new();
+    matcher.subgraph_isomorphism_iter(&mut mapping);
+    assert_eq!(mapping.len(), 1);
+    assert_eq!(mapping[0].get("lhs").unwrap(), "a");
+    assert_eq!(mapping[0].get("rhs").unwrap(), "b");
+}
+
+#[test]
+fn iso_digraph_graph_test() {
+    // a 4-cycle with one chord, and the same graph relabeled
+    let g1: DiGraph = vec![("A", "B"), ("B", "C"), ("C", "D"), ("D", "A"), ("A", "C")]
+        .into_iter()
+        .collect();
+    let g2: DiGraph = vec![("3", "4"), ("4", "1"), ("1", "2"), ("2", "3"), ("3", "1")]
+        .into_iter()
+        .collect();
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    assert!(matcher.is_isomorphic());
+    let mut mapping = Vec::new();
+    matcher.isomorphisms_iter(&mut mapping);
+    assert_eq!(mapping.len(), 1);
+    assert_eq!(mapping[0].get("3").unwrap(), "A");
+
+    // turning the chord around just swaps the roles of A and C
+    let mut g3: DiGraph = vec![("A", "B"), ("B", "C"), ("C", "D"), ("D", "A"), ("C", "A")]
+        .into_iter()
+        .collect();
+    assert!(iso::DiGraphMatcher::new(&g1, &g3).is_isomorphic());
+
+    // reversing an edge of the cycle is caught by the invariants alone
+    g3.remove_edge("B", "C");
+    g3.add_edge(Some("C"), Some("B"));
+    assert!(!invariants::could_be_isomorphic(&g1, &g3));
+    assert!(!iso::DiGraphMatcher::new(&g1, &g3).is_isomorphic());
+}
+
+#[test]
+fn iso_digraph_bitset_test() {
+    // rings on both sides of the 128-node bitset limit, with a chord so the
+    // look-ahead rules have something to prune
+    let ring = |n: usize, prefix: &str| {
+        let mut g = DiGraph::new(None);
+        for i in 0..n {
+            let from = format!("{}{}", prefix, i);
+            let to = format!("{}{}", prefix, (i + 1) % n);
+            g.add_edge(Some(from.as_str()), Some(to.as_str()));
+        }
+        let chord = format!("{}{}", prefix, n / 2);
+        g.add_edge(Some(format!("{}0", prefix).as_str()), Some(chord.as_str()));
+        g
+    };
+    let mut pattern = DiGraph::new(None);
+    pattern.add_edge(Some("x"), Some("y"));
+    pattern.add_edge(Some("y"), Some("z"));
+
+    for n in [100, 128, 129, 150] {
+        let host = ring(n, "v");
+        let mut matcher = iso::DiGraphMatcher::new(&host, &pattern);
+        let mut mapping = Vec::new();
+        matcher.subgraph_isomorphism_iter(&mut mapping);
+        // every two-edge path around the ring, plus the two through the chord
+        assert_eq!(mapping.len(), n + 2, "{} nodes", n);
+
+        let relabeled = ring(n, "w");
+        assert!(iso::DiGraphMatcher::new(&host, &relabeled).is_isomorphic());
+    }
+}
+
+#[test]
+fn iso_digraph_ordering_test() {
+    // a triangle with a tail in a weighted host, so the orders differ
+    let mut g1: DiGraph = vec![
+        ("A", "B"),
+        ("B", "C"),
+        ("C", "A"),
+        ("C", "D"),
+        ("D", "E"),
+        ("E", "C"),
+        ("E", "F"),
+    ]
+    .into_iter()
+    .collect();
+    g1.get_node_mut("F")
+        .unwrap()
+        .set_weight(Some("out".to_string()));
+    let mut g2: DiGraph = vec![("1", "2"), ("2", "3"), ("3", "1"), ("3", "4")]
+        .into_iter()
+        .collect();
+    g2.get_node_mut("4")
+        .unwrap()
+        .set_weight(Some("out".to_string()));
+
+    let mut expected = Vec::new();
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    assert!(matches!(
+        matcher.ordering(),
+        iso::OrderingStrategy::G2InsertionOrder
+    ));
+    matcher.subgraph_monomorphism_iter(&mut expected);
+    assert_eq!(expected.len(), 1);
+
+    let strategies = vec![
+        iso::OrderingStrategy::DegreeDescending,
+        iso::OrderingStrategy::VF2pp,
+        iso::OrderingStrategy::Custom(Arc::new(|name: &str| -name.parse::<i64>().unwrap())),
+    ];
+    for strategy in strategies {
+        let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+        matcher.set_ordering(strategy.clone());
+        let mut mapping = Vec::new();
+        matcher.subgraph_monomorphism_iter(&mut mapping);
+        assert_eq!(mapping, expected, "{:?}", strategy);
+    }
+
+    // every order finds all the automorphisms of a cycle
+    let cycle: DiGraph = vec![("a", "b"), ("b", "c"), ("c", "d"), ("d", "a")]
+        .into_iter()
+        .collect();
+    for strategy in [
+        iso::OrderingStrategy::G2InsertionOrder,
+        iso::OrderingStrategy::DegreeDescending,
+        iso::OrderingStrategy::VF2pp,
+    ] {
+        let mut matcher = iso::DiGraphMatcher::new(&cycle, &cycle);
+        matcher.set_ordering(strategy);
+        let mut mapping = Vec::new();
+        matcher.isomorphisms_iter(&mut mapping);
+        assert_eq!(mapping.len(), 4);
+    }
+}
+
+#[test]
+fn iso_digraph_report_test() {
+    let g1: DiGraph = vec![("A", "B"), ("B", "C"), ("A", "C"), ("C", "D")]
+        .into_iter()
+        .collect();
+    let g2: DiGraph = vec![("1", "2"), ("2", "3")].into_iter().collect();
+
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    let mut mapping = Vec::new();
+    matcher.subgraph_isomorphism_iter(&mut mapping);
+    assert!(matcher.report.is_none());
+
+    matcher.report = Some(iso::MatchReport::default());
+    let mut mapping = Vec::new();
+    matcher.subgraph_isomorphism_iter(&mut mapping);
+    let report = matcher.report.clone().unwrap();
+    // A -> B -> C has a chord, so only the paths into D are induced
+    assert_eq!(mapping.len(), 2);
+    assert_eq!(report.mapping_times.len(), 2);
+    assert_eq!(report.max_depth, 3);
+    assert!(report.states > 3);
+    assert!(report.total_prunes() > 0);
+    assert!(report.elapsed >= report.mapping_times[0]);
+
+    // monomorphism also takes the path with the chord, and skips the
+    // look-ahead rules
+    let mut mapping = Vec::new();
+    matcher.subgraph_monomorphism_iter(&mut mapping);
+    let mono = matcher.report.clone().unwrap();
+    assert_eq!(mapping.len(), 3);
+    assert_eq!(mono.mapping_times.len(), 3);
+    assert_eq!(mono.pruned_by(iso::FeasibilityRule::In), 0);
+    assert_eq!(mono.pruned_by(iso::FeasibilityRule::Out), 0);
+    assert_eq!(mono.pruned_by(iso::FeasibilityRule::New), 0);
+
+    // graphs told apart by their invariants are not searched
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    matcher.report = Some(iso::MatchReport::default());
+    assert!(!matcher.is_isomorphic());
+    assert_eq!(matcher.report.as_ref().unwrap().states, 0);
+}
+
+#[test]
+fn iso_digraph_checkpoint_test() {
+    // every two-edge path around a ring with a chord
+    let mut g1 = DiGraph::new(None);
+    for i in 0..12 {
+        let (from, to) = (format!("v{}", i), format!("v{}", (i + 1) % 12));
+        g1.add_edge(Some(from.as_str()), Some(to.as_str()));
+    }
+    g1.add_edge(Some("v0"), Some("v6"));
+    let g2: DiGraph = vec![("x", "y"), ("y", "z")].into_iter().collect();
+
+    let mut all = Vec::new();
+    iso::DiGraphMatcher::new(&g1, &g2).subgraph_isomorphism_iter(&mut all);
+    assert_eq!(all.len(), 14);
+
+    // in batches of five, through a checkpoint serialized between batches
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    matcher.limit = Some(5);
+    let mut found = Vec::new();
+    matcher.subgraph_isomorphism_iter(&mut found);
+    let mut checkpoint = matcher.checkpoint().cloned();
+    while let Some(saved) = checkpoint {
+        let saved = serde_json::to_string(&saved).unwrap();
+        let restored: iso::SearchCheckpoint = serde_json::from_str(&saved).unwrap();
+        let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+        matcher.limit = Some(5);
+        let mut batch = Vec::new();
+        matcher.resume(&restored, &mut batch).unwrap();
+        assert!(!batch.is_empty());
+        found.extend(batch);
+        checkpoint = matcher.checkpoint().cloned();
+    }
+    assert_eq!(found, all);
+
+    // shards split the search without overlap
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    let shards = matcher.shards("subgraph");
+    assert_eq!(shards.len(), 12);
+    let mut sharded = Vec::new();
+    for shard in shards.iter() {
+        matcher.resume(shard, &mut sharded).unwrap();
+    }
+    assert_eq!(sharded, all);
+
+    // a checkpoint that does not fit the graphs
+    let mut stale = shards[0].clone();
+    stale.path.push(("v0".to_string(), "nowhere".to_string()));
+    assert!(matches!(
+        matcher.resume(&stale, &mut Vec::new()),
+        Err(GraphError::NotFoundNode(_))
+    ));
+    stale.path[1].1 = "x".to_string();
+    assert!(matches!(
+        matcher.resume(&stale, &mut Vec::new()),
+        Err(GraphError::Serialization(_))
+    ));
+}
+
+#[test]
+fn edge_subgraph_digraph_test() {
+    // a two-cycle has two node mappings onto A <-> B, but one edge set
+    let g1: DiGraph = vec![("A", "B"), ("B", "A"), ("B", "C"), ("C", "B")]
+        .into_iter()
+        .collect();
+    let g2: DiGraph = vec![("x", "y"), ("y", "x")].into_iter().collect();
+
+    let mut mapping = Vec::new();
+    iso::DiGraphMatcher::new(&g1, &g2).subgraph_monomorphism_iter(&mut mapping);
+    assert_eq!(mapping.len(), 4);
+
+    let mut matcher = iso::DiGraphMatcher::new(&g1, &g2);
+    let mut matches = Vec::new();
+    matcher.edge_subgraph_iter(&mut matches);
+    assert_eq!(matches.len(), 2);
+    let edge = |from: &str, to: &str| (from.to_string(), to.to_string());
+    assert_eq!(matches[0][&edge("x", "y")], edge("A", "B"));
+    assert_eq!(matches[0][&edge("y", "x")], edge("B", "A"));
+    assert_eq!(matches[1][&edge("x", "y")], edge("B", "C"));
+
+    // the limit counts edge sets
+    matcher.limit = Some(1);
+    let mut matches = Vec::new();
+    matcher.edge_subgraph_iter(&mut matches);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matcher.limit, Some(1));
+
+    // the search stops at the first edge set: the second node mapping onto
+    // A <-> B is dropped without exploring further
+    matcher.report = Some(iso::MatchReport::default());
+    let mut matches = Vec::new();
+    matcher.edge_subgraph_iter(&mut matches);
+    assert_eq!(matches.len(), 1);
+    let report = matcher.report.take().unwrap();
+    assert_eq!(report.mapping_times.len(), 1);
+    assert!(matcher.checkpoint().is_some());
+
+    // resuming skips the edge set found before the checkpoint, though the
+    // second node mapping onto it comes after
+    let saved = serde_json::to_string(matcher.checkpoint().unwrap()).unwrap();
+    let restored: iso::SearchCheckpoint = serde_json::from_str(&saved).unwrap();
+    assert_eq!(restored.edge_sets.as_ref().map(Vec::len), Some(1));
+    matcher.limit = None;
+    matcher
+        .resume_edge_subgraph(&restored, &mut matches)
+        .unwrap();
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[1][&edge("x", "y")], edge("B", "C"));
+    assert!(matches!(
+        matcher.resume(&restored, &mut Vec::new()),
+        Err(GraphError::Serialization(_))
+    ));
+
+    // a fan-out of two leaves matches each pair of out-edges once
+    let star: DiGraph = vec![("c", "a"), ("c", "b"), ("c", "d")]
+        .into_iter()
+        .collect();
+    let fan: DiGraph = vec![("r", "s"), ("r", "t")].into_iter().collect();
+    let mut matches = Vec::new();
+    iso::DiGraphMatcher::new(&star, &fan).edge_subgraph_iter(&mut matches);
+    assert_eq!(matches.len(), 3);
+}